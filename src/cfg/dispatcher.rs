@@ -0,0 +1,457 @@
+//! This module contains the recognition of the function dispatcher of a
+//! contract, producing a table from the selectors of the functions that it
+//! exposes to the offsets at which their bodies start.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::{
+    cfg::{BasicBlock, ControlFlowGraph, Edge, EdgeKind},
+    disassembly::InstructionStream,
+    opcode::{
+        arithmetic::Sub,
+        control::JumpI,
+        logic::{Eq, Gt, IsZero, Lt, Xor},
+        memory::{CallDataSize, DupN, PushN},
+        DynOpcode,
+    },
+};
+
+/// The size of a function selector in bytes.
+const SELECTOR_SIZE: usize = 4;
+
+/// A table of the functions exposed by a contract, as recognised from its
+/// dispatcher.
+///
+/// # Recognised Patterns
+///
+/// The dispatcher is explored from the entry of the contract, following the
+/// statically-known edges of its [`ControlFlowGraph`] but never entering the
+/// functions that it dispatches to. The following patterns are recognised at
+/// the ends of the blocks that it visits:
+///
+/// - Comparisons of the selector against a constant using `EQ` followed by a
+///   `JUMPI`, as emitted by Solidity in linear chains and in the `switch`
+///   statements produced by the IR pipeline. The function starts at the target
+///   of the jump.
+/// - Comparisons of the selector against a constant using `XOR`, `SUB`, or a
+///   negated `EQ`, followed by a `JUMPI` to the next comparison, as emitted by
+///   Vyper. The function starts immediately after the jump.
+/// - The `GT` and `LT` comparisons that split the dispatcher for a binary
+///   search, which are followed like any other branch.
+/// - The check that the call data is shorter than a selector, whose target is
+///   taken to be the fallback, and a check of `CALLDATASIZE` at the start of
+///   the fallback that distinguishes it from the receive function.
+///
+/// The sparse jump tables emitted by more recent versions of Vyper dispatch
+/// using computed jumps, and hence are not recognised.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SelectorTable {
+    /// The offsets at which the bodies of the dispatched functions start,
+    /// keyed by their selectors.
+    pub functions: BTreeMap<[u8; SELECTOR_SIZE], u32>,
+
+    /// The offset at which execution continues if the call data does not
+    /// match any selector, if it was recognised.
+    pub fallback: Option<u32>,
+
+    /// The offset at which execution continues if the call data is empty, if
+    /// it was recognised as differing from the fallback.
+    pub receive: Option<u32>,
+}
+
+impl SelectorTable {
+    /// Recognises the dispatcher in the provided `instructions`.
+    #[must_use]
+    pub fn new(instructions: &InstructionStream) -> Self {
+        Self::from_graph(&ControlFlowGraph::new(instructions), instructions)
+    }
+
+    /// Recognises the dispatcher in the provided `instructions`, using the
+    /// already-built `graph` for them.
+    #[must_use]
+    pub fn from_graph(graph: &ControlFlowGraph, instructions: &InstructionStream) -> Self {
+        let mut table = Self::default();
+        let Some(entry) = graph.entry() else {
+            return table;
+        };
+
+        let mut visited = BTreeSet::new();
+        let mut worklist = vec![entry.start];
+        while let Some(start) = worklist.pop() {
+            let Some(block) = graph.block(start) else {
+                continue;
+            };
+            if !visited.insert(start) {
+                continue;
+            }
+
+            let (jump_target, fall_through) = Self::branches(graph, block);
+            match Check::recognise(&block.instructions(instructions)) {
+                Some(Check::Selector {
+                    selector,
+                    on_jump: true,
+                }) => {
+                    if let Some(target) = jump_target {
+                        table.functions.entry(selector).or_insert(target);
+                    }
+                    worklist.extend(fall_through);
+                }
+                Some(Check::Selector {
+                    selector,
+                    on_jump: false,
+                }) => {
+                    if let Some(target) = fall_through {
+                        table.functions.entry(selector).or_insert(target);
+                    }
+                    worklist.extend(jump_target);
+                }
+                Some(Check::ShortCallData { on_jump }) => {
+                    let (fallback, dispatch) = if on_jump {
+                        (jump_target, fall_through)
+                    } else {
+                        (fall_through, jump_target)
+                    };
+                    if let Some(fallback) = fallback.filter(|f| visited.insert(*f)) {
+                        table.recognise_fallback(graph, instructions, fallback);
+                    }
+                    worklist.extend(dispatch);
+                }
+                _ => worklist.extend(graph.successors(start)),
+            }
+        }
+
+        table
+    }
+
+    /// Gets the offset at which the body of the function with the provided
+    /// `selector` starts, if it is dispatched to.
+    #[must_use]
+    pub fn entry(&self, selector: [u8; SELECTOR_SIZE]) -> Option<u32> {
+        self.functions.get(&selector).copied()
+    }
+
+    /// Gets the selectors of the dispatched functions in ascending order.
+    pub fn selectors(&self) -> impl Iterator<Item = [u8; SELECTOR_SIZE]> + '_ {
+        self.functions.keys().copied()
+    }
+
+    /// Gets the number of dispatched functions.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.functions.len()
+    }
+
+    /// Checks whether no dispatched functions were recognised.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.functions.is_empty()
+    }
+
+    /// Records the fallback starting at `fallback`, splitting out the receive
+    /// function if the fallback starts by checking for empty call data.
+    fn recognise_fallback(
+        &mut self,
+        graph: &ControlFlowGraph,
+        instructions: &InstructionStream,
+        fallback: u32,
+    ) {
+        self.fallback = Some(fallback);
+        let Some(block) = graph.block(fallback) else {
+            return;
+        };
+        let Some(Check::HasCallData { on_jump }) =
+            Check::recognise(&block.instructions(instructions))
+        else {
+            return;
+        };
+
+        let (jump_target, fall_through) = Self::branches(graph, block);
+        let (with_data, without_data) = if on_jump {
+            (jump_target, fall_through)
+        } else {
+            (fall_through, jump_target)
+        };
+        if let (Some(with_data), Some(without_data)) = (with_data, without_data) {
+            self.fallback = Some(with_data);
+            self.receive = Some(without_data);
+        }
+    }
+
+    /// Gets the start offsets of the blocks to which control passes from the
+    /// conditional jump ending `block` when it is taken and when it is not
+    /// taken respectively.
+    fn branches(graph: &ControlFlowGraph, block: &BasicBlock) -> (Option<u32>, Option<u32>) {
+        let jump_target = graph
+            .edges_from(block.start)
+            .find(|e| e.kind == EdgeKind::ConditionalJump)
+            .and_then(Edge::target_block);
+        let fall_through = graph.block(block.end).map(|b| b.start);
+
+        (jump_target, fall_through)
+    }
+}
+
+/// The checks performed by the dispatcher that are recognised at the end of a
+/// block.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Check {
+    /// The selector is compared against a constant, and the function is
+    /// entered `on_jump` if they are equal, or on fall-through otherwise.
+    Selector { selector: [u8; SELECTOR_SIZE], on_jump: bool },
+
+    /// The size of the call data is compared against the size of a selector,
+    /// and control passes to the fallback `on_jump` if it is too short, or on
+    /// fall-through otherwise.
+    ShortCallData { on_jump: bool },
+
+    /// The call data is checked for being non-empty, and control passes to
+    /// the fallback `on_jump` if it is, or on fall-through otherwise.
+    HasCallData { on_jump: bool },
+}
+
+impl Check {
+    /// Recognises the check, if any, performed by the conditional jump that
+    /// ends a block consisting of `instructions`.
+    fn recognise(instructions: &[(u32, &DynOpcode)]) -> Option<Self> {
+        let mut ops = instructions.iter().rev().map(|(_, op)| *op);
+        if !is::<JumpI>(ops.next()?) || !is::<PushN>(ops.next()?) {
+            return None;
+        }
+
+        // Each negation of the condition swaps the targets
+        let mut condition = ops.next()?;
+        let mut negated = false;
+        while is::<IsZero>(condition) {
+            negated = !negated;
+            condition = ops.next()?;
+        }
+
+        if is::<CallDataSize>(condition) {
+            return Some(Self::HasCallData { on_jump: !negated });
+        }
+
+        let first = ops.next()?;
+        let second = ops.next();
+        if is::<Eq>(condition) || is::<Xor>(condition) || is::<Sub>(condition) {
+            let selector = Self::selector_operand(first, second?)?;
+            let on_jump = is::<Eq>(condition) != negated;
+            Some(Self::Selector { selector, on_jump })
+        } else if is::<Lt>(condition) || is::<Gt>(condition) {
+            // The constant is compared with `CALLDATASIZE` such that the comparison holds
+            // for short call data
+            let (size, constant) = if is::<Lt>(condition) {
+                (first, second?)
+            } else {
+                (second?, first)
+            };
+            let is_selector_size =
+                push_value(constant).and_then(|v| usize::try_from(v).ok()) == Some(SELECTOR_SIZE);
+            let is_short_check = is::<CallDataSize>(size) && is_selector_size;
+            is_short_check.then_some(Self::ShortCallData { on_jump: !negated })
+        } else {
+            None
+        }
+    }
+
+    /// Gets the selector from the operands of a comparison, given the `first`
+    /// and `second` instructions preceding it, if one operand is a constant
+    /// selector and the other is a duplicate of the selector on the stack.
+    fn selector_operand(first: &DynOpcode, second: &DynOpcode) -> Option<[u8; SELECTOR_SIZE]> {
+        let constant = match (dup_depth(first), dup_depth(second)) {
+            (Some(2), None) => second,
+            (None, Some(1)) => first,
+            _ => return None,
+        };
+        let push = constant.as_ref().as_any().downcast_ref::<PushN>()?;
+        if usize::from(push.byte_size()) > SELECTOR_SIZE {
+            return None;
+        }
+
+        push_value(constant).map(u32::to_be_bytes)
+    }
+}
+
+/// Gets the value pushed by `opcode`, if it is a push of a value that fits in
+/// a [`u32`].
+fn push_value(opcode: &DynOpcode) -> Option<u32> {
+    let push = opcode.as_ref().as_any().downcast_ref::<PushN>()?;
+    u32::try_from(push.bytes_as_word().value_le()).ok()
+}
+
+/// Gets the depth of the stack item duplicated by `opcode`, if it is a `DUP`.
+fn dup_depth(opcode: &DynOpcode) -> Option<u8> {
+    opcode.as_ref().as_any().downcast_ref::<DupN>().map(DupN::n)
+}
+
+/// Checks whether `opcode` is of the concrete type `T`.
+fn is<T: 'static>(opcode: &DynOpcode) -> bool {
+    opcode.as_ref().as_any().is::<T>()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bytecode,
+        cfg::dispatcher::SelectorTable,
+        disassembly::InstructionStream,
+        opcode::{arithmetic, control, logic, memory},
+    };
+
+    /// Recognises the dispatcher in the provided `bytes`.
+    fn table_for(bytes: &[u8]) -> SelectorTable {
+        let instructions = InstructionStream::try_from(bytes).expect("Disassembly failed");
+        SelectorTable::new(&instructions)
+    }
+
+    #[test]
+    fn recognises_linear_solidity_dispatcher() {
+        let table = table_for(&bytecode![
+            memory::PushN::new(1, vec![0x04]).unwrap(), // 0x00
+            memory::CallDataSize,                       // 0x02
+            logic::Lt,                                  // 0x03
+            memory::PushN::new(1, vec![0x23]).unwrap(), // 0x04
+            control::JumpI,                             // 0x06
+            memory::Push0,                              // 0x07
+            memory::CallDataLoad,                       // 0x08
+            memory::PushN::new(1, vec![0xe0]).unwrap(), // 0x09
+            logic::Shr,                                 // 0x0b
+            memory::DupN::new(1).unwrap(),              // 0x0c
+            memory::PushN::new(4, vec![0x12, 0x34, 0x56, 0x78]).unwrap(), // 0x0d
+            logic::Eq,                                  // 0x12
+            memory::PushN::new(1, vec![0x27]).unwrap(), // 0x13
+            control::JumpI,                             // 0x15
+            memory::PushN::new(4, vec![0x9a, 0xbc, 0xde, 0xf0]).unwrap(), // 0x16
+            memory::DupN::new(2).unwrap(),              // 0x1b
+            logic::Eq,                                  // 0x1c
+            memory::PushN::new(1, vec![0x29]).unwrap(), // 0x1d
+            control::JumpI,                             // 0x1f
+            memory::PushN::new(1, vec![0x23]).unwrap(), // 0x20
+            control::Jump,                              // 0x22
+            control::JumpDest,                          // 0x23
+            memory::Push0,                              // 0x24
+            memory::DupN::new(1).unwrap(),              // 0x25
+            control::Revert,                            // 0x26
+            control::JumpDest,                          // 0x27
+            control::Stop,                              // 0x28
+            control::JumpDest,                          // 0x29
+            control::Stop,                              // 0x2a
+        ]);
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.entry([0x12, 0x34, 0x56, 0x78]), Some(0x27));
+        assert_eq!(table.entry([0x9a, 0xbc, 0xde, 0xf0]), Some(0x29));
+        assert_eq!(table.fallback, Some(0x23));
+        assert_eq!(table.receive, None);
+    }
+
+    #[test]
+    fn recognises_binary_search_splits() {
+        let table = table_for(&bytecode![
+            memory::Push0,                                                // 0x00
+            memory::CallDataLoad,                                         // 0x01
+            memory::PushN::new(1, vec![0xe0]).unwrap(),                   // 0x02
+            logic::Shr,                                                   // 0x04
+            memory::DupN::new(1).unwrap(),                                // 0x05
+            memory::PushN::new(4, vec![0x50, 0x00, 0x00, 0x00]).unwrap(), // 0x06
+            logic::Gt,                                                    // 0x0b
+            memory::PushN::new(1, vec![0x1b]).unwrap(),                   // 0x0c
+            control::JumpI,                                               // 0x0e
+            memory::DupN::new(1).unwrap(),                                // 0x0f
+            memory::PushN::new(4, vec![0x10, 0x00, 0x00, 0x00]).unwrap(), // 0x10
+            logic::Eq,                                                    // 0x15
+            memory::PushN::new(1, vec![0x27]).unwrap(),                   // 0x16
+            control::JumpI,                                               // 0x18
+            memory::Push0,                                                // 0x19
+            control::Revert,                                              // 0x1a
+            control::JumpDest,                                            // 0x1b
+            memory::DupN::new(1).unwrap(),                                // 0x1c
+            memory::PushN::new(4, vec![0x60, 0x00, 0x00, 0x00]).unwrap(), // 0x1d
+            logic::Eq,                                                    // 0x22
+            memory::PushN::new(1, vec![0x29]).unwrap(),                   // 0x23
+            control::JumpI,                                               // 0x25
+            control::Stop,                                                // 0x26
+            control::JumpDest,                                            // 0x27
+            control::Stop,                                                // 0x28
+            control::JumpDest,                                            // 0x29
+            control::Stop,                                                // 0x2a
+        ]);
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.entry([0x10, 0x00, 0x00, 0x00]), Some(0x27));
+        assert_eq!(table.entry([0x60, 0x00, 0x00, 0x00]), Some(0x29));
+        assert_eq!(table.entry([0x50, 0x00, 0x00, 0x00]), None);
+    }
+
+    #[test]
+    fn recognises_vyper_not_equal_chain() {
+        let table = table_for(&bytecode![
+            memory::Push0,                                                // 0x00
+            memory::CallDataLoad,                                         // 0x01
+            memory::PushN::new(1, vec![0xe0]).unwrap(),                   // 0x02
+            logic::Shr,                                                   // 0x04
+            memory::PushN::new(4, vec![0x12, 0x34, 0x56, 0x78]).unwrap(), // 0x05
+            memory::DupN::new(2).unwrap(),                                // 0x0a
+            logic::Xor,                                                   // 0x0b
+            memory::PushN::new(1, vec![0x11]).unwrap(),                   // 0x0c
+            control::JumpI,                                               // 0x0e
+            memory::Push0,                                                // 0x0f
+            control::Stop,                                                // 0x10
+            control::JumpDest,                                            // 0x11
+            memory::PushN::new(4, vec![0x9a, 0xbc, 0xde, 0xf0]).unwrap(), // 0x12
+            memory::DupN::new(2).unwrap(),                                // 0x17
+            arithmetic::Sub,                                              // 0x18
+            memory::PushN::new(1, vec![0x1e]).unwrap(),                   // 0x19
+            control::JumpI,                                               // 0x1b
+            memory::Push0,                                                // 0x1c
+            control::Stop,                                                // 0x1d
+            control::JumpDest,                                            // 0x1e
+            control::Stop,                                                // 0x1f
+        ]);
+
+        assert_eq!(table.len(), 2);
+        assert_eq!(table.entry([0x12, 0x34, 0x56, 0x78]), Some(0x0f));
+        assert_eq!(table.entry([0x9a, 0xbc, 0xde, 0xf0]), Some(0x1c));
+    }
+
+    #[test]
+    fn recognises_receive_and_fallback() {
+        let table = table_for(&bytecode![
+            memory::PushN::new(1, vec![0x04]).unwrap(), // 0x00
+            memory::CallDataSize,                       // 0x02
+            logic::Lt,                                  // 0x03
+            memory::PushN::new(1, vec![0x0a]).unwrap(), // 0x04
+            control::JumpI,                             // 0x06
+            memory::Push0,                              // 0x07
+            memory::DupN::new(1).unwrap(),              // 0x08
+            control::Revert,                            // 0x09
+            control::JumpDest,                          // 0x0a
+            memory::CallDataSize,                       // 0x0b
+            memory::PushN::new(1, vec![0x10]).unwrap(), // 0x0c
+            control::JumpI,                             // 0x0e
+            control::Stop,                              // 0x0f
+            control::JumpDest,                          // 0x10
+            control::Stop,                              // 0x11
+        ]);
+
+        assert!(table.is_empty());
+        assert_eq!(table.fallback, Some(0x10));
+        assert_eq!(table.receive, Some(0x0f));
+    }
+
+    #[test]
+    fn ignores_comparisons_with_non_selector_constants() {
+        let table = table_for(&bytecode![
+            memory::CallDataSize,                                               // 0x00
+            memory::DupN::new(1).unwrap(),                                      // 0x01
+            memory::PushN::new(5, vec![0x01, 0x02, 0x03, 0x04, 0x05]).unwrap(), // 0x02
+            logic::Eq,                                                          // 0x08
+            memory::PushN::new(1, vec![0x0c]).unwrap(),                         // 0x09
+            control::JumpI,                                                     // 0x0b
+            control::JumpDest,                                                  // 0x0c
+            control::Stop,                                                      // 0x0d
+        ]);
+
+        assert!(table.is_empty());
+        assert_eq!(table.fallback, None);
+    }
+}
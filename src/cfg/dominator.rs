@@ -0,0 +1,211 @@
+//! This module contains the computation of the dominance relation over the
+//! blocks of a [`ControlFlowGraph`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::cfg::ControlFlowGraph;
+
+/// The dominance relation over the blocks of a [`ControlFlowGraph`].
+///
+/// A block `a` dominates a block `b` if every path from the entry of the graph
+/// to `b` passes through `a`. Only the statically-known edges of the graph are
+/// considered, and so blocks that are only reachable through dynamic jumps are
+/// treated as unreachable.
+///
+/// The relation is computed using the iterative algorithm described in "A
+/// Simple, Fast Dominance Algorithm" by Cooper, Harvey and Kennedy.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Dominators {
+    /// The immediate dominator of each reachable block, where the entry block
+    /// is its own immediate dominator.
+    immediate: BTreeMap<u32, u32>,
+}
+
+impl Dominators {
+    /// Computes the dominance relation over the blocks of `graph`.
+    #[must_use]
+    pub fn new(graph: &ControlFlowGraph) -> Self {
+        let Some(entry) = graph.entry().map(|b| b.start) else {
+            let immediate = BTreeMap::new();
+            return Self { immediate };
+        };
+
+        // Number the reachable blocks in reverse post-order
+        let order = Self::reverse_post_order(graph, entry);
+        let index: BTreeMap<u32, usize> = order.iter().enumerate().map(|(i, b)| (*b, i)).collect();
+        let predecessors: Vec<Vec<usize>> = order
+            .iter()
+            .map(|block| {
+                graph
+                    .predecessors(*block)
+                    .into_iter()
+                    .filter_map(|p| index.get(&p).copied())
+                    .collect()
+            })
+            .collect();
+
+        let mut dominators: Vec<Option<usize>> = vec![None; order.len()];
+        dominators[0] = Some(0);
+
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for block in 1..order.len() {
+                let new_dominator = predecessors[block]
+                    .iter()
+                    .copied()
+                    .filter(|p| dominators[*p].is_some())
+                    .reduce(|a, b| Self::intersect(&dominators, a, b));
+
+                if new_dominator.is_some() && dominators[block] != new_dominator {
+                    dominators[block] = new_dominator;
+                    changed = true;
+                }
+            }
+        }
+
+        let immediate = dominators
+            .into_iter()
+            .enumerate()
+            .filter_map(|(block, dominator)| dominator.map(|d| (order[block], order[d])))
+            .collect();
+
+        Self { immediate }
+    }
+
+    /// Gets the immediate dominator of the block starting at `block`.
+    ///
+    /// Returns [`None`] if `block` is the entry block or is not reachable from
+    /// the entry block.
+    #[must_use]
+    pub fn immediate_dominator(&self, block: u32) -> Option<u32> {
+        self.immediate.get(&block).copied().filter(|d| *d != block)
+    }
+
+    /// Checks whether the block starting at `block` is statically reachable
+    /// from the entry block.
+    #[must_use]
+    pub fn is_reachable(&self, block: u32) -> bool {
+        self.immediate.contains_key(&block)
+    }
+
+    /// Checks whether the block starting at `dominator` dominates the block
+    /// starting at `block`.
+    ///
+    /// Every reachable block dominates itself, and unreachable blocks neither
+    /// dominate nor are dominated by any block.
+    #[must_use]
+    pub fn dominates(&self, dominator: u32, block: u32) -> bool {
+        if !self.is_reachable(dominator) {
+            return false;
+        }
+
+        let mut current = block;
+        loop {
+            if current == dominator {
+                return true;
+            }
+            match self.immediate_dominator(current) {
+                Some(next) => current = next,
+                None => return false,
+            }
+        }
+    }
+
+    /// Gets the start offsets of all the blocks that dominate the block
+    /// starting at `block`, including itself.
+    #[must_use]
+    pub fn dominators_of(&self, block: u32) -> BTreeSet<u32> {
+        let mut result = BTreeSet::new();
+        if !self.is_reachable(block) {
+            return result;
+        }
+
+        let mut current = Some(block);
+        while let Some(b) = current {
+            result.insert(b);
+            current = self.immediate_dominator(b);
+        }
+
+        result
+    }
+
+    /// Finds the closest common dominator of the blocks with reverse post-order
+    /// indices `a` and `b`.
+    fn intersect(dominators: &[Option<usize>], mut a: usize, mut b: usize) -> usize {
+        while a != b {
+            while a > b {
+                a = dominators[a].expect("Processed blocks have a dominator");
+            }
+            while b > a {
+                b = dominators[b].expect("Processed blocks have a dominator");
+            }
+        }
+
+        a
+    }
+
+    /// Computes the reverse post-order of the blocks in `graph` that are
+    /// reachable from `entry`.
+    fn reverse_post_order(graph: &ControlFlowGraph, entry: u32) -> Vec<u32> {
+        let mut visited = BTreeSet::from([entry]);
+        let mut order = Vec::new();
+        let mut stack = vec![(entry, graph.successors(entry))];
+
+        while let Some((block, successors)) = stack.last_mut() {
+            if let Some(next) = successors.pop() {
+                if visited.insert(next) {
+                    let next_successors = graph.successors(next);
+                    stack.push((next, next_successors));
+                }
+            } else {
+                order.push(*block);
+                stack.pop();
+            }
+        }
+
+        order.reverse();
+        order
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bytecode,
+        cfg::ControlFlowGraph,
+        disassembly::InstructionStream,
+        opcode::{control, memory},
+    };
+
+    #[test]
+    fn computes_dominators_for_diamond() {
+        let bytes = bytecode![
+            memory::CallDataSize,                       // 0x00
+            memory::PushN::new(1, vec![0x08]).unwrap(), // 0x01
+            control::JumpI,                             // 0x03
+            memory::PushN::new(1, vec![0x0a]).unwrap(), // 0x04
+            control::Jump,                              // 0x06
+            control::Invalid::default(),                // 0x07
+            control::JumpDest,                          // 0x08
+            memory::Pop,                                // 0x09
+            control::JumpDest,                          // 0x0a
+            control::Stop,                              // 0x0b
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice()).unwrap();
+        let graph = ControlFlowGraph::new(&instructions);
+        let dominators = graph.dominators();
+
+        assert_eq!(dominators.immediate_dominator(0x00), None);
+        assert_eq!(dominators.immediate_dominator(0x04), Some(0x00));
+        assert_eq!(dominators.immediate_dominator(0x08), Some(0x00));
+        assert_eq!(dominators.immediate_dominator(0x0a), Some(0x00));
+        assert!(dominators.dominates(0x00, 0x0a));
+        assert!(!dominators.dominates(0x08, 0x0a));
+        assert!(!dominators.is_reachable(0x07));
+        assert_eq!(
+            dominators.dominators_of(0x0a).into_iter().collect::<Vec<_>>(),
+            vec![0x00, 0x0a]
+        );
+    }
+}
@@ -0,0 +1,235 @@
+//! This module contains the detection of the natural loops in a
+//! [`ControlFlowGraph`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::cfg::{dominator::Dominators, ControlFlowGraph, EdgeKind};
+
+/// A natural loop in a [`ControlFlowGraph`].
+///
+/// A loop is formed by one or more back-edges, which are edges from a block
+/// (a latch) to a block that dominates it (the header). The body of the loop
+/// consists of the header, and every block that can reach a latch without
+/// passing through the header.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Loop {
+    /// The start offset of the block through which the loop is entered.
+    pub header: u32,
+
+    /// The start offsets of the blocks that jump back to the header.
+    pub latches: BTreeSet<u32>,
+
+    /// The start offsets of all the blocks in the loop, including the header
+    /// and the latches.
+    pub body: BTreeSet<u32>,
+}
+
+impl Loop {
+    /// Finds all of the natural loops in `graph`, ordered by the offsets of
+    /// their headers, using the provided `dominators` for the graph.
+    ///
+    /// Back-edges that share a header are combined into a single loop.
+    #[must_use]
+    pub fn find_all(graph: &ControlFlowGraph, dominators: &Dominators) -> Vec<Self> {
+        let mut latches: BTreeMap<u32, BTreeSet<u32>> = BTreeMap::new();
+        for edge in graph.edges() {
+            if let Some(target) = edge.target_block() {
+                if dominators.dominates(target, edge.source) {
+                    latches.entry(target).or_default().insert(edge.source);
+                }
+            }
+        }
+
+        latches
+            .into_iter()
+            .map(|(header, latches)| {
+                let body = Self::body(graph, header, &latches);
+                Self {
+                    header,
+                    latches,
+                    body,
+                }
+            })
+            .collect()
+    }
+
+    /// Checks whether the block starting at `block` is part of the loop.
+    #[must_use]
+    pub fn contains(&self, block: u32) -> bool {
+        self.body.contains(&block)
+    }
+
+    /// Gets the start offsets of the blocks outside the loop to which control
+    /// can statically pass from inside the loop.
+    #[must_use]
+    pub fn exits(&self, graph: &ControlFlowGraph) -> BTreeSet<u32> {
+        self.body
+            .iter()
+            .flat_map(|b| graph.successors(*b))
+            .filter(|b| !self.contains(*b))
+            .collect()
+    }
+
+    /// Computes the body of the loop with the provided `header` and `latches`.
+    fn body(graph: &ControlFlowGraph, header: u32, latches: &BTreeSet<u32>) -> BTreeSet<u32> {
+        let mut body = BTreeSet::from([header]);
+        let mut worklist: Vec<u32> = latches.iter().copied().collect();
+
+        while let Some(block) = worklist.pop() {
+            if body.insert(block) {
+                worklist.extend(graph.predecessors(block));
+            }
+        }
+
+        body
+    }
+}
+
+/// The static classification of the jumps in a [`ControlFlowGraph`] by
+/// whether they close a natural loop.
+///
+/// Only jumps with a statically-known target that are statically reachable
+/// from the entry block can be classified directly. This excludes the jumps in
+/// code that is only reached through dynamic jumps, such as the code that
+/// follows the return from an internal function, which are instead classified
+/// by whether their target is the header of a loop.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct BackEdges {
+    /// Whether each classified jump closes a loop, keyed by the offset of the
+    /// jump instruction and the offset of its target.
+    jumps: BTreeMap<(u32, u32), bool>,
+
+    /// Whether each target of a classified jump is the header of a loop.
+    headers: BTreeMap<u32, bool>,
+}
+
+impl BackEdges {
+    /// Classifies the jumps in `graph`.
+    #[must_use]
+    pub fn new(graph: &ControlFlowGraph) -> Self {
+        let dominators = graph.dominators();
+        let jumps = graph
+            .edges()
+            .iter()
+            .filter(|edge| edge.kind != EdgeKind::FallThrough)
+            .filter(|edge| dominators.is_reachable(edge.source))
+            .filter_map(|edge| {
+                let target = edge.target_block()?;
+                let jump = graph.block(edge.source)?.last;
+                Some(((jump, target), dominators.dominates(target, edge.source)))
+            })
+            .collect::<BTreeMap<_, _>>();
+        let mut headers: BTreeMap<u32, bool> = BTreeMap::new();
+        for (&(_, target), &closes_loop) in &jumps {
+            *headers.entry(target).or_default() |= closes_loop;
+        }
+
+        Self { jumps, headers }
+    }
+
+    /// Checks whether the jump at `jump` to `target` closes the natural loop
+    /// with its header at `target`.
+    ///
+    /// A jump that could not be classified itself closes a loop if its target
+    /// is the header of a loop, as it can then only be a latch of that loop.
+    ///
+    /// Returns [`None`] if neither the jump nor its target could be classified
+    /// statically.
+    #[must_use]
+    pub fn closes_loop(&self, jump: u32, target: u32) -> Option<bool> {
+        self.jumps
+            .get(&(jump, target))
+            .or_else(|| self.headers.get(&target))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::{
+        bytecode,
+        cfg::{loops::BackEdges, ControlFlowGraph},
+        disassembly::InstructionStream,
+        opcode::{control, memory},
+    };
+
+    #[test]
+    fn finds_loop_formed_by_back_edge() {
+        let bytes = bytecode![
+            memory::PushN::new(1, vec![0x00]).unwrap(), // 0x00
+            control::JumpDest,                          // 0x02
+            memory::CallDataSize,                       // 0x03
+            memory::PushN::new(1, vec![0x0b]).unwrap(), // 0x04
+            control::JumpI,                             // 0x06
+            memory::PushN::new(1, vec![0x02]).unwrap(), // 0x07
+            control::Jump,                              // 0x09
+            control::Invalid::default(),                // 0x0a
+            control::JumpDest,                          // 0x0b
+            control::Stop,                              // 0x0c
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice()).unwrap();
+        let graph = ControlFlowGraph::new(&instructions);
+        let loops = graph.loops();
+
+        assert_eq!(loops.len(), 1);
+        let found = &loops[0];
+        assert_eq!(found.header, 0x02);
+        assert_eq!(found.latches, BTreeSet::from([0x07]));
+        assert_eq!(found.body, BTreeSet::from([0x02, 0x07]));
+        assert_eq!(found.exits(&graph), BTreeSet::from([0x0b]));
+    }
+
+    #[test]
+    fn finds_no_loops_in_acyclic_code() {
+        let bytes = bytecode![
+            memory::CallDataSize,
+            memory::PushN::new(1, vec![0x05]).unwrap(),
+            control::JumpI,
+            control::Stop,
+            control::JumpDest,
+            control::Stop,
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice()).unwrap();
+        let graph = ControlFlowGraph::new(&instructions);
+
+        assert!(graph.loops().is_empty());
+    }
+
+    #[test]
+    fn classifies_only_back_edges_as_closing_loops() {
+        let bytes = bytecode![
+            memory::PushN::new(1, vec![0x05]).unwrap(), // 0x00
+            control::Jump,                              // 0x02
+            control::JumpDest,                          // 0x03
+            control::Jump,                              // 0x04
+            control::JumpDest,                          // 0x05
+            memory::CallDataSize,                       // 0x06
+            memory::PushN::new(1, vec![0x05]).unwrap(), // 0x07
+            control::JumpI,                             // 0x09
+            memory::PushN::new(1, vec![0x11]).unwrap(), // 0x0a
+            memory::PushN::new(1, vec![0x03]).unwrap(), // 0x0c
+            control::Jump,                              // 0x0e
+            control::Invalid::default(),                // 0x0f
+            control::Invalid::default(),                // 0x10
+            control::JumpDest,                          // 0x11
+            memory::PushN::new(1, vec![0x05]).unwrap(), // 0x12
+            control::Jump,                              // 0x14
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice()).unwrap();
+        let back_edges = BackEdges::new(&ControlFlowGraph::new(&instructions));
+
+        // The loop at 0x05 is closed by the jump at 0x09, but not entered by it
+        assert_eq!(back_edges.closes_loop(0x09, 0x05), Some(true));
+        assert_eq!(back_edges.closes_loop(0x02, 0x05), Some(false));
+
+        // The backwards call to the function at 0x03 does not close a loop
+        assert_eq!(back_edges.closes_loop(0x0e, 0x03), Some(false));
+
+        // The jumps after the dynamic return from it are classified by their targets
+        assert_eq!(back_edges.closes_loop(0x14, 0x05), Some(true));
+        assert_eq!(back_edges.closes_loop(0x14, 0x03), Some(false));
+        assert_eq!(back_edges.closes_loop(0x14, 0x11), None);
+    }
+}
@@ -0,0 +1,502 @@
+//! This module contains the [`ControlFlowGraph`], a static approximation of the
+//! control flow of an [`InstructionStream`].
+//!
+//! Where the [`crate::vm::VM`] discovers control flow dynamically as it
+//! executes, the control-flow graph is built purely from the structure of the
+//! bytecode. It splits the instructions into [`BasicBlock`]s, and connects
+//! these with [`Edge`]s for fall-through, for jumps whose targets are pushed
+//! immediately before the jump, and for the jumps whose targets cannot be
+//! determined statically.
+//!
+//! Information about the dominance relation over the graph, and the loops
+//! formed by its back-edges, can be obtained from
+//! [`ControlFlowGraph::dominators`] and [`ControlFlowGraph::loops`]
+//! respectively. The functions exposed by the contract can be recognised from
+//! its dispatcher using [`dispatcher::SelectorTable`].
+
+pub mod dispatcher;
+pub mod dominator;
+pub mod loops;
+
+use std::{collections::BTreeMap, ops::Range};
+
+use crate::{
+    cfg::{dominator::Dominators, loops::Loop},
+    disassembly::InstructionStream,
+    opcode::{
+        control::{Invalid, Jump, JumpDest, JumpI, Return, Revert, Stop},
+        environment::SelfDestruct,
+        memory::PushN,
+        DynOpcode,
+    },
+};
+
+/// A basic block is a maximal sequence of instructions that is always entered
+/// at its first instruction and always exited after its last instruction.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct BasicBlock {
+    /// The byte offset of the first instruction in the block, which is also
+    /// used to identify the block.
+    pub start: u32,
+
+    /// The byte offset immediately after the last instruction in the block,
+    /// including any immediate data of that instruction.
+    pub end: u32,
+
+    /// The byte offset of the last instruction in the block.
+    pub last: u32,
+}
+
+impl BasicBlock {
+    /// Constructs a new block spanning from `start` to `end`, with its last
+    /// instruction at `last`.
+    #[must_use]
+    pub fn new(start: u32, end: u32, last: u32) -> Self {
+        Self { start, end, last }
+    }
+
+    /// Gets the range of byte offsets covered by the block.
+    #[must_use]
+    pub fn range(&self) -> Range<u32> {
+        self.start..self.end
+    }
+
+    /// Checks whether the instruction at `offset` is part of this block.
+    #[must_use]
+    pub fn contains(&self, offset: u32) -> bool {
+        self.range().contains(&offset)
+    }
+
+    /// Gets the instructions in the block, paired with their byte offsets,
+    /// from the `instructions` from which the block was built.
+    ///
+    /// The padding that follows push instructions is not included.
+    #[must_use]
+    pub fn instructions<'a>(
+        &self,
+        instructions: &'a InstructionStream,
+    ) -> Vec<(u32, &'a DynOpcode)> {
+        let opcodes = instructions.instructions();
+        let mut result = Vec::new();
+        let mut offset = self.start;
+        while offset < self.end {
+            let Some(opcode) = opcodes.get(offset as usize) else {
+                break;
+            };
+            result.push((offset, opcode));
+            offset += ControlFlowGraph::instruction_size(opcode);
+        }
+
+        result
+    }
+}
+
+/// The ways in which control can pass from one block to another.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EdgeKind {
+    /// Control passes to the following block without a jump, either because
+    /// the following block starts with a `JUMPDEST` or because a `JUMPI` was
+    /// not taken.
+    FallThrough,
+
+    /// Control passes to the target of an unconditional `JUMP`.
+    Jump,
+
+    /// Control passes to the target of a `JUMPI` whose condition holds.
+    ConditionalJump,
+}
+
+/// The target of an [`Edge`] in the control-flow graph.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EdgeTarget {
+    /// The edge passes control to the block that starts at the given offset.
+    Block(u32),
+
+    /// The edge is a jump whose target is not pushed immediately before it,
+    /// and hence can only be resolved during execution.
+    Dynamic,
+}
+
+/// An edge between two blocks in the control-flow graph.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Edge {
+    /// The start offset of the block from which control passes.
+    pub source: u32,
+
+    /// The block to which control passes.
+    pub target: EdgeTarget,
+
+    /// The way in which control passes along the edge.
+    pub kind: EdgeKind,
+}
+
+impl Edge {
+    /// Gets the start offset of the target block, if the target of the edge is
+    /// statically known.
+    #[must_use]
+    pub fn target_block(&self) -> Option<u32> {
+        match self.target {
+            EdgeTarget::Block(target) => Some(target),
+            EdgeTarget::Dynamic => None,
+        }
+    }
+
+    /// Checks whether the target of the edge can only be resolved during
+    /// execution.
+    #[must_use]
+    pub fn is_dynamic(&self) -> bool {
+        self.target == EdgeTarget::Dynamic
+    }
+}
+
+/// A static approximation of the control flow of an [`InstructionStream`].
+///
+/// # Static Jumps
+///
+/// A jump is resolved statically when its target is pushed by the instruction
+/// immediately preceding it in the same block, as is the case for the vast
+/// majority of the jumps emitted by the Solidity and Vyper compilers. Jumps
+/// whose statically-known target is not a `JUMPDEST` are invalid, and hence
+/// produce no edge at all.
+///
+/// All other jumps produce an edge with a target of [`EdgeTarget::Dynamic`].
+///
+/// # Data Regions
+///
+/// The regions of the stream that contain data (see
+/// [`InstructionStream::data_regions`]) are not part of any block, and hence
+/// any `JUMPDEST`-like bytes within them are not valid jump targets.
+///
+/// # Block Identity
+///
+/// Blocks are identified by the byte offset of their first instruction. The
+/// entry block of the graph always starts at offset `0`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ControlFlowGraph {
+    /// The blocks in the graph, keyed by their start offset.
+    blocks: BTreeMap<u32, BasicBlock>,
+
+    /// The edges between the blocks in the graph.
+    edges: Vec<Edge>,
+}
+
+impl ControlFlowGraph {
+    /// Builds the control-flow graph for the provided `instructions`.
+    ///
+    /// # Panics
+    ///
+    /// If the length of the instruction stream exceeds [`u32::MAX`]. This
+    /// cannot happen for a stream constructed through disassembly.
+    #[must_use]
+    pub fn new(instructions: &InstructionStream) -> Self {
+        let opcodes = instructions.instructions();
+        let length = u32::try_from(opcodes.len())
+            .unwrap_or_else(|_| panic!("Bytecode length cannot exceed u32::MAX"));
+
+        let mut blocks = BTreeMap::new();
+        let mut edges = Vec::new();
+        let mut block_start = 0;
+        let mut previous: Option<(u32, &DynOpcode)> = None;
+        let mut offset = 0;
+
+        while offset < length {
+            // Data regions are never executed, and so are not part of any block
+            if let Some(region) = instructions.data_regions().iter().find(|r| r.contains(&offset)) {
+                if let Some((last, _)) = previous {
+                    blocks.insert(block_start, BasicBlock::new(block_start, offset, last));
+                }
+                offset = region.end;
+                block_start = offset;
+                previous = None;
+                continue;
+            }
+
+            let opcode = &opcodes[offset as usize];
+            let next = offset + Self::instruction_size(opcode);
+
+            // A jump destination always starts a new block, into which the prior block
+            // falls through
+            if Self::is::<JumpDest>(opcode) && offset != block_start {
+                if let Some((last, _)) = previous {
+                    blocks.insert(block_start, BasicBlock::new(block_start, offset, last));
+                }
+                edges.push(Edge {
+                    source: block_start,
+                    target: EdgeTarget::Block(offset),
+                    kind:   EdgeKind::FallThrough,
+                });
+                block_start = offset;
+                previous = None;
+            }
+
+            let is_jump = Self::is::<Jump>(opcode);
+            let is_conditional_jump = Self::is::<JumpI>(opcode);
+            if is_jump || is_conditional_jump {
+                let target = Self::static_jump_target(opcodes, previous);
+                if let Some(target) = target {
+                    let kind = if is_jump {
+                        EdgeKind::Jump
+                    } else {
+                        EdgeKind::ConditionalJump
+                    };
+                    edges.push(Edge {
+                        source: block_start,
+                        target,
+                        kind,
+                    });
+                }
+                if is_conditional_jump && next < length {
+                    edges.push(Edge {
+                        source: block_start,
+                        target: EdgeTarget::Block(next),
+                        kind:   EdgeKind::FallThrough,
+                    });
+                }
+            }
+
+            if is_jump || is_conditional_jump || Self::is_halting(opcode) {
+                blocks.insert(block_start, BasicBlock::new(block_start, next, offset));
+                block_start = next;
+                previous = None;
+            } else {
+                previous = Some((offset, opcode));
+            }
+
+            offset = next;
+        }
+
+        // Execution that runs off the end of the code halts
+        if let Some((last, _)) = previous {
+            blocks.insert(block_start, BasicBlock::new(block_start, length, last));
+        }
+
+        Self { blocks, edges }
+    }
+
+    /// Gets the block at which execution enters the graph, if the graph has
+    /// any blocks.
+    #[must_use]
+    pub fn entry(&self) -> Option<&BasicBlock> {
+        self.blocks.get(&0)
+    }
+
+    /// Gets the blocks in the graph, ordered by their start offsets.
+    pub fn blocks(&self) -> impl Iterator<Item = &BasicBlock> {
+        self.blocks.values()
+    }
+
+    /// Gets the number of blocks in the graph.
+    #[must_use]
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Gets the block that starts at the provided `start` offset, if it exists.
+    #[must_use]
+    pub fn block(&self, start: u32) -> Option<&BasicBlock> {
+        self.blocks.get(&start)
+    }
+
+    /// Gets the block that contains the instruction at `offset`, if it exists.
+    #[must_use]
+    pub fn block_containing(&self, offset: u32) -> Option<&BasicBlock> {
+        self.blocks
+            .range(..=offset)
+            .next_back()
+            .map(|(_, block)| block)
+            .filter(|block| block.contains(offset))
+    }
+
+    /// Gets all of the edges in the graph.
+    #[must_use]
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    /// Gets the edges that leave the block starting at `block`.
+    pub fn edges_from(&self, block: u32) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(move |e| e.source == block)
+    }
+
+    /// Gets the start offsets of the blocks to which control can statically
+    /// pass from the block starting at `block`.
+    #[must_use]
+    pub fn successors(&self, block: u32) -> Vec<u32> {
+        self.edges_from(block).filter_map(Edge::target_block).collect()
+    }
+
+    /// Gets the start offsets of the blocks from which control can statically
+    /// pass to the block starting at `block`.
+    #[must_use]
+    pub fn predecessors(&self, block: u32) -> Vec<u32> {
+        self.edges
+            .iter()
+            .filter(|e| e.target == EdgeTarget::Block(block))
+            .map(|e| e.source)
+            .collect()
+    }
+
+    /// Gets the edges whose targets can only be resolved during execution.
+    pub fn dynamic_edges(&self) -> impl Iterator<Item = &Edge> {
+        self.edges.iter().filter(|e| e.is_dynamic())
+    }
+
+    /// Computes the dominance relation over the blocks of the graph.
+    #[must_use]
+    pub fn dominators(&self) -> Dominators {
+        Dominators::new(self)
+    }
+
+    /// Computes the natural loops in the graph, ordered by the offsets of
+    /// their headers.
+    #[must_use]
+    pub fn loops(&self) -> Vec<Loop> {
+        Loop::find_all(self, &self.dominators())
+    }
+
+    /// Gets the number of bytes occupied by `opcode` in the bytecode.
+    fn instruction_size(opcode: &DynOpcode) -> u32 {
+        match opcode.as_ref().as_any().downcast_ref::<PushN>() {
+            Some(push) => 1 + u32::from(push.byte_size()),
+            None => 1,
+        }
+    }
+
+    /// Gets the statically-known target of a jump, given the instruction that
+    /// `previous`ly executed in the same block.
+    ///
+    /// Returns [`None`] if the target is known but is not a valid jump
+    /// destination.
+    fn static_jump_target(
+        opcodes: &[DynOpcode],
+        previous: Option<(u32, &DynOpcode)>,
+    ) -> Option<EdgeTarget> {
+        let Some(push) = previous.and_then(|(_, op)| op.as_ref().as_any().downcast_ref::<PushN>())
+        else {
+            return Some(EdgeTarget::Dynamic);
+        };
+
+        let target = u32::try_from(push.bytes_as_word().value_le()).ok()?;
+        let is_valid = opcodes.get(target as usize).map_or(false, Self::is::<JumpDest>);
+        is_valid.then_some(EdgeTarget::Block(target))
+    }
+
+    /// Checks whether `opcode` halts execution.
+    fn is_halting(opcode: &DynOpcode) -> bool {
+        Self::is::<Stop>(opcode)
+            || Self::is::<Return>(opcode)
+            || Self::is::<Revert>(opcode)
+            || Self::is::<Invalid>(opcode)
+            || Self::is::<SelfDestruct>(opcode)
+    }
+
+    /// Checks whether `opcode` is of the concrete type `T`.
+    fn is<T: 'static>(opcode: &DynOpcode) -> bool {
+        opcode.as_ref().as_any().is::<T>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bytecode,
+        cfg::{ControlFlowGraph, Edge, EdgeKind, EdgeTarget},
+        disassembly::InstructionStream,
+        opcode::{control, memory},
+    };
+
+    /// Builds the control-flow graph for the provided `bytes`.
+    fn graph_for(bytes: &[u8]) -> ControlFlowGraph {
+        let instructions = InstructionStream::try_from(bytes).expect("Disassembly failed");
+        ControlFlowGraph::new(&instructions)
+    }
+
+    #[test]
+    fn splits_straight_line_code_into_single_block() {
+        let graph = graph_for(&bytecode![
+            memory::CallDataSize,
+            memory::PushN::new(1, vec![0x00]).unwrap(),
+            memory::SStore,
+            control::Stop,
+        ]);
+
+        assert_eq!(graph.block_count(), 1);
+        let entry = graph.entry().expect("No entry block");
+        assert_eq!(entry.range(), 0..5);
+        assert_eq!(entry.last, 4);
+        assert!(graph.edges().is_empty());
+    }
+
+    #[test]
+    fn resolves_static_jumps_and_fall_through() {
+        let graph = graph_for(&bytecode![
+            memory::CallDataSize,                       // 0x00
+            memory::PushN::new(1, vec![0x07]).unwrap(), // 0x01
+            control::JumpI,                             // 0x03
+            control::Stop,                              // 0x04
+            control::Invalid::default(),                // 0x05
+            control::Invalid::default(),                // 0x06
+            control::JumpDest,                          // 0x07
+            control::Stop,                              // 0x08
+        ]);
+
+        assert_eq!(
+            graph.blocks().map(|b| b.start).collect::<Vec<_>>(),
+            vec![0x00, 0x04, 0x05, 0x06, 0x07]
+        );
+        assert!(graph.edges().contains(&Edge {
+            source: 0x00,
+            target: EdgeTarget::Block(0x07),
+            kind:   EdgeKind::ConditionalJump,
+        }));
+        assert!(graph.edges().contains(&Edge {
+            source: 0x00,
+            target: EdgeTarget::Block(0x04),
+            kind:   EdgeKind::FallThrough,
+        }));
+        assert_eq!(graph.successors(0x04), Vec::<u32>::new());
+        assert_eq!(graph.predecessors(0x07), vec![0x00]);
+        assert_eq!(graph.block_containing(0x02).map(|b| b.start), Some(0x00));
+    }
+
+    #[test]
+    fn records_dynamic_jumps() {
+        let graph = graph_for(&bytecode![
+            memory::CallDataSize,
+            control::Jump,
+            control::JumpDest,
+            control::Stop,
+        ]);
+
+        let dynamic: Vec<_> = graph.dynamic_edges().collect();
+        assert_eq!(dynamic.len(), 1);
+        assert_eq!(dynamic[0].source, 0x00);
+        assert_eq!(dynamic[0].kind, EdgeKind::Jump);
+        assert_eq!(graph.predecessors(0x02), Vec::<u32>::new());
+    }
+
+    #[test]
+    fn omits_edges_for_invalid_static_jumps() {
+        let graph = graph_for(&bytecode![
+            memory::PushN::new(1, vec![0x04]).unwrap(),
+            control::Jump,
+            control::JumpDest,
+            control::Stop,
+        ]);
+
+        assert!(graph.edges_from(0x00).next().is_none());
+    }
+
+    #[test]
+    fn falls_through_into_jump_destinations() {
+        let graph = graph_for(&bytecode![
+            memory::CallDataSize,
+            control::JumpDest,
+            control::Stop,
+        ]);
+
+        assert_eq!(graph.block_count(), 2);
+        assert_eq!(graph.successors(0x00), vec![0x01]);
+        assert_eq!(graph.edges()[0].kind, EdgeKind::FallThrough);
+    }
+}
@@ -6,6 +6,14 @@
 /// This is specified in [EIP-170](https://eips.ethereum.org/EIPS/eip-170).
 pub const CONTRACT_MAXIMUM_SIZE_BYTES: usize = 24_576;
 
+/// The number of bytes used to encode the length of the CBOR metadata section
+/// that compilers append to the end of contract bytecode.
+pub const METADATA_LENGTH_SUFFIX_BYTES: usize = 2;
+
+/// The number of hexadecimal characters occupied by an unlinked library
+/// placeholder (e.g. `__$<34 hex characters>$__`) in compiler output.
+pub const LIBRARY_PLACEHOLDER_LENGTH_CHARS: usize = 40;
+
 /// The maximum amount of gas that can be spent in a given block on the EVM.
 pub const BLOCK_GAS_LIMIT: usize = 30_000_000;
 
@@ -58,6 +66,10 @@ pub const PUSH_OPCODE_MAX_BYTES: u8 = 32;
 /// The maximum stack depth for the EVM.
 pub const MAXIMUM_STACK_DEPTH: usize = 1024;
 
+/// The number of items beneath the top of the stack that instructions can
+/// reach, as with `DUP16` and `SWAP16`.
+pub const MAXIMUM_STACK_REACH: u32 = 16;
+
 /// The width of word on the EVM in bits.
 pub const WORD_SIZE_BITS: usize = 256;
 
@@ -73,9 +85,15 @@ pub const BOOL_WIDTH_BITS: usize = BYTE_SIZE_BITS;
 /// The bit-width of an address type.
 pub const ADDRESS_WIDTH_BITS: usize = 160;
 
+/// The byte-width of an address type.
+pub const ADDRESS_WIDTH_BYTES: usize = ADDRESS_WIDTH_BITS / BYTE_SIZE_BITS;
+
 /// The bit-width of a selector type.
 pub const SELECTOR_WIDTH_BITS: usize = 32;
 
+/// The byte-width of a selector type.
+pub const SELECTOR_WIDTH_BYTES: usize = SELECTOR_WIDTH_BITS / BYTE_SIZE_BITS;
+
 /// The bit-width of a function type.
 pub const FUNCTION_WIDTH_BITS: usize = ADDRESS_WIDTH_BITS + SELECTOR_WIDTH_BITS;
 
@@ -91,6 +109,22 @@ pub const DEFAULT_CONDITIONAL_JUMP_PER_TARGET_FORK_LIMIT: usize = 50;
 /// culled.
 pub const DEFAULT_VALUE_SIZE_LIMIT: usize = 250;
 
+/// The default maximum number of nested internal function calls that the
+/// virtual machine tracks for each thread.
+///
+/// This is zero by default, disabling the tracking of calls, as it changes
+/// which paths are explored compared to treating calls as ordinary jumps.
+pub const DEFAULT_MAXIMUM_CALL_DEPTH: usize = 0;
+
+/// The default maximum number of words that a jump destination can take for
+/// the virtual machine to follow the jump to each of them.
+pub const DEFAULT_JUMP_DESTINATION_SET_LIMIT: usize = 16;
+
+/// The number of instructions ahead of each queued thread that the
+/// coverage-guided scheduler inspects for storage accesses that have not yet
+/// been executed.
+pub const SCHEDULER_LOOKAHEAD_INSTRUCTIONS: usize = 64;
+
 /// The default number of loop iterations the extractor will wait before polling
 /// the watchdog.
 pub const DEFAULT_WATCHDOG_POLL_LOOP_ITERATIONS: usize = 100;
@@ -105,3 +139,55 @@ pub const SOLIDITY_STRING_POINTER: usize = 0x20;
 /// presence of non-fatal errors. See [`crate::vm::Config`] for more information
 /// on what this entails.
 pub const DEFAULT_PERMISSIVE_ERRORS_ENABLED: bool = false;
+
+/// The default value for whether the virtual machine uses the path conditions
+/// of its threads to skip branches that can never be taken.
+///
+/// This is disabled by default, as the type inference currently relies on some
+/// evidence that is only found by executing infeasible paths.
+pub const DEFAULT_PATH_CONDITION_SOLVING_ENABLED: bool = false;
+
+/// The default value for whether the virtual machine merges threads of
+/// execution that converge on the same jump destination.
+pub const DEFAULT_STATE_MERGING_ENABLED: bool = false;
+
+/// The default value for whether the virtual machine stops threads that reach
+/// an abstract state that has already been explored.
+pub const DEFAULT_THREAD_DEDUPLICATION_ENABLED: bool = false;
+
+/// The default value for whether the virtual machine widens the values that
+/// are carried between the iterations of loops.
+pub const DEFAULT_LOOP_WIDENING_ENABLED: bool = false;
+
+/// The default number of workers over which the virtual machine spreads the
+/// execution of its threads.
+///
+/// A single worker executes all threads sequentially, on the calling thread.
+pub const DEFAULT_WORKER_THREADS: usize = 1;
+
+/// The default value for whether the virtual machine records a trace of the
+/// events that occur during execution.
+pub const DEFAULT_TRACE_RECORDING_ENABLED: bool = false;
+
+/// The maximum number of characters used to summarise a symbolic value in the
+/// trace of execution.
+pub const TRACE_VALUE_SUMMARY_MAX_CHARS: usize = 256;
+
+/// The default value for whether the virtual machine summarises internal
+/// functions that only manipulate the stack.
+pub const DEFAULT_INTERNAL_CALL_SUMMARIES_ENABLED: bool = false;
+
+/// The minimum number of nodes that each shard of a value arena records before
+/// it sweeps out the nodes that are no longer in use.
+///
+/// After each sweep, the arena waits until it has recorded as many nodes again
+/// as remained in use before sweeping once more, so the cost of sweeping is
+/// amortised over the nodes that are interned.
+pub const MINIMUM_ARENA_SWEEP_INTERVAL: usize = 1024;
+
+/// The number of shards into which a value arena splits its nodes.
+///
+/// Each shard has its own lock, so the workers of the virtual machine only
+/// contend for the arena when they intern structures in the same shard. The
+/// sweep interval applies to each shard separately.
+pub const ARENA_SHARD_COUNT: usize = 16;
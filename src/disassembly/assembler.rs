@@ -0,0 +1,289 @@
+//! This module contains a simple assembler for turning textual EVM assembly
+//! into bytecode.
+//!
+//! # Syntax
+//!
+//! The assembler accepts one instruction per line, written as the mnemonic of
+//! the opcode (as given by [`crate::opcode::Opcode::as_text_code`]) followed by
+//! its operand, if any. Mnemonics are case-insensitive.
+//!
+//! - `PUSHn` instructions take an operand that is either a number, written in
+//!   decimal or in hexadecimal with a `0x` prefix, or the name of a label.
+//! - `INVALID` instructions may take the byte that they should be encoded as,
+//!   defaulting to the designated `INVALID` byte.
+//! - A label is defined by writing its name followed by a colon, either on its
+//!   own line or before an instruction, and refers to the offset of the next
+//!   instruction.
+//! - Everything after a `;` on a line is a comment.
+//! - A byte offset followed by a colon (such as `0x0010:`) at the start of a
+//!   line is ignored, allowing the output of [`super::listing::Listing`] to be
+//!   assembled directly.
+//!
+//! ```text
+//!     PUSH1 0x04
+//!     CALLDATASIZE
+//!     LT
+//!     PUSH2 fallback ; Jump if the call data is too short
+//!     JUMPI
+//!     STOP
+//! fallback:
+//!     JUMPDEST
+//!     PUSH0
+//!     DUP1
+//!     REVERT
+//! ```
+
+use std::collections::HashMap;
+
+use ethnum::U256;
+
+use crate::{
+    constant::{PUSH_OPCODE_BASE_VALUE, PUSH_OPCODE_MAX_BYTES},
+    disassembly::disassembler,
+    error::{
+        container::Locatable,
+        disassembly::{Error, Result},
+    },
+    extractor::chain::Chain,
+    opcode::{control::Invalid, Opcode},
+};
+
+/// The character that starts a comment.
+const COMMENT_START: char = ';';
+
+/// Assembles the provided textual `source` into bytecode.
+///
+/// Errors are located at the byte offset of the instruction that caused them.
+///
+/// # Errors
+///
+/// If `source` contains an unknown mnemonic, an invalid operand, or a reference
+/// to a label that is not defined exactly once, or if it assembles to no
+/// bytecode at all.
+pub fn assemble(source: &str) -> Result<Vec<u8>> {
+    let mnemonics = mnemonics();
+    let mut bytes = Vec::new();
+    let mut labels: HashMap<&str, usize> = HashMap::new();
+    let mut references: Vec<(usize, usize, &str)> = Vec::new();
+
+    for line in source.lines() {
+        let line = line.split(COMMENT_START).next().unwrap_or_default();
+        let mut tokens = line.split_whitespace().peekable();
+
+        // Strip any leading offset and label definitions
+        while let Some(name) = tokens.peek().and_then(|t| t.strip_suffix(':')) {
+            let location = offset(bytes.len())?;
+            if name.starts_with("0x") {
+                // Offsets are recomputed during assembly
+            } else if !is_label(name) {
+                return Err(Error::InvalidLabel(name.into()).locate(location));
+            } else if labels.insert(name, bytes.len()).is_some() {
+                return Err(Error::DuplicateLabel(name.into()).locate(location));
+            }
+            tokens.next();
+        }
+
+        let Some(mnemonic) = tokens.next() else {
+            continue;
+        };
+        let mnemonic = mnemonic.to_uppercase();
+        let operand = tokens.next();
+        let location = offset(bytes.len())?;
+        if let Some(extra) = tokens.next() {
+            return Err(Error::InvalidOperand {
+                operand:  extra.into(),
+                mnemonic: mnemonic.clone(),
+            }
+            .locate(location));
+        }
+
+        let Some(&byte) = mnemonics.get(mnemonic.as_str()) else {
+            return Err(Error::UnknownMnemonic(mnemonic).locate(location));
+        };
+        let invalid_operand = |operand: &str| {
+            Error::InvalidOperand {
+                operand:  operand.into(),
+                mnemonic: mnemonic.clone(),
+            }
+            .locate(location)
+        };
+
+        match (byte, operand) {
+            (b, Some(operand)) if is_push(b) => {
+                let size = usize::from(b - PUSH_OPCODE_BASE_VALUE);
+                bytes.push(b);
+                if let Some(value) = parse_number(operand) {
+                    let encoded =
+                        encode_immediate(value, size).ok_or_else(|| invalid_operand(operand))?;
+                    bytes.extend(encoded);
+                } else if is_label(operand) {
+                    references.push((bytes.len(), size, operand));
+                    bytes.resize(bytes.len() + size, 0);
+                } else {
+                    return Err(invalid_operand(operand));
+                }
+            }
+            (b, None) if is_push(b) => {
+                return Err(Error::MissingOperand(mnemonic).locate(location));
+            }
+            (b, Some(operand)) if b == Invalid::default().byte => {
+                let value = parse_number(operand)
+                    .and_then(|v| u8::try_from(v).ok())
+                    .ok_or_else(|| invalid_operand(operand))?;
+                bytes.push(value);
+            }
+            (_, Some(operand)) => return Err(invalid_operand(operand)),
+            (b, None) => bytes.push(b),
+        }
+    }
+
+    // Now that all the labels are known, their offsets can be filled in
+    for (position, size, label) in references {
+        let location = offset(position - 1)?;
+        let target = *labels
+            .get(label)
+            .ok_or_else(|| Error::UndefinedLabel(label.into()).locate(location))?;
+        let encoded = encode_immediate(U256::from(offset(target)?), size).ok_or_else(|| {
+            Error::InvalidOperand {
+                operand:  label.into(),
+                mnemonic: format!("PUSH{size}"),
+            }
+            .locate(location)
+        })?;
+        bytes[position..position + size].copy_from_slice(&encoded);
+    }
+
+    if bytes.is_empty() {
+        return Err(Error::EmptyBytecode.locate(0));
+    }
+
+    Ok(bytes)
+}
+
+/// Builds the table from the mnemonic of each opcode to its byte.
+///
+/// The table is built by disassembling each possible byte, ensuring that the
+/// assembler always agrees with the disassembler.
+fn mnemonics() -> HashMap<String, u8> {
+    let mut table = HashMap::new();
+    table.insert(Invalid::default().as_text_code(), Invalid::default().byte);
+
+    for byte in 0..=u8::MAX {
+        // Pad the byte such that pushes are always complete
+        let mut code = vec![0; 1 + PUSH_OPCODE_MAX_BYTES as usize];
+        code[0] = byte;
+
+        let Ok(opcodes) = disassembler::disassemble(&code, &Chain::default(), &[]) else {
+            continue;
+        };
+        if !opcodes[0].as_ref().as_any().is::<Invalid>() {
+            table.insert(opcodes[0].as_text_code(), byte);
+        }
+    }
+
+    table
+}
+
+/// Checks whether `byte` is one of the push opcodes that take an immediate.
+fn is_push(byte: u8) -> bool {
+    (PUSH_OPCODE_BASE_VALUE + 1..=PUSH_OPCODE_BASE_VALUE + PUSH_OPCODE_MAX_BYTES).contains(&byte)
+}
+
+/// Checks whether `token` is a valid label name.
+fn is_label(token: &str) -> bool {
+    let mut chars = token.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Parses `token` as a number in decimal, or in hexadecimal if it is prefixed
+/// with `0x`.
+fn parse_number(token: &str) -> Option<U256> {
+    match token.strip_prefix("0x") {
+        Some(hex) => U256::from_str_radix(hex, 16).ok(),
+        None => U256::from_str_radix(token, 10).ok(),
+    }
+}
+
+/// Encodes `value` as a big-endian immediate of `size` bytes, if it fits.
+fn encode_immediate(value: U256, size: usize) -> Option<Vec<u8>> {
+    let bytes = value.to_be_bytes();
+    let (excess, immediate) = bytes.split_at(bytes.len() - size);
+    excess.iter().all(|b| *b == 0).then(|| immediate.to_vec())
+}
+
+/// Converts the byte offset `position` into the location of an error.
+fn offset(position: usize) -> Result<u32> {
+    u32::try_from(position).map_err(|_| Error::BytecodeTooLarge.locate(u32::MAX))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bytecode,
+        disassembly::assembler::assemble,
+        error::disassembly::Error,
+        opcode::{control, logic, memory},
+    };
+
+    #[test]
+    fn assembles_mnemonics_and_resolves_labels() -> anyhow::Result<()> {
+        let source = "
+            push1 4          ; The size of a selector
+            CALLDATASIZE
+            LT
+            PUSH2 fallback
+            JUMPI
+            STOP
+        fallback: JUMPDEST
+            PUSH32 0xff
+            INVALID 0x0c
+        ";
+
+        assert_eq!(
+            assemble(source)?,
+            bytecode![
+                memory::PushN::new(1, vec![0x04]).unwrap(),
+                memory::CallDataSize,
+                logic::Lt,
+                memory::PushN::new(2, vec![0x00, 0x09]).unwrap(),
+                control::JumpI,
+                control::Stop,
+                control::JumpDest,
+                memory::PushN::new(32, [vec![0x00; 31], vec![0xff]].concat()).unwrap(),
+                control::Invalid::new(0x0c),
+            ]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn rejects_unknown_mnemonics() {
+        let error = assemble("STOP\nPUHS1 0x01").unwrap_err();
+        assert_eq!(error.location, 1);
+        assert_eq!(error.payload, Error::UnknownMnemonic("PUHS1".into()));
+    }
+
+    #[test]
+    fn rejects_immediates_that_do_not_fit() {
+        let error = assemble("PUSH1 0x0100").unwrap_err();
+        assert_eq!(
+            error.payload,
+            Error::InvalidOperand {
+                operand:  "0x0100".into(),
+                mnemonic: "PUSH1".into(),
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_undefined_and_duplicate_labels() {
+        let undefined = assemble("PUSH1 target\nJUMP").unwrap_err();
+        assert_eq!(undefined.payload, Error::UndefinedLabel("target".into()));
+
+        let duplicate = assemble("a: JUMPDEST\na: STOP").unwrap_err();
+        assert_eq!(duplicate.location, 1);
+        assert_eq!(duplicate.payload, Error::DuplicateLabel("a".into()));
+    }
+}
@@ -7,7 +7,7 @@
 //! on parser combinators from a library like [`nom`](https://docs.rs/nom), for
 //! now it makes sense to stick to a simple system.
 
-use std::rc::Rc;
+use std::{ops::Range, sync::Arc};
 
 use crate::{
     constant::{
@@ -21,6 +21,7 @@ use crate::{
         container::Locatable,
         disassembly::{Error, Result},
     },
+    extractor::chain::Chain,
     opcode::{
         arithmetic as arith,
         control,
@@ -44,14 +45,30 @@ use crate::{
 /// execution to revert if ever actually executed.
 ///
 /// This is much simpler than trying to strip the metadata beforehand, and is
-/// more robust against changes in the metadata format.
+/// more robust against changes in the metadata format. Where the metadata is
+/// well-formed, however, it can be removed before disassembly by constructing
+/// the contract using
+/// [`crate::extractor::contract::Contract::new_stripping_metadata`].
+///
+/// # Chain Availability
+///
+/// Opcodes that are not available on the provided `chain` (such as `PUSH0`
+/// before the Shanghai fork of Ethereum) are also translated to
+/// [`control::Invalid`].
+///
+/// # Data Regions
+///
+/// Every byte in the provided `data` regions is translated to
+/// [`control::Invalid`] without being interpreted as an opcode, ensuring that
+/// data cannot contain jump destinations, and that the immediate of a bogus
+/// push in the data cannot swallow the code that follows it.
 ///
 /// # Errors
 ///
 /// When one of the `bytes` cannot be parsed as a valid opcode, or when `bytes`
 /// is empty or too large.
 #[allow(clippy::too_many_lines)] // Splitting the function up brings no benefit
-pub fn disassemble(bytes: &[u8]) -> Result<Vec<DynOpcode>> {
+pub fn disassemble(bytes: &[u8], chain: &Chain, data: &[Range<u32>]) -> Result<Vec<DynOpcode>> {
     if bytes.is_empty() {
         return Err(Error::EmptyBytecode.locate(0));
     }
@@ -95,6 +112,12 @@ pub fn disassemble(bytes: &[u8]) -> Result<Vec<DynOpcode>> {
                 push_size = 0;
                 last_push = 0;
             }
+        } else if data.iter().any(|region| region.contains(&instruction_pointer)) {
+            // Data is never executed, so there is no need to interpret it.
+            add_op(ops, control::Invalid::new(*byte));
+        } else if !chain.supports_opcode(*byte) {
+            // Opcodes that the chain does not support are invalid when executed.
+            add_op(ops, control::Invalid::new(*byte));
         } else {
             // Now we can match the next byte and process the opcode.
             match byte {
@@ -150,6 +173,8 @@ pub fn disassemble(bytes: &[u8]) -> Result<Vec<DynOpcode>> {
                 0x46 => add_op(ops, env::ChainId),
                 0x47 => add_op(ops, env::SelfBalance),
                 0x48 => add_op(ops, env::BaseFee),
+                0x49 => add_op(ops, env::BlobHash),
+                0x4a => add_op(ops, env::BlobBaseFee),
                 0x50 => add_op(ops, mem::Pop),
                 0x51 => add_op(ops, mem::MLoad),
                 0x52 => add_op(ops, mem::MStore),
@@ -162,6 +187,9 @@ pub fn disassemble(bytes: &[u8]) -> Result<Vec<DynOpcode>> {
                 0x59 => add_op(ops, mem::MSize),
                 0x5a => add_op(ops, env::Gas),
                 0x5b => add_op(ops, control::JumpDest),
+                0x5c => add_op(ops, mem::TLoad),
+                0x5d => add_op(ops, mem::TStore),
+                0x5e => add_op(ops, mem::MCopy),
                 0x5f => add_op(ops, mem::Push0),
                 0x60..=0x7f => {
                     last_push = *byte;
@@ -222,5 +250,5 @@ pub fn disassemble(bytes: &[u8]) -> Result<Vec<DynOpcode>> {
 
 /// Adds an operation `elem` to the array of opcodes `ops`.
 fn add_op<T: Opcode>(ops: &mut Vec<DynOpcode>, elem: T) {
-    ops.push(Rc::new(elem));
+    ops.push(Arc::new(elem));
 }
@@ -0,0 +1,128 @@
+//! This module contains the printer for annotated textual listings of an
+//! [`InstructionStream`].
+
+use std::fmt::{Display, Formatter};
+
+use crate::{
+    disassembly::InstructionStream,
+    opcode::{
+        control::{Invalid, JumpDest},
+        memory::PushN,
+    },
+};
+
+/// An annotated textual listing of an [`InstructionStream`], as produced by
+/// [`InstructionStream::listing`].
+///
+/// Each instruction is printed on its own line, prefixed by its byte offset
+/// in the bytecode. Push instructions are followed by their immediates in
+/// hexadecimal, and every `JUMPDEST` is preceded by a label of the form
+/// `label_XXXX` naming its offset. The padding that follows each push
+/// instruction in the stream is not printed.
+///
+/// Invalid instructions that do not use the designated `INVALID` byte are
+/// printed along with their byte, such that the listing can always be turned
+/// back into the same bytecode by [`InstructionStream::assemble`].
+///
+/// ```text
+/// 0x0000: PUSH1 0x04
+/// 0x0002: JUMP
+/// 0x0003: INVALID
+/// label_0004:
+/// 0x0004: JUMPDEST
+/// 0x0005: STOP
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Listing<'a> {
+    /// The instructions being listed.
+    instructions: &'a InstructionStream,
+}
+
+impl<'a> Listing<'a> {
+    /// Creates a new listing of the provided `instructions`.
+    #[must_use]
+    pub fn new(instructions: &'a InstructionStream) -> Self {
+        Self { instructions }
+    }
+
+    /// Gets the name of the label for a jump destination at `offset`.
+    #[must_use]
+    pub fn label_for(offset: usize) -> String {
+        format!("label_{offset:04x}")
+    }
+}
+
+impl Display for Listing<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let opcodes = self.instructions.instructions();
+        let mut offset = 0;
+
+        while let Some(opcode) = opcodes.get(offset) {
+            let as_any = opcode.as_ref().as_any();
+            if as_any.is::<JumpDest>() {
+                writeln!(f, "{}:", Self::label_for(offset))?;
+            }
+
+            write!(f, "{offset:#06x}: {}", opcode.as_text_code())?;
+            if let Some(push) = as_any.downcast_ref::<PushN>() {
+                // The bytes of the immediate are stored in little-endian order
+                let bytes: Vec<u8> = push.bytes_data().iter().rev().copied().collect();
+                write!(f, " 0x{}", hex::encode(bytes))?;
+                offset += push.bytes_data().len();
+            } else if let Some(invalid) = as_any.downcast_ref::<Invalid>() {
+                if *invalid != Invalid::default() {
+                    write!(f, " {:#04x}", invalid.byte)?;
+                }
+            }
+            writeln!(f)?;
+
+            offset += 1;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bytecode,
+        disassembly::InstructionStream,
+        extractor::chain::Chain,
+        opcode::{control, memory},
+    };
+
+    #[test]
+    fn prints_offsets_immediates_and_labels() {
+        let bytes = bytecode![
+            memory::PushN::new(2, vec![0x00, 0x06]).unwrap(),
+            control::Jump,
+            control::Invalid::default(),
+            control::Invalid::new(0x0c),
+            control::JumpDest,
+            control::Stop,
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(
+            instructions.listing().to_string(),
+            "0x0000: PUSH2 0x0006\n0x0003: JUMP\n0x0004: INVALID\n0x0005: INVALID \
+             0x0c\nlabel_0006:\n0x0006: JUMPDEST\n0x0007: STOP\n"
+        );
+    }
+
+    #[test]
+    fn round_trips_through_assembler() -> anyhow::Result<()> {
+        let bytes = hex::decode(
+            "6080604052348015600e575f80fd5b50600436106030575f3560e01c8063871d757314603457\
+             5b5f80fd5b005bfea2646970667358",
+        )?;
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let listing = instructions.listing().to_string();
+        let assembled = InstructionStream::assemble(&listing, &Chain::default())?;
+
+        assert_eq!(assembled.as_bytecode(), bytes);
+
+        Ok(())
+    }
+}
@@ -2,15 +2,20 @@
 //! that represents a sequence of bytecode instructions and provides utilities
 //! for implementing it.
 
+pub mod assembler;
 mod disassembler;
+pub mod listing;
+pub mod sections;
 
-use std::{ops::Range, rc::Rc};
+use std::{ops::Range, sync::Arc};
 
 use downcast_rs::Downcast;
 use hex::FromHexError;
 
 use crate::{
+    disassembly::listing::Listing,
     error::{container::Locatable, disassembly, disassembly::Error, execution},
+    extractor::chain::Chain,
     opcode::{DynOpcode, Opcode},
 };
 
@@ -61,10 +66,56 @@ pub const INSTRUCTION_STREAM_MAX_SIZE: u32 = u32::MAX;
 #[derive(Clone, Debug)]
 pub struct InstructionStream {
     /// The sequence of [`Opcode`]s.
-    instructions: Rc<Vec<DynOpcode>>,
+    instructions: Arc<Vec<DynOpcode>>,
+
+    /// The regions of the stream that contain data rather than code.
+    data: Arc<Vec<Range<u32>>>,
 }
 
 impl InstructionStream {
+    /// Disassembles the provided `bytes` into an instruction stream, treating
+    /// any opcodes that are not available on the provided `chain` as invalid.
+    ///
+    /// The regions of the `bytes` that contain data rather than code are found
+    /// using [`sections::find_data_regions`], and are disassembled as invalid
+    /// instructions.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the `bytes` cannot be disassembled.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the disassembled instructions do not encode back to `bytes`.
+    /// This is a programmer bug.
+    pub fn disassemble(bytes: &[u8], chain: &Chain) -> disassembly::Result<Self> {
+        if u32::try_from(bytes.len()).is_err() {
+            return Err(Error::BytecodeTooLarge.locate(u32::MAX));
+        }
+        let data = sections::find_data_regions(bytes, chain);
+        let instructions = Arc::new(disassembler::disassemble(bytes, chain, &data)?);
+        let data = Arc::new(data);
+        let result = Self { instructions, data };
+
+        // An assertion that will be disabled in production builds, but a good sanity
+        // check that disassembly didn't go wrong
+        assert_eq!(result.as_bytecode().as_slice(), bytes);
+        Ok(result)
+    }
+
+    /// Assembles the provided textual `source` (see [`assembler`]) and
+    /// disassembles the result into an instruction stream, treating any
+    /// opcodes that are not available on the provided `chain` as invalid.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the `source` cannot be assembled, or if the
+    /// assembled bytes cannot be disassembled.
+    pub fn assemble(source: &str, chain: &Chain) -> disassembly::Result<Self> {
+        let bytes = assembler::assemble(source)?;
+        Self::disassemble(bytes.as_slice(), chain)
+    }
+
     /// Gets a new thread of execution as a view on the instruction stream.
     ///
     /// Each view has its independent `instruction_pointer` and can represent a
@@ -96,6 +147,26 @@ impl InstructionStream {
         self.instructions.len()
     }
 
+    /// Gets the instructions in the stream, where the index of each
+    /// instruction is its byte offset in the bytecode.
+    #[must_use]
+    pub fn instructions(&self) -> &[DynOpcode] {
+        self.instructions.as_slice()
+    }
+
+    /// Gets the regions of the stream that contain data rather than code, in
+    /// ascending order of offset.
+    #[must_use]
+    pub fn data_regions(&self) -> &[Range<u32>] {
+        self.data.as_slice()
+    }
+
+    /// Checks whether the byte at `offset` is part of a data region.
+    #[must_use]
+    pub fn is_data(&self, offset: u32) -> bool {
+        self.data.iter().any(|region| region.contains(&offset))
+    }
+
     /// Converts the instructions in the instruction stream to their
     /// corresponding bytecode.
     ///
@@ -105,20 +176,25 @@ impl InstructionStream {
     pub fn as_bytecode(&self) -> Vec<u8> {
         self.instructions.iter().flat_map(|opcode| opcode.encode()).collect()
     }
+
+    /// Gets an annotated textual listing of the instructions in the stream,
+    /// which can be turned back into the same stream using
+    /// [`Self::assemble`].
+    #[must_use]
+    pub fn listing(&self) -> Listing<'_> {
+        Listing::new(self)
+    }
 }
 
 /// An [`InstructionStream`] is usually created from a byte array of bytecode.
+///
+/// The bytecode is disassembled for the latest version of Ethereum main-net. To
+/// disassemble for a different chain use [`InstructionStream::disassemble`].
 impl<'a> TryFrom<&'a [u8]> for InstructionStream {
     type Error = disassembly::LocatedError;
 
     fn try_from(value: &'a [u8]) -> Result<Self, Self::Error> {
-        let instructions = Rc::new(disassembler::disassemble(value)?);
-        let result = Self { instructions };
-
-        // An assertion that will be disabled in production builds, but a good sanity
-        // check that disassembly didn't go wrong
-        assert_eq!(result.as_bytecode().as_slice(), value);
-        Ok(result)
+        Self::disassemble(value, &Chain::default())
     }
 }
 
@@ -160,7 +236,7 @@ impl From<InstructionStream> for Vec<u8> {
     }
 }
 
-impl From<InstructionStream> for Rc<Vec<DynOpcode>> {
+impl From<InstructionStream> for Arc<Vec<DynOpcode>> {
     fn from(value: InstructionStream) -> Self {
         value.instructions
     }
@@ -180,7 +256,7 @@ pub struct ExecutionThread {
     instruction_pointer: u32,
 
     /// The sequence of [`Opcode`]s.
-    instructions: Rc<Vec<DynOpcode>>,
+    instructions: Arc<Vec<DynOpcode>>,
 }
 
 impl ExecutionThread {
@@ -337,10 +413,13 @@ impl ExecutionThread {
 
 #[cfg(test)]
 mod test {
+    use std::ops::Range;
+
     use crate::{
         constant::{DUP_OPCODE_BASE_VALUE, LOG_OPCODE_BASE_VALUE, SWAP_OPCODE_BASE_VALUE},
         disassembly::InstructionStream,
         error::disassembly,
+        extractor::chain::{version::EthereumVersion, Chain},
         opcode::{control, memory, Opcode},
     };
 
@@ -372,6 +451,29 @@ mod test {
         assert_eq!(bytecode, bytes);
     }
 
+    #[test]
+    fn disassembles_data_regions_as_invalid() {
+        // PUSH1 0x04 PUSH1 0x09 PUSH0 CODECOPY PUSH1 0x0d JUMP <data> JUMPDEST STOP
+        let bytes = hex::decode("600460095f39600d56fe01027f5b00").unwrap();
+        let instructions = InstructionStream::try_from(bytes.as_slice()).unwrap();
+
+        assert_eq!(
+            instructions.data_regions(),
+            &[Range {
+                start: 0x09,
+                end:   0x0d,
+            }]
+        );
+        assert!(instructions.is_data(0x0c));
+        assert!(!instructions.is_data(0x0d));
+
+        // The bogus push in the data does not swallow the code that follows it
+        let opcodes = instructions.instructions();
+        assert_eq!(opcodes[0x0c].encode(), control::Invalid::new(0x7f).encode());
+        assert!(opcodes[0x0d].as_ref().as_any().is::<control::JumpDest>());
+        assert_eq!(instructions.as_bytecode(), bytes);
+    }
+
     #[test]
     fn translates_unknown_opcode_to_invalid() {
         // This opcode doesn't exist.
@@ -386,6 +488,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn translates_opcodes_unavailable_on_chain_to_invalid() {
+        // `PUSH0` was introduced in Shanghai, and `TLOAD` in Cancun.
+        let bytes: Vec<u8> = vec![0x5f, 0x5c];
+        let chain = Chain::Ethereum {
+            version: EthereumVersion::Paris,
+        };
+
+        // So they should be invalid on Paris.
+        let result = InstructionStream::disassemble(&bytes, &chain).expect("Parsing errored");
+        let thread = result.new_thread(0).unwrap();
+        assert_eq!(thread.instruction(0).unwrap().as_text_code(), "INVALID");
+        assert_eq!(thread.instruction(1).unwrap().as_text_code(), "INVALID");
+
+        // But valid on the latest version.
+        let result = InstructionStream::try_from(bytes.as_slice()).expect("Parsing errored");
+        let thread = result.new_thread(0).unwrap();
+        assert_eq!(thread.instruction(0).unwrap().as_text_code(), "PUSH0");
+        assert_eq!(thread.instruction(1).unwrap().as_text_code(), "TLOAD");
+    }
+
     #[test]
     fn emits_parse_error_on_incorrectly_encoded_hex_string() {
         // This is not actually hex-encoded.
@@ -546,9 +669,9 @@ mod test {
                 0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x10, 0x11,
                 0x12, 0x13, 0x14, 0x15, 0x16, 0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x20, 0x30,
                 0x31, 0x32, 0x33, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39, 0x3a, 0x3b, 0x3c, 0x3d, 0x3e,
-                0x3f, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x50, 0x51, 0x52, 0x53,
-                0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x5b, 0x5f, 0xf0, 0xf1, 0xf2, 0xf3, 0xf4,
-                0xf5, 0xfa, 0xfd, 0xfe, 0xff,
+                0x3f, 0x40, 0x41, 0x42, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49, 0x4a, 0x50, 0x51,
+                0x52, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59, 0x5a, 0x5b, 0x5c, 0x5d, 0x5e, 0x5f,
+                0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xfa, 0xfd, 0xfe, 0xff,
             ];
 
             bytes
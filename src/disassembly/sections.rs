@@ -0,0 +1,173 @@
+//! This module contains the analysis that splits bytecode into the regions that
+//! contain code and the regions that contain data.
+//!
+//! Compilers place a variety of data into the bytecode alongside the code
+//! itself, such as long string constants, tables of jump targets, the runtime
+//! code in creation bytecode, and the CBOR metadata appended to the end of the
+//! code. Interpreting these bytes as instructions produces bogus opcodes and
+//! fake `JUMPDEST`s, and can cause a bogus `PUSH` to swallow the start of the
+//! code that follows the data.
+
+use std::{collections::BTreeSet, ops::Range};
+
+use crate::{
+    constant::{PUSH_OPCODE_BASE_VALUE, PUSH_OPCODE_MAX_BYTES},
+    extractor::chain::Chain,
+};
+
+/// The byte of the `JUMPDEST` opcode.
+const JUMPDEST: u8 = 0x5b;
+
+/// The byte of the `CODECOPY` opcode.
+const CODECOPY: u8 = 0x39;
+
+/// Finds the regions of the provided `bytes` that contain data rather than
+/// code, in ascending order of offset.
+///
+/// # Reachability
+///
+/// Code is discovered by following control flow from the entry point, where
+/// the potential targets of any jump are all of the `JUMPDEST`s whose offsets
+/// are pushed as constants by reachable code. This covers both the static
+/// jumps and the internal function returns emitted by the Solidity and Vyper
+/// compilers. Instructions that are not available on the `chain` halt
+/// execution.
+///
+/// # Data Regions
+///
+/// A maximal run of unreachable bytes is only classified as data if it either
+/// extends to the end of the bytecode, or contains an offset that reachable
+/// code copies from using `CODECOPY`. Other unreachable bytes are left as code,
+/// as they may still be the targets of jumps that are computed during
+/// execution.
+///
+/// # Panics
+///
+/// If the length of `bytes` exceeds [`u32::MAX`]. This is validated by
+/// disassembly before the regions are computed.
+#[must_use]
+pub fn find_data_regions(bytes: &[u8], chain: &Chain) -> Vec<Range<u32>> {
+    let mut covered = vec![false; bytes.len()];
+    let mut visited = vec![false; bytes.len()];
+    let mut copied_offsets = BTreeSet::new();
+    let mut worklist = vec![0];
+
+    while let Some(start) = worklist.pop() {
+        // The values pushed by the two instructions before the current one
+        let mut recent_pushes: [Option<usize>; 2] = [None, None];
+        let mut offset = start;
+
+        while offset < bytes.len() && !visited[offset] {
+            visited[offset] = true;
+            let byte = bytes[offset];
+            let end = (offset + 1 + push_size(byte)).min(bytes.len());
+            covered[offset..end].iter_mut().for_each(|c| *c = true);
+
+            let immediate = &bytes[offset + 1..end];
+            let pushed = (!immediate.is_empty()).then(|| pushed_value(immediate)).flatten();
+            if let Some(target) = pushed.filter(|t| bytes.get(*t) == Some(&JUMPDEST)) {
+                worklist.push(target);
+            }
+
+            // The offset being copied from is the second item on the stack
+            if byte == CODECOPY {
+                copied_offsets.extend(recent_pushes[0]);
+            }
+
+            if halts(byte, chain) {
+                break;
+            }
+            recent_pushes = [recent_pushes[1], pushed];
+            offset = end;
+        }
+    }
+
+    let mut regions = Vec::new();
+    let mut offset = 0;
+    while offset < bytes.len() {
+        let run_length = covered[offset..].iter().take_while(|c| !**c).count();
+        if run_length == 0 {
+            offset += 1;
+            continue;
+        }
+
+        let run = offset..offset + run_length;
+        let is_tail = run.end == bytes.len();
+        if is_tail || copied_offsets.range(run.clone()).next().is_some() {
+            let as_u32 = |o: usize| u32::try_from(o).expect("Bytecode length exceeds u32::MAX");
+            regions.push(as_u32(run.start)..as_u32(run.end));
+        }
+        offset = run.end;
+    }
+
+    regions
+}
+
+/// Gets the number of immediate bytes that follow the opcode `byte`.
+fn push_size(byte: u8) -> usize {
+    if byte > PUSH_OPCODE_BASE_VALUE && byte <= PUSH_OPCODE_BASE_VALUE + PUSH_OPCODE_MAX_BYTES {
+        usize::from(byte - PUSH_OPCODE_BASE_VALUE)
+    } else {
+        0
+    }
+}
+
+/// Gets the value of the big-endian `immediate`, if it fits in a [`u32`].
+fn pushed_value(immediate: &[u8]) -> Option<usize> {
+    let significant: Vec<u8> = immediate.iter().copied().skip_while(|b| *b == 0).collect();
+    if significant.len() > std::mem::size_of::<u32>() {
+        return None;
+    }
+
+    let value = significant.iter().fold(0u32, |acc, b| (acc << 8) | u32::from(*b));
+    usize::try_from(value).ok()
+}
+
+/// Checks whether executing the opcode `byte` on `chain` never continues to the
+/// following instruction.
+fn halts(byte: u8, chain: &Chain) -> bool {
+    // STOP, JUMP, RETURN, REVERT, INVALID, SELFDESTRUCT
+    matches!(byte, 0x00 | 0x56 | 0xf3 | 0xfd | 0xfe | 0xff) || !chain.supports_opcode(byte)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{disassembly::sections::find_data_regions, extractor::chain::Chain};
+
+    #[test]
+    fn marks_trailing_metadata_as_data() {
+        // PUSH1 0x00 PUSH1 0x00 RETURN INVALID <metadata>
+        let bytes = hex::decode("60006000f3fea2646970667358").unwrap();
+        assert_eq!(
+            find_data_regions(&bytes, &Chain::default()),
+            vec![0x05..0x0d]
+        );
+    }
+
+    #[test]
+    fn marks_copied_constants_as_data() {
+        // PUSH1 0x04 PUSH1 0x09 PUSH0 CODECOPY PUSH1 0x0d JUMP <data> JUMPDEST STOP
+        let bytes = hex::decode("600460095f39600d567f5b7f5b5b00").unwrap();
+        assert_eq!(
+            find_data_regions(&bytes, &Chain::default()),
+            vec![0x09..0x0d]
+        );
+    }
+
+    #[test]
+    fn follows_pushed_jump_destinations() {
+        // PUSH1 0x05 PUSH1 0x07 JUMP JUMPDEST STOP JUMPDEST JUMP <metadata>
+        let bytes = hex::decode("60056007565b005b56fe01").unwrap();
+        assert_eq!(
+            find_data_regions(&bytes, &Chain::default()),
+            vec![0x09..0x0b]
+        );
+    }
+
+    #[test]
+    fn keeps_unreachable_code_that_is_not_copied() {
+        // PUSH1 0x05 JUMP STOP STOP JUMPDEST STOP
+        let bytes = hex::decode("60055600005b00").unwrap();
+        assert!(find_data_regions(&bytes, &Chain::default()).is_empty());
+    }
+}
@@ -0,0 +1,50 @@
+//! This module contains the error type that pertains to the loading of
+//! contracts from compiler artifacts.
+
+use thiserror::Error;
+
+use crate::error::container;
+
+/// Errors that occur when loading a [`crate::extractor::contract::Contract`]
+/// from a compiler artifact or hex-encoded bytecode.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+pub enum Error {
+    #[error("The artifact could not be parsed: {_0}")]
+    InvalidArtifact(String),
+
+    #[error("The artifact did not contain any {_0} bytecode")]
+    MissingBytecode(String),
+
+    #[error("No contract named {_0:?} was found in the compiler output")]
+    ContractNotFound(String),
+
+    #[error("The compiler output contains multiple contracts named {_0:?}")]
+    AmbiguousContract(String),
+
+    #[error("The provided hexadecimal input had an odd length")]
+    InvalidHexLength,
+
+    #[error("Encountered invalid hex char {_0:?} at index {_1:?}")]
+    InvalidHexCharacter(char, usize),
+
+    #[error("Encountered unlinked library placeholder {_0:?}")]
+    UnlinkedLibrary(String),
+}
+
+/// An artifact error with an associated location in the bytecode.
+pub type LocatedError = container::Located<Error>;
+
+/// The result type for functions that may return artifact errors.
+pub type Result<T> = std::result::Result<T, LocatedError>;
+
+/// Make it possible to attach locations to these errors.
+impl container::Locatable for Error {
+    type Located = LocatedError;
+
+    fn locate(self, instruction_pointer: u32) -> Self::Located {
+        container::Located {
+            location: instruction_pointer,
+            payload:  self,
+        }
+    }
+}
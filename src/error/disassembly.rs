@@ -29,6 +29,24 @@ pub enum Error {
 
     #[error("The length of the bytecode exceeded {}", u32::MAX)]
     BytecodeTooLarge,
+
+    #[error("Unknown mnemonic {_0:?} in assembly")]
+    UnknownMnemonic(String),
+
+    #[error("Invalid operand {operand:?} provided to the `{mnemonic}` opcode")]
+    InvalidOperand { operand: String, mnemonic: String },
+
+    #[error("No operand provided to the `{_0}` opcode")]
+    MissingOperand(String),
+
+    #[error("Invalid label name {_0:?}")]
+    InvalidLabel(String),
+
+    #[error("The label {_0:?} is defined more than once")]
+    DuplicateLabel(String),
+
+    #[error("The label {_0:?} is not defined")]
+    UndefinedLabel(String),
 }
 
 /// A disassembly error with an associated location in the bytecode.
@@ -44,11 +44,14 @@ pub enum Error {
     #[error("The instruction at {pointer} is not a JUMPDEST")]
     NotJumpTarget { pointer: u32 },
 
-    #[error("The instruction at {pointer} is not a JUMPI")]
+    #[error("The instruction at {pointer} is not a JUMP or JUMPI")]
     NotJumpSource { pointer: u32 },
 
     #[error("Execution was stopped by the watchdog")]
     StoppedByWatchdog,
+
+    #[error("The constructor did not return any recognisable runtime code")]
+    NoRuntimeCodeReturned,
 }
 
 /// An execution error with an associated location in the bytecode.
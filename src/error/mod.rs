@@ -2,6 +2,7 @@
 //! It also re-exports the more specific error types that are
 //! subsystem-specific.
 
+pub mod artifact;
 pub mod container;
 pub mod disassembly;
 pub mod execution;
@@ -27,6 +28,10 @@ pub type Result<T> = std::result::Result<T, Errors>;
 /// clients of the library) should be members of this enum.
 #[derive(Clone, Debug, Error)]
 pub enum Error {
+    /// Errors that come from loading contracts from compiler artifacts.
+    #[error(transparent)]
+    Artifact(#[from] artifact::Error),
+
     /// Errors that come from the disassembly process.
     #[error(transparent)]
     Disassembly(#[from] disassembly::Error),
@@ -69,6 +74,28 @@ pub type LocatedError = container::Located<Error>;
 /// A container of errors that may occur in the extractor.
 pub type Errors = container::Errors<LocatedError>;
 
+/// Allow simple conversions from located artifact errors by re-wrapping the
+/// located error around the more general payload.
+impl From<artifact::LocatedError> for LocatedError {
+    fn from(value: artifact::LocatedError) -> Self {
+        let instruction_pointer = value.location;
+        let payload = Error::from(value.payload);
+        Self {
+            location: instruction_pointer,
+            payload,
+        }
+    }
+}
+
+/// Allow simple conversions from located artifact errors by re-wrapping the
+/// located error around the more general payload in the Errors container.
+impl From<artifact::LocatedError> for Errors {
+    fn from(value: artifact::LocatedError) -> Self {
+        let re_wrapped: LocatedError = value.into();
+        re_wrapped.into()
+    }
+}
+
 /// Allow simple conversions from located disassembly errors by re-wrapping the
 /// located error around the more general payload.
 impl From<disassembly::LocatedError> for LocatedError {
@@ -0,0 +1,510 @@
+//! This module contains loaders that construct a [`Contract`] from the output
+//! of common compilers and development frameworks, as well as from raw
+//! hex-encoded bytecode.
+//!
+//! # Supported Formats
+//!
+//! - The standard-JSON output of `solc`, using [`from_solc_standard_json`].
+//! - The per-contract artifacts that Foundry writes into `out/`, using
+//!   [`from_foundry`].
+//! - The per-contract artifacts that Hardhat writes into `artifacts/`, using
+//!   [`from_hardhat`].
+//! - Raw hexadecimal bytecode, using [`from_hex`].
+//!
+//! # Library Placeholders
+//!
+//! Contracts that use external libraries are emitted with placeholders of the
+//! form `__$<34 hex characters>$__` (or `__<library name>__` for older
+//! compilers) where the address of each library will be linked in. By default
+//! these cause loading to fail with [`Error::UnlinkedLibrary`], but a
+//! placeholder address can be substituted instead by using
+//! [`Config::with_library_address`].
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+
+use crate::{
+    constant::{ADDRESS_WIDTH_BYTES, LIBRARY_PLACEHOLDER_LENGTH_CHARS},
+    error,
+    error::{
+        artifact::{Error, Result},
+        container::Locatable,
+    },
+    extractor::{
+        chain::Chain,
+        contract::{BytecodeKind, Contract},
+    },
+};
+
+/// The configuration for the artifact loaders, allowing their behaviour to be
+/// configured externally.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    /// Which of the bytecodes in the artifact should be loaded.
+    ///
+    /// Defaults to [`BytecodeKind::Runtime`].
+    pub bytecode_kind: BytecodeKind,
+
+    /// The address to substitute for any unlinked library placeholders in the
+    /// bytecode. If this is [`None`], encountering a placeholder is an error.
+    ///
+    /// Defaults to [`None`].
+    pub library_address: Option<[u8; ADDRESS_WIDTH_BYTES]>,
+
+    /// Whether the compiler metadata should be stripped from the end of
+    /// runtime bytecode. It is never stripped from creation bytecode, as it
+    /// does not occur at the end of it.
+    ///
+    /// Defaults to `true`.
+    pub strip_metadata: bool,
+}
+
+impl Config {
+    /// Sets the `bytecode_kind` config parameter to `value`.
+    #[must_use]
+    pub fn with_bytecode_kind(mut self, value: BytecodeKind) -> Self {
+        self.bytecode_kind = value;
+        self
+    }
+
+    /// Sets the `library_address` config parameter to `value`.
+    #[must_use]
+    pub fn with_library_address(mut self, value: [u8; ADDRESS_WIDTH_BYTES]) -> Self {
+        self.library_address = Some(value);
+        self
+    }
+
+    /// Sets the `strip_metadata` config parameter to `value`.
+    #[must_use]
+    pub fn with_metadata_stripping(mut self, value: bool) -> Self {
+        self.strip_metadata = value;
+        self
+    }
+}
+
+/// Creates a default artifact loader configuration.
+impl Default for Config {
+    fn default() -> Self {
+        let bytecode_kind = BytecodeKind::Runtime;
+        let library_address = None;
+        let strip_metadata = true;
+        Self {
+            bytecode_kind,
+            library_address,
+            strip_metadata,
+        }
+    }
+}
+
+/// Creates a contract on `chain` from the hex-encoded bytecode in `code`.
+///
+/// The bytecode may or may not start with the `0x` prefix, and any whitespace
+/// within it is ignored. It is interpreted as being of the kind specified in
+/// `config`.
+///
+/// # Errors
+///
+/// Returns [`Err`] if `code` is not valid hexadecimal, or if it contains an
+/// unlinked library placeholder and no `library_address` is configured.
+pub fn from_hex(code: &str, chain: Chain, config: &Config) -> error::Result<Contract> {
+    let bytecode = decode_hex(code, config)?;
+    Ok(new_contract(bytecode, chain, config))
+}
+
+/// Creates a contract on `chain` from the Foundry artifact (as found in
+/// `out/<file>.sol/<name>.json`) in `json`.
+///
+/// # Errors
+///
+/// Returns [`Err`] if `json` is not a valid Foundry artifact, if it does not
+/// contain the configured kind of bytecode, or if the bytecode cannot be
+/// decoded.
+pub fn from_foundry(json: &str, chain: Chain, config: &Config) -> error::Result<Contract> {
+    let artifact: FoundryArtifact = parse_json(json)?;
+    let bytecode = match config.bytecode_kind {
+        BytecodeKind::Runtime => artifact.deployed_bytecode,
+        BytecodeKind::Creation => artifact.bytecode,
+    };
+
+    from_bytecode_object(bytecode, chain, config)
+}
+
+/// Creates a contract on `chain` from the Hardhat artifact (as found in
+/// `artifacts/<file>.sol/<name>.json`) in `json`.
+///
+/// # Errors
+///
+/// Returns [`Err`] if `json` is not a valid Hardhat artifact, if it does not
+/// contain the configured kind of bytecode, or if the bytecode cannot be
+/// decoded.
+pub fn from_hardhat(json: &str, chain: Chain, config: &Config) -> error::Result<Contract> {
+    let artifact: HardhatArtifact = parse_json(json)?;
+    let bytecode = match config.bytecode_kind {
+        BytecodeKind::Runtime => artifact.deployed_bytecode,
+        BytecodeKind::Creation => artifact.bytecode,
+    };
+
+    from_bytecode_string(bytecode, chain, config)
+}
+
+/// Creates a contract on `chain` from the contract named `contract_name` in the
+/// `solc` standard-JSON output in `json`.
+///
+/// The `contract_name` may either be the bare name of the contract (e.g.
+/// `Token`), or may be qualified with the name of its source file (e.g.
+/// `src/Token.sol:Token`) to disambiguate between contracts of the same name.
+///
+/// # Errors
+///
+/// Returns [`Err`] if `json` is not valid `solc` standard-JSON output, if the
+/// contract cannot be unambiguously found, if it does not contain the
+/// configured kind of bytecode, or if the bytecode cannot be decoded.
+pub fn from_solc_standard_json(
+    json: &str,
+    contract_name: &str,
+    chain: Chain,
+    config: &Config,
+) -> error::Result<Contract> {
+    let output: SolcOutput = parse_json(json)?;
+    let (source_name, contract_name) = match contract_name.rsplit_once(':') {
+        Some((source_name, contract_name)) => (Some(source_name), contract_name),
+        None => (None, contract_name),
+    };
+
+    let mut candidates = output
+        .contracts
+        .into_iter()
+        .filter(|(source, _)| source_name.map_or(true, |name| name == source))
+        .filter_map(|(_, mut contracts)| contracts.remove(contract_name));
+    let contract = candidates
+        .next()
+        .ok_or(Error::ContractNotFound(contract_name.into()).locate(0))?;
+    if candidates.next().is_some() {
+        Err(Error::AmbiguousContract(contract_name.into()).locate(0))?;
+    }
+
+    let bytecode = contract.evm.and_then(|evm| match config.bytecode_kind {
+        BytecodeKind::Runtime => evm.deployed_bytecode,
+        BytecodeKind::Creation => evm.bytecode,
+    });
+
+    from_bytecode_object(bytecode, chain, config)
+}
+
+/// Creates a contract from the bytecode object (containing the hex-encoded
+/// bytecode in its `object` field) that may be present in an artifact.
+fn from_bytecode_object(
+    bytecode: Option<BytecodeObject>,
+    chain: Chain,
+    config: &Config,
+) -> error::Result<Contract> {
+    from_bytecode_string(bytecode.map(|b| b.object), chain, config)
+}
+
+/// Creates a contract from the hex-encoded bytecode that may be present in an
+/// artifact, treating empty bytecode (as emitted for interfaces and abstract
+/// contracts) as absent.
+fn from_bytecode_string(
+    bytecode: Option<String>,
+    chain: Chain,
+    config: &Config,
+) -> error::Result<Contract> {
+    let bytecode = bytecode
+        .map(|code| decode_hex(&code, config))
+        .transpose()?
+        .filter(|bytes| !bytes.is_empty())
+        .ok_or(Error::MissingBytecode(kind_name(config.bytecode_kind).into()).locate(0))?;
+
+    Ok(new_contract(bytecode, chain, config))
+}
+
+/// Constructs the contract from the decoded `bytecode` as described by
+/// `config`.
+fn new_contract(bytecode: Vec<u8>, chain: Chain, config: &Config) -> Contract {
+    match config.bytecode_kind {
+        BytecodeKind::Runtime if config.strip_metadata => {
+            Contract::new_stripping_metadata(bytecode, chain)
+        }
+        BytecodeKind::Runtime => Contract::new(bytecode, chain),
+        BytecodeKind::Creation => Contract::new_creation(bytecode, chain),
+    }
+}
+
+/// Parses the provided `json` into the artifact type `T`.
+fn parse_json<'de, T: Deserialize<'de>>(json: &'de str) -> Result<T> {
+    serde_json::from_str(json)
+        .map_err(|e| Error::InvalidArtifact(e.to_string()))
+        .locate(0)
+}
+
+/// Decodes the hex-encoded bytecode in `code`, ignoring whitespace and an
+/// optional `0x` prefix, and linking any library placeholders as specified by
+/// `config`.
+///
+/// # Errors
+///
+/// Returns [`Err`] if `code` is not valid hexadecimal, or if it contains an
+/// unlinked library placeholder and no `library_address` is configured.
+pub fn decode_hex(code: &str, config: &Config) -> Result<Vec<u8>> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    let code = code.strip_prefix("0x").unwrap_or(&code);
+    let code = link_libraries(code, config)?;
+
+    hex::decode(&code).map_err(|e| match e {
+        hex::FromHexError::InvalidHexCharacter { c, index } => {
+            Error::InvalidHexCharacter(c, index).locate(byte_offset(index))
+        }
+        _ => Error::InvalidHexLength.locate(byte_offset(code.len())),
+    })
+}
+
+/// Replaces any library placeholders in the hex-encoded `code` with the
+/// `library_address` specified in `config`.
+///
+/// # Errors
+///
+/// Returns [`Err`] if a placeholder is encountered but no `library_address` is
+/// configured.
+fn link_libraries(code: &str, config: &Config) -> Result<String> {
+    let address = config.library_address.map(hex::encode);
+    let mut linked = String::with_capacity(code.len());
+    let mut remaining = code;
+
+    // Placeholders always start on a byte boundary, and hence at an even offset
+    while let Some(index) = find_placeholder_start(remaining) {
+        let offset = code.len() - remaining.len() + index;
+        let Some(placeholder) = remaining.get(index..index + LIBRARY_PLACEHOLDER_LENGTH_CHARS)
+        else {
+            break;
+        };
+        if !placeholder.ends_with("__") {
+            break;
+        }
+        let Some(address) = &address else {
+            return Err(Error::UnlinkedLibrary(placeholder.into()).locate(byte_offset(offset)));
+        };
+
+        linked.push_str(&remaining[..index]);
+        linked.push_str(address);
+        remaining = &remaining[index + LIBRARY_PLACEHOLDER_LENGTH_CHARS..];
+    }
+    linked.push_str(remaining);
+
+    Ok(linked)
+}
+
+/// Finds the start of the first potential library placeholder in `code`, if
+/// any.
+fn find_placeholder_start(code: &str) -> Option<usize> {
+    code.as_bytes()
+        .chunks(2)
+        .position(|chunk| chunk == b"__")
+        .map(|position| position * 2)
+}
+
+/// Converts the provided index into a hex string into the offset of the
+/// corresponding byte in the bytecode.
+fn byte_offset(index: usize) -> u32 {
+    u32::try_from(index / 2).unwrap_or(u32::MAX)
+}
+
+/// Gets the human-readable name for the provided `kind` of bytecode.
+fn kind_name(kind: BytecodeKind) -> &'static str {
+    match kind {
+        BytecodeKind::Runtime => "runtime",
+        BytecodeKind::Creation => "creation",
+    }
+}
+
+/// The parts of a bytecode object in compiler output that we care about.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct BytecodeObject {
+    object: String,
+}
+
+/// The parts of a Foundry artifact that we care about.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct FoundryArtifact {
+    bytecode:          Option<BytecodeObject>,
+    deployed_bytecode: Option<BytecodeObject>,
+}
+
+/// The parts of a Hardhat artifact that we care about.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct HardhatArtifact {
+    bytecode:          Option<String>,
+    deployed_bytecode: Option<String>,
+}
+
+/// The parts of the `solc` standard-JSON output that we care about.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct SolcOutput {
+    #[serde(default)]
+    contracts: BTreeMap<String, BTreeMap<String, SolcContract>>,
+}
+
+/// The parts of a single contract in the `solc` standard-JSON output that we
+/// care about.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+struct SolcContract {
+    evm: Option<SolcEvm>,
+}
+
+/// The parts of the `evm` section of a contract in the `solc` standard-JSON
+/// output that we care about.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq)]
+#[serde(rename_all = "camelCase")]
+struct SolcEvm {
+    bytecode:          Option<BytecodeObject>,
+    deployed_bytecode: Option<BytecodeObject>,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        error,
+        error::artifact::Error,
+        extractor::{
+            artifact,
+            artifact::Config,
+            chain::{
+                version::{ChainVersion, EthereumVersion},
+                Chain,
+            },
+            contract::BytecodeKind,
+        },
+    };
+
+    /// Gets the chain used for the tests.
+    fn chain() -> Chain {
+        Chain::Ethereum {
+            version: EthereumVersion::latest(),
+        }
+    }
+
+    #[test]
+    fn decodes_hex_with_prefix_and_whitespace() -> anyhow::Result<()> {
+        let config = Config::default();
+        let bytes = artifact::decode_hex("  0x6080 6040\n52\t00 ", &config)?;
+        assert_eq!(bytes, vec![0x60, 0x80, 0x60, 0x40, 0x52, 0x00]);
+
+        let contract = artifact::from_hex("60806040", chain(), &config)?;
+        assert_eq!(contract.bytecode(), &vec![0x60, 0x80, 0x60, 0x40]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn errors_on_invalid_hex() {
+        let config = Config::default();
+        let result = artifact::decode_hex("0x60x0", &config).unwrap_err();
+        assert_eq!(result.payload, Error::InvalidHexCharacter('x', 2));
+        assert_eq!(result.location, 1);
+
+        let result = artifact::decode_hex("0x608", &config).unwrap_err();
+        assert_eq!(result.payload, Error::InvalidHexLength);
+    }
+
+    #[test]
+    fn errors_on_unlinked_library_placeholder() {
+        let placeholder = "__$4f2a94c4a5c7b6a5e7b8d9c8a7f6e5d4c3$__";
+        let code = format!("73{placeholder}3014");
+        let result = artifact::decode_hex(&code, &Config::default()).unwrap_err();
+        assert_eq!(result.payload, Error::UnlinkedLibrary(placeholder.into()));
+        assert_eq!(result.location, 1);
+    }
+
+    #[test]
+    fn links_library_placeholders_with_configured_address() -> anyhow::Result<()> {
+        let code = concat!(
+            "73__$4f2a94c4a5c7b6a5e7b8d9c8a7f6e5d4c3$__",
+            "30__LibraryName___________________________",
+            "00"
+        );
+        let config = Config::default().with_library_address([0xab; 20]);
+        let bytes = artifact::decode_hex(code, &config)?;
+
+        let mut expected = vec![0x73];
+        expected.extend([0xab; 20]);
+        expected.push(0x30);
+        expected.extend([0xab; 20]);
+        expected.push(0x00);
+        assert_eq!(bytes, expected);
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_foundry_and_hardhat_artifacts() -> anyhow::Result<()> {
+        let foundry = r#"{
+            "abi": [],
+            "bytecode": { "object": "0x6001600055", "linkReferences": {} },
+            "deployedBytecode": { "object": "0x60016000f3", "linkReferences": {} }
+        }"#;
+        let runtime = artifact::from_foundry(foundry, chain(), &Config::default())?;
+        assert_eq!(runtime.bytecode(), &vec![0x60, 0x01, 0x60, 0x00, 0xf3]);
+        assert!(!runtime.is_creation());
+
+        let config = Config::default().with_bytecode_kind(BytecodeKind::Creation);
+        let creation = artifact::from_foundry(foundry, chain(), &config)?;
+        assert_eq!(creation.bytecode(), &vec![0x60, 0x01, 0x60, 0x00, 0x55]);
+        assert!(creation.is_creation());
+
+        let hardhat = r#"{
+            "_format": "hh-sol-artifact-1",
+            "contractName": "Test",
+            "bytecode": "0x6001600055",
+            "deployedBytecode": "0x"
+        }"#;
+        let creation = artifact::from_hardhat(hardhat, chain(), &config)?;
+        assert_eq!(creation.bytecode(), &vec![0x60, 0x01, 0x60, 0x00, 0x55]);
+        let result = artifact::from_hardhat(hardhat, chain(), &Config::default()).unwrap_err();
+        assert!(matches!(
+            &result.payloads()[0].payload,
+            error::Error::Artifact(Error::MissingBytecode(kind)) if kind == "runtime"
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn loads_contracts_from_solc_standard_json() -> anyhow::Result<()> {
+        let output = r#"{
+            "contracts": {
+                "src/A.sol": {
+                    "Token": { "evm": { "deployedBytecode": { "object": "6001" } } }
+                },
+                "src/B.sol": {
+                    "Token": { "evm": { "deployedBytecode": { "object": "6002" } } },
+                    "Vault": { "evm": { "deployedBytecode": { "object": "6003" } } }
+                }
+            }
+        }"#;
+        let config = Config::default();
+
+        let vault = artifact::from_solc_standard_json(output, "Vault", chain(), &config)?;
+        assert_eq!(vault.bytecode(), &vec![0x60, 0x03]);
+
+        let token = artifact::from_solc_standard_json(output, "src/B.sol:Token", chain(), &config)?;
+        assert_eq!(token.bytecode(), &vec![0x60, 0x02]);
+
+        let result =
+            artifact::from_solc_standard_json(output, "Token", chain(), &config).unwrap_err();
+        assert!(matches!(
+            &result.payloads()[0].payload,
+            error::Error::Artifact(Error::AmbiguousContract(name)) if name == "Token"
+        ));
+
+        let result =
+            artifact::from_solc_standard_json(output, "Missing", chain(), &config).unwrap_err();
+        assert!(matches!(
+            &result.payloads()[0].payload,
+            error::Error::Artifact(Error::ContractNotFound(name)) if name == "Missing"
+        ));
+
+        Ok(())
+    }
+}
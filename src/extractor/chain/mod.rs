@@ -5,17 +5,192 @@
 //! [`crate::tc::lift::LiftingPasses`] to be run, and the exact
 //! [`crate::tc::rule::InferenceRules`] that are used.
 //!
-//! For now we only deal with Ethereum and the latest fork and so the machinery
-//! for switching based on the chain configuration is missing. This will be
-//! expanded in the future.
+//! The chain determines which opcodes are available during disassembly and
+//! execution, the gas costs of those opcodes, any chain-specific semantics of
+//! the opcodes, and the system contracts whose return values have known types.
+//! The default type checker configuration for a chain can be obtained using
+//! [`crate::tc::Config::for_chain`], and [`crate::extractor::new`] extends the
+//! provided type checker configuration with the rules for the active chain.
 
+pub mod system_contract;
 pub mod version;
 
-use crate::extractor::chain::version::EthereumVersion;
+use crate::{
+    extractor::chain::{
+        system_contract::SystemContract,
+        version::{ChainVersion, EthereumVersion},
+    },
+    opcode::{control::Nop, Opcode},
+};
 
 /// A representation of the chain on which the contract is running.
+///
+/// All of the supported chains follow the execution semantics of a version of
+/// Ethereum, with chain-specific modifications on top.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Chain {
     /// Ethereum main-net.
     Ethereum { version: EthereumVersion },
+
+    /// Arbitrum One, Arbitrum Nova, and other Arbitrum chains, following the
+    /// semantics of the Ethereum `version` supported by the chain.
+    ///
+    /// On Arbitrum, `NUMBER` returns the number of the most recent L1 block
+    /// seen by the sequencer, and `BLOCKHASH` returns a pseudo-random hash
+    /// rather than the hash of an L1 block. The blob opcodes are not supported.
+    Arbitrum { version: EthereumVersion },
+
+    /// OP Mainnet, Base, and other OP Stack chains, following the semantics of
+    /// the Ethereum `version` supported by the chain.
+    Optimism { version: EthereumVersion },
+
+    /// The Polygon proof-of-stake chain, following the semantics of the
+    /// Ethereum `version` supported by the chain.
+    Polygon { version: EthereumVersion },
+
+    /// Any other EVM-compatible chain, following the semantics of the Ethereum
+    /// `version` and with the provided `system_contracts`.
+    Custom {
+        version:          EthereumVersion,
+        system_contracts: Vec<SystemContract>,
+    },
+}
+
+impl Chain {
+    /// Gets the version of Ethereum whose execution semantics the chain
+    /// follows.
+    #[must_use]
+    pub fn version(&self) -> EthereumVersion {
+        match self {
+            Self::Ethereum { version }
+            | Self::Arbitrum { version }
+            | Self::Optimism { version }
+            | Self::Polygon { version }
+            | Self::Custom { version, .. } => *version,
+        }
+    }
+
+    /// Checks whether the opcode with the provided `byte` is available on this
+    /// chain.
+    ///
+    /// Opcodes that are not available are treated as invalid during both
+    /// disassembly and execution.
+    #[must_use]
+    pub fn supports_opcode(&self, byte: u8) -> bool {
+        match self {
+            // BLOBHASH, BLOBBASEFEE
+            Self::Arbitrum { .. } if matches!(byte, 0x49 | 0x4a) => false,
+            _ => self.version().supports_opcode(byte),
+        }
+    }
+
+    /// Checks whether the provided `opcode` is available on this chain.
+    ///
+    /// The no-op padding that follows push instructions is always available.
+    #[must_use]
+    pub fn supports(&self, opcode: &dyn Opcode) -> bool {
+        Self::byte_of(opcode).map_or(true, |byte| self.supports_opcode(byte))
+    }
+
+    /// Gets the minimum gas cost of executing the provided `opcode` on this
+    /// chain.
+    #[must_use]
+    pub fn min_gas_cost(&self, opcode: &dyn Opcode) -> usize {
+        let override_cost =
+            Self::byte_of(opcode).and_then(|byte| self.version().gas_cost_override(byte));
+
+        override_cost.unwrap_or_else(|| opcode.min_gas_cost())
+    }
+
+    /// Checks whether `SELFDESTRUCT` deletes the executing account on this
+    /// chain, outside of the transaction that created the account.
+    #[must_use]
+    pub fn self_destruct_deletes_account(&self) -> bool {
+        self.version().self_destruct_deletes_account()
+    }
+
+    /// Checks whether the `NUMBER` opcode returns the number of a block on the
+    /// parent (L1) chain rather than on this chain.
+    #[must_use]
+    pub fn reports_l1_block_number(&self) -> bool {
+        matches!(self, Self::Arbitrum { .. })
+    }
+
+    /// Gets the precompiles and system contracts with known semantics that are
+    /// present on this chain.
+    #[must_use]
+    pub fn system_contracts(&self) -> Vec<SystemContract> {
+        match self {
+            Self::Ethereum { .. } | Self::Polygon { .. } => Vec::new(),
+            Self::Arbitrum { .. } => vec![SystemContract::arb_sys()],
+            Self::Optimism { .. } => vec![SystemContract::l1_block()],
+            Self::Custom {
+                system_contracts, ..
+            } => system_contracts.clone(),
+        }
+    }
+
+    /// Checks whether there is a system contract with known semantics at the
+    /// provided `address` on this chain.
+    #[must_use]
+    pub fn has_system_contract_at(&self, address: &[u8; 20]) -> bool {
+        self.system_contracts().iter().any(|c| &c.address == address)
+    }
+
+    /// Gets the byte corresponding to the provided `opcode`, if it occupies
+    /// space in the bytecode.
+    fn byte_of(opcode: &dyn Opcode) -> Option<u8> {
+        (!opcode.as_any().is::<Nop>()).then(|| opcode.as_byte())
+    }
+}
+
+/// The default chain is the latest version of Ethereum main-net.
+impl Default for Chain {
+    fn default() -> Self {
+        let version = EthereumVersion::latest();
+        Self::Ethereum { version }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extractor::chain::{
+        system_contract::{ARB_SYS_ADDRESS, L1_BLOCK_ADDRESS},
+        version::EthereumVersion,
+        Chain,
+    };
+
+    #[test]
+    fn follows_opcode_availability_of_ethereum_version() {
+        let polygon = Chain::Polygon {
+            version: EthereumVersion::Paris,
+        };
+        assert!(!polygon.supports_opcode(0x5f));
+        assert!(polygon.supports_opcode(0x48));
+    }
+
+    #[test]
+    fn does_not_support_blob_opcodes_on_arbitrum() {
+        let arbitrum = Chain::Arbitrum {
+            version: EthereumVersion::Cancun,
+        };
+        assert!(!arbitrum.supports_opcode(0x49));
+        assert!(!arbitrum.supports_opcode(0x4a));
+        assert!(arbitrum.supports_opcode(0x5c));
+        assert!(arbitrum.reports_l1_block_number());
+    }
+
+    #[test]
+    fn knows_system_contracts_for_chain() {
+        let arbitrum = Chain::Arbitrum {
+            version: EthereumVersion::Cancun,
+        };
+        let optimism = Chain::Optimism {
+            version: EthereumVersion::Cancun,
+        };
+        assert!(arbitrum.has_system_contract_at(&ARB_SYS_ADDRESS));
+        assert!(!arbitrum.has_system_contract_at(&L1_BLOCK_ADDRESS));
+        assert!(optimism.has_system_contract_at(&L1_BLOCK_ADDRESS));
+        assert!(Chain::default().system_contracts().is_empty());
+    }
 }
@@ -0,0 +1,196 @@
+//! This module contains the definitions of the precompiles and system contracts
+//! that are present at well-known addresses on the various EVM-compatible
+//! chains.
+//!
+//! Calls to these contracts have known semantics, and so the data they return
+//! can be given a type without any further evidence from the bytecode.
+
+use crate::{constant::WORD_SIZE_BITS, tc::expression::TypeExpression};
+
+/// The address of the `ArbSys` precompile on Arbitrum chains.
+pub const ARB_SYS_ADDRESS: [u8; 20] = [
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x64,
+];
+
+/// The address of the `L1Block` predeploy on OP Stack chains.
+pub const L1_BLOCK_ADDRESS: [u8; 20] = [
+    0x42, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x15,
+];
+
+/// A precompile or system contract that lives at a well-known `address` on a
+/// chain.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SystemContract {
+    /// The name of the system contract.
+    pub name: String,
+
+    /// The address at which the system contract lives.
+    pub address: [u8; 20],
+
+    /// The functions of the system contract whose return types are known.
+    pub functions: Vec<SystemFunction>,
+}
+
+impl SystemContract {
+    /// Creates a new system contract with the provided `name` that lives at
+    /// `address` and has no known functions.
+    #[must_use]
+    pub fn new(name: impl Into<String>, address: [u8; 20]) -> Self {
+        let name = name.into();
+        let functions = Vec::new();
+        Self {
+            name,
+            address,
+            functions,
+        }
+    }
+
+    /// Adds a function with the provided `name` and `selector` that returns a
+    /// word of type `returns` to the system contract.
+    #[must_use]
+    pub fn with_function(
+        mut self,
+        name: impl Into<String>,
+        selector: [u8; 4],
+        returns: TypeExpression,
+    ) -> Self {
+        let name = name.into();
+        self.functions.push(SystemFunction {
+            name,
+            selector,
+            returns,
+        });
+        self
+    }
+
+    /// Gets the function of the system contract with the provided `selector`,
+    /// if it is known.
+    #[must_use]
+    pub fn function(&self, selector: [u8; 4]) -> Option<&SystemFunction> {
+        self.functions.iter().find(|f| f.selector == selector)
+    }
+
+    /// Gets the `ArbSys` precompile that is present on all Arbitrum chains.
+    #[must_use]
+    pub fn arb_sys() -> Self {
+        Self::new("ArbSys", ARB_SYS_ADDRESS)
+            .with_function(
+                "arbBlockNumber",
+                [0xa3, 0xb1, 0xb3, 0x1d],
+                TypeExpression::unsigned_word(None),
+            )
+            .with_function(
+                "arbBlockHash",
+                [0x2b, 0x40, 0x7a, 0x82],
+                TypeExpression::bytes(Some(WORD_SIZE_BITS)),
+            )
+            .with_function(
+                "arbChainID",
+                [0xd1, 0x27, 0xf5, 0x4a],
+                TypeExpression::unsigned_word(None),
+            )
+            .with_function(
+                "arbOSVersion",
+                [0x05, 0x10, 0x38, 0xf2],
+                TypeExpression::unsigned_word(None),
+            )
+            .with_function(
+                "wasMyCallersAddressAliased",
+                [0x17, 0x5a, 0x26, 0x0b],
+                TypeExpression::bool(),
+            )
+            .with_function(
+                "myCallersAddressWithoutAliasing",
+                [0xd7, 0x45, 0x23, 0xb3],
+                TypeExpression::address(),
+            )
+    }
+
+    /// Gets the `L1Block` predeploy that is present on all OP Stack chains.
+    #[must_use]
+    pub fn l1_block() -> Self {
+        Self::new("L1Block", L1_BLOCK_ADDRESS)
+            .with_function(
+                "number",
+                [0x83, 0x81, 0xf5, 0x8a],
+                TypeExpression::unsigned_word(Some(64)),
+            )
+            .with_function(
+                "timestamp",
+                [0xb8, 0x07, 0x77, 0xea],
+                TypeExpression::unsigned_word(Some(64)),
+            )
+            .with_function(
+                "basefee",
+                [0x5c, 0xf2, 0x49, 0x69],
+                TypeExpression::unsigned_word(None),
+            )
+            .with_function(
+                "hash",
+                [0x09, 0xbd, 0x5a, 0x60],
+                TypeExpression::bytes(Some(WORD_SIZE_BITS)),
+            )
+            .with_function(
+                "sequenceNumber",
+                [0x64, 0xca, 0x23, 0xef],
+                TypeExpression::unsigned_word(Some(64)),
+            )
+            .with_function(
+                "batcherHash",
+                [0xe8, 0x1b, 0x2c, 0x6d],
+                TypeExpression::bytes(Some(WORD_SIZE_BITS)),
+            )
+            .with_function(
+                "blobBaseFee",
+                [0xf8, 0x20, 0x61, 0x40],
+                TypeExpression::unsigned_word(None),
+            )
+    }
+}
+
+/// A function of a [`SystemContract`] whose return type is known.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SystemFunction {
+    /// The name of the function.
+    pub name: String,
+
+    /// The four-byte selector of the function.
+    pub selector: [u8; 4],
+
+    /// The type of the word returned by the function.
+    pub returns: TypeExpression,
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constant::WORD_SIZE_BITS,
+        extractor::chain::system_contract::{SystemContract, ARB_SYS_ADDRESS, L1_BLOCK_ADDRESS},
+        tc::expression::TE,
+    };
+
+    #[test]
+    fn has_correct_system_contract_addresses() {
+        assert_eq!(
+            hex::encode(ARB_SYS_ADDRESS),
+            "0000000000000000000000000000000000000064"
+        );
+        assert_eq!(
+            hex::encode(L1_BLOCK_ADDRESS),
+            "4200000000000000000000000000000000000015"
+        );
+    }
+
+    #[test]
+    fn can_look_up_functions_by_selector() {
+        let l1_block = SystemContract::l1_block();
+        let function = l1_block
+            .function([0x09, 0xbd, 0x5a, 0x60])
+            .expect("Function not found");
+        assert_eq!(function.name, "hash");
+        assert_eq!(function.returns, TE::bytes(Some(WORD_SIZE_BITS)));
+        assert!(l1_block.function([0x00, 0x00, 0x00, 0x00]).is_none());
+    }
+}
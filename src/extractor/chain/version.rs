@@ -3,8 +3,8 @@
 //! [`crate::extractor::chain::Chain`] to configure the behaviour of the
 //! library.
 //!
-//! For now we only support Shanghai on Ethereum main-net, but this will change
-//! in the future.
+//! The versions are used to determine which opcodes are available during
+//! disassembly and execution, as well as the gas costs of those opcodes.
 
 use std::fmt::Debug;
 
@@ -18,15 +18,196 @@ where
 }
 
 /// Ethereum chain versions.
-#[derive(Clone, Debug, Eq, PartialEq)]
+///
+/// The versions are ordered by their activation on main-net, so a version
+/// compares less than all the versions that follow it.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
 pub enum EthereumVersion {
+    /// The frontier release of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#frontier).
+    Frontier,
+
+    /// The homestead fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#homestead).
+    Homestead,
+
+    /// The tangerine whistle fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#tangerine-whistle).
+    TangerineWhistle,
+
+    /// The spurious dragon fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#spurious-dragon).
+    SpuriousDragon,
+
+    /// The byzantium fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#byzantium).
+    Byzantium,
+
+    /// The constantinople fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#constantinople).
+    Constantinople,
+
+    /// The petersburg fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#constantinople).
+    Petersburg,
+
+    /// The istanbul fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#istanbul).
+    Istanbul,
+
+    /// The berlin fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#berlin).
+    Berlin,
+
+    /// The london fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#london).
+    London,
+
+    /// The paris fork of ethereum (the merge), as described
+    /// [here](https://ethereum.org/en/history/#paris).
+    Paris,
+
     /// The shanghai fork of ethereum, as described
     /// [here](https://ethereum.org/en/history/#shanghai).
     Shanghai,
+
+    /// The cancun fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#cancun).
+    Cancun,
+
+    /// The prague fork of ethereum, as described
+    /// [here](https://ethereum.org/en/history/#pectra).
+    Prague,
+}
+
+impl EthereumVersion {
+    /// Gets the version of ethereum in which the opcode with the provided
+    /// `byte` was introduced.
+    ///
+    /// Bytes that do not correspond to any opcode are treated as having
+    /// existed since [`Self::Frontier`], as they are invalid in all versions.
+    #[must_use]
+    pub fn introducing(byte: u8) -> Self {
+        match byte {
+            // DELEGATECALL
+            0xf4 => Self::Homestead,
+            // RETURNDATASIZE, RETURNDATACOPY, STATICCALL, REVERT
+            0x3d | 0x3e | 0xfa | 0xfd => Self::Byzantium,
+            // SHL, SHR, SAR, EXTCODEHASH, CREATE2
+            0x1b | 0x1c | 0x1d | 0x3f | 0xf5 => Self::Constantinople,
+            // CHAINID, SELFBALANCE
+            0x46 | 0x47 => Self::Istanbul,
+            // BASEFEE
+            0x48 => Self::London,
+            // PUSH0
+            0x5f => Self::Shanghai,
+            // BLOBHASH, BLOBBASEFEE, TLOAD, TSTORE, MCOPY
+            0x49 | 0x4a | 0x5c | 0x5d | 0x5e => Self::Cancun,
+            _ => Self::Frontier,
+        }
+    }
+
+    /// Checks whether the opcode with the provided `byte` is available in this
+    /// version of ethereum.
+    #[must_use]
+    pub fn supports_opcode(self, byte: u8) -> bool {
+        Self::introducing(byte) <= self
+    }
+
+    /// Gets the minimum gas cost of the opcode with the provided `byte` in
+    /// this version of ethereum, if it differs from the cost in the latest
+    /// version.
+    ///
+    /// Costs are given assuming the cheapest (warm) access where the version
+    /// distinguishes between warm and cold accesses.
+    #[allow(clippy::match_same_arms)] // Grouping the arms by opcode is clearer
+    #[must_use]
+    pub fn gas_cost_override(self, byte: u8) -> Option<usize> {
+        let cost = match byte {
+            // SLOAD
+            0x54 if self < Self::TangerineWhistle => 50,
+            0x54 if self < Self::Istanbul => 200,
+            0x54 if self < Self::Berlin => 800,
+            // SSTORE
+            0x55 if self == Self::Constantinople => 200,
+            0x55 if self < Self::Istanbul => 5000,
+            0x55 if self < Self::Berlin => 800,
+            // BALANCE
+            0x31 if self < Self::TangerineWhistle => 20,
+            0x31 if self < Self::Istanbul => 400,
+            0x31 if self < Self::Berlin => 700,
+            // EXTCODESIZE, EXTCODECOPY
+            0x3b | 0x3c if self < Self::TangerineWhistle => 20,
+            0x3b | 0x3c if self < Self::Berlin => 700,
+            // EXTCODEHASH
+            0x3f if self < Self::Istanbul => 400,
+            0x3f if self < Self::Berlin => 700,
+            // CALL, CALLCODE, DELEGATECALL, STATICCALL
+            0xf1 | 0xf2 | 0xf4 | 0xfa if self < Self::TangerineWhistle => 40,
+            0xf1 | 0xf2 | 0xf4 | 0xfa if self < Self::Berlin => 700,
+            // SELFDESTRUCT
+            0xff if self < Self::TangerineWhistle => 0,
+            _ => return None,
+        };
+
+        Some(cost)
+    }
+
+    /// Checks whether `SELFDESTRUCT` deletes the executing account in this
+    /// version of ethereum.
+    ///
+    /// From [`Self::Cancun`] onwards (see
+    /// [EIP-6780](https://eips.ethereum.org/EIPS/eip-6780)) the account is only
+    /// deleted when `SELFDESTRUCT` is executed in the transaction that created
+    /// it, and otherwise only has its balance sent to the target.
+    #[must_use]
+    pub fn self_destruct_deletes_account(self) -> bool {
+        self < Self::Cancun
+    }
 }
 
 impl ChainVersion for EthereumVersion {
     fn latest() -> Self {
-        Self::Shanghai
+        Self::Prague
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extractor::chain::version::EthereumVersion;
+
+    #[test]
+    fn versions_are_ordered_by_activation() {
+        assert!(EthereumVersion::Frontier < EthereumVersion::Homestead);
+        assert!(EthereumVersion::Berlin < EthereumVersion::London);
+        assert!(EthereumVersion::Shanghai < EthereumVersion::Cancun);
+        assert!(EthereumVersion::Cancun < EthereumVersion::Prague);
+    }
+
+    #[test]
+    fn gates_opcodes_by_version() {
+        assert!(!EthereumVersion::Paris.supports_opcode(0x5f));
+        assert!(EthereumVersion::Shanghai.supports_opcode(0x5f));
+        assert!(!EthereumVersion::Shanghai.supports_opcode(0x5c));
+        assert!(EthereumVersion::Cancun.supports_opcode(0x5c));
+        assert!(!EthereumVersion::Frontier.supports_opcode(0xf4));
+        assert!(EthereumVersion::Frontier.supports_opcode(0x01));
+    }
+
+    #[test]
+    fn provides_version_specific_gas_costs() {
+        assert_eq!(EthereumVersion::Frontier.gas_cost_override(0x54), Some(50));
+        assert_eq!(EthereumVersion::Istanbul.gas_cost_override(0x54), Some(800));
+        assert_eq!(EthereumVersion::Berlin.gas_cost_override(0x54), None);
+        assert_eq!(EthereumVersion::Frontier.gas_cost_override(0xff), Some(0));
+        assert_eq!(EthereumVersion::Cancun.gas_cost_override(0x01), None);
+    }
+
+    #[test]
+    fn gates_self_destruct_semantics_by_version() {
+        assert!(EthereumVersion::Frontier.self_destruct_deletes_account());
+        assert!(EthereumVersion::Shanghai.self_destruct_deletes_account());
+        assert!(!EthereumVersion::Cancun.self_destruct_deletes_account());
+        assert!(!EthereumVersion::Prague.self_destruct_deletes_account());
     }
 }
@@ -1,7 +1,7 @@
 //! This module contains types useful for dealing with concrete contracts that
 //! you want to analyze.
 
-use crate::extractor::chain::Chain;
+use crate::extractor::{chain::Chain, metadata, metadata::Metadata};
 
 /// The contract that is to be analyzed by the library.
 ///
@@ -12,17 +12,72 @@ pub struct Contract {
     /// The bytecode of the contract.
     bytecode: Vec<u8>,
 
+    /// Whether `bytecode` is the runtime or creation bytecode of the contract.
+    kind: BytecodeKind,
+
     /// The chain on which the contract is running.
     chain: Chain,
+
+    /// The compiler metadata that was stripped from the end of the bytecode,
+    /// if any.
+    metadata: Option<Metadata>,
 }
 
 impl Contract {
     /// Creates a new contract from the provided `bytecode` and `chain`.
     ///
-    /// This must be the contract bytecode _without_ the CBOR metadata.
+    /// This must be the contract bytecode _without_ the CBOR metadata. If your
+    /// bytecode may contain the metadata, use [`Self::new_stripping_metadata`]
+    /// instead.
     #[must_use]
     pub fn new(bytecode: Vec<u8>, chain: Chain) -> Self {
-        Self { bytecode, chain }
+        let kind = BytecodeKind::Runtime;
+        let metadata = None;
+        Self {
+            bytecode,
+            kind,
+            chain,
+            metadata,
+        }
+    }
+
+    /// Creates a new contract from the provided `bytecode` and `chain`,
+    /// removing the CBOR metadata from the end of `bytecode` if it is present.
+    ///
+    /// The decoded metadata is available through [`Self::metadata`]. If no
+    /// well-formed metadata section is found, `bytecode` is used unchanged.
+    #[must_use]
+    pub fn new_stripping_metadata(mut bytecode: Vec<u8>, chain: Chain) -> Self {
+        let metadata = metadata::decode(&bytecode);
+        if let Some(metadata) = &metadata {
+            bytecode.truncate(bytecode.len() - metadata.size);
+        }
+        let kind = BytecodeKind::Runtime;
+        Self {
+            bytecode,
+            kind,
+            chain,
+            metadata,
+        }
+    }
+
+    /// Creates a new contract from the provided creation `bytecode` (also known
+    /// as the init code) and `chain`.
+    ///
+    /// When analyzed, the constructor is executed first in order to discover
+    /// the runtime code it returns. The evidence gathered from both the
+    /// constructor and the runtime code is then combined into a single storage
+    /// layout.
+    #[must_use]
+    pub fn new_creation(bytecode: Vec<u8>, chain: Chain) -> Self {
+        let kind = BytecodeKind::Creation;
+        let metadata = None;
+        Self {
+            bytecode,
+            kind,
+            chain,
+            metadata,
+        }
     }
 
     /// Gets a reference to the bytecode of the contract.
@@ -31,9 +86,39 @@ impl Contract {
         &self.bytecode
     }
 
+    /// Gets the kind of bytecode that the contract was constructed with.
+    #[must_use]
+    pub fn kind(&self) -> BytecodeKind {
+        self.kind
+    }
+
+    /// Checks whether the contract was constructed from creation bytecode.
+    #[must_use]
+    pub fn is_creation(&self) -> bool {
+        self.kind == BytecodeKind::Creation
+    }
+
     /// Gets a reference to the chain on which the contract is running.
     #[must_use]
     pub fn chain(&self) -> &Chain {
         &self.chain
     }
+
+    /// Gets the compiler metadata that was stripped from the contract's
+    /// bytecode, if any was present.
+    #[must_use]
+    pub fn metadata(&self) -> Option<&Metadata> {
+        self.metadata.as_ref()
+    }
+}
+
+/// The kinds of bytecode from which a contract can be constructed.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum BytecodeKind {
+    /// The runtime bytecode that is deployed on chain.
+    Runtime,
+
+    /// The creation bytecode that executes the constructor and returns the
+    /// runtime bytecode.
+    Creation,
 }
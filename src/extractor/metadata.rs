@@ -0,0 +1,396 @@
+//! This module contains the functionality for recognising, decoding, and
+//! stripping the CBOR-encoded metadata that compilers append to the end of
+//! contract bytecode.
+//!
+//! # Metadata Format
+//!
+//! Both `solc` and `vyper` append a section of the following form to the
+//! bytecode they emit:
+//!
+//! ```text
+//! <cbor-encoded data> <length of the cbor-encoded data as a big-endian u16>
+//! ```
+//!
+//! For `solc` (and older versions of `vyper`) the CBOR data is a map from
+//! string keys (`ipfs`, `bzzr0`, `bzzr1`, `solc`, `vyper`, `experimental`) to
+//! their values. Newer versions of `vyper` instead emit an array whose final
+//! element is such a map. Both forms are supported here.
+//!
+//! The decoder is intentionally minimal, only supporting the subset of CBOR
+//! that compilers actually emit. If the trailing bytes do not decode as a
+//! well-formed metadata section that exactly fills its stated length, they are
+//! assumed not to be metadata and are left untouched.
+
+use crate::constant::METADATA_LENGTH_SUFFIX_BYTES;
+
+/// The compiler metadata that was decoded from the trailing section of the
+/// contract bytecode.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Metadata {
+    /// The compiler that emitted the bytecode, if it was recorded.
+    pub compiler: Option<Compiler>,
+
+    /// The hash of the contract's metadata JSON file, if it was recorded.
+    pub source_hash: Option<SourceHash>,
+
+    /// Whether the contract was compiled using experimental compiler features.
+    pub experimental: bool,
+
+    /// The total number of bytes, including the length suffix, that the
+    /// metadata section occupied at the end of the bytecode.
+    pub size: usize,
+}
+
+/// The compiler that emitted a given piece of bytecode.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Compiler {
+    /// The kind of the compiler.
+    pub kind: CompilerKind,
+
+    /// The version of the compiler, formatted as `major.minor.patch` for
+    /// release builds or as the verbatim version string otherwise.
+    pub version: String,
+}
+
+/// The compilers whose metadata format is understood.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum CompilerKind {
+    /// The Solidity compiler.
+    Solc,
+
+    /// The Vyper compiler.
+    Vyper,
+}
+
+/// The hash of the metadata file for the contract, as recorded by the compiler.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum SourceHash {
+    /// An IPFS multihash.
+    Ipfs(Vec<u8>),
+
+    /// A Swarm hash using the legacy (`bzzr0`) format.
+    Bzzr0(Vec<u8>),
+
+    /// A Swarm hash using the `bzzr1` format.
+    Bzzr1(Vec<u8>),
+}
+
+/// Splits the provided `bytecode` into the executable code and the decoded
+/// compiler metadata that trails it.
+///
+/// If no well-formed metadata section can be found at the end of `bytecode`,
+/// the bytecode is returned unchanged alongside [`None`].
+#[must_use]
+pub fn split(bytecode: &[u8]) -> (&[u8], Option<Metadata>) {
+    match decode(bytecode) {
+        Some(metadata) => (&bytecode[..bytecode.len() - metadata.size], Some(metadata)),
+        None => (bytecode, None),
+    }
+}
+
+/// Decodes the compiler metadata from the end of the provided `bytecode`, if it
+/// is present.
+///
+/// Returns [`None`] if there is no well-formed metadata section at the end of
+/// `bytecode`.
+#[must_use]
+pub fn decode(bytecode: &[u8]) -> Option<Metadata> {
+    let suffix_start = bytecode.len().checked_sub(METADATA_LENGTH_SUFFIX_BYTES)?;
+    let cbor_len = usize::from(u16::from_be_bytes([
+        bytecode[suffix_start],
+        bytecode[suffix_start + 1],
+    ]));
+    if cbor_len == 0 {
+        return None;
+    }
+    let cbor_start = suffix_start.checked_sub(cbor_len)?;
+    let cbor_bytes = &bytecode[cbor_start..suffix_start];
+
+    // The section must decode as exactly one CBOR item that fills it completely.
+    let mut reader = CborReader::new(cbor_bytes);
+    let item = reader.read_item()?;
+    if !reader.is_done() {
+        return None;
+    }
+
+    // Newer vyper versions wrap the map in an array, placing it last.
+    let entries = match item {
+        CborItem::Map(entries) => entries,
+        CborItem::Array(mut items) => match items.pop() {
+            Some(CborItem::Map(entries)) => entries,
+            _ => return None,
+        },
+        _ => return None,
+    };
+
+    let mut metadata = Metadata {
+        size: cbor_len + METADATA_LENGTH_SUFFIX_BYTES,
+        ..Metadata::default()
+    };
+    let mut recognised_any = false;
+    for (key, value) in entries {
+        let CborItem::Text(key) = key else {
+            return None;
+        };
+        match (key.as_str(), value) {
+            ("ipfs", CborItem::Bytes(hash)) => metadata.source_hash = Some(SourceHash::Ipfs(hash)),
+            ("bzzr0", CborItem::Bytes(hash)) => {
+                metadata.source_hash = Some(SourceHash::Bzzr0(hash));
+            }
+            ("bzzr1", CborItem::Bytes(hash)) => {
+                metadata.source_hash = Some(SourceHash::Bzzr1(hash));
+            }
+            ("solc", version) => {
+                metadata.compiler = Some(Compiler {
+                    kind:    CompilerKind::Solc,
+                    version: format_version(version)?,
+                });
+            }
+            ("vyper", version) => {
+                metadata.compiler = Some(Compiler {
+                    kind:    CompilerKind::Vyper,
+                    version: format_version(version)?,
+                });
+            }
+            ("experimental", CborItem::Bool(flag)) => metadata.experimental = flag,
+            // Unknown keys are allowed, but do not alone make this metadata.
+            _ => continue,
+        }
+        recognised_any = true;
+    }
+
+    recognised_any.then_some(metadata)
+}
+
+/// Formats the compiler version from the provided CBOR `item`.
+///
+/// Release versions are encoded as three bytes (or as an array of three
+/// integers), while pre-release versions are encoded as a text string.
+fn format_version(item: CborItem) -> Option<String> {
+    match item {
+        CborItem::Bytes(bytes) if bytes.len() == 3 => {
+            Some(format!("{}.{}.{}", bytes[0], bytes[1], bytes[2]))
+        }
+        CborItem::Array(items) if items.len() == 3 => {
+            let parts = items
+                .into_iter()
+                .map(|i| match i {
+                    CborItem::Unsigned(n) => Some(n.to_string()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?;
+            Some(parts.join("."))
+        }
+        CborItem::Text(version) => Some(version),
+        _ => None,
+    }
+}
+
+/// The subset of CBOR data items that can appear in compiler metadata.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum CborItem {
+    Unsigned(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<CborItem>),
+    Map(Vec<(CborItem, CborItem)>),
+    Bool(bool),
+    Null,
+}
+
+/// A minimal reader for definite-length CBOR data items.
+struct CborReader<'a> {
+    bytes:    &'a [u8],
+    position: usize,
+}
+
+impl<'a> CborReader<'a> {
+    /// The maximum nesting depth that the reader will descend to, preventing
+    /// adversarial input from exhausting the stack.
+    const MAX_DEPTH: usize = 8;
+
+    /// Creates a new reader over the provided `bytes`.
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// Checks whether all of the input has been consumed.
+    fn is_done(&self) -> bool {
+        self.position == self.bytes.len()
+    }
+
+    /// Reads a single data item from the input.
+    fn read_item(&mut self) -> Option<CborItem> {
+        self.read_item_at_depth(0)
+    }
+
+    /// Reads a single data item from the input, where that item is nested
+    /// `depth` containers deep.
+    fn read_item_at_depth(&mut self, depth: usize) -> Option<CborItem> {
+        if depth > Self::MAX_DEPTH {
+            return None;
+        }
+
+        let initial = self.take(1)?[0];
+        let major_type = initial >> 5;
+        let additional = initial & 0x1f;
+
+        // Simple values have their payload in the additional information.
+        if major_type == 7 {
+            return match additional {
+                20 => Some(CborItem::Bool(false)),
+                21 => Some(CborItem::Bool(true)),
+                22 => Some(CborItem::Null),
+                _ => None,
+            };
+        }
+
+        let argument = self.read_argument(additional)?;
+        match major_type {
+            0 => Some(CborItem::Unsigned(argument)),
+            2 => Some(CborItem::Bytes(self.take(argument)?.to_vec())),
+            3 => String::from_utf8(self.take(argument)?.to_vec())
+                .ok()
+                .map(CborItem::Text),
+            4 => {
+                let items = (0..argument)
+                    .map(|_| self.read_item_at_depth(depth + 1))
+                    .collect::<Option<Vec<_>>>()?;
+                Some(CborItem::Array(items))
+            }
+            5 => {
+                let entries = (0..argument)
+                    .map(|_| {
+                        let key = self.read_item_at_depth(depth + 1)?;
+                        let value = self.read_item_at_depth(depth + 1)?;
+                        Some((key, value))
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(CborItem::Map(entries))
+            }
+            // Negative integers, tags, and indefinite lengths are never emitted.
+            _ => None,
+        }
+    }
+
+    /// Reads the argument for a data item whose initial byte had the provided
+    /// `additional` information.
+    fn read_argument(&mut self, additional: u8) -> Option<u64> {
+        let width = match additional {
+            0..=23 => return Some(u64::from(additional)),
+            24 => 1,
+            25 => 2,
+            26 => 4,
+            27 => 8,
+            _ => return None,
+        };
+
+        Some(
+            self.take(width)?
+                .iter()
+                .fold(0u64, |acc, byte| (acc << 8) | u64::from(*byte)),
+        )
+    }
+
+    /// Takes the next `count` bytes from the input, returning [`None`] if there
+    /// are insufficient bytes remaining.
+    fn take(&mut self, count: u64) -> Option<&'a [u8]> {
+        let count = usize::try_from(count).ok()?;
+        let end = self.position.checked_add(count)?;
+        let slice = self.bytes.get(self.position..end)?;
+        self.position = end;
+
+        Some(slice)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::extractor::metadata::{self, CompilerKind, SourceHash};
+
+    #[test]
+    fn decodes_solc_metadata_with_ipfs_hash() -> anyhow::Result<()> {
+        let code = hex::decode("6080604052600080fd")?;
+        let trailer = hex::decode(
+            "a2646970667358221220d2b0f2a25c0b4f1a3d6c1c3b0b2bd1e9f7e0cc4d22bbd4a1b8a6b5c3d1f2e3\
+             a464736f6c63430008110033",
+        )?;
+        let mut bytecode = code.clone();
+        bytecode.extend(&trailer);
+
+        let (stripped, metadata) = metadata::split(&bytecode);
+        let metadata = metadata.expect("Metadata was not decoded");
+
+        assert_eq!(stripped, code.as_slice());
+        assert_eq!(metadata.size, trailer.len());
+        assert!(!metadata.experimental);
+        let compiler = metadata.compiler.expect("No compiler was decoded");
+        assert_eq!(compiler.kind, CompilerKind::Solc);
+        assert_eq!(compiler.version, "0.8.17");
+        assert!(matches!(metadata.source_hash, Some(SourceHash::Ipfs(h)) if h.len() == 34));
+
+        Ok(())
+    }
+
+    #[test]
+    fn decodes_solc_metadata_without_hash() -> anyhow::Result<()> {
+        // This is the trailer emitted for the Permit2 contract.
+        let bytecode = hex::decode("6040fdfea164736f6c6343000811000a")?;
+
+        let (stripped, metadata) = metadata::split(&bytecode);
+        let metadata = metadata.expect("Metadata was not decoded");
+
+        assert_eq!(stripped, hex::decode("6040fdfe")?.as_slice());
+        assert_eq!(metadata.compiler.unwrap().version, "0.8.17");
+        assert!(metadata.source_hash.is_none());
+
+        Ok(())
+    }
+
+    #[test]
+    fn decodes_legacy_bzzr0_metadata() -> anyhow::Result<()> {
+        let mut bytecode = hex::decode("00a165627a7a72305820")?;
+        bytecode.extend([0xab; 32]);
+        bytecode.extend(hex::decode("0029")?);
+
+        let (stripped, metadata) = metadata::split(&bytecode);
+        let metadata = metadata.expect("Metadata was not decoded");
+
+        assert_eq!(stripped, &[0x00]);
+        assert!(metadata.compiler.is_none());
+        assert_eq!(
+            metadata.source_hash,
+            Some(SourceHash::Bzzr0(vec![0xab; 32]))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn decodes_vyper_array_metadata() -> anyhow::Result<()> {
+        // [runtime_size, [], 0, {"vyper": [0, 3, 10]}]
+        let bytecode = hex::decode("5b00841901238000a16576797065728300030a0011")?;
+
+        let (stripped, metadata) = metadata::split(&bytecode);
+        let metadata = metadata.expect("Metadata was not decoded");
+
+        assert_eq!(stripped, &[0x5b, 0x00]);
+        let compiler = metadata.compiler.expect("No compiler was decoded");
+        assert_eq!(compiler.kind, CompilerKind::Vyper);
+        assert_eq!(compiler.version, "0.3.10");
+
+        Ok(())
+    }
+
+    #[test]
+    fn leaves_bytecode_without_metadata_untouched() -> anyhow::Result<()> {
+        let bytecode = hex::decode("6080604052600080fd")?;
+
+        let (stripped, metadata) = metadata::split(&bytecode);
+
+        assert_eq!(stripped, bytecode.as_slice());
+        assert!(metadata.is_none());
+
+        Ok(())
+    }
+}
@@ -1,12 +1,16 @@
 //! This module contains the definition of the extractor itself.
 
+pub mod artifact;
 pub mod chain;
 pub mod contract;
+pub mod metadata;
 pub mod state;
 
 use crate::{
+    cfg::dispatcher::SelectorTable,
     disassembly::InstructionStream,
     error,
+    error::{container::Locatable, execution::Error},
     extractor::{contract::Contract, state::State},
     tc,
     tc::TypeChecker,
@@ -18,13 +22,27 @@ use crate::{
 
 /// Creates a new extractor wrapping the provided `contract`, and with the
 /// provided `vm_config` and `unifier_config`.
+///
+/// If no chain is explicitly configured in the `vm_config`, the chain of the
+/// `contract` is used, ensuring that disassembly and execution follow the
+/// semantics of the chain on which the contract is deployed. An explicitly
+/// configured chain takes precedence over the chain of the `contract`.
+///
+/// The inference rules in the `tc_config` are extended with those specific to
+/// the same chain (see [`tc::rule::InferenceRules::add_for_chain`]), so that
+/// the types of values obtained from chain-specific sources can be inferred.
 #[must_use]
 pub fn new(
     contract: Contract,
     vm_config: vm::Config,
-    tc_config: tc::Config,
+    mut tc_config: tc::Config,
     watchdog: DynWatchdog,
 ) -> Extractor<state::HasContract> {
+    let vm_config = match vm_config.chain {
+        Some(_) => vm_config,
+        None => vm_config.with_chain(contract.chain().clone()),
+    };
+    tc_config.inference_rules.add_for_chain(vm_config.chain());
     let state = state::HasContract {
         vm_config,
         tc_config,
@@ -44,6 +62,18 @@ pub fn new(
 ///
 /// There is the [`Self::state`] function that provides access to the state data
 /// of whichever state the extractor is currently in.
+///
+/// # Threading
+///
+/// The extractor is [`Send`] and [`Sync`] in every state, as is the
+/// [`StorageLayout`] that it produces. This allows analyses to be run on a
+/// thread pool, with any [`crate::watchdog::Watchdog`] used to stop them being
+/// shared with the thread that started them.
+///
+/// To make this possible, the extractor states, the virtual machine's
+/// [`crate::vm::scheduler::Scheduler`]s, and the type checker's
+/// [`crate::tc::lift::Lift`] passes and [`crate::tc::rule::InferenceRule`]s
+/// are all required to be [`Send`] and [`Sync`] themselves.
 pub struct Extractor<S: State> {
     /// The contract that is being analyzed.
     contract: Contract,
@@ -182,11 +212,23 @@ impl Extractor<state::HasContract> {
     /// Performs the disassembly process to turn the input contract code into
     /// bytecode.
     ///
+    /// If the contract was constructed from its creation bytecode, this first
+    /// executes the constructor (see [`Self::execute_constructor`]) and then
+    /// disassembles the runtime code that it returns.
+    ///
     /// # Errors
     ///
     /// Returns [`Err`] if disassembly fails.
     pub fn disassemble(self) -> error::Result<Extractor<state::DisassemblyComplete>> {
-        let bytecode = InstructionStream::try_from(self.contract.bytecode().as_slice())?;
+        if self.contract.is_creation() {
+            return self.execute_constructor()?.disassemble();
+        }
+
+        let bytecode = InstructionStream::disassemble(
+            self.contract.bytecode().as_slice(),
+            self.state.vm_config.chain(),
+        )?;
+        let selectors = SelectorTable::new(&bytecode);
         unsafe {
             self.transform_state(|old_state| {
                 let vm_config = old_state.vm_config;
@@ -194,6 +236,88 @@ impl Extractor<state::HasContract> {
                 let watchdog = old_state.watchdog;
                 Ok(state::DisassemblyComplete {
                     bytecode,
+                    selectors,
+                    constructor_result: None,
+                    vm_config,
+                    tc_config,
+                    watchdog,
+                })
+            })
+        }
+    }
+
+    /// Symbolically executes the contract's creation bytecode in order to
+    /// record the constructor's effects on storage and to discover the runtime
+    /// code that it returns.
+    ///
+    /// The runtime code is recognised as the region of the creation bytecode
+    /// that is copied into memory using `CODECOPY` and then returned using
+    /// `RETURN`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the constructor cannot be disassembled or executed,
+    /// or if it does not return any recognisable runtime code.
+    pub fn execute_constructor(self) -> error::Result<Extractor<state::ConstructorComplete>> {
+        let init_code = InstructionStream::disassemble(
+            self.contract.bytecode().as_slice(),
+            self.state.vm_config.chain(),
+        )?;
+        let contract_bytecode = self.contract.bytecode().clone();
+        unsafe {
+            self.transform_state(|old_state| {
+                let vm_config = old_state.vm_config;
+                let tc_config = old_state.tc_config;
+                let watchdog = old_state.watchdog;
+
+                let mut vm = VM::new(init_code, vm_config.clone(), watchdog.clone())?;
+                vm.execute()?;
+                let constructor_result = vm.consume();
+
+                let runtime_bytecode = constructor_result
+                    .returned_code()
+                    .and_then(|range| contract_bytecode.get(range))
+                    .filter(|code| !code.is_empty())
+                    .ok_or(Error::NoRuntimeCodeReturned.locate(0))?
+                    .to_vec();
+
+                Ok(state::ConstructorComplete {
+                    runtime_bytecode,
+                    constructor_result,
+                    vm_config,
+                    tc_config,
+                    watchdog,
+                })
+            })
+        }
+    }
+}
+
+/// Operations available on an extractor that has executed the constructor of a
+/// contract constructed from its creation bytecode.
+impl Extractor<state::ConstructorComplete> {
+    /// Performs the disassembly process to turn the runtime code returned by
+    /// the constructor into bytecode.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if disassembly fails.
+    pub fn disassemble(self) -> error::Result<Extractor<state::DisassemblyComplete>> {
+        let bytecode = InstructionStream::disassemble(
+            self.state.runtime_bytecode.as_slice(),
+            self.state.vm_config.chain(),
+        )?;
+        let selectors = SelectorTable::new(&bytecode);
+        unsafe {
+            self.transform_state(|old_state| {
+                let constructor_result = Some(old_state.constructor_result);
+                let vm_config = old_state.vm_config;
+                let tc_config = old_state.tc_config;
+                let watchdog = old_state.watchdog;
+                Ok(state::DisassemblyComplete {
+                    bytecode,
+                    selectors,
+                    constructor_result,
                     vm_config,
                     tc_config,
                     watchdog,
@@ -206,6 +330,13 @@ impl Extractor<state::HasContract> {
 /// Operations available on an extractor that has completed the disassembly of
 /// the bytecode.
 impl Extractor<state::DisassemblyComplete> {
+    /// Gets the functions exposed by the contract, as recognised from its
+    /// dispatcher.
+    #[must_use]
+    pub fn selectors(&self) -> &SelectorTable {
+        &self.state.selectors
+    }
+
     /// Prepares the virtual machine for symbolic execution of the bytecode.
     ///
     /// # Errors
@@ -217,11 +348,15 @@ impl Extractor<state::DisassemblyComplete> {
             self.transform_state(|old_state| {
                 let tc_config = old_state.tc_config;
                 let watchdog = old_state.watchdog;
+                let constructor_result = old_state.constructor_result;
+                let selectors = old_state.selectors;
                 let vm = VM::new(old_state.bytecode, old_state.vm_config, watchdog.clone())?;
                 Ok(state::VMReady {
                     vm,
+                    constructor_result,
                     tc_config,
                     watchdog,
+                    selectors,
                 })
             })
         }
@@ -234,6 +369,9 @@ impl Extractor<state::VMReady> {
     /// Symbolically executes the disassembled bytecode on the [`VM`] to gather
     /// symbolic values that are built during execution.
     ///
+    /// If the constructor was executed previously, its results are merged into
+    /// the results of executing the runtime code.
+    ///
     /// # Errors
     ///
     /// Returns [`Err`] if execution in the virtual machine fails for any
@@ -242,13 +380,18 @@ impl Extractor<state::VMReady> {
         unsafe {
             self.transform_state(|mut old_state| {
                 old_state.vm.execute()?;
-                let execution_result = old_state.vm.consume();
+                let mut execution_result = old_state.vm.consume();
+                if let Some(constructor_result) = old_state.constructor_result {
+                    execution_result.merge(constructor_result);
+                }
                 let tc_config = old_state.tc_config;
                 let watchdog = old_state.watchdog;
+                let selectors = old_state.selectors;
                 Ok(state::ExecutionComplete {
                     execution_result,
                     tc_config,
                     watchdog,
+                    selectors,
                 })
             })
         }
@@ -268,11 +411,13 @@ impl Extractor<state::ExecutionComplete> {
             self.transform_state(|old_state| {
                 let watchdog = old_state.watchdog;
                 let execution_result = old_state.execution_result;
+                let selectors = old_state.selectors;
                 let engine = TypeChecker::new(old_state.tc_config, watchdog.clone());
                 Ok(state::InferenceReady {
                     engine,
                     watchdog,
                     execution_result,
+                    selectors,
                 })
             })
             .expect("Explicit closure cannot return Err")
@@ -294,7 +439,12 @@ impl Extractor<state::InferenceReady> {
             self.transform_state(|mut old_state| {
                 let layout = old_state.engine.run(old_state.execution_result)?;
                 let engine = old_state.engine;
-                Ok(state::InferenceComplete { engine, layout })
+                let selectors = old_state.selectors;
+                Ok(state::InferenceComplete {
+                    engine,
+                    layout,
+                    selectors,
+                })
             })
         }
     }
@@ -314,4 +464,11 @@ impl Extractor<state::InferenceComplete> {
     pub fn layout(&self) -> &StorageLayout {
         &self.state.layout
     }
+
+    /// Gets the functions exposed by the contract, as recognised from its
+    /// dispatcher.
+    #[must_use]
+    pub fn selectors(&self) -> &SelectorTable {
+        &self.state.selectors
+    }
 }
@@ -3,6 +3,7 @@
 use std::fmt::Debug;
 
 use crate::{
+    cfg::dispatcher::SelectorTable,
     disassembly::InstructionStream,
     tc,
     tc::TypeChecker,
@@ -20,7 +21,7 @@ use crate::{
 /// that correct state transitions take place.
 pub trait State
 where
-    Self: Debug + Sized,
+    Self: Debug + Send + Sized + Sync,
 {
 }
 
@@ -38,12 +39,41 @@ pub struct HasContract {
 }
 impl State for HasContract {}
 
+/// The state for an extractor that has executed the constructor of a contract
+/// constructed from its creation bytecode, and has discovered the runtime code.
+#[derive(Debug)]
+pub struct ConstructorComplete {
+    /// The runtime bytecode that was returned by the constructor.
+    pub runtime_bytecode: Vec<u8>,
+
+    /// The result of executing the constructor.
+    pub constructor_result: ExecutionResult,
+
+    /// The virtual machine configuration.
+    pub vm_config: vm::Config,
+
+    /// The unifier configuration.
+    pub tc_config: tc::Config,
+
+    /// The watchdog that is monitoring the progress of the extractor.
+    pub watchdog: DynWatchdog,
+}
+impl State for ConstructorComplete {}
+
 /// The state for an extractor that has successfully disassembled the bytecode.
 #[derive(Debug)]
 pub struct DisassemblyComplete {
     /// The disassembled bytecode for the contract being analyzed.
     pub bytecode: InstructionStream,
 
+    /// The functions exposed by the contract, as recognised from its
+    /// dispatcher.
+    pub selectors: SelectorTable,
+
+    /// The result of executing the constructor, if the contract was
+    /// constructed from its creation bytecode.
+    pub constructor_result: Option<ExecutionResult>,
+
     /// The configuration for the extractor's virtual machine.
     pub vm_config: vm::Config,
 
@@ -63,17 +93,29 @@ pub struct VMReady {
     /// execute.
     pub vm: VM,
 
+    /// The result of executing the constructor, if the contract was
+    /// constructed from its creation bytecode.
+    pub constructor_result: Option<ExecutionResult>,
+
     /// The configuration for the extractor's type checker.
     pub tc_config: tc::Config,
 
     /// The watchdog that is monitoring the progress of the extractor.
     pub watchdog: DynWatchdog,
+
+    /// The functions exposed by the contract, as recognised from its
+    /// dispatcher.
+    pub selectors: SelectorTable,
 }
 impl State for VMReady {}
 
+/// The extractor has symbolically executed the contract's bytecode.
 #[derive(Debug)]
 pub struct ExecutionComplete {
     /// The result from executing the bytecode.
+    ///
+    /// If the contract was constructed from its creation bytecode, this also
+    /// contains the results of executing the constructor.
     pub execution_result: ExecutionResult,
 
     /// The configuration for the extractor's type checker.
@@ -81,6 +123,10 @@ pub struct ExecutionComplete {
 
     /// The watchdog that is monitoring the progress of the extractor.
     pub watchdog: DynWatchdog,
+
+    /// The functions exposed by the contract, as recognised from its
+    /// dispatcher.
+    pub selectors: SelectorTable,
 }
 impl State for ExecutionComplete {}
 
@@ -95,6 +141,10 @@ pub struct InferenceReady {
 
     /// The result of executing the virtual machine on the config.
     pub execution_result: ExecutionResult,
+
+    /// The functions exposed by the contract, as recognised from its
+    /// dispatcher.
+    pub selectors: SelectorTable,
 }
 impl State for InferenceReady {}
 
@@ -107,5 +157,33 @@ pub struct InferenceComplete {
 
     /// The computed storage layout.
     pub layout: StorageLayout,
+
+    /// The functions exposed by the contract, as recognised from its
+    /// dispatcher.
+    pub selectors: SelectorTable,
 }
 impl State for InferenceComplete {}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        extractor::{state, Extractor},
+        StorageLayout,
+    };
+
+    /// Fails to compile unless `T` can be both moved and shared between
+    /// threads.
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn extractor_is_send_and_sync_in_every_state() {
+        assert_send_sync::<Extractor<state::HasContract>>();
+        assert_send_sync::<Extractor<state::ConstructorComplete>>();
+        assert_send_sync::<Extractor<state::DisassemblyComplete>>();
+        assert_send_sync::<Extractor<state::VMReady>>();
+        assert_send_sync::<Extractor<state::ExecutionComplete>>();
+        assert_send_sync::<Extractor<state::InferenceReady>>();
+        assert_send_sync::<Extractor<state::InferenceComplete>>();
+        assert_send_sync::<StorageLayout>();
+    }
+}
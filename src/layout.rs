@@ -12,9 +12,15 @@ use crate::{tc::abi::AbiType, utility::U256Wrapper};
 ///
 /// Note that it may contain non-Solidity types in order to provide the
 /// most-informative output for downstream tools.
+///
+/// The slots of the contract's transient storage (see
+/// [EIP-1153](https://eips.ethereum.org/EIPS/eip-1153)) are kept in a separate
+/// section of the layout, as they occupy an entirely separate address space to
+/// the persistent storage slots.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct StorageLayout {
-    slots: Vec<StorageSlot>,
+    slots:           Vec<StorageSlot>,
+    transient_slots: Vec<StorageSlot>,
 }
 
 impl StorageLayout {
@@ -29,6 +35,17 @@ impl StorageLayout {
         self.slots.sort_by_key(|s| (s.index, s.offset));
     }
 
+    /// Adds a transient storage slot specified at the specified `index` and
+    /// with the specified `typ` to the storage layout.
+    pub fn add_transient(&mut self, index: impl Into<U256Wrapper>, offset: usize, typ: AbiType) {
+        let slot = StorageSlot::new(index, offset, typ);
+        self.transient_slots.push(slot);
+
+        // Keep them sorted by slot index with ties broken by the offset within the
+        // slot.
+        self.transient_slots.sort_by_key(|s| (s.index, s.offset));
+    }
+
     /// Gets the storage slots that make up this layout.
     ///
     /// These are guaranteed to be sorted in ascending order by slot index and
@@ -37,6 +54,15 @@ impl StorageLayout {
     pub fn slots(&self) -> &Vec<StorageSlot> {
         &self.slots
     }
+
+    /// Gets the transient storage slots that make up this layout.
+    ///
+    /// These are guaranteed to be sorted in ascending order by slot index and
+    /// then offset within the slot.
+    #[must_use]
+    pub fn transient_slots(&self) -> &Vec<StorageSlot> {
+        &self.transient_slots
+    }
 }
 
 /// Additional utility functions to enable cleaner testing with the storage
@@ -65,17 +91,39 @@ impl StorageLayout {
         self.slots.len()
     }
 
-    /// Checks if the storage layout is empty (has no slots).
+    /// Checks if the storage layout is empty, having neither persistent nor
+    /// transient storage slots.
     #[must_use]
     pub fn is_empty(&self) -> bool {
-        self.slots.is_empty()
+        self.slots.is_empty() && self.transient_slots.is_empty()
+    }
+
+    /// Checks if the layout contains the specified transient storage slot.
+    #[must_use]
+    pub fn has_transient_slot(
+        &self,
+        index: impl Into<U256Wrapper>,
+        offset: usize,
+        typ: AbiType,
+    ) -> bool {
+        self.transient_slots.contains(&StorageSlot::new(index, offset, typ))
+    }
+
+    /// Gets the number of transient slots in the storage layout.
+    #[must_use]
+    pub fn transient_slot_count(&self) -> usize {
+        self.transient_slots.len()
     }
 }
 
 impl Default for StorageLayout {
     fn default() -> Self {
         let slots = Vec::new();
-        Self { slots }
+        let transient_slots = Vec::new();
+        Self {
+            slots,
+            transient_slots,
+        }
     }
 }
 
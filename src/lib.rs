@@ -128,6 +128,7 @@
 #![warn(clippy::all, clippy::cargo, clippy::pedantic)]
 #![allow(clippy::module_name_repetitions)] // Allows for better API naming
 
+pub mod cfg;
 pub mod constant;
 pub mod data;
 pub mod disassembly;
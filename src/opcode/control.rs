@@ -1,9 +1,14 @@
 //! Opcodes that perform control(-flow) operations on the EVM.
 
 use crate::{
+    constant::MAXIMUM_STACK_REACH,
     error::{container::Locatable, execution, execution::Error},
     opcode::{util, ExecuteResult, Opcode},
     vm::{
+        call::{CallFrame, CallSummary},
+        scheduler,
+        scheduler::Dispatch,
+        thread::VMThread,
         value::{known::KnownWord, Provenance, RuntimeBoxedVal, RSVD},
         VM,
     },
@@ -110,6 +115,20 @@ impl Default for Invalid {
 /// - `$pc` is the program counter
 /// - `counter` is the new value for the program counter
 ///
+/// # Computed Destinations
+///
+/// If `counter` is not a known immediate but can only take a small number of
+/// words, as computed by [`crate::vm::value_set`], the current thread jumps to
+/// the first of them that is a valid destination. A thread is forked to each of
+/// the other valid destinations, subject to the fork limit for that target.
+///
+/// # Internal Calls
+///
+/// A jump with the address of a `JUMPDEST` pushed beneath its arguments is
+/// treated as a call to an internal function, and a later jump to that address
+/// as the return from it. These are tracked in the call stack of the current
+/// thread, as described by [`crate::vm::Config::maximum_call_depth`].
+///
 /// # Errors
 ///
 /// Execution is reverted if there is not enough gas or if there are not enough
@@ -122,12 +141,38 @@ pub struct Jump;
 impl Opcode for Jump {
     fn execute(&self, vm: &mut VM) -> ExecuteResult {
         // Get the argument
+        let instruction_pointer = vm.instruction_pointer()?;
         let counter = vm.stack_handle()?.pop()?;
 
+        Self::follow(instruction_pointer, counter, vm)?;
+        Self::track_call(instruction_pointer, vm)
+    }
+
+    fn min_gas_cost(&self) -> usize {
+        8
+    }
+
+    fn arg_count(&self) -> usize {
+        1
+    }
+
+    fn as_text_code(&self) -> String {
+        "JUMP".into()
+    }
+
+    fn as_byte(&self) -> u8 {
+        0x56
+    }
+}
+
+impl Jump {
+    /// Jumps the current thread from the jump at `instruction_pointer` to
+    /// `counter`, forking to any other destinations that `counter` can take.
+    fn follow(instruction_pointer: u32, counter: RuntimeBoxedVal, vm: &mut VM) -> ExecuteResult {
         // In `solc` compiled code, the top of the stack at jump time is a non-computed
         // immediate, allowing us to actually alter the program counter
-        let jump_target = match util::validate_jump_destination(&counter, vm) {
-            Ok(target) => target,
+        let jump_targets = match util::resolve_jump_destinations(&counter, vm) {
+            Ok(targets) => targets,
             Err(payload) => {
                 // Counter was non-trivial here, so we hold onto it.
                 vm.state()?.record_value(counter);
@@ -144,6 +189,23 @@ impl Opcode for Jump {
                 };
             }
         };
+        let Some((&jump_target, other_targets)) = jump_targets.split_first() else {
+            vm.kill_current_thread();
+            return Ok(());
+        };
+
+        // A computed counter is non-trivial even when we know where it can go
+        if !counter.constant_fold().is_known_data() {
+            vm.state()?.record_value(counter);
+        }
+
+        // Every other destination is explored by its own thread, but only up to the
+        // fork limit for that target
+        for &target in other_targets {
+            if vm.jump_targets_mut().fork_to(instruction_pointer, target)? {
+                vm.fork_current_thread(target)?;
+            }
+        }
 
         // If it is, we set the execution position to there, as executing the next
         // instruction would be incorrect. Note that the `VM` will step from the target,
@@ -154,20 +216,133 @@ impl Opcode for Jump {
         Ok(())
     }
 
-    fn min_gas_cost(&self) -> usize {
-        8
-    }
+    /// Updates the call stack of the current thread after it has taken the
+    /// jump at `instruction_pointer`, recognising calls to and returns from
+    /// internal functions.
+    fn track_call(instruction_pointer: u32, vm: &mut VM) -> ExecuteResult {
+        if vm.current_thread_killed() || vm.config().maximum_call_depth == 0 {
+            return Ok(());
+        }
+        let target = vm.execution_thread_mut()?.instruction_pointer();
+
+        // A jump to the return address of the innermost call returns from it
+        let call_stack = vm.current_thread_mut()?.call_stack_mut();
+        let returns = call_stack
+            .innermost()
+            .is_some_and(|frame| frame.return_address == target);
+        if returns {
+            if let Some(frame) = call_stack.pop() {
+                return Self::complete_call(frame, vm);
+            }
+        }
 
-    fn arg_count(&self) -> usize {
-        1
+        // Otherwise, a jump with a fresh return address beneath its arguments calls the
+        // function at the target
+        let Some((argument_count, return_address)) = Self::find_return_address(target, vm)? else {
+            return Ok(());
+        };
+        if vm.current_thread()?.call_stack().depth() >= vm.config().maximum_call_depth {
+            return Ok(());
+        }
+
+        let state = vm.state()?;
+        let stack = state.stack();
+        let arguments = (0..argument_count)
+            .map(|depth| stack.read(depth).cloned())
+            .collect::<Result<Vec<_>, _>>()
+            .locate(instruction_pointer)?;
+        let frame = CallFrame {
+            call_site: instruction_pointer,
+            function: target,
+            return_address,
+            stack_depth: stack.depth() - argument_count as usize - 1,
+            arguments,
+            pure: true,
+            visits: state.visited_instructions().clone(),
+        };
+
+        // A function that has already been summarised for arguments of this shape is
+        // not executed again, and instead returns immediately
+        if vm.config().internal_call_summaries {
+            if let Some(summary) = vm.call_summaries().get(&frame.shape()) {
+                let returns = summary.apply(&frame.arguments);
+                let mut stack = vm.stack_handle()?;
+                for _ in 0..=argument_count {
+                    stack.pop()?;
+                }
+                for value in returns.into_iter().rev() {
+                    stack.push(value)?;
+                }
+                vm.execution_thread_mut()?.jump(return_address);
+
+                return Ok(());
+            }
+        }
+
+        vm.current_thread_mut()?.call_stack_mut().push(frame);
+
+        Ok(())
     }
 
-    fn as_text_code(&self) -> String {
-        "JUMP".into()
+    /// Finds the return address for a call to the function at `target`, which
+    /// is the topmost pushed jump destination on the stack that is not already
+    /// the return address of an active call.
+    ///
+    /// Returns the number of arguments above the return address along with the
+    /// address itself, or [`None`] if the jump is not a call.
+    fn find_return_address(target: u32, vm: &mut VM) -> execution::Result<Option<(u32, u32)>> {
+        let stack_depth = u32::try_from(vm.state()?.stack().depth()).unwrap_or(u32::MAX);
+
+        for depth in 0..stack_depth.min(MAXIMUM_STACK_REACH + 1) {
+            let value = vm.state()?.stack().read(depth).cloned().locate(target)?;
+            if value.provenance() != Provenance::Bytecode {
+                continue;
+            }
+            let Some(address) = value.as_word().and_then(|w| u32::try_from(w.value_le()).ok())
+            else {
+                continue;
+            };
+
+            // Anything beneath the return address of an active call belongs to its caller
+            if vm.current_thread()?.call_stack().returns_to(address) {
+                return Ok(None);
+            }
+            if address != target && util::check_jump_target(address, vm).is_ok() {
+                return Ok(Some((depth, address)));
+            }
+        }
+
+        Ok(None)
     }
 
-    fn as_byte(&self) -> u8 {
-        0x56
+    /// Completes the call described by `frame`, whose function has just
+    /// returned, by restoring the caller's visits to instructions.
+    ///
+    /// If enabled, and if the function only manipulated the stack, the values
+    /// that it returned are recorded as its summary.
+    fn complete_call(frame: CallFrame, vm: &mut VM) -> ExecuteResult {
+        let shape = frame.shape();
+        let state = vm.state()?;
+        *state.visited_instructions_mut() = frame.visits;
+
+        if !vm.config().internal_call_summaries || !frame.pure {
+            return Ok(());
+        }
+        let stack = vm.state()?.stack();
+        let Some(return_count) = stack.depth().checked_sub(frame.stack_depth) else {
+            return Ok(());
+        };
+        let returns = (0..u32::try_from(return_count).unwrap_or(0))
+            .map(|depth| stack.read(depth).cloned())
+            .collect::<Result<Vec<_>, _>>()
+            .locate(frame.return_address)?;
+        let summary = CallSummary {
+            arguments: frame.arguments,
+            returns,
+        };
+        vm.call_summaries_mut().record(shape, summary);
+
+        Ok(())
     }
 }
 
@@ -189,6 +364,29 @@ impl Opcode for Jump {
 /// - `counter` is the new value for the program counter
 /// - `cond` is the boolean value to check before executing the jump.
 ///
+/// If `counter` is not a known immediate, a thread is forked to each of the
+/// destinations that it can take in the same way as for [`Jump`].
+///
+/// # Pruning
+///
+/// If `cond` constant-folds to a known word, only the feasible branch is
+/// followed by the current thread and no new thread is forked. The other branch
+/// is recorded as pruned in the virtual machine's
+/// [`crate::vm::data::JumpTargets`].
+///
+/// This is only done when `cond` is computed entirely in the same basic block
+/// as the jump, as values passed in from elsewhere depend on a calling context
+/// that the virtual machine may not fully explore. It is also not done once the
+/// current thread has visited the jump the maximum number of times, as it would
+/// otherwise never exit a loop with a concrete bound larger than the iteration
+/// limit.
+///
+/// When [`crate::vm::Config::path_condition_solving`] is enabled, a branch is
+/// also pruned if the path condition of the current thread proves that it can
+/// never be taken, subject to the same limit on visits. Otherwise, each thread
+/// that continues from the jump records the outcome of `cond` in its path
+/// condition.
+///
 /// # Errors
 ///
 /// Execution is reverted if there is not enough gas or if there are not enough
@@ -205,21 +403,93 @@ impl Opcode for JumpI {
         let counter = vm.stack_handle()?.pop()?;
         let condition = vm.stack_handle()?.pop()?;
 
+        // If the condition is concretely known only one of the branches is feasible, so
+        // we follow it alone and record that the other was pruned. A thread that has
+        // reached the jump as often as it may is stuck in a loop it cannot exit within
+        // the iteration limit, however, so it explores both branches instead.
+        let at_visit_limit = vm
+            .state()?
+            .visited_instructions()
+            .at_visit_limit(instruction_pointer)?;
+        let known_condition = if at_visit_limit {
+            None
+        } else {
+            condition
+                .constant_fold()
+                .as_word()
+                .filter(|_| Self::is_local(&condition, instruction_pointer, vm))
+        };
+
         // We want to store that the condition existed, even if the jump target is
         // invalid, so we record it in the buffer of otherwise-lost values
-        vm.state()?.record_value(condition);
+        vm.state()?.record_value(condition.clone());
+
+        // Either branch of the jump may be entering an external function
+        let dispatch = scheduler::dispatch(&condition, vm.selectors());
+
+        if let Some(condition) = known_condition {
+            let jumps = condition != KnownWord::zero();
+            Self::dispatch_thread(vm.current_thread_mut()?, dispatch, jumps);
+            return if jumps {
+                Self::take_jump(instruction_pointer, counter, vm)
+            } else {
+                Self::prune_jump(instruction_pointer, counter, vm)
+            };
+        }
+
+        // The path through any active internal functions now depends on the condition,
+        // so they cannot be summarised by the values they return on this path alone
+        vm.current_thread_mut()?.call_stack_mut().mark_impure();
+
+        // Otherwise, a branch is also infeasible if it contradicts the conditions under
+        // which the current thread got here, subject to the same iteration limit
+        let solving = vm.config().path_condition_solving;
+        if solving && !at_visit_limit {
+            let path_condition = vm.current_thread()?.path_condition();
+            let can_jump = path_condition.admits(&condition, true);
+            let can_fall_through = path_condition.admits(&condition, false);
+
+            if !can_jump || !can_fall_through {
+                let thread = vm.current_thread_mut()?;
+                thread.path_condition_mut().assume(&condition, can_jump);
+                Self::dispatch_thread(thread, dispatch, can_jump);
+                return if can_jump {
+                    Self::take_jump(instruction_pointer, counter, vm)
+                } else {
+                    Self::prune_jump(instruction_pointer, counter, vm)
+                };
+            }
+        }
 
         // In `solc` compiled code, the top of the stack at jump time is a non-computed
         // immediate, allowing us to actually alter the program counter
-        match util::validate_jump_destination(&counter, vm) {
-            Ok(target) => {
-                // We only want to fork up to the provided limit, so we check if we can first
-                if vm.jump_targets_mut().fork_to(instruction_pointer, target)? {
-                    // If we do have a valid jump target, we need to fork off an execution thread so
-                    // that both branches can be executed. Note that the `VM` will step from the
-                    // target, but as it is a JUMPDEST no-op this is fine.
-                    vm.fork_current_thread(target)?;
+        match util::resolve_jump_destinations(&counter, vm) {
+            Ok(targets) => {
+                // A computed counter is non-trivial even when we know where it can go
+                if !counter.constant_fold().is_known_data() {
+                    vm.state()?.record_value(counter);
+                }
+
+                for target in targets {
+                    // We only want to fork up to the provided limit, so we check if we can first
+                    if vm.jump_targets_mut().fork_to(instruction_pointer, target)? {
+                        // If we do have a valid jump target, we need to fork off an execution
+                        // thread so that both branches can be executed. Note that the `VM` will
+                        // step from the target, but as it is a JUMPDEST no-op this is fine.
+                        let new_thread = vm.fork_current_thread(target)?;
+                        if solving {
+                            new_thread.path_condition_mut().assume(&condition, true);
+                        }
+                        Self::dispatch_thread(new_thread, dispatch, true);
+                    }
+                }
+
+                // The current thread only continues if the condition was zero
+                let thread = vm.current_thread_mut()?;
+                if solving {
+                    thread.path_condition_mut().assume(&condition, false);
                 }
+                Self::dispatch_thread(thread, dispatch, false);
 
                 // Done, so return ok, leaving the current thread in the same position as it
                 // needs to be stepped by the `VM`
@@ -242,6 +512,10 @@ impl Opcode for JumpI {
                 // If it is an error that only affects the potential _target_ thread, we need to
                 // store it and continue execution on the current thread.
                 vm.store_error(result);
+                if solving {
+                    let path_condition = vm.current_thread_mut()?.path_condition_mut();
+                    path_condition.assume(&condition, false);
+                }
                 Ok(())
             }
         }
@@ -264,6 +538,84 @@ impl Opcode for JumpI {
     }
 }
 
+impl JumpI {
+    /// Checks whether every part of the `condition` of the jump at
+    /// `instruction_pointer` was computed within the basic block that ends with
+    /// the jump.
+    ///
+    /// Conditions that are passed in from elsewhere, such as the arguments to
+    /// internal functions, depend on the calling context. As the virtual
+    /// machine does not explore every calling context, only local conditions
+    /// are used to prune branches.
+    fn is_local(condition: &RuntimeBoxedVal, instruction_pointer: u32, vm: &VM) -> bool {
+        let Some(block) = vm.control_flow_graph().block_containing(instruction_pointer) else {
+            return false;
+        };
+        let block = block.start..instruction_pointer;
+
+        // Every part of the condition, including the constants it is built from, needs
+        // to come from the block
+        let mut parts = vec![condition.clone()];
+        while let Some(part) = parts.pop() {
+            if !block.contains(&part.instruction_pointer()) {
+                return false;
+            }
+            parts.extend(part.children());
+        }
+
+        true
+    }
+
+    /// Continues execution of the current thread at the instruction following
+    /// the jump at `instruction_pointer`, recording that the branch to
+    /// `counter` was pruned as the condition was known to be false.
+    ///
+    /// The target is not validated, as it can never be jumped to.
+    fn prune_jump(
+        instruction_pointer: u32,
+        counter: RuntimeBoxedVal,
+        vm: &mut VM,
+    ) -> ExecuteResult {
+        match util::resolve_jump_destinations(&counter, vm) {
+            Ok(targets) => {
+                for target in targets {
+                    vm.jump_targets_mut().prune(instruction_pointer, target);
+                }
+            }
+            Err(_) => vm.state()?.record_value(counter),
+        }
+
+        Ok(())
+    }
+
+    /// Records that `thread` has entered the external function of `dispatch`
+    /// if it is entered on the branch of the jump given by `jumps`.
+    fn dispatch_thread(thread: &mut VMThread, dispatch: Option<Dispatch>, jumps: bool) {
+        if let Some(dispatch) = dispatch.filter(|d| d.on_jump == jumps) {
+            thread.dispatch_to(dispatch.selector);
+        }
+    }
+
+    /// Jumps the current thread to `counter` without forking, recording that
+    /// the fall-through branch of the jump at `instruction_pointer` was pruned
+    /// as the condition was known to be true.
+    ///
+    /// This behaves as an unconditional [`Jump`], and hence does not count
+    /// towards the fork limit for the target.
+    fn take_jump(instruction_pointer: u32, counter: RuntimeBoxedVal, vm: &mut VM) -> ExecuteResult {
+        Jump::follow(instruction_pointer, counter, vm)?;
+
+        // Without knowing where to jump to the thread cannot continue, in which case
+        // nothing was pruned
+        if !vm.current_thread_killed() {
+            vm.jump_targets_mut()
+                .prune(instruction_pointer, instruction_pointer + 1);
+        }
+
+        Ok(())
+    }
+}
+
 /// The `PC` opcode gets the value of the program counter _prior_ to the
 /// increment corresponding to this instruction.
 ///
@@ -352,11 +704,48 @@ impl Opcode for JumpDest {
     }
 }
 
+/// Determines whether a message call to `address` with `argument_data` is a
+/// call to one of the known system contracts of the chain on which the `vm` is
+/// executing.
+///
+/// If it is, this returns the constant address and the function selector of
+/// the call, and returns [`None`] otherwise.
+fn system_call_target(
+    address: &RuntimeBoxedVal,
+    argument_data: &RuntimeBoxedVal,
+    vm: &mut VM,
+) -> Option<(RuntimeBoxedVal, RuntimeBoxedVal)> {
+    let instruction_pointer = vm.instruction_pointer().ok()?;
+    let address = address.constant_fold();
+    let address_bytes: [u8; 20] = address.as_word()?.bytes_be()[12..].try_into().ok()?;
+    if !vm.config().chain().has_system_contract_at(&address_bytes) {
+        return None;
+    }
+
+    // The selector occupies the first four bytes of the call data
+    let first_word = match argument_data.data() {
+        RSVD::Concat { values } => values.first()?.constant_fold(),
+        _ => argument_data.constant_fold(),
+    };
+    let selector_bytes: [u8; 4] = first_word.as_word()?.bytes_be()[..4].try_into().ok()?;
+    let selector = vm.build().known_exec(
+        instruction_pointer,
+        KnownWord::from(u32::from_be_bytes(selector_bytes) as usize),
+    );
+
+    Some((address, selector))
+}
+
 /// Stores the return data from an external message call for `ret_size` at
 /// `ret_offset`.
+///
+/// If the call is to a known system contract, as given by the `system_call`
+/// address and selector, the return data is instead represented as
+/// [`RSVD::SystemCallData`].
 fn store_return_data(
     ret_size: &RuntimeBoxedVal,
     ret_offset: &RuntimeBoxedVal,
+    system_call: Option<&(RuntimeBoxedVal, RuntimeBoxedVal)>,
     vm: &mut VM,
 ) -> ExecuteResult {
     let instruction_pointer = vm.instruction_pointer()?;
@@ -392,22 +781,24 @@ fn store_return_data(
             let src_offset = vm
                 .build()
                 .known_exec(instruction_pointer, KnownWord::from(internal_offset));
-            let value = vm.build().symbolic_exec(
-                instruction_pointer,
-                RSVD::ReturnData {
+            let data = match system_call {
+                Some((address, selector)) => RSVD::SystemCallData {
+                    address:  address.clone(),
+                    selector: selector.clone(),
+                    offset:   src_offset,
+                    size:     num_32.clone(),
+                },
+                None => RSVD::ReturnData {
                     offset: src_offset,
                     size:   num_32.clone(),
                 },
-            );
+            };
+            let value = vm.build().symbolic_exec(instruction_pointer, data);
             let memory = vm.state()?.memory_mut();
             memory.store(dest_offset, value);
         }
     } else {
-        let ret_value = vm.build().symbolic(
-            instruction_pointer,
-            RSVD::new_value(),
-            Provenance::MessageCall,
-        );
+        let ret_value = vm.build().value(instruction_pointer, Provenance::MessageCall);
         vm.state()?.memory_mut().store(ret_offset.clone(), ret_value);
     }
 
@@ -470,7 +861,8 @@ impl Opcode for Call {
                 .load_slice(&arg_offset, &arg_size, instruction_pointer);
 
         // Create the return value and store it in memory
-        store_return_data(&ret_size, &ret_offset, vm)?;
+        let system_call = system_call_target(&address, &argument_data, vm);
+        store_return_data(&ret_size, &ret_offset, system_call.as_ref(), vm)?;
 
         // Create the value representing the call
         let call_return = vm.build().symbolic_exec(
@@ -626,7 +1018,8 @@ impl Opcode for DelegateCall {
                 .load_slice(&arg_offset, &arg_size, instruction_pointer);
 
         // Create the return value and store it in memory
-        store_return_data(&ret_size, &ret_offset, vm)?;
+        let system_call = system_call_target(&address, &argument_data, vm);
+        store_return_data(&ret_size, &ret_offset, system_call.as_ref(), vm)?;
 
         // Create the value representing the call
         let call_return = vm.build().symbolic_exec(
@@ -764,6 +1157,18 @@ impl Opcode for Return {
         let offset = stack.pop()?;
         let size = stack.pop()?;
 
+        // Where the returned memory is concrete we record it, as this allows
+        // recognising when a constructor returns the runtime code.
+        if let (Some(offset), Some(size)) = (
+            offset.constant_fold().as_word(),
+            size.constant_fold().as_word(),
+        ) {
+            let offset: usize = offset.into();
+            let size: usize = size.into();
+            vm.state()?
+                .record_returned_memory(offset..offset.saturating_add(size));
+        }
+
         // Construct the value and hold onto it
         let data = vm
             .state()?
@@ -900,7 +1305,8 @@ mod test {
     use crate::{
         disassembly::InstructionStream,
         error::execution,
-        opcode::{control, macros::bytecode, test_util as util, Opcode},
+        extractor::chain::{system_contract::L1_BLOCK_ADDRESS, version::EthereumVersion, Chain},
+        opcode::{control, macros::bytecode, memory, test_util as util, Opcode},
         vm::value::{known::KnownWord, Provenance, RSV, RSVD},
     };
 
@@ -936,11 +1342,12 @@ mod test {
 
     #[test]
     fn valid_jump_continues_execution() -> anyhow::Result<()> {
-        // Prepare the instruction stream, as it actually does matter this time
+        // Prepare the instruction stream, as it actually does matter this time. The
+        // target is pushed so that the `JUMPDEST` is not treated as trailing data.
         let bytes: Vec<u8> = bytecode![
+            memory::PushN::new(1, vec![0x04]).unwrap(),
             control::Jump,
             control::Invalid::default(),
-            control::Invalid::default(),
             control::JumpDest,
         ];
         let instructions = InstructionStream::try_from(bytes.as_slice())?;
@@ -948,7 +1355,7 @@ mod test {
         // Prepare the VM
         let immediate = RSV::new_known_value(
             0,
-            KnownWord::from_le(0x03u32), // Offset of JUMPDEST in the bytes above
+            KnownWord::from_le(0x04u32), // Offset of JUMPDEST in the bytes above
             Provenance::Synthetic,
             None,
         );
@@ -963,7 +1370,7 @@ mod test {
         assert!(vm.state()?.stack_mut().is_empty());
 
         // Inspect the execution position.
-        assert_eq!(vm.execution_thread_mut()?.instruction_pointer(), 0x03);
+        assert_eq!(vm.execution_thread_mut()?.instruction_pointer(), 0x04);
 
         Ok(())
     }
@@ -1000,6 +1407,81 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn jump_with_computed_destination_forks_to_each() -> anyhow::Result<()> {
+        // Prepare the instruction stream, as it actually does matter this time. The
+        // targets are pushed so that the `JUMPDEST`s are not treated as data.
+        let bytes: Vec<u8> = bytecode![
+            memory::PushN::new(1, vec![0x05]).unwrap(),
+            memory::PushN::new(1, vec![0x07]).unwrap(),
+            control::Jump,
+            control::JumpDest,
+            control::Stop,
+            control::JumpDest,
+            control::Stop
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+
+        // Prepare the VM with a counter of `5 + iszero(x) * 2`, which is 5 or 7
+        let counter = RSV::new_synthetic(
+            0,
+            RSVD::Add {
+                left:  RSV::new_known_value(
+                    0,
+                    KnownWord::from_le(5u32),
+                    Provenance::Synthetic,
+                    None,
+                ),
+                right: RSV::new_synthetic(
+                    0,
+                    RSVD::Multiply {
+                        left:  RSV::new_synthetic(
+                            0,
+                            RSVD::IsZero {
+                                number: RSV::new_synthetic(0, RSVD::new_value()),
+                            },
+                        ),
+                        right: RSV::new_known_value(
+                            0,
+                            KnownWord::from_le(2u32),
+                            Provenance::Synthetic,
+                            None,
+                        ),
+                    },
+                ),
+            },
+        );
+        let mut vm = util::new_vm_with_instructions_and_values_on_stack(
+            instructions,
+            vec![counter.clone()],
+        )?;
+        vm.execution_thread_mut()?.jump(0x04);
+
+        // Prepare and execute the opcode
+        let opcode = control::Jump;
+        opcode.execute(&mut vm)?;
+
+        // The current thread should jump to the first destination
+        assert!(!vm.current_thread_killed());
+        assert_eq!(vm.execution_thread_mut()?.instruction_pointer(), 0x05);
+
+        // And a new thread should be forked to the other
+        assert_eq!(vm.remaining_thread_count(), 2);
+        assert_eq!(
+            vm.remaining_threads_mut()
+                .back_mut()
+                .expect("No threads remaining")
+                .instructions_mut()
+                .instruction_pointer(),
+            0x07
+        );
+
+        // The counter should also have been recorded
+        assert_eq!(vm.state()?.recorded_values()[0], counter);
+
+        Ok(())
+    }
+
     #[test]
     fn jump_with_oob_target_halts_execution() -> anyhow::Result<()> {
         // Prepare the instruction stream, as it actually does matter this time
@@ -1072,6 +1554,41 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn jump_into_data_halts_execution() -> anyhow::Result<()> {
+        // The trailing `JUMPDEST` is never reached statically, so is treated as data
+        let bytes: Vec<u8> = bytecode![
+            control::Jump,
+            control::Invalid::default(),
+            control::JumpDest,
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        assert!(instructions.is_data(0x02));
+
+        // Prepare the VM
+        let immediate = RSV::new_known_value(
+            0,
+            KnownWord::from_le(0x02u32), // Offset of JUMPDEST in the bytes above
+            Provenance::Synthetic,
+            None,
+        );
+        let mut vm =
+            util::new_vm_with_instructions_and_values_on_stack(instructions, vec![immediate])?;
+
+        // Prepare and execute the opcode
+        let opcode = control::Jump;
+        let result = opcode.execute(&mut vm);
+
+        // Check that it errored
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.payload,
+            execution::Error::InvalidJumpTarget { offset: 0x02 }
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn valid_conditional_jump_continues_execution() -> anyhow::Result<()> {
         // Prepare the instruction stream, as it actually does matter this time
@@ -1123,6 +1640,146 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn conditional_jump_with_known_false_condition_prunes_target() -> anyhow::Result<()> {
+        // Prepare the instruction stream, as it actually does matter this time
+        let bytes: Vec<u8> = bytecode![
+            control::PC,
+            control::PC,
+            control::JumpI,
+            control::PC,
+            control::JumpDest,
+            control::PC
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+
+        // Prepare the VM
+        let immediate = RSV::new_known_value(
+            0,
+            KnownWord::from_le(0x04u32), // Offset of JUMPDEST in the bytes above
+            Provenance::Synthetic,
+            None,
+        );
+        let cond = RSV::new_known_value(1, KnownWord::zero(), Provenance::Synthetic, None);
+        let mut vm = util::new_vm_with_instructions_and_values_on_stack(
+            instructions,
+            vec![cond, immediate],
+        )?;
+
+        // The condition is computed in the same block as the jump
+        vm.execution_thread_mut()?.jump(0x02);
+
+        // Prepare and execute the opcode
+        let opcode = control::JumpI;
+        opcode.execute(&mut vm)?;
+
+        // The current thread should fall through without forking
+        assert_eq!(vm.execution_thread_mut()?.instruction_pointer(), 0x02);
+        assert_eq!(vm.remaining_thread_count(), 1);
+
+        // And the jump to the target should have been pruned
+        let pruned = vm.jump_targets().pruned_branches();
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].jump, 0x02);
+        assert_eq!(pruned[0].destination, 0x04);
+        assert_eq!(vm.jump_targets().cond_jump_count(0x04)?, 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn conditional_jump_with_known_true_condition_prunes_fall_through() -> anyhow::Result<()> {
+        // Prepare the instruction stream, as it actually does matter this time
+        let bytes: Vec<u8> = bytecode![
+            control::PC,
+            control::PC,
+            control::JumpI,
+            control::PC,
+            control::JumpDest,
+            control::PC
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+
+        // Prepare the VM
+        let immediate = RSV::new_known_value(
+            0,
+            KnownWord::from_le(0x04u32), // Offset of JUMPDEST in the bytes above
+            Provenance::Synthetic,
+            None,
+        );
+        let cond = RSV::new_synthetic(
+            1,
+            RSVD::IsZero {
+                number: RSV::new_known_value(1, KnownWord::zero(), Provenance::Synthetic, None),
+            },
+        );
+        let mut vm = util::new_vm_with_instructions_and_values_on_stack(
+            instructions,
+            vec![cond.clone(), immediate],
+        )?;
+
+        // The condition is computed in the same block as the jump
+        vm.execution_thread_mut()?.jump(0x02);
+
+        // Prepare and execute the opcode
+        let opcode = control::JumpI;
+        opcode.execute(&mut vm)?;
+
+        // The current thread should jump to the target without forking
+        assert_eq!(vm.execution_thread_mut()?.instruction_pointer(), 0x04);
+        assert_eq!(vm.remaining_thread_count(), 1);
+
+        // And the fall-through should have been pruned
+        let pruned = vm.jump_targets().pruned_branches();
+        assert_eq!(pruned.len(), 1);
+        assert_eq!(pruned[0].jump, 0x02);
+        assert_eq!(pruned[0].destination, 0x03);
+        assert_eq!(vm.jump_targets().cond_jump_count(0x04)?, 0);
+
+        // The condition should still be recorded
+        assert_eq!(vm.state()?.recorded_values()[0], cond);
+
+        Ok(())
+    }
+
+    #[test]
+    fn conditional_jump_with_known_condition_from_elsewhere_forks() -> anyhow::Result<()> {
+        // Prepare the instruction stream, as it actually does matter this time
+        let bytes: Vec<u8> = bytecode![
+            control::PC,
+            control::JumpDest,
+            control::JumpI,
+            control::PC,
+            control::JumpDest,
+            control::PC
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+
+        // Prepare the VM with a condition that comes from before the jump's block
+        let immediate = RSV::new_known_value(
+            0,
+            KnownWord::from_le(0x04u32), // Offset of JUMPDEST in the bytes above
+            Provenance::Synthetic,
+            None,
+        );
+        let cond = RSV::new_known_value(0, KnownWord::zero(), Provenance::Synthetic, None);
+        let mut vm = util::new_vm_with_instructions_and_values_on_stack(
+            instructions,
+            vec![cond, immediate],
+        )?;
+        vm.execution_thread_mut()?.jump(0x02);
+
+        // Prepare and execute the opcode
+        let opcode = control::JumpI;
+        opcode.execute(&mut vm)?;
+
+        // Both branches should be explored as the condition depends on context
+        assert_eq!(vm.remaining_thread_count(), 2);
+        assert!(vm.jump_targets().pruned_branches().is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn conditional_jump_with_oob_target_halts_execution() -> anyhow::Result<()> {
         // Prepare the instruction stream, as it actually does matter this time
@@ -1355,6 +2012,69 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn call_to_system_contract_produces_system_call_data() -> anyhow::Result<()> {
+        // Prepare the vm to call `L1Block.hash()` on an OP Stack chain
+        let known = |value: usize| {
+            RSV::new_known_value(0, KnownWord::from(value), Provenance::Synthetic, None)
+        };
+        let mut address_word = [0u8; 32];
+        address_word[12..].copy_from_slice(&L1_BLOCK_ADDRESS);
+        let address = RSV::new_known_value(
+            0,
+            KnownWord::from_be_bytes(address_word),
+            Provenance::Synthetic,
+            None,
+        );
+        let chain = Chain::Optimism {
+            version: EthereumVersion::Cancun,
+        };
+        let mut vm = util::new_vm_on_chain_with_values_on_stack(
+            chain,
+            vec![
+                known(0x20),
+                known(0x20),
+                known(0x04),
+                known(0x00),
+                address,
+                known(0x00),
+            ],
+        )?;
+        let mut selector_word = [0u8; 32];
+        selector_word[..4].copy_from_slice(&[0x09, 0xbd, 0x5a, 0x60]);
+        let selector_value = RSV::new_known_value(
+            0,
+            KnownWord::from_be_bytes(selector_word),
+            Provenance::Synthetic,
+            None,
+        );
+        vm.state()?.memory_mut().store(known(0x00), selector_value);
+
+        // Prepare and execute the opcode
+        let opcode = control::StaticCall;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the memory state
+        let return_value = vm.state()?.memory_mut().load(&known(0x20));
+        match return_value.data() {
+            RSVD::SystemCallData {
+                address, selector, ..
+            } => {
+                let RSVD::KnownData { value } = address.data() else {
+                    panic!("Address was not known")
+                };
+                assert_eq!(value.bytes_be()[12..], L1_BLOCK_ADDRESS);
+                let RSVD::KnownData { value } = selector.data() else {
+                    panic!("Selector was not known")
+                };
+                assert_eq!(u32::from(value), 0x09bd_5a60);
+            }
+            _ => panic!("Invalid payload"),
+        }
+
+        Ok(())
+    }
+
     #[test]
     fn return_stores_data_and_ends_execution() -> anyhow::Result<()> {
         // Prepare the vm
@@ -452,6 +452,10 @@ impl Opcode for ExtCodeHash {
 /// - `hash` is the keccak256 hash of the chosen block, or 0 if `n` is not in
 ///   the valid range
 ///
+/// On Arbitrum chains the hash is a pseudo-random value rather than the hash
+/// of an L1 block, but as it is still a 32-byte value it is modelled
+/// identically.
+///
 /// # Errors
 ///
 /// Execution is reverted if there is not enough gas or if there are not enough
@@ -604,6 +608,11 @@ impl Opcode for Timestamp {
 /// | :---------: | :---: | :------------: |
 /// | 1           |       | `block.number` |
 ///
+/// On chains where the opcode instead reports the number of the most recent
+/// block on the parent chain (see
+/// [`crate::extractor::chain::Chain::reports_l1_block_number`]), the
+/// output is `l1_block.number`.
+///
 /// # Errors
 ///
 /// Execution is reverted if there is not enough gas or if there are not enough
@@ -617,7 +626,12 @@ impl Opcode for Number {
         let instruction_pointer = vm.instruction_pointer()?;
 
         // Create and push the value onto the stack
-        let value = vm.build().symbolic_exec(instruction_pointer, RSVD::BlockNumber);
+        let data = if vm.config().chain().reports_l1_block_number() {
+            RSVD::L1BlockNumber
+        } else {
+            RSVD::BlockNumber
+        };
+        let value = vm.build().symbolic_exec(instruction_pointer, data);
         let mut stack = vm.stack_handle()?;
         stack.push(value)?;
 
@@ -882,6 +896,116 @@ impl Opcode for BaseFee {
     }
 }
 
+/// The `BLOBHASH` opcode gets the versioned hash of one of the blobs associated
+/// with the current transaction.
+///
+/// # Semantics
+///
+/// | Stack Index | Input   | Output                           |
+/// | :---------: | :-----: | :------------------------------: |
+/// | 1           | `index` | `hash := tx.blob_hashes[index]` |
+///
+/// where:
+///
+/// - `index` is the index of the blob in the transaction
+/// - `hash` is the versioned hash of the blob, or zero if there is no blob at
+///   `index`
+///
+/// # Errors
+///
+/// Execution is reverted if there is not enough gas or if there are not enough
+/// operands on the stack.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BlobHash;
+
+impl Opcode for BlobHash {
+    fn execute(&self, vm: &mut VM) -> ExecuteResult {
+        // Get the stack and environment data
+        let instruction_pointer = vm.instruction_pointer()?;
+        let mut stack = vm.stack_handle()?;
+
+        // Get the argument
+        let index = stack.pop()?;
+
+        // Create and push the value onto the stack
+        let value = vm
+            .build()
+            .symbolic_exec(instruction_pointer, RSVD::BlobHash { index });
+        let mut stack = vm.stack_handle()?;
+        stack.push(value)?;
+
+        // Done, so return ok
+        Ok(())
+    }
+
+    fn min_gas_cost(&self) -> usize {
+        3
+    }
+
+    fn arg_count(&self) -> usize {
+        1
+    }
+
+    fn as_text_code(&self) -> String {
+        "BLOBHASH".into()
+    }
+
+    fn as_byte(&self) -> u8 {
+        0x49
+    }
+}
+
+/// The `BLOBBASEFEE` opcode gets the blob base fee of the current block in
+/// WEI.
+///
+/// # Semantics
+///
+/// | Stack Index | Input | Output                     |
+/// | :---------: | :---: | :------------------------: |
+/// | 1           |       | `fee := block.blobbasefee` |
+///
+/// where:
+///
+/// - `fee` is the block's blob base fee in WEI
+///
+/// # Errors
+///
+/// Execution is reverted if there is not enough gas or if there are not enough
+/// operands on the stack.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct BlobBaseFee;
+
+impl Opcode for BlobBaseFee {
+    fn execute(&self, vm: &mut VM) -> ExecuteResult {
+        // Get the stack and environment data
+        let instruction_pointer = vm.instruction_pointer()?;
+
+        // Create and push the value onto the stack
+        let value = vm.build().symbolic_exec(instruction_pointer, RSVD::BlobBaseFee);
+        let mut stack = vm.stack_handle()?;
+        stack.push(value)?;
+
+        // Done, so return ok
+        Ok(())
+    }
+
+    fn min_gas_cost(&self) -> usize {
+        2
+    }
+
+    fn arg_count(&self) -> usize {
+        0
+    }
+
+    fn as_text_code(&self) -> String {
+        "BLOBBASEFEE".into()
+    }
+
+    fn as_byte(&self) -> u8 {
+        0x4a
+    }
+}
+
 /// The `GAS` opcode gets the amount of gas currently available.
 ///
 /// # Semantics
@@ -1203,8 +1327,19 @@ impl Opcode for Create2 {
     }
 }
 
-/// The `SELFDESTRUCT` opcode halts execution and registers the account for
-/// later deletion.
+/// The `SELFDESTRUCT` opcode halts execution and sends the account's balance
+/// to the target `address`.
+///
+/// Prior to the Cancun fork of Ethereum this also registers the account for
+/// deletion. From Cancun onwards (see
+/// [EIP-6780](https://eips.ethereum.org/EIPS/eip-6780)) the account is only
+/// deleted when the opcode is executed in the same transaction that created
+/// it. In both cases execution on the current thread ends.
+///
+/// Which of these behaviours applies is determined by the chain that the
+/// virtual machine is configured for, and is recorded in the resulting value.
+/// The recorded behaviour assumes that the opcode is executed outside of the
+/// transaction that created the account.
 ///
 /// # Semantics
 ///
@@ -1233,15 +1368,23 @@ impl Opcode for SelfDestruct {
         // Get the argument from the stack
         let target = stack.pop()?;
 
-        // Construct the result
-        let destroy = vm
-            .build()
-            .symbolic_exec(instruction_pointer, RSVD::SelfDestruct { target });
+        // Construct the result, following the semantics of the configured chain
+        let deletes_account = vm.config().chain().self_destruct_deletes_account();
+        let destroy = vm.build().symbolic_exec(
+            instruction_pointer,
+            RSVD::SelfDestruct {
+                target,
+                deletes_account,
+            },
+        );
 
         // Store it in the recorded values store, as otherwise it would be dropped and
         // we would lose info
         vm.state()?.record_value(destroy);
 
+        // Nothing after a `SELFDESTRUCT` is executed, so we kill the current thread
+        vm.kill_current_thread();
+
         // Done, so return ok
         Ok(())
     }
@@ -1266,6 +1409,7 @@ impl Opcode for SelfDestruct {
 #[cfg(test)]
 mod test {
     use crate::{
+        extractor::chain::{version::EthereumVersion, Chain},
         opcode::{environment, test_util as util, Opcode},
         vm::value::{Provenance, RuntimeBoxedVal, RSV, RSVD},
     };
@@ -1525,6 +1669,28 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn number_pushes_l1_block_number_on_arbitrum() -> anyhow::Result<()> {
+        // Prepare the vm
+        let chain = Chain::Arbitrum {
+            version: EthereumVersion::Cancun,
+        };
+        let mut vm = util::new_vm_on_chain_with_values_on_stack(chain, vec![])?;
+
+        // Prepare and run the opcode
+        let opcode = environment::Number;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the stack
+        let stack = vm.state()?.stack_mut();
+        assert_eq!(stack.depth(), 1);
+        let result = stack.read(0)?;
+        assert_eq!(result.provenance(), Provenance::Execution);
+        assert_eq!(result.data(), &RSVD::L1BlockNumber);
+
+        Ok(())
+    }
+
     #[test]
     fn difficulty_pushes_value_onto_stack() -> anyhow::Result<()> {
         // Prepare the vm
@@ -1620,6 +1786,50 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn blob_hash_pushes_value_onto_stack() -> anyhow::Result<()> {
+        // Prepare the vm
+        let input_index = RSV::new_synthetic(0, RSVD::new_value());
+        let mut vm = util::new_vm_with_values_on_stack(vec![input_index.clone()])?;
+
+        // Prepare and run the opcode
+        let opcode = environment::BlobHash;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the stack
+        let stack = vm.state()?.stack_mut();
+        assert_eq!(stack.depth(), 1);
+        let result = stack.read(0)?;
+        assert_eq!(result.provenance(), Provenance::Execution);
+        match result.data() {
+            RSVD::BlobHash { index } => {
+                assert_eq!(index, &input_index);
+            }
+            _ => panic!("Invalid payload"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn blob_base_fee_pushes_value_onto_stack() -> anyhow::Result<()> {
+        // Prepare the vm
+        let mut vm = util::new_vm_with_values_on_stack(vec![])?;
+
+        // Prepare and run the opcode
+        let opcode = environment::BlobBaseFee;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the stack
+        let stack = vm.state()?.stack_mut();
+        assert_eq!(stack.depth(), 1);
+        let result = stack.read(0)?;
+        assert_eq!(result.provenance(), Provenance::Execution);
+        assert_eq!(result.data(), &RSVD::BlobBaseFee);
+
+        Ok(())
+    }
+
     #[test]
     fn gas_pushes_value_onto_stack() -> anyhow::Result<()> {
         // Prepare the vm
@@ -1780,12 +1990,46 @@ mod test {
         let value = &state.recorded_values()[0];
         assert_eq!(value.provenance(), Provenance::Execution);
         match value.data() {
-            RSVD::SelfDestruct { target } => {
+            RSVD::SelfDestruct {
+                target,
+                deletes_account,
+            } => {
                 assert_eq!(target, &input_address);
+                assert!(!deletes_account);
             }
             _ => panic!("Invalid payload"),
         }
 
+        // Inspect the thread
+        assert!(vm.current_thread_killed());
+
+        Ok(())
+    }
+
+    #[test]
+    fn self_destruct_deletes_account_before_cancun() -> anyhow::Result<()> {
+        // Prepare the vm
+        let input_address = RSV::new_synthetic(0, RSVD::new_value());
+        let chain = Chain::Ethereum {
+            version: EthereumVersion::Shanghai,
+        };
+        let mut vm = util::new_vm_on_chain_with_values_on_stack(chain, vec![input_address])?;
+
+        // Prepare and run the opcode
+        let opcode = environment::SelfDestruct;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the state
+        let state = vm.state()?;
+        assert_eq!(state.recorded_values().len(), 1);
+        match state.recorded_values()[0].data() {
+            RSVD::SelfDestruct {
+                deletes_account, ..
+            } => assert!(deletes_account),
+            _ => panic!("Invalid payload"),
+        }
+        assert!(vm.current_thread_killed());
+
         Ok(())
     }
 }
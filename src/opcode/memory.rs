@@ -13,7 +13,8 @@ use crate::{
     error::{container::Locatable, disassembly, execution::Error},
     opcode::{ExecuteResult, Opcode},
     vm::{
-        value::{known::KnownWord, Provenance, RSV, RSVD},
+        state::CodeCopyRecord,
+        value::{known::KnownWord, Provenance, RSVD},
         VM,
     },
 };
@@ -103,12 +104,12 @@ pub struct CallDataSize;
 
 impl Opcode for CallDataSize {
     fn execute(&self, vm: &mut VM) -> ExecuteResult {
-        // Get the stack so a value can be pushed onto it
+        // Construct the value
         let instruction_pointer = vm.instruction_pointer()?;
-        let mut stack = vm.stack_handle()?;
+        let length = vm.build().value(instruction_pointer, Provenance::CallDataSize);
 
-        // Construct the value and push it onto the stack.
-        let length = RSV::new_value(instruction_pointer, Provenance::CallDataSize);
+        // And push it onto the stack.
+        let mut stack = vm.stack_handle()?;
         stack.push(length)?;
 
         // Done, so return ok
@@ -325,6 +326,20 @@ impl Opcode for CodeCopy {
         let offset = stack.pop()?.constant_fold();
         let size = stack.pop()?.constant_fold();
 
+        // Where the copy is entirely concrete we record it, as this allows
+        // recognising when a constructor returns the runtime code.
+        if let (Some(memory_offset), Some(code_offset), Some(copy_size)) = (
+            dest_offset.constant_fold().as_word(),
+            offset.as_word(),
+            size.as_word(),
+        ) {
+            vm.state()?.record_code_copy(CodeCopyRecord {
+                memory_offset: memory_offset.into(),
+                code_offset:   code_offset.into(),
+                size:          copy_size.into(),
+            });
+        }
+
         // Modify the memory
         if let RSVD::KnownData { value } = size.data() {
             let num_32 = vm.build().known_exec(instruction_pointer, KnownWord::from(32));
@@ -595,12 +610,12 @@ pub struct ReturnDataSize;
 
 impl Opcode for ReturnDataSize {
     fn execute(&self, vm: &mut VM) -> ExecuteResult {
-        // Get the stack and environment
+        // Construct the value
         let instruction_pointer = vm.instruction_pointer()?;
-        let mut stack = vm.stack_handle()?;
+        let size = vm.build().value(instruction_pointer, Provenance::ReturnDataSize);
 
-        // Construct the value and shove it onto the stack.
-        let size = RSV::new_value(instruction_pointer, Provenance::ReturnDataSize);
+        // And shove it onto the stack.
+        let mut stack = vm.stack_handle()?;
         stack.push(size)?;
 
         // Done, so return ok
@@ -1076,14 +1091,12 @@ pub struct MSize;
 
 impl Opcode for MSize {
     fn execute(&self, vm: &mut VM) -> ExecuteResult {
-        // Get the stack and env data
-        let instruction_pointer = vm.instruction_pointer()?;
-        let mut stack = vm.stack_handle()?;
-
         // Prepare the value
-        let size = RSV::new_value(instruction_pointer, Provenance::MSize);
+        let instruction_pointer = vm.instruction_pointer()?;
+        let size = vm.build().value(instruction_pointer, Provenance::MSize);
 
         // Push it onto the stack
+        let mut stack = vm.stack_handle()?;
         stack.push(size)?;
 
         // Done, so return ok
@@ -1107,6 +1120,223 @@ impl Opcode for MSize {
     }
 }
 
+/// The `TLOAD` opcode loads a word from transient storage.
+///
+/// # Semantics
+///
+/// | Stack Index | Input | Output                              |
+/// | :---------: | :---: | :---------------------------------: |
+/// | 1           | `key` | `value := transient_storage\[key\]` |
+///
+/// where:
+///
+/// - `key` is the 32-byte transient storage key to read from
+/// - `value` is the 32-byte value read from transient storage, or 0 if that key
+///   was never written to during the current transaction
+///
+/// # Errors
+///
+/// Execution is reverted if there is not enough gas or if there are not enough
+/// operands on the stack.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TLoad;
+
+impl Opcode for TLoad {
+    fn execute(&self, vm: &mut VM) -> ExecuteResult {
+        // Get the key from the stack
+        let key = vm.stack_handle()?.pop()?;
+
+        // Read from transient storage using that key
+        let transient_storage = vm.state()?.transient_storage_mut();
+        let result = transient_storage.load(&key);
+
+        // Write it into the stack
+        vm.stack_handle()?.push(result)?;
+
+        // Done, so return ok
+        Ok(())
+    }
+
+    fn min_gas_cost(&self) -> usize {
+        100
+    }
+
+    fn arg_count(&self) -> usize {
+        1
+    }
+
+    fn as_text_code(&self) -> String {
+        "TLOAD".into()
+    }
+
+    fn as_byte(&self) -> u8 {
+        0x5c
+    }
+}
+
+/// The `TSTORE` opcode writes a word to transient storage based on a 32-byte
+/// key `k`.
+///
+/// # Semantics
+///
+/// | Stack Index | Input   | Output |
+/// | :---------: | :-----: | :----: |
+/// | 1           | `key`   |        |
+/// | 2           | `value` |        |
+///
+/// where:
+///
+/// - `key` is the 32-byte transient storage key to write to
+/// - `value` is the 32-byte value to be written to as follows
+///   `transient_storage\[key\] := value`
+///
+/// # Errors
+///
+/// Execution is reverted if there is not enough gas or if there are not enough
+/// operands on the stack.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct TStore;
+
+impl Opcode for TStore {
+    fn execute(&self, vm: &mut VM) -> ExecuteResult {
+        let mut stack = vm.stack_handle()?;
+
+        // Load the inputs from the stack
+        let key = stack.pop()?;
+        let value = stack.pop()?;
+
+        // Store the value into transient storage
+        vm.state()?.transient_storage_mut().store(key, value);
+
+        // Done, so return ok
+        Ok(())
+    }
+
+    fn min_gas_cost(&self) -> usize {
+        100
+    }
+
+    fn arg_count(&self) -> usize {
+        2
+    }
+
+    fn as_text_code(&self) -> String {
+        "TSTORE".into()
+    }
+
+    fn as_byte(&self) -> u8 {
+        0x5d
+    }
+}
+
+/// The `MCOPY` opcode copies a region of memory to another location in memory.
+///
+/// # Semantics
+///
+/// | Stack Index | Input        | Output |
+/// | :---------: | :----------: | :----: |
+/// | 1           | `destOffset` |        |
+/// | 2           | `offset`     |        |
+/// | 3           | `size`       |        |
+///
+/// where:
+///
+/// - `destOffset` is the byte offset in memory where the result will be copied
+/// - `offset` is the byte offset in memory from which to start copying
+/// - `size` is the number of bytes to copy
+///
+/// The source and destination regions may overlap, in which case the copy
+/// behaves as if the source region was first copied to an intermediate buffer.
+///
+/// # Errors
+///
+/// Execution is reverted if there is not enough gas or if there are not enough
+/// operands on the stack.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct MCopy;
+
+impl Opcode for MCopy {
+    fn execute(&self, vm: &mut VM) -> ExecuteResult {
+        // Get the current stack to pull the inputs from
+        let instruction_pointer = vm.instruction_pointer()?;
+        let mut stack = vm.stack_handle()?;
+
+        // Get the inputs
+        let dest_offset = stack.pop()?;
+        let offset = stack.pop()?;
+        let size = stack.pop()?.constant_fold();
+
+        // Modify the memory
+        if let RSVD::KnownData { value } = size.data() {
+            // We bound the copied size to avoid blowing up memory usage for very large
+            // copies
+            let actual_size: usize = value.into();
+            let size_limit = actual_size.min(vm.config().single_memory_operation_size_limit);
+
+            let polling_interval = vm.watchdog().poll_every();
+
+            // We read all of the source words before writing any of them so that
+            // overlapping copies see the memory as it was before the copy
+            let mut copies = Vec::new();
+            for (count, internal_offset) in (0..size_limit).step_by(32).enumerate() {
+                // If we have been told to stop, stop and return an error
+                if count % polling_interval == 0 && vm.watchdog().should_stop() {
+                    Err(Error::StoppedByWatchdog).locate(instruction_pointer)?;
+                }
+
+                let to_add_to_offset = vm
+                    .build()
+                    .known_exec(instruction_pointer, KnownWord::from(internal_offset));
+                let dest_offset = vm.build().symbolic_exec(
+                    instruction_pointer,
+                    RSVD::Add {
+                        left:  dest_offset.clone(),
+                        right: to_add_to_offset.clone(),
+                    },
+                );
+                let src_offset = vm.build().symbolic_exec(
+                    instruction_pointer,
+                    RSVD::Add {
+                        left:  offset.clone(),
+                        right: to_add_to_offset,
+                    },
+                );
+                let value = vm.state()?.memory_mut().load(&src_offset);
+                copies.push((dest_offset, value));
+            }
+
+            let memory = vm.state()?.memory_mut();
+            for (dest_offset, value) in copies {
+                memory.store(dest_offset, value);
+            }
+        } else {
+            // Without a concrete size the best we can do is to copy the word at `offset`
+            let memory = vm.state()?.memory_mut();
+            let value = memory.load(&offset);
+            memory.store(dest_offset, value);
+        }
+
+        // Done, so return ok
+        Ok(())
+    }
+
+    fn min_gas_cost(&self) -> usize {
+        3
+    }
+
+    fn arg_count(&self) -> usize {
+        3
+    }
+
+    fn as_text_code(&self) -> String {
+        "MCOPY".into()
+    }
+
+    fn as_byte(&self) -> u8 {
+        0x5e
+    }
+}
+
 /// The `PUSH0` opcode places the value 0 on the top of the stack.
 ///
 /// # Semantics
@@ -1856,6 +2086,145 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn t_load_loads_word_from_transient_storage() -> anyhow::Result<()> {
+        // Prepare the values and the vm
+        let input_key = RSV::new_synthetic(0, RSVD::new_value());
+        let input_value = RSV::new_synthetic(1, RSVD::new_value());
+        let mut vm = util::new_vm_with_values_on_stack(vec![input_key.clone()])?;
+        vm.state()?
+            .transient_storage_mut()
+            .store(input_key.clone(), input_value.clone());
+
+        // Prepare and run the opcode
+        let opcode = memory::TLoad;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the stack state
+        let stack = vm.state()?.stack_mut();
+        assert_eq!(stack.depth(), 1);
+        match stack.read(0)?.data() {
+            RSVD::TLoad { key, value } => {
+                assert_eq!(key, &input_key);
+                assert_eq!(value, &input_value);
+            }
+            _ => panic!("Incorrect payload"),
+        }
+
+        // Persistent storage should be untouched
+        assert_eq!(vm.state()?.storage().entry_count(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn t_store_writes_word_to_transient_storage() -> anyhow::Result<()> {
+        // Prepare the values and the vm
+        let input_key = RSV::new_synthetic(0, RSVD::new_value());
+        let input_value = RSV::new_synthetic(1, RSVD::new_value());
+        let mut vm =
+            util::new_vm_with_values_on_stack(vec![input_value.clone(), input_key.clone()])?;
+
+        // Prepare and run the opcode
+        let opcode = memory::TStore;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the stack state
+        assert!(vm.state()?.stack_mut().is_empty());
+
+        // Inspect the storage states
+        assert_eq!(vm.state()?.storage().entry_count(), 0);
+        let transient_storage = vm.state()?.transient_storage_mut();
+        assert_eq!(transient_storage.entry_count(), 1);
+        match transient_storage.load(&input_key).data() {
+            RSVD::TLoad { key, value } => {
+                assert_eq!(key, &input_key);
+                assert_eq!(value, &input_value);
+            }
+            _ => panic!("Invalid payload"),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn m_copy_copies_words_within_memory() -> anyhow::Result<()> {
+        // Prepare the values and the vm
+        let known =
+            |n: usize| RSV::new_known_value(0, KnownWord::from(n), Provenance::Synthetic, None);
+        let first_value = RSV::new_synthetic(1, RSVD::new_value());
+        let second_value = RSV::new_synthetic(2, RSVD::new_value());
+        let mut vm = util::new_vm_with_values_on_stack(vec![known(64), known(0), known(128)])?;
+        let memory = vm.state()?.memory_mut();
+        memory.store(known(0), first_value.clone());
+        memory.store(known(32), second_value.clone());
+
+        // Prepare and run the opcode
+        let opcode = memory::MCopy;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the stack
+        assert!(vm.state()?.stack_mut().is_empty());
+
+        // Inspect the memory
+        let memory = vm.state()?.memory_mut();
+        assert_eq!(memory.load(&known(128)), first_value);
+        assert_eq!(memory.load(&known(160)), second_value);
+        assert_eq!(memory.load(&known(0)), first_value);
+        assert_eq!(memory.load(&known(32)), second_value);
+
+        Ok(())
+    }
+
+    #[test]
+    fn m_copy_handles_overlapping_regions() -> anyhow::Result<()> {
+        // Prepare the values and the vm
+        let known =
+            |n: usize| RSV::new_known_value(0, KnownWord::from(n), Provenance::Synthetic, None);
+        let first_value = RSV::new_synthetic(1, RSVD::new_value());
+        let second_value = RSV::new_synthetic(2, RSVD::new_value());
+        let mut vm = util::new_vm_with_values_on_stack(vec![known(64), known(0), known(32)])?;
+        let memory = vm.state()?.memory_mut();
+        memory.store(known(0), first_value.clone());
+        memory.store(known(32), second_value.clone());
+
+        // Prepare and run the opcode
+        let opcode = memory::MCopy;
+        opcode.execute(&mut vm)?;
+
+        // The copy should behave as if it went through an intermediate buffer
+        let memory = vm.state()?.memory_mut();
+        assert_eq!(memory.load(&known(0)), first_value);
+        assert_eq!(memory.load(&known(32)), first_value);
+        assert_eq!(memory.load(&known(64)), second_value);
+
+        Ok(())
+    }
+
+    #[test]
+    fn m_copy_copies_single_word_with_symbolic_size() -> anyhow::Result<()> {
+        // Prepare the values and the vm
+        let dest_offset = RSV::new_synthetic(0, RSVD::new_value());
+        let input_offset = RSV::new_synthetic(1, RSVD::new_value());
+        let input_size = RSV::new_synthetic(2, RSVD::new_value());
+        let mut vm = util::new_vm_with_values_on_stack(vec![
+            input_size,
+            input_offset.clone(),
+            dest_offset.clone(),
+        ])?;
+        let value = vm.state()?.memory_mut().load(&input_offset);
+
+        // Prepare and run the opcode
+        let opcode = memory::MCopy;
+        opcode.execute(&mut vm)?;
+
+        // Inspect the memory
+        let memory = vm.state()?.memory_mut();
+        assert_eq!(memory.load(&dest_offset), value);
+
+        Ok(())
+    }
+
     #[test]
     fn m_size_writes_variable_to_stack() -> anyhow::Result<()> {
         // Prepare the vm
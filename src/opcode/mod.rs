@@ -9,7 +9,7 @@ mod macros;
 pub mod memory;
 mod util;
 
-use std::{any::Any, fmt::Debug, rc::Rc};
+use std::{any::Any, fmt::Debug, sync::Arc};
 
 use downcast_rs::Downcast;
 
@@ -44,7 +44,7 @@ use crate::{error::execution::Result, vm::VM};
 /// stack.
 pub trait Opcode
 where
-    Self: Any + Debug + Downcast,
+    Self: Any + Debug + Downcast + Send + Sync,
 {
     /// Executes the opcode described by `self`, modifying the state of the `vm`
     /// as necessary.
@@ -91,7 +91,7 @@ where
 }
 
 /// A type for an [`Opcode`] that is dynamically dispatched.
-pub type DynOpcode = Rc<dyn Opcode>;
+pub type DynOpcode = Arc<dyn Opcode>;
 
 /// The result of the [`Opcode::execute`] methods.
 pub type ExecuteResult = Result<()>;
@@ -100,6 +100,7 @@ pub type ExecuteResult = Result<()>;
 mod test_util {
     use crate::{
         disassembly::InstructionStream,
+        extractor::chain::Chain,
         vm::{value::RuntimeBoxedVal, Config, VM},
         watchdog::LazyWatchdog,
     };
@@ -120,6 +121,21 @@ mod test_util {
         new_vm_with_instructions_and_values_on_stack(instructions, values)
     }
 
+    /// Constructs a new virtual machine executing on the provided `chain` with
+    /// the provided `values` pushed onto its stack in order.
+    ///
+    /// This means that the last item in `values` will be put on the top of
+    /// the stack.
+    pub fn new_vm_on_chain_with_values_on_stack(
+        chain: Chain,
+        values: Vec<RuntimeBoxedVal>,
+    ) -> anyhow::Result<VM> {
+        let bytes: Vec<u8> = vec![0x00];
+        let instructions = InstructionStream::disassemble(bytes.as_slice(), &chain)?;
+        let config = Config::default().with_chain(chain);
+        new_vm_with_config_and_values_on_stack(instructions, config, values)
+    }
+
     /// Constructs a new virtual machine with the provided `instructions` and
     /// with the provided `values` pushed onto its stack in order.
     ///
@@ -129,8 +145,17 @@ mod test_util {
         instructions: InstructionStream,
         values: Vec<RuntimeBoxedVal>,
     ) -> anyhow::Result<VM> {
-        let config = Config::default();
+        new_vm_with_config_and_values_on_stack(instructions, Config::default(), values)
+    }
 
+    /// Constructs a new virtual machine with the provided `instructions` and
+    /// `config`, and with the provided `values` pushed onto its stack in
+    /// order.
+    fn new_vm_with_config_and_values_on_stack(
+        instructions: InstructionStream,
+        config: Config,
+        values: Vec<RuntimeBoxedVal>,
+    ) -> anyhow::Result<VM> {
         let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
         let stack = vm.state()?.stack_mut();
 
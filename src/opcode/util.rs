@@ -6,6 +6,7 @@ use crate::{
     opcode::control::JumpDest,
     vm::{
         value::{RuntimeBoxedVal, RSVD},
+        value_set,
         VM,
     },
 };
@@ -23,7 +24,8 @@ use crate::{
 /// - When the provided `counter` is not a valid, known, immediate.
 /// - When the jump target destination is not in bounds in the instruction
 ///   stream.
-/// - When the jump target is not a valid [`JumpDest`] instruction.
+/// - When the jump target is not a valid [`JumpDest`] instruction, including
+///   when it lies in a data region of the instruction stream.
 ///
 /// It is assumed that all errors returned by this function are instances of
 /// [`crate::error::execution::Error`].
@@ -37,6 +39,72 @@ pub fn validate_jump_destination(counter: &RuntimeBoxedVal, vm: &mut VM) -> exec
         }
     };
 
+    check_jump_target(jump_target, vm)
+}
+
+/// Resolves the destinations that the provided `counter` can jump to in the
+/// provided `vm`.
+///
+/// Where the counter is not a known immediate, such as for the return address
+/// of an internal function that has been threaded through the stack, the
+/// finite set of words that it can take is computed using
+/// [`value_set::possible_values`]. Every word that is a valid jump destination
+/// is returned, and the rest are discarded. The returned destinations are in
+/// ascending order and never empty.
+///
+/// # Errors
+///
+/// This returns [`Err`] in the same situations as
+/// [`validate_jump_destination`], except that a counter that is not a known
+/// immediate is only an error if none of the words it can take are valid jump
+/// destinations.
+pub fn resolve_jump_destinations(
+    counter: &RuntimeBoxedVal,
+    vm: &mut VM,
+) -> execution::Result<Vec<u32>> {
+    let error = match validate_jump_destination(counter, vm) {
+        Ok(target) => return Ok(vec![target]),
+        Err(error) => error,
+    };
+    if !matches!(error.payload, execution::Error::NoConcreteJumpDestination) {
+        return Err(error);
+    }
+
+    let limit = vm.config().jump_destination_set_limit;
+    let Some(values) = value_set::possible_values(counter, limit) else {
+        return Err(error);
+    };
+    let targets: Vec<u32> = values
+        .into_iter()
+        .filter_map(|value| u32::try_from(value.value_le()).ok())
+        .filter(|target| check_jump_target(*target, vm).is_ok())
+        .collect();
+
+    if targets.is_empty() {
+        Err(error)
+    } else {
+        Ok(targets)
+    }
+}
+
+/// Checks that the concrete `jump_target` is a valid jump destination in the
+/// provided `vm`.
+///
+/// # Errors
+///
+/// Returns [`Err`] if the jump target is not in bounds in the instruction
+/// stream, or if it is not a valid [`JumpDest`] instruction.
+pub fn check_jump_target(jump_target: u32, vm: &mut VM) -> execution::Result<u32> {
+    let instruction_pointer = vm.instruction_pointer()?;
+
+    // Bytes in data regions are never executed, even if they look like a `JUMPDEST`
+    if vm.instructions().is_data(jump_target) {
+        return Err(execution::Error::InvalidJumpTarget {
+            offset: jump_target,
+        }
+        .locate(instruction_pointer));
+    }
+
     // We need to check that the jump target is valid.
     let thread = vm.execution_thread_mut()?;
     let target_instruction = thread.instruction(jump_target).ok_or(
@@ -151,4 +151,43 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn lifts_dyn_array_iteration() -> anyhow::Result<()> {
+        let input_value = RSV::new_value(0, Provenance::Synthetic);
+        let input_slot = RSV::new_known_value(1, KnownWord::from(3), Provenance::Synthetic, None);
+        let hash = RSV::new_synthetic(
+            2,
+            RSVD::Sha3 {
+                data: input_slot.clone(),
+            },
+        );
+        let element = RSV::new_induction(3, 3, hash, KnownWord::from(1));
+        let store = RSV::new_synthetic(
+            4,
+            RSVD::StorageWrite {
+                key:   element,
+                value: input_value,
+            },
+        );
+
+        // Run the pass
+        let state = TypeCheckerState::empty();
+        let result = DynamicArrayIndex.run(store, &state)?;
+
+        // The widened loop counter should become the index into the array
+        let RSVD::StorageWrite { key, .. } = result.data() else {
+            panic!("Incorrect payload")
+        };
+        let RSVD::DynamicArrayIndex { slot, index } = key.data() else {
+            panic!("Incorrect payload")
+        };
+        assert_eq!(slot, &input_slot);
+        let RSVD::Multiply { left: counter, .. } = index.data() else {
+            panic!("Incorrect payload")
+        };
+        assert_eq!(counter.data(), &RSVD::LoopCounter { header: 3 });
+
+        Ok(())
+    }
 }
@@ -42,7 +42,7 @@ use crate::{
 /// into the symbolic value representation.
 pub trait Lift
 where
-    Self: Any + Debug + Downcast,
+    Self: Any + Debug + Downcast + Send + Sync,
 {
     /// Executes the pass on the provided `value`, with access to the pass state
     /// in `self` and the unifier state in `state`, returning a
@@ -116,6 +116,9 @@ impl LiftingPasses {
     /// access to modify the unifier `state`, returning the potentially-modified
     /// value.
     ///
+    /// The result of each pass is interned in the [`TypeCheckerState::arena`],
+    /// so the passes that follow it operate on canonical values.
+    ///
     /// # Errors
     ///
     /// Returns [`Err`] if any of the passes error.
@@ -125,7 +128,7 @@ impl LiftingPasses {
         state: &TypeCheckerState,
     ) -> Result<RuntimeBoxedVal> {
         for pass in &mut self.passes {
-            value = pass.run(value, state)?;
+            value = state.arena().intern(pass.run(value, state)?);
         }
 
         Ok(value)
@@ -1,5 +1,9 @@
 //! This module provides a lifting pass that recognises accesses to storage
 //! slots, and wraps the constant slots in fixed expressions.
+//!
+//! Accesses to transient storage are wrapped in
+//! [`RSVD::TransientStorageSlot`] instead, keeping them distinct from accesses
+//! to persistent storage.
 
 use crate::{
     tc::{lift::Lift, state::TypeCheckerState},
@@ -93,6 +97,32 @@ impl Lift for StorageSlots {
                         value: value.clone().transform_data(insert_storage_accesses),
                     })
                 }
+                RSVD::TransientStorageWrite { key, value } => {
+                    let data = match &key.data() {
+                        RSVD::TransientStorageSlot { .. } => key.data().clone(),
+                        _ => RSVD::TransientStorageSlot {
+                            key: key.clone().transform_data(insert_storage_accesses),
+                        },
+                    };
+                    let new_key = RSV::new(key.instruction_pointer(), data, key.provenance(), None);
+                    Some(RSVD::TransientStorageWrite {
+                        key:   new_key,
+                        value: value.clone().transform_data(insert_storage_accesses),
+                    })
+                }
+                RSVD::TLoad { key, value } => {
+                    let data = match &key.data() {
+                        RSVD::TransientStorageSlot { .. } => key.data().clone(),
+                        _ => RSVD::TransientStorageSlot {
+                            key: key.clone().transform_data(insert_storage_accesses),
+                        },
+                    };
+                    let slot = RSV::new(key.instruction_pointer(), data, key.provenance(), None);
+                    Some(RSVD::TLoad {
+                        key:   slot,
+                        value: value.clone().transform_data(insert_storage_accesses),
+                    })
+                }
                 _ => None,
             }
         }
@@ -397,4 +427,45 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn wraps_slots_in_transient_accesses() -> anyhow::Result<()> {
+        let input_key = RSV::new_value(0, Provenance::Synthetic);
+        let input_value = RSV::new_value(1, Provenance::Synthetic);
+        let t_load = RSV::new_synthetic(
+            2,
+            RSVD::TLoad {
+                key:   input_key.clone(),
+                value: input_value.clone(),
+            },
+        );
+        let t_store = RSV::new_synthetic(
+            3,
+            RSVD::TransientStorageWrite {
+                key:   input_key.clone(),
+                value: t_load,
+            },
+        );
+
+        let state = TypeCheckerState::empty();
+        let result = StorageSlots.run(t_store, &state)?;
+
+        let RSVD::TransientStorageWrite { key, value } = result.data() else {
+            panic!("Incorrect payload")
+        };
+        match key.data() {
+            RSVD::TransientStorageSlot { key } => assert_eq!(key, &input_key),
+            _ => panic!("Incorrect payload"),
+        }
+        let RSVD::TLoad { key, value } = value.data() else {
+            panic!("Incorrect payload")
+        };
+        assert_eq!(value, &input_value);
+        match key.data() {
+            RSVD::TransientStorageSlot { key } => assert_eq!(key, &input_key),
+            _ => panic!("Incorrect payload"),
+        }
+
+        Ok(())
+    }
 }
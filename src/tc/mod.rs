@@ -12,6 +12,7 @@ use crate::{
         container::Locatable,
         unification::{Error, Errors, Result},
     },
+    extractor::chain::Chain,
     tc::{
         abi::{AbiType, StructElement},
         expression::{Span, TypeExpression, WordUse, TE},
@@ -91,6 +92,9 @@ impl TypeChecker {
     ///
     /// Returns [`Err`] if one or more of the lifting passes returns an error.
     pub fn lift(&mut self, execution_result: ExecutionResult) -> Result<VecDeque<RuntimeBoxedVal>> {
+        // The lifted values are interned alongside those built during execution
+        self.state.set_arena(execution_result.arena.clone());
+
         // Identically structured values tell us the same thing at inference time, so we
         // remove any exact duplicates to make the type checking process faster.
         let mut result_values: VecDeque<_> =
@@ -196,7 +200,11 @@ impl TypeChecker {
     /// Returns [`Err`] if the unification process fails.
     pub fn unify(&mut self) -> Result<StorageLayout> {
         fn is_constant_storage_slot(value: &TCBoxedVal) -> bool {
-            matches!(value.data(), TCSVD::StorageSlot { key } if matches!(key.data(), TCSVD::KnownData { .. }))
+            matches!(
+                value.data(),
+                TCSVD::StorageSlot { key } | TCSVD::TransientStorageSlot { key }
+                    if matches!(key.data(), TCSVD::KnownData { .. })
+            )
         }
 
         // Actually run unification
@@ -222,7 +230,9 @@ impl TypeChecker {
             }
 
             let ty_var = self.state.var_unchecked(&slot);
-            let TCSVD::StorageSlot { key } = slot.data() else {
+            let is_transient = matches!(slot.data(), TCSVD::TransientStorageSlot { .. });
+            let (TCSVD::StorageSlot { key } | TCSVD::TransientStorageSlot { key }) = slot.data()
+            else {
                 Err(Error::InvalidTree {
                     value:  slot.clone(),
                     reason: "Failed to destructure supposedly known structure".into(),
@@ -237,12 +247,22 @@ impl TypeChecker {
                 .locate(slot.instruction_pointer()))?
             };
 
-            // Get one or more types from the storage slot
+            // Get one or more types from the storage slot, keeping transient slots in their
+            // own section of the layout
+            let mut add_to_layout = |offset, typ| {
+                if is_transient {
+                    layout.add_transient(index, offset, typ);
+                } else {
+                    layout.add(index, offset, typ);
+                }
+            };
             match self.abi_type_for(ty_var)? {
-                AbiValue::Type(typ) => layout.add(index, 0, typ),
-                AbiValue::Packed(types) => types
-                    .into_iter()
-                    .for_each(|(typ, offset)| layout.add(index, offset, typ)),
+                AbiValue::Type(typ) => add_to_layout(0, typ),
+                AbiValue::Packed(types) => {
+                    for (typ, offset) in types {
+                        add_to_layout(offset, typ);
+                    }
+                }
             }
         }
 
@@ -557,6 +577,22 @@ pub struct Config {
 }
 
 impl Config {
+    /// Creates the default type checker configuration for the provided
+    /// `chain`.
+    ///
+    /// This uses the default [`LiftingPasses`], which apply on every chain, and
+    /// [`InferenceRules::for_chain`], so that the types of values obtained from
+    /// chain-specific sources such as system contracts can be inferred.
+    #[must_use]
+    pub fn for_chain(chain: &Chain) -> Self {
+        let lifting_passes = LiftingPasses::default();
+        let inference_rules = InferenceRules::for_chain(chain);
+        Self {
+            lifting_passes,
+            inference_rules,
+        }
+    }
+
     /// Sets the `lifting_passes` config parameter to `value`.
     #[must_use]
     pub fn with_lifting_passes(mut self, value: LiftingPasses) -> Config {
@@ -844,17 +880,30 @@ pub mod test {
             disassembly::InstructionStream,
             error::execution,
             opcode::control::Invalid,
-            vm::{state::VMState, value::RuntimeBoxedVal, Config, ExecutionResult},
+            vm::{
+                coverage::CoverageReport,
+                state::VMState,
+                value::{arena::ValueArena, RuntimeBoxedVal},
+                Config,
+                ExecutionResult,
+            },
         };
 
         /// Creates a default execution result.
         #[must_use]
         pub fn default_execution_result() -> ExecutionResult {
             ExecutionResult {
-                instructions: InstructionStream::try_from(bytecode![Invalid::default()].as_slice())
-                    .expect("Cannot actually panic due to statically-known bytecode"),
-                states:       Vec::new(),
-                errors:       execution::Errors::new(),
+                instructions:    InstructionStream::try_from(
+                    bytecode![Invalid::default()].as_slice(),
+                )
+                .expect("Cannot actually panic due to statically-known bytecode"),
+                states:          Vec::new(),
+                errors:          execution::Errors::new(),
+                pruned_branches: Vec::new(),
+                coverage:        CoverageReport::default(),
+                trace:           None,
+                dropped_threads: 0,
+                arena:           ValueArena::new(),
             }
         }
 
@@ -866,10 +915,17 @@ pub mod test {
             values.into_iter().for_each(|v| state_with_values.record_value(v));
 
             ExecutionResult {
-                instructions: InstructionStream::try_from(bytecode![Invalid::default()].as_slice())
-                    .expect("Cannot actually panic due to statically-known bytecode"),
-                states:       vec![state_with_values],
-                errors:       execution::Errors::new(),
+                instructions:    InstructionStream::try_from(
+                    bytecode![Invalid::default()].as_slice(),
+                )
+                .expect("Cannot actually panic due to statically-known bytecode"),
+                states:          vec![state_with_values],
+                errors:          execution::Errors::new(),
+                pruned_branches: Vec::new(),
+                coverage:        CoverageReport::default(),
+                trace:           None,
+                dropped_threads: 0,
+                arena:           ValueArena::new(),
             }
         }
     }
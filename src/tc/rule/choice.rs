@@ -0,0 +1,85 @@
+//! This module contains an inference rule that equates every alternative of a
+//! choice, as created by the merging of threads of execution, with the choice
+//! itself.
+
+use crate::{
+    error::unification::Result,
+    tc::{expression::TE, rule::InferenceRule, state::TypeCheckerState},
+    vm::value::{TCBoxedVal, TCSVD},
+};
+
+/// This rule creates the following equations in the typing state for
+/// expressions of the following form.
+///
+/// ```text
+/// choice(alt_1, ..., alt_n)
+///    a     b_1        b_n
+/// ```
+///
+/// equating
+///
+/// - `a = b_i` for every alternative `b_i`
+///
+/// As every alternative is a value that the same location may hold, they must
+/// all be of the same type.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct ChoiceRule;
+
+impl InferenceRule for ChoiceRule {
+    fn infer(&self, value: &TCBoxedVal, state: &mut TypeCheckerState) -> Result<()> {
+        let TCSVD::Choice { alternatives } = value.data() else {
+            return Ok(());
+        };
+
+        for alternative in alternatives {
+            let alternative_tv = state.var_unchecked(alternative);
+            state.infer_for(value, TE::eq(alternative_tv));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        tc::{
+            expression::TE,
+            rule::{choice::ChoiceRule, InferenceRule},
+            state::TypeCheckerState,
+        },
+        vm::value::{Provenance, RSV, TCSVD},
+    };
+
+    #[test]
+    fn creates_correct_equations_in_state() -> anyhow::Result<()> {
+        // Create the expressions to be typed
+        let left = RSV::new_value(0, Provenance::Synthetic);
+        let right = RSV::new_value(1, Provenance::Synthetic);
+        let choice = RSV::new_choice(2, left.clone(), right.clone());
+
+        // Register these in the state
+        let mut state = TypeCheckerState::empty();
+        let choice_ty = state.register(choice);
+        let tc_input = state.value_unchecked(choice_ty).clone();
+        let [left_ty, right_ty] = match tc_input.data() {
+            TCSVD::Choice { alternatives } => {
+                [alternatives[0].type_var(), alternatives[1].type_var()]
+            }
+            _ => panic!("Incorrect payload"),
+        };
+        ChoiceRule.infer(&tc_input, &mut state)?;
+
+        assert_eq!(state.inferences(left_ty).len(), 1);
+        assert!(state.inferences(left_ty).contains(&TE::eq(choice_ty)));
+
+        assert_eq!(state.inferences(right_ty).len(), 1);
+        assert!(state.inferences(right_ty).contains(&TE::eq(choice_ty)));
+
+        assert_eq!(state.inferences(choice_ty).len(), 2);
+        assert!(state.inferences(choice_ty).contains(&TE::eq(left_ty)));
+        assert!(state.inferences(choice_ty).contains(&TE::eq(right_ty)));
+
+        Ok(())
+    }
+}
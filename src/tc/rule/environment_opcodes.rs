@@ -3,6 +3,7 @@
 //! environment.
 
 use crate::{
+    constant::WORD_SIZE_BITS,
     error::unification::Result,
     tc::{expression::TE, rule::InferenceRule, state::TypeCheckerState},
     vm::value::{TCBoxedVal, TCSVD},
@@ -29,16 +30,22 @@ impl InferenceRule for EnvironmentCodesRule {
             | TCSVD::GasPrice
             | TCSVD::BlockTimestamp
             | TCSVD::BlockNumber
+            | TCSVD::L1BlockNumber
             | TCSVD::Prevrandao
             | TCSVD::GasLimit
             | TCSVD::ChainId
             | TCSVD::SelfBalance
             | TCSVD::BaseFee
+            | TCSVD::BlobBaseFee
             | TCSVD::Gas
             | TCSVD::CallDataSize => {
                 state.infer_for(value, TE::unsigned_word(None));
             }
-            TCSVD::SelfDestruct { target } => {
+            TCSVD::BlobHash { index } => {
+                state.infer_for(index, TE::unsigned_word(None));
+                state.infer_for(value, TE::bytes(Some(WORD_SIZE_BITS)));
+            }
+            TCSVD::SelfDestruct { target, .. } => {
                 state.infer_for(target, TE::address());
             }
             _ => (),
@@ -51,6 +58,7 @@ impl InferenceRule for EnvironmentCodesRule {
 #[cfg(test)]
 mod test {
     use crate::{
+        constant::WORD_SIZE_BITS,
         tc::{
             expression::TE,
             rule::{environment_opcodes::EnvironmentCodesRule, InferenceRule},
@@ -223,6 +231,23 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn creates_correct_equations_for_l1_block_number() -> anyhow::Result<()> {
+        // Create a value
+        let value = RSV::new_synthetic(0, RSVD::L1BlockNumber);
+
+        // Create the state and run tc
+        let mut state = TypeCheckerState::empty();
+        let value_tv = state.register(value.clone());
+        let tc_input = state.value_unchecked(value_tv).clone();
+        EnvironmentCodesRule.infer(&tc_input, &mut state)?;
+
+        // Check that we get the right equations
+        assert!(state.inferences(value_tv).contains(&TE::unsigned_word(None)));
+
+        Ok(())
+    }
+
     #[test]
     fn creates_correct_equations_for_prevrandao() -> anyhow::Result<()> {
         // Create a value
@@ -308,6 +333,46 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn creates_correct_equations_for_blob_hash() -> anyhow::Result<()> {
+        // Create a value
+        let index = RSV::new_value(0, Provenance::Synthetic);
+        let value = RSV::new_synthetic(1, RSVD::BlobHash { index });
+
+        // Create the state and run tc
+        let mut state = TypeCheckerState::empty();
+        let value_tv = state.register(value);
+        let tc_input = state.value_unchecked(value_tv).clone();
+        let index_tv = match tc_input.data() {
+            TCSVD::BlobHash { index } => index.type_var(),
+            _ => panic!("Incorrect payload"),
+        };
+        EnvironmentCodesRule.infer(&tc_input, &mut state)?;
+
+        // Check that we get the right equations
+        assert!(state.inferences(index_tv).contains(&TE::unsigned_word(None)));
+        assert!(state.inferences(value_tv).contains(&TE::bytes(Some(WORD_SIZE_BITS))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn creates_correct_equations_for_blob_base_fee() -> anyhow::Result<()> {
+        // Create a value
+        let value = RSV::new_synthetic(0, RSVD::BlobBaseFee);
+
+        // Create the state and run tc
+        let mut state = TypeCheckerState::empty();
+        let value_tv = state.register(value.clone());
+        let tc_input = state.value_unchecked(value_tv).clone();
+        EnvironmentCodesRule.infer(&tc_input, &mut state)?;
+
+        // Check that we get the right equations
+        assert!(state.inferences(value_tv).contains(&TE::unsigned_word(None)));
+
+        Ok(())
+    }
+
     #[test]
     fn creates_correct_equations_for_gas() -> anyhow::Result<()> {
         // Create a value
@@ -349,7 +414,8 @@ mod test {
         let value = RSV::new_synthetic(
             1,
             RSVD::SelfDestruct {
-                target: address.clone(),
+                target:          address.clone(),
+                deletes_account: false,
             },
         );
 
@@ -358,7 +424,7 @@ mod test {
         let value_tv = state.register(value);
         let tc_input = state.value_unchecked(value_tv).clone();
         let address_tv = match tc_input.data() {
-            TCSVD::SelfDestruct { target } => target.type_var(),
+            TCSVD::SelfDestruct { target, .. } => target.type_var(),
             _ => panic!("Incorrect payload"),
         };
         EnvironmentCodesRule.infer(&tc_input, &mut state)?;
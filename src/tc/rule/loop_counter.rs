@@ -0,0 +1,65 @@
+//! This module contains an inference rule that types the counters of loops that
+//! have been widened during execution.
+
+use crate::{
+    error::unification::Result,
+    tc::{expression::TE, rule::InferenceRule, state::TypeCheckerState},
+    vm::value::{TCBoxedVal, TCSVD},
+};
+
+/// This rule creates the following equations in the typing state for
+/// expressions of the following form.
+///
+/// ```text
+/// loop_counter(header)
+///      a
+/// ```
+///
+/// equating
+///
+/// - `a = uintN`
+///
+/// The counter is the number of iterations of a loop that have completed, and
+/// so is always an unsigned number.
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+pub struct LoopCounterRule;
+
+impl InferenceRule for LoopCounterRule {
+    fn infer(&self, value: &TCBoxedVal, state: &mut TypeCheckerState) -> Result<()> {
+        if let TCSVD::LoopCounter { .. } = value.data() {
+            state.infer_for(value, TE::unsigned_word(None));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        tc::{
+            expression::TE,
+            rule::{loop_counter::LoopCounterRule, InferenceRule},
+            state::TypeCheckerState,
+        },
+        vm::value::{RSV, RSVD},
+    };
+
+    #[test]
+    fn creates_correct_equations_in_state() -> anyhow::Result<()> {
+        // Create a value
+        let value = RSV::new_synthetic(0, RSVD::LoopCounter { header: 4 });
+
+        // Create the state and run tc
+        let mut state = TypeCheckerState::empty();
+        let value_tv = state.register(value);
+        let tc_input = state.value_unchecked(value_tv).clone();
+        LoopCounterRule.infer(&tc_input, &mut state)?;
+
+        // Check that we get the right equations
+        assert_eq!(state.inferences(value_tv).len(), 1);
+        assert!(state.inferences(value_tv).contains(&TE::unsigned_word(None)));
+
+        Ok(())
+    }
+}
@@ -6,11 +6,13 @@ pub mod arithmetic_operations;
 pub mod bit_shifts;
 pub mod boolean_operations;
 pub mod call_data;
+pub mod choice;
 pub mod create;
 pub mod dynamic_array_write;
 pub mod environment_opcodes;
 pub mod ext_code;
 pub mod external_calls;
+pub mod loop_counter;
 pub mod mapping_access;
 pub mod masked_word;
 pub mod offset_size;
@@ -19,6 +21,7 @@ pub mod s_load_is_inner_types;
 pub mod sha3;
 pub mod storage_key;
 pub mod storage_write;
+pub mod system_contracts;
 
 use std::{
     any::{Any, TypeId},
@@ -32,16 +35,19 @@ use downcast_rs::Downcast;
 
 use crate::{
     error::unification::Result,
+    extractor::chain::Chain,
     tc::{
         rule::{
             arithmetic_operations::ArithmeticOperationRule,
             bit_shifts::BitShiftRule,
             boolean_operations::BooleanOpsRule,
             call_data::CallDataRule,
+            choice::ChoiceRule,
             create::CreateContractRule,
             dynamic_array_write::DynamicArrayWriteRule,
             environment_opcodes::EnvironmentCodesRule,
             external_calls::ExternalCallRule,
+            loop_counter::LoopCounterRule,
             mapping_access::MappingAccessRule,
             masked_word::MaskedWordRule,
             offset_size::OffsetSizeRule,
@@ -50,6 +56,7 @@ use crate::{
             sha3::HashRule,
             storage_key::StorageKeyRule,
             storage_write::StorageWriteRule,
+            system_contracts::SystemContractRule,
         },
         state::TypeCheckerState,
     },
@@ -61,7 +68,7 @@ use crate::{
 /// execution.
 pub trait InferenceRule
 where
-    Self: Any + Debug + Downcast,
+    Self: Any + Debug + Downcast + Send + Sync,
 {
     /// Runs the analysis on the provided `value` and its associated with access
     /// to the type checker state in `state` into which it can write typing
@@ -112,6 +119,31 @@ impl InferenceRules {
         Self { rules }
     }
 
+    /// Constructs the default set of inference rules for the provided `chain`.
+    ///
+    /// This is the [`Self::default`] set of rules, along with a
+    /// [`SystemContractRule`] for any system contracts that are known to be
+    /// present on the chain.
+    #[must_use]
+    pub fn for_chain(chain: &Chain) -> Self {
+        let mut rules = Self::default();
+        rules.add_for_chain(chain);
+        rules
+    }
+
+    /// Adds the rules that are specific to the provided `chain`, such as a
+    /// [`SystemContractRule`] for any system contracts that are known to be
+    /// present on the chain.
+    ///
+    /// As with [`Self::add`], a rule of a type that is already present will not
+    /// be added.
+    pub fn add_for_chain(&mut self, chain: &Chain) {
+        let system_contracts = chain.system_contracts();
+        if !system_contracts.is_empty() {
+            self.add(SystemContractRule::new(system_contracts));
+        }
+    }
+
     /// Adds the `rule` to the list of rules.
     ///
     /// If a pass of the given type already exists in the ordering, it will not
@@ -147,11 +179,13 @@ impl Default for InferenceRules {
         rules.add(BitShiftRule);
         rules.add(BooleanOpsRule);
         rules.add(CallDataRule);
+        rules.add(ChoiceRule);
         rules.add(CreateContractRule);
         rules.add(DynamicArrayWriteRule);
         rules.add(EnvironmentCodesRule);
         rules.add(ExternalCallRule);
         rules.add(HashRule);
+        rules.add(LoopCounterRule);
         rules.add(MappingAccessRule);
         rules.add(MaskedWordRule);
         rules.add(OffsetSizeRule);
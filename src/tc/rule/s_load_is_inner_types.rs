@@ -1,5 +1,5 @@
-//! This module contains an inference rule that equates every `SLoad` with the
-//! type of its element and its key.
+//! This module contains an inference rule that equates every `SLoad` and
+//! `TLoad` with the type of its element and its key.
 
 use crate::{
     error::unification::Result,
@@ -13,6 +13,9 @@ use crate::{
 /// ```text
 /// s_load(key, value)
 ///    a    b     c
+///
+/// t_load(key, value)
+///    a    b     c
 /// ```
 ///
 /// equating
@@ -24,10 +27,14 @@ pub struct SLoadIsInnerTypesRule;
 
 impl InferenceRule for SLoadIsInnerTypesRule {
     fn infer(&self, value: &TCBoxedVal, state: &mut TypeCheckerState) -> Result<()> {
-        let TCSVD::SLoad {
+        let (TCSVD::SLoad {
+            value: inner_value,
+            key,
+        }
+        | TCSVD::TLoad {
             value: inner_value,
             key,
-        } = value.data()
+        }) = value.data()
         else {
             return Ok(());
         };
@@ -88,4 +95,40 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn creates_correct_equations_in_state_for_transient_loads() -> anyhow::Result<()> {
+        // Create the expressions to be typed
+        let key = RSV::new_value(0, Provenance::Synthetic);
+        let value = RSV::new_value(1, Provenance::Synthetic);
+        let t_load = RSV::new_synthetic(
+            2,
+            RSVD::TLoad {
+                key:   key.clone(),
+                value: value.clone(),
+            },
+        );
+
+        // Register these in the state
+        let mut state = TypeCheckerState::empty();
+        let t_load_ty = state.register(t_load);
+        let tc_input = state.value_unchecked(t_load_ty).clone();
+        let [key_ty, value_ty] = match tc_input.data() {
+            TCSVD::TLoad { key, value } => [key.type_var(), value.type_var()],
+            _ => panic!("Incorrect payload"),
+        };
+        SLoadIsInnerTypesRule.infer(&tc_input, &mut state)?;
+
+        assert_eq!(state.inferences(key_ty).len(), 1);
+        assert!(state.inferences(key_ty).contains(&TE::eq(t_load_ty)));
+
+        assert_eq!(state.inferences(value_ty).len(), 1);
+        assert!(state.inferences(value_ty).contains(&TE::eq(t_load_ty)));
+
+        assert_eq!(state.inferences(t_load_ty).len(), 2);
+        assert!(state.inferences(t_load_ty).contains(&TE::eq(value_ty)));
+        assert!(state.inferences(t_load_ty).contains(&TE::eq(key_ty)));
+
+        Ok(())
+    }
 }
@@ -1,5 +1,6 @@
 //! This module contains the inference rule definition for equating the types of
-//! storage slots to the types of their contained values.
+//! storage slots (both persistent and transient) to the types of their
+//! contained values.
 
 use crate::{
     error::unification::Result,
@@ -13,6 +14,9 @@ use crate::{
 /// ```text
 /// s_store(slot, value)
 ///    a     b      c
+///
+/// t_store(slot, value)
+///    a     b      c
 /// ```
 ///
 /// equating:
@@ -24,7 +28,7 @@ pub struct StorageWriteRule;
 impl InferenceRule for StorageWriteRule {
     fn infer(&self, value: &TCBoxedVal, state: &mut TypeCheckerState) -> Result<()> {
         match value.data() {
-            TCSVD::StorageWrite { key, value } => {
+            TCSVD::StorageWrite { key, value } | TCSVD::TransientStorageWrite { key, value } => {
                 // An equality for the key's type
                 let key_tv = state.var_unchecked(key);
                 let key_type = TE::eq(key_tv);
@@ -87,4 +91,37 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn equates_transient_slot_type_and_value_type() -> anyhow::Result<()> {
+        // Create a value of the relevant kind
+        let input_key = RSV::new_value(0, Provenance::Synthetic);
+        let input_value = RSV::new_value(1, Provenance::Synthetic);
+        let write = RSV::new_synthetic(
+            2,
+            RSVD::TransientStorageWrite {
+                key:   input_key.clone(),
+                value: input_value.clone(),
+            },
+        );
+
+        // Set up the unifier state
+        let mut state = TypeCheckerState::empty();
+        let write_tv = state.register(write);
+        let tc_input = state.value_unchecked(write_tv).clone();
+        let [key_tv, value_tv] = match tc_input.data() {
+            TCSVD::TransientStorageWrite { key, value } => [key.type_var(), value.type_var()],
+            _ => panic!("Invalid payload"),
+        };
+        StorageWriteRule.infer(&tc_input, &mut state)?;
+
+        // Check that the equalities hold and that we only get the judgements we expect
+        assert_eq!(state.inferences(key_tv).len(), 1);
+        assert!(state.inferences(key_tv).contains(&TE::eq(value_tv)));
+        assert_eq!(state.inferences(value_tv).len(), 1);
+        assert!(state.inferences(value_tv).contains(&TE::eq(key_tv)));
+        assert!(state.inferences(write_tv).is_empty());
+
+        Ok(())
+    }
 }
@@ -0,0 +1,172 @@
+//! This module contains an inference rule for dealing with the data returned
+//! from calls to the known system contracts of a chain.
+
+use crate::{
+    error::unification::Result,
+    extractor::chain::system_contract::SystemContract,
+    tc::{expression::TE, rule::InferenceRule, state::TypeCheckerState},
+    vm::value::{known::KnownWord, TCBoxedVal, TCSVD},
+};
+
+/// This rule creates the following equations for any expressions of the
+/// following form:
+///
+/// ```text
+/// system_call(address, selector)[offset, size]
+///      a         b                  c      d
+/// ```
+///
+/// - `b = address`
+/// - `c = unsigned`
+/// - `d = unsigned`
+/// - `a = returns` if `b` and `selector` are constants identifying a function
+///   of one of the known `contracts`, where `returns` is the return type of
+///   that function, and `c` is the constant `0`
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SystemContractRule {
+    /// The system contracts whose functions have known return types.
+    pub contracts: Vec<SystemContract>,
+}
+
+impl SystemContractRule {
+    /// Creates a new instance of the rule that knows about the provided
+    /// `contracts`.
+    #[must_use]
+    pub fn new(contracts: Vec<SystemContract>) -> Self {
+        Self { contracts }
+    }
+
+    /// Gets the return type of the function with `selector` on the contract at
+    /// `address`, if it is known.
+    fn return_type(&self, address: &TCBoxedVal, selector: &TCBoxedVal) -> Option<TE> {
+        let address: [u8; 20] = address.as_word()?.bytes_be()[12..].try_into().ok()?;
+        let selector = u32::from(selector.as_word()?).to_be_bytes();
+
+        self.contracts
+            .iter()
+            .find(|c| c.address == address)?
+            .function(selector)
+            .map(|f| f.returns.clone())
+    }
+}
+
+impl InferenceRule for SystemContractRule {
+    fn infer(&self, value: &TCBoxedVal, state: &mut TypeCheckerState) -> Result<()> {
+        let TCSVD::SystemCallData {
+            address,
+            selector,
+            offset,
+            size,
+        } = value.data()
+        else {
+            return Ok(());
+        };
+
+        state.infer_for(address, TE::address());
+        state.infer_for_many([offset, size], TE::unsigned_word(None));
+
+        // Only the first word of the return data is the returned value
+        if offset.as_word() == Some(KnownWord::zero()) {
+            if let Some(returns) = self.return_type(address, selector) {
+                state.infer_for(value, returns);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constant::WORD_SIZE_BITS,
+        extractor::chain::system_contract::{SystemContract, L1_BLOCK_ADDRESS},
+        tc::{
+            expression::TE,
+            rule::{system_contracts::SystemContractRule, InferenceRule},
+            state::TypeCheckerState,
+        },
+        vm::value::{known::KnownWord, Provenance, RSV, RSVD, TCSVD},
+    };
+
+    #[test]
+    fn creates_correct_equations_for_system_call_data() -> anyhow::Result<()> {
+        // Create some values
+        let mut address_word = [0u8; 32];
+        address_word[12..].copy_from_slice(&L1_BLOCK_ADDRESS);
+        let address = RSV::new_known_value(
+            0,
+            KnownWord::from_be_bytes(address_word),
+            Provenance::Synthetic,
+            None,
+        );
+        let selector =
+            RSV::new_known_value(1, KnownWord::from(0x09bd_5a60), Provenance::Synthetic, None);
+        let offset = RSV::new_known_value(2, KnownWord::zero(), Provenance::Synthetic, None);
+        let size = RSV::new_known_value(3, KnownWord::from(32), Provenance::Synthetic, None);
+        let value = RSV::new_synthetic(
+            4,
+            RSVD::SystemCallData {
+                address,
+                selector,
+                offset,
+                size,
+            },
+        );
+
+        // Create the state and run the rule
+        let mut state = TypeCheckerState::empty();
+        let value_tv = state.register(value);
+        let tc_input = state.value_unchecked(value_tv).clone();
+        let [address_tv, offset_tv, size_tv] = match tc_input.data() {
+            TCSVD::SystemCallData {
+                address,
+                offset,
+                size,
+                ..
+            } => [address.type_var(), offset.type_var(), size.type_var()],
+            _ => panic!("Incorrect payload"),
+        };
+        let rule = SystemContractRule::new(vec![SystemContract::l1_block()]);
+        rule.infer(&tc_input, &mut state)?;
+
+        // Check that we get the right equations
+        assert!(state.inferences(value_tv).contains(&TE::bytes(Some(WORD_SIZE_BITS))));
+        assert!(state.inferences(address_tv).contains(&TE::address()));
+        assert!(state.inferences(offset_tv).contains(&TE::unsigned_word(None)));
+        assert!(state.inferences(size_tv).contains(&TE::unsigned_word(None)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_type_unknown_system_call_data() -> anyhow::Result<()> {
+        // Create some values
+        let address = RSV::new_known_value(0, KnownWord::from(1), Provenance::Synthetic, None);
+        let selector =
+            RSV::new_known_value(1, KnownWord::from(0x09bd_5a60), Provenance::Synthetic, None);
+        let offset = RSV::new_known_value(2, KnownWord::zero(), Provenance::Synthetic, None);
+        let size = RSV::new_known_value(3, KnownWord::from(32), Provenance::Synthetic, None);
+        let value = RSV::new_synthetic(
+            4,
+            RSVD::SystemCallData {
+                address,
+                selector,
+                offset,
+                size,
+            },
+        );
+
+        // Create the state and run the rule
+        let mut state = TypeCheckerState::empty();
+        let value_tv = state.register(value);
+        let tc_input = state.value_unchecked(value_tv).clone();
+        let rule = SystemContractRule::new(vec![SystemContract::l1_block()]);
+        rule.infer(&tc_input, &mut state)?;
+
+        // Check that the value itself is not typed
+        assert!(state.inferences(value_tv).is_empty());
+
+        Ok(())
+    }
+}
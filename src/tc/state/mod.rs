@@ -13,7 +13,16 @@ use crate::{
         expression::{InferenceSet, TypeExpression, TE},
         unification::UnificationForest,
     },
-    vm::value::{PackedSpan, Provenance, RuntimeBoxedVal, TCBoxedVal, RSVD, TCSV, TCSVD},
+    vm::value::{
+        arena::ValueArena,
+        PackedSpan,
+        Provenance,
+        RuntimeBoxedVal,
+        TCBoxedVal,
+        RSVD,
+        TCSV,
+        TCSVD,
+    },
 };
 
 pub mod type_variable;
@@ -39,6 +48,10 @@ pub struct TypeCheckerState {
 
     /// A source of fresh type variables.
     tyvar_source: TypeVariableSource,
+
+    /// The arena in which the runtime values seen by the type checker are
+    /// interned.
+    arena: ValueArena,
 }
 
 impl TypeCheckerState {
@@ -50,15 +63,35 @@ impl TypeCheckerState {
         let inferences = HashMap::new();
         let unification_result = UnificationForest::new();
         let tyvar_source = TypeVariableSource::new();
+        let arena = ValueArena::new();
         Self {
             expressions,
             stable_types,
             inferences,
             unification_result,
             tyvar_source,
+            arena,
         }
     }
 
+    /// Gets the arena in which the runtime values seen by the type checker are
+    /// interned.
+    ///
+    /// Lifting passes should intern the values that they build in this arena,
+    /// so that identical values share their storage.
+    #[must_use]
+    pub fn arena(&self) -> &ValueArena {
+        &self.arena
+    }
+
+    /// Sets the arena in which the runtime values seen by the type checker are
+    /// interned to `arena`.
+    ///
+    /// This allows the type checker to share the arena used during execution.
+    pub fn set_arena(&mut self, arena: ValueArena) {
+        self.arena = arena;
+    }
+
     /// Registers a symbolic `value` in the state, returning the associated type
     /// variable for the top-level value.
     ///
@@ -67,6 +100,8 @@ impl TypeCheckerState {
     /// variable. This means that you do not have easy access to the type
     /// variables of the child nodes.
     ///
+    /// The `value` is interned in [`Self::arena`] before it is registered.
+    ///
     /// # Registration Uniqueness
     ///
     /// Two calls to `register` with the same `value` will implicitly create
@@ -80,6 +115,7 @@ impl TypeCheckerState {
     /// corresponding typing judgements should be ascribed.
     #[must_use]
     pub fn register(&mut self, value: RuntimeBoxedVal) -> TypeVariable {
+        let value = self.arena.intern(value);
         let returned_val = self.register_internal(value);
         self.var_unchecked(&returned_val)
     }
@@ -108,8 +144,8 @@ impl TypeCheckerState {
     ///
     /// A stable type is one that should not change based on occurrence if the
     /// structure of the value is the same. A value is stably typed if it
-    /// contains one or more of [`RSVD::Value`], [`RSVD::CallData`] and
-    /// [`RSVD::StorageSlot`] in its tree.
+    /// contains one or more of [`RSVD::Value`], [`RSVD::CallData`],
+    /// [`RSVD::StorageSlot`] and [`RSVD::TransientStorageSlot`] in its tree.
     ///
     /// # Quadratic Traversal
     ///
@@ -122,7 +158,10 @@ impl TypeCheckerState {
     #[must_use]
     fn is_stable_typed(value: &RuntimeBoxedVal) -> bool {
         match value.data() {
-            RSVD::StorageSlot { .. } | RSVD::Value { .. } | RSVD::CallData { .. } => true,
+            RSVD::StorageSlot { .. }
+            | RSVD::TransientStorageSlot { .. }
+            | RSVD::Value { .. }
+            | RSVD::CallData { .. } => true,
             _ => value.children().into_iter().any(|c| Self::is_stable_typed(&c)),
         }
     }
@@ -232,11 +271,16 @@ impl TypeCheckerState {
             RSVD::CoinBase => TCSVD::CoinBase,
             RSVD::BlockTimestamp => TCSVD::BlockTimestamp,
             RSVD::BlockNumber => TCSVD::BlockNumber,
+            RSVD::L1BlockNumber => TCSVD::L1BlockNumber,
             RSVD::Prevrandao => TCSVD::Prevrandao,
             RSVD::GasLimit => TCSVD::GasLimit,
             RSVD::ChainId => TCSVD::ChainId,
             RSVD::SelfBalance => TCSVD::SelfBalance,
             RSVD::BaseFee => TCSVD::BaseFee,
+            RSVD::BlobHash { index } => TCSVD::BlobHash {
+                index: self.register_internal(index),
+            },
+            RSVD::BlobBaseFee => TCSVD::BlobBaseFee,
             RSVD::Gas => TCSVD::Gas,
             RSVD::Log { data, topics } => TCSVD::Log {
                 data:   self.register_internal(data),
@@ -251,8 +295,12 @@ impl TypeCheckerState {
                 salt:  self.register_internal(salt),
                 data:  self.register_internal(data),
             },
-            RSVD::SelfDestruct { target } => TCSVD::SelfDestruct {
+            RSVD::SelfDestruct {
+                target,
+                deletes_account,
+            } => TCSVD::SelfDestruct {
                 target: self.register_internal(target),
+                deletes_account,
             },
             RSVD::LessThan { left, right } => TCSVD::LessThan {
                 left:  self.register_internal(left),
@@ -330,6 +378,17 @@ impl TypeCheckerState {
                 offset: self.register_internal(offset),
                 size:   self.register_internal(size),
             },
+            RSVD::SystemCallData {
+                address,
+                selector,
+                offset,
+                size,
+            } => TCSVD::SystemCallData {
+                address:  self.register_internal(address),
+                selector: self.register_internal(selector),
+                offset:   self.register_internal(offset),
+                size:     self.register_internal(size),
+            },
             RSVD::Return { data } => TCSVD::Return {
                 data: self.register_internal(data),
             },
@@ -347,6 +406,17 @@ impl TypeCheckerState {
                 key:   self.register_internal(key),
                 value: self.register_internal(value),
             },
+            RSVD::UnwrittenTransientStorageValue { key } => TCSVD::UnwrittenTransientStorageValue {
+                key: self.register_internal(key),
+            },
+            RSVD::TLoad { key, value } => TCSVD::TLoad {
+                key:   self.register_internal(key),
+                value: self.register_internal(value),
+            },
+            RSVD::TransientStorageWrite { key, value } => TCSVD::TransientStorageWrite {
+                key:   self.register_internal(key),
+                value: self.register_internal(value),
+            },
             RSVD::Concat { values } => TCSVD::Concat {
                 values: values.into_iter().map(|v| self.register_internal(v)).collect(),
             },
@@ -385,9 +455,16 @@ impl TypeCheckerState {
                     })
                     .collect(),
             },
+            RSVD::Choice { alternatives } => TCSVD::Choice {
+                alternatives: alternatives.into_iter().map(|v| self.register_internal(v)).collect(),
+            },
+            RSVD::LoopCounter { header } => TCSVD::LoopCounter { header },
             RSVD::StorageSlot { key } => TCSVD::StorageSlot {
                 key: self.register_internal(key),
             },
+            RSVD::TransientStorageSlot { key } => TCSVD::TransientStorageSlot {
+                key: self.register_internal(key),
+            },
         };
         let type_var = self.tyvar_source.fresh();
         let new_value = TCSV::new(instruction_pointer, new_data, provenance, type_var);
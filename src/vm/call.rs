@@ -0,0 +1,381 @@
+//! This module contains the types used to model calls to internal functions
+//! during symbolic execution.
+//!
+//! The EVM has no notion of an internal function. Instead, compilers push a
+//! return address before the arguments to a function, and then jump to the
+//! function's body, which ends by jumping back to the return address. The
+//! virtual machine recognises these pairs of jumps, and tracks the active calls
+//! of each thread so that the body of a function can be executed afresh from
+//! each of its call sites.
+
+use std::{collections::HashMap, mem::Discriminant};
+
+use ethnum::U256;
+
+use crate::{
+    constant::{PUSH_OPCODE_BASE_VALUE, SWAP_OPCODE_BASE_VALUE},
+    opcode::Opcode,
+    vm::{
+        data::VisitedOpcodes,
+        value::{RuntimeBoxedVal, RSVD},
+    },
+};
+
+/// A call to an internal function that has not yet returned.
+#[derive(Clone, Debug)]
+pub struct CallFrame {
+    /// The offset of the jump that called the function.
+    pub call_site: u32,
+
+    /// The offset at which the body of the function starts.
+    pub function: u32,
+
+    /// The offset that the function returns to.
+    pub return_address: u32,
+
+    /// The depth of the stack beneath the return address at the time of the
+    /// call.
+    pub stack_depth: usize,
+
+    /// The arguments to the function, ordered from the top of the stack.
+    pub arguments: Vec<RuntimeBoxedVal>,
+
+    /// Whether the function has only executed instructions whose sole effect
+    /// is on the stack.
+    pub pure: bool,
+
+    /// The visits to instructions made by the thread before the call.
+    pub visits: VisitedOpcodes,
+}
+
+impl CallFrame {
+    /// Gets the shape of the call, which identifies calls to the same function
+    /// that can share a summary.
+    #[must_use]
+    pub fn shape(&self) -> CallShape {
+        CallShape::new(self.function, &self.arguments)
+    }
+}
+
+/// The internal functions that a thread of execution is currently inside,
+/// ordered from the outermost call.
+#[derive(Clone, Debug, Default)]
+pub struct CallStack {
+    frames: Vec<CallFrame>,
+}
+
+impl CallStack {
+    /// Constructs a new call stack with no active calls.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `frame` is the innermost active call.
+    pub fn push(&mut self, frame: CallFrame) {
+        self.frames.push(frame);
+    }
+
+    /// Removes the innermost active call, returning it if there was one.
+    pub fn pop(&mut self) -> Option<CallFrame> {
+        self.frames.pop()
+    }
+
+    /// Gets the innermost active call, if there is one.
+    #[must_use]
+    pub fn innermost(&self) -> Option<&CallFrame> {
+        self.frames.last()
+    }
+
+    /// Checks whether any active call returns to `offset`.
+    #[must_use]
+    pub fn returns_to(&self, offset: u32) -> bool {
+        self.frames.iter().any(|frame| frame.return_address == offset)
+    }
+
+    /// Records that `instruction` has been executed inside all of the active
+    /// calls.
+    pub fn record_instruction(&mut self, instruction: &dyn Opcode) {
+        if self.frames.is_empty() {
+            return;
+        }
+
+        // Instructions that take up no space in the bytecode do nothing at all
+        if !instruction.encode().first().map_or(true, |&byte| is_pure(byte)) {
+            self.mark_impure();
+        }
+    }
+
+    /// Records that the active calls have taken a path that depends on a value
+    /// that is not known, such that their results cannot be summarised.
+    pub fn mark_impure(&mut self) {
+        self.frames.iter_mut().for_each(|frame| frame.pure = false);
+    }
+
+    /// Gets the addresses that the active calls return to, ordered from the
+    /// outermost call.
+    pub fn return_addresses(&self) -> impl Iterator<Item = u32> + '_ {
+        self.frames.iter().map(|frame| frame.return_address)
+    }
+
+    /// Checks whether `other` is inside calls to the same functions with the
+    /// same return addresses as this call stack.
+    #[must_use]
+    pub fn is_compatible_with(&self, other: &CallStack) -> bool {
+        self.frames.len() == other.frames.len()
+            && self.frames.iter().zip(&other.frames).all(|(ours, theirs)| {
+                ours.function == theirs.function && ours.return_address == theirs.return_address
+            })
+    }
+
+    /// Merges the compatible call stack `other` into this one, such that a call
+    /// is only treated as pure if it was pure in both.
+    pub fn merge(&mut self, other: &CallStack) {
+        for (ours, theirs) in self.frames.iter_mut().zip(&other.frames) {
+            ours.pure &= theirs.pure;
+        }
+    }
+
+    /// Gets the number of active calls.
+    #[must_use]
+    pub fn depth(&self) -> usize {
+        self.frames.len()
+    }
+
+    /// Checks whether there are no active calls.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+}
+
+/// The abstract shape of a call to an internal function.
+///
+/// Arguments that are known words are identified by their value, while all
+/// other arguments are identified only by the kind of operation that produced
+/// them.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct CallShape {
+    function:  u32,
+    arguments: Vec<ArgumentShape>,
+}
+
+impl CallShape {
+    /// Computes the shape of a call to the function at `function` with the
+    /// provided `arguments`.
+    #[must_use]
+    pub fn new(function: u32, arguments: &[RuntimeBoxedVal]) -> Self {
+        let arguments = arguments
+            .iter()
+            .map(|argument| match argument.constant_fold().as_word() {
+                Some(word) => ArgumentShape::Known(word.value_le()),
+                None => ArgumentShape::Symbolic(std::mem::discriminant(argument.data())),
+            })
+            .collect();
+
+        Self {
+            function,
+            arguments,
+        }
+    }
+}
+
+/// The abstract shape of a single argument to an internal function.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum ArgumentShape {
+    Known(U256),
+    Symbolic(Discriminant<RSVD>),
+}
+
+/// The effect on the stack of a call to an internal function, as observed
+/// when it was first executed with a given [`CallShape`].
+#[derive(Clone, Debug)]
+pub struct CallSummary {
+    /// The arguments to the observed call, ordered from the top of the stack.
+    pub arguments: Vec<RuntimeBoxedVal>,
+
+    /// The values returned by the observed call, ordered from the top of the
+    /// stack.
+    pub returns: Vec<RuntimeBoxedVal>,
+}
+
+impl CallSummary {
+    /// Computes the values returned by a call with the provided `arguments`,
+    /// ordered from the top of the stack.
+    ///
+    /// Each occurrence of an observed argument in the observed return values
+    /// is replaced by the corresponding argument in `arguments`.
+    #[must_use]
+    pub fn apply(&self, arguments: &[RuntimeBoxedVal]) -> Vec<RuntimeBoxedVal> {
+        let substitutions: Vec<_> = self
+            .arguments
+            .iter()
+            .zip(arguments)
+            .filter(|(observed, _)| !observed.is_known_data())
+            .map(|(observed, actual)| (observed.data().clone(), actual.data().clone()))
+            .collect();
+        let substitutions = substitutions.as_slice();
+
+        self.returns
+            .iter()
+            .map(|value| {
+                value.transform_data(|data| {
+                    substitutions
+                        .iter()
+                        .find(|(observed, _)| observed == data)
+                        .map(|(_, actual)| actual.clone())
+                })
+            })
+            .collect()
+    }
+}
+
+/// The summaries of internal functions that have been observed to return
+/// during execution.
+#[derive(Clone, Debug, Default)]
+pub struct CallSummaries {
+    summaries: HashMap<CallShape, CallSummary>,
+}
+
+impl CallSummaries {
+    /// Constructs a new container with no summaries.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records `summary` for calls with the provided `shape`, unless there is
+    /// already a summary for that shape.
+    pub fn record(&mut self, shape: CallShape, summary: CallSummary) {
+        self.summaries.entry(shape).or_insert(summary);
+    }
+
+    /// Merges the summaries in `other` into `self`, keeping the summary in
+    /// `self` for any shape that both have a summary for.
+    pub fn merge(&mut self, other: CallSummaries) {
+        for (shape, summary) in other.summaries {
+            self.record(shape, summary);
+        }
+    }
+
+    /// Gets the summary for calls with the provided `shape`, if there is one.
+    #[must_use]
+    pub fn get(&self, shape: &CallShape) -> Option<&CallSummary> {
+        self.summaries.get(shape)
+    }
+
+    /// Gets the number of summaries in the container.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.summaries.len()
+    }
+
+    /// Checks whether the container has no summaries.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.summaries.is_empty()
+    }
+}
+
+/// Checks whether the instruction with the byte `opcode` affects nothing but
+/// the stack and the instruction pointer.
+///
+/// A `JUMPI` is only pure when its condition is known, as otherwise the values
+/// returned by the call depend on the branch that was taken. The conditional
+/// jump itself is responsible for marking the active calls as impure (see
+/// [`CallStack::mark_impure`]) when its condition is not known.
+fn is_pure(opcode: u8) -> bool {
+    let arithmetic = 0x01..=0x0b;
+    let comparisons_and_logic = 0x10..=0x1d;
+    let control = [0x50, 0x56, 0x57, 0x58, 0x5b];
+    let push_dup_and_swap = PUSH_OPCODE_BASE_VALUE..=SWAP_OPCODE_BASE_VALUE + 16;
+
+    arithmetic.contains(&opcode)
+        || comparisons_and_logic.contains(&opcode)
+        || control.contains(&opcode)
+        || push_dup_and_swap.contains(&opcode)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        opcode::{arithmetic, control, memory},
+        vm::{
+            call::{CallFrame, CallShape, CallStack, CallSummary},
+            data::VisitedOpcodes,
+            test_util::{known, synthetic},
+            value::RSVD,
+        },
+    };
+
+    #[test]
+    fn shapes_distinguish_known_arguments_by_value() {
+        let one = CallShape::new(4, &[known(1), synthetic(RSVD::CallValue)]);
+        let other = CallShape::new(4, &[known(2), synthetic(RSVD::CallValue)]);
+        assert_ne!(one, other);
+    }
+
+    #[test]
+    fn shapes_distinguish_symbolic_arguments_by_kind() {
+        let caller = CallShape::new(4, &[synthetic(RSVD::Caller)]);
+        let origin = CallShape::new(4, &[synthetic(RSVD::Origin)]);
+        let add = |left, right| synthetic(RSVD::Add { left, right });
+
+        assert_ne!(caller, origin);
+        assert_eq!(
+            CallShape::new(4, &[add(known(1), synthetic(RSVD::Caller))]),
+            CallShape::new(4, &[add(synthetic(RSVD::Origin), known(2))])
+        );
+    }
+
+    #[test]
+    fn summaries_substitute_symbolic_arguments() {
+        let observed = synthetic(RSVD::Caller);
+        let summary = CallSummary {
+            arguments: vec![observed.clone(), known(3)],
+            returns:   vec![synthetic(RSVD::Add {
+                left:  observed,
+                right: known(3),
+            })],
+        };
+
+        let returns = summary.apply(&[synthetic(RSVD::Origin), known(3)]);
+        assert_eq!(
+            returns,
+            vec![synthetic(RSVD::Add {
+                left:  synthetic(RSVD::Origin),
+                right: known(3),
+            })]
+        );
+    }
+
+    #[test]
+    fn call_stack_tracks_purity_of_all_frames() {
+        let frame = |return_address| CallFrame {
+            call_site: 0,
+            function: 1,
+            return_address,
+            stack_depth: 0,
+            arguments: Vec::new(),
+            pure: true,
+            visits: VisitedOpcodes::new(8, 1),
+        };
+        let mut stack = CallStack::new();
+        stack.push(frame(2));
+        stack.push(frame(3));
+
+        // Stack manipulation leaves the calls pure
+        stack.record_instruction(&arithmetic::Add);
+        stack.record_instruction(&memory::PushN::new(1, vec![0x01]).unwrap());
+        stack.record_instruction(&memory::SwapN::new(1).unwrap());
+        stack.record_instruction(&control::Nop);
+        assert!(stack.innermost().is_some_and(|frame| frame.pure));
+
+        // But touching memory does not
+        stack.record_instruction(&memory::MStore);
+        assert!(stack.returns_to(2));
+        assert!(stack.pop().is_some_and(|frame| !frame.pure));
+        assert!(stack.pop().is_some_and(|frame| !frame.pure));
+        assert!(stack.is_empty());
+    }
+}
@@ -0,0 +1,264 @@
+//! This module contains the tracking of how much of the bytecode the virtual
+//! machine explored, and the report of that coverage that is produced at the
+//! end of execution.
+//!
+//! The exploration performed by the virtual machine is bounded, so for large
+//! contracts it may never reach some of the code. As the layout is inferred
+//! only from the code that was executed, the coverage report gives an
+//! indication of how far the resulting layout can be trusted.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use crate::disassembly::InstructionStream;
+
+/// The tracking of which instructions and branches have been executed by any
+/// thread of the virtual machine.
+///
+/// Unlike the per-thread [`crate::vm::data::VisitedOpcodes`], this is shared
+/// between all threads and is never reset.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Coverage {
+    /// The offsets of the instructions that have been executed.
+    executed: BTreeSet<u32>,
+
+    /// The directions in which each executed conditional jump has continued.
+    branches: BTreeMap<u32, BTreeSet<BranchDirection>>,
+
+    /// The offsets of the instructions executed by threads that have been
+    /// dispatched to each function selector.
+    selectors: BTreeMap<u32, BTreeSet<u32>>,
+}
+
+impl Coverage {
+    /// Constructs a new coverage tracker in which nothing has been executed.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the instruction at `instruction_pointer` was executed by a
+    /// thread dispatched to `selector`, if any.
+    pub fn record_instruction(&mut self, instruction_pointer: u32, selector: Option<u32>) {
+        self.executed.insert(instruction_pointer);
+        if let Some(selector) = selector {
+            self.selectors
+                .entry(selector)
+                .or_default()
+                .insert(instruction_pointer);
+        }
+    }
+
+    /// Records that execution continued from the conditional jump at `jump` in
+    /// the specified `direction`.
+    pub fn record_branch(&mut self, jump: u32, direction: BranchDirection) {
+        self.branches.entry(jump).or_default().insert(direction);
+    }
+
+    /// Merges everything that was executed according to `other` into `self`.
+    pub fn merge(&mut self, other: Coverage) {
+        self.executed.extend(other.executed);
+        for (jump, directions) in other.branches {
+            self.branches.entry(jump).or_default().extend(directions);
+        }
+        for (selector, executed) in other.selectors {
+            self.selectors.entry(selector).or_default().extend(executed);
+        }
+    }
+
+    /// Checks whether the instruction at `instruction_pointer` has been
+    /// executed.
+    #[must_use]
+    pub fn is_executed(&self, instruction_pointer: u32) -> bool {
+        self.executed.contains(&instruction_pointer)
+    }
+
+    /// Produces the coverage report for execution over `instructions`.
+    #[must_use]
+    pub fn report(&self, instructions: &InstructionStream) -> CoverageReport {
+        let mut report = CoverageReport::default();
+        let mut code = BTreeSet::new();
+
+        for (offset, instruction) in (0u32..).zip(instructions.instructions()) {
+            // Instructions that take up no space in the bytecode do nothing at all
+            let Some(&byte) = instruction.encode().first() else {
+                continue;
+            };
+            if instructions.is_data(offset) {
+                continue;
+            }
+
+            code.insert(offset);
+            if self.is_executed(offset) {
+                continue;
+            }
+            match byte {
+                0x54 => report.unvisited_storage_loads.push(offset),
+                0x55 => report.unvisited_storage_stores.push(offset),
+                0x5b => report.unvisited_jump_destinations.push(offset),
+                _ => (),
+            }
+        }
+
+        report.instruction_count = code.len();
+        report.visited_instructions = self.executed.intersection(&code).copied().collect();
+        report.untaken_branches = self
+            .branches
+            .iter()
+            .flat_map(|(&jump, taken)| {
+                [BranchDirection::Jumped, BranchDirection::FellThrough]
+                    .into_iter()
+                    .filter(|direction| !taken.contains(direction))
+                    .map(move |direction| UntakenBranch { jump, direction })
+            })
+            .collect();
+        report.selectors = self
+            .selectors
+            .iter()
+            .map(|(&selector, executed)| (selector, executed.intersection(&code).count()))
+            .collect();
+
+        report
+    }
+}
+
+/// A direction in which execution can continue from a conditional jump.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum BranchDirection {
+    /// Execution continued at the jump destination.
+    Jumped,
+
+    /// Execution continued at the instruction following the jump.
+    FellThrough,
+}
+
+/// A direction in which no thread continued from a conditional jump that was
+/// executed.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+pub struct UntakenBranch {
+    /// The offset of the conditional jump instruction.
+    pub jump: u32,
+
+    /// The direction in which execution never continued from the jump.
+    pub direction: BranchDirection,
+}
+
+/// A report of the parts of the bytecode that were explored by the virtual
+/// machine.
+///
+/// All locations are given as byte offsets into the bytecode, and exclude any
+/// regions of the bytecode that were found to contain data rather than code.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CoverageReport {
+    /// The number of instructions in the code.
+    pub instruction_count: usize,
+
+    /// The instructions that were executed by at least one thread.
+    pub visited_instructions: BTreeSet<u32>,
+
+    /// The `JUMPDEST` instructions that were never executed.
+    pub unvisited_jump_destinations: Vec<u32>,
+
+    /// The `SLOAD` instructions that were never executed.
+    pub unvisited_storage_loads: Vec<u32>,
+
+    /// The `SSTORE` instructions that were never executed.
+    pub unvisited_storage_stores: Vec<u32>,
+
+    /// The branches of executed conditional jumps that no thread continued
+    /// along, including those that were pruned as infeasible.
+    pub untaken_branches: Vec<UntakenBranch>,
+
+    /// The number of distinct instructions executed by the threads that were
+    /// dispatched to each function selector.
+    ///
+    /// This is empty if the dispatcher of the contract was not recognised.
+    pub selectors: BTreeMap<u32, usize>,
+}
+
+impl CoverageReport {
+    /// Gets the fraction of the instructions in the code that were executed,
+    /// between zero and one.
+    #[allow(clippy::cast_precision_loss)] // Bytecode is far smaller than 2^52 instructions
+    #[must_use]
+    pub fn instruction_coverage(&self) -> f64 {
+        if self.instruction_count == 0 {
+            1.0
+        } else {
+            self.visited_instructions.len() as f64 / self.instruction_count as f64
+        }
+    }
+
+    /// Checks whether every storage access in the code was executed.
+    #[must_use]
+    pub fn covers_all_storage_accesses(&self) -> bool {
+        self.unvisited_storage_loads.is_empty() && self.unvisited_storage_stores.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        disassembly::InstructionStream,
+        extractor::chain::Chain,
+        vm::coverage::{BranchDirection, Coverage, CoverageReport, UntakenBranch},
+    };
+
+    #[test]
+    fn reports_unvisited_instructions_of_interest() -> anyhow::Result<()> {
+        let source = "
+                PUSH1 0x00
+                SLOAD
+                PUSH1 store ; 0x03
+                JUMPI
+                STOP
+            store:
+                JUMPDEST    ; 0x07
+                PUSH1 0x00
+                DUP1
+                SSTORE
+                STOP
+        ";
+        let instructions = InstructionStream::assemble(source, &Chain::default())?;
+        let mut coverage = Coverage::new();
+        for offset in [0x00, 0x02, 0x03, 0x05, 0x06] {
+            coverage.record_instruction(offset, Some(0x1234));
+        }
+        coverage.record_branch(0x05, BranchDirection::FellThrough);
+
+        let report = coverage.report(&instructions);
+        assert_eq!(report.instruction_count, 10);
+        assert_eq!(report.unvisited_jump_destinations, vec![0x07]);
+        assert_eq!(report.unvisited_storage_loads, Vec::<u32>::new());
+        assert_eq!(report.unvisited_storage_stores, vec![0x0b]);
+        assert_eq!(
+            report.untaken_branches,
+            vec![UntakenBranch {
+                jump:      0x05,
+                direction: BranchDirection::Jumped,
+            }]
+        );
+        assert_eq!(report.selectors, BTreeMap::from([(0x1234, 5)]));
+        assert!((report.instruction_coverage() - 0.5).abs() < f64::EPSILON);
+        assert!(!report.covers_all_storage_accesses());
+
+        Ok(())
+    }
+
+    #[test]
+    fn reports_can_be_serialized() -> anyhow::Result<()> {
+        let mut coverage = Coverage::new();
+        coverage.record_instruction(0, Some(0xa905_9cbb));
+        coverage.record_branch(0, BranchDirection::Jumped);
+        let report = coverage.report(&InstructionStream::try_from("5700")?);
+
+        let json = serde_json::to_string(&report)?;
+        let decoded: CoverageReport = serde_json::from_str(&json)?;
+        assert_eq!(decoded, report);
+
+        Ok(())
+    }
+}
@@ -1,12 +1,12 @@
 //! This module contains miscellaneous small data-types that are used throughout
 //! the virtual machine.
 
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 
 use crate::{
     disassembly::ExecutionThread,
     error::{container::Locatable, execution, execution::Error},
-    opcode::control::{JumpDest, JumpI},
+    opcode::control::{Jump, JumpDest, JumpI},
 };
 
 /// A container that tracks how many times an opcode has been visited by the
@@ -103,6 +103,16 @@ impl VisitedOpcodes {
         }
     }
 
+    /// Merges the visits in `other` into this container, such that each opcode
+    /// is treated as having been visited the greater of the number of times
+    /// that it was visited in each.
+    pub fn merge(&mut self, other: &VisitedOpcodes) {
+        for (&instruction_pointer, &count) in &other.data {
+            let ours = self.data.entry(instruction_pointer).or_insert(0);
+            *ours = (*ours).max(count);
+        }
+    }
+
     /// Checks if the opcode at `instruction_pointer` has reached the maximum
     /// number of times that it can be visited.
     ///
@@ -124,6 +134,80 @@ impl VisitedOpcodes {
     }
 }
 
+/// A count of the number of times that the virtual machine has forked to each
+/// jump target, bounded by a limit per target.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ForkBudget {
+    maximum_forks_per_jump_target: usize,
+    counts: Vec<usize>,
+}
+
+impl ForkBudget {
+    /// Constructs a new budget for up to `instructions_len` jump targets, in
+    /// which no forks have yet been taken.
+    #[must_use]
+    pub fn new(instructions_len: usize, maximum_forks_per_jump_target: usize) -> Self {
+        let counts = vec![0; instructions_len];
+
+        Self {
+            maximum_forks_per_jump_target,
+            counts,
+        }
+    }
+
+    /// Takes a fork to the jump target at `instruction_pointer` from the
+    /// budget, returning `false` if the limit for that target has already been
+    /// reached.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the provided `instruction_pointer` is out of bounds
+    /// in the instruction stream.
+    pub fn take(&mut self, instruction_pointer: u32) -> execution::Result<bool> {
+        let maximum = self.maximum_forks_per_jump_target;
+        let count = self.count_mut(instruction_pointer)?;
+        let taken = *count < maximum;
+        if taken {
+            *count += 1;
+        }
+
+        Ok(taken)
+    }
+
+    /// Gets the number of forks that have been taken to the jump target at
+    /// `instruction_pointer`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the provided `instruction_pointer` is out of bounds
+    /// in the instruction stream.
+    pub fn fork_count(&self, instruction_pointer: u32) -> execution::Result<usize> {
+        let available = self.counts.len();
+        self.counts
+            .get(instruction_pointer as usize)
+            .copied()
+            .ok_or_else(|| Self::out_of_bounds(instruction_pointer, available))
+    }
+
+    /// Gets the counter for the jump target at `instruction_pointer`.
+    fn count_mut(&mut self, instruction_pointer: u32) -> execution::Result<&mut usize> {
+        let available = self.counts.len();
+        self.counts
+            .get_mut(instruction_pointer as usize)
+            .ok_or_else(|| Self::out_of_bounds(instruction_pointer, available))
+    }
+
+    /// Creates the error for a jump target at `instruction_pointer` that is
+    /// outside of the `available` jump targets in the budget.
+    fn out_of_bounds(instruction_pointer: u32, available: usize) -> execution::LocatedError {
+        Error::InstructionPointerOutOfBounds {
+            requested: instruction_pointer as usize,
+            available,
+        }
+        .locate(instruction_pointer)
+    }
+}
+
 /// A container that tracks how many times a given jump target has been visited
 /// by the virtual machine from conditional jumps.
 ///
@@ -134,39 +218,64 @@ pub struct JumpTargets {
     /// The instructions over which the virtual machine is executing.
     instructions: ExecutionThread,
 
-    /// Tracking for how many times a given opcode has been visited.
-    ///
-    /// This is used to implement tracking specific to jump targets and via
-    /// conditional jumps.
-    tracker: VisitedOpcodes,
+    /// The number of times that each jump target has been forked to.
+    budget: ForkBudget,
+
+    /// The branches of conditional jumps that were never explored as their
+    /// conditions were known to make them infeasible.
+    pruned: BTreeSet<PrunedBranch>,
 }
 
 impl JumpTargets {
     /// Creates a new tracker for jump targets.
-    #[allow(clippy::missing_panics_doc)] // Instructions len has already been checked
     #[must_use]
     pub fn new(instructions: ExecutionThread, maximum_forks_per_jump_target: usize) -> Self {
-        let instructions_len = instructions.len();
-        let tracker = VisitedOpcodes::new(
-            u32::try_from(instructions_len).expect("Invalid instruction length provided"),
-            maximum_forks_per_jump_target,
-        );
+        let budget = ForkBudget::new(instructions.len(), maximum_forks_per_jump_target);
+        let pruned = BTreeSet::new();
         Self {
             instructions,
-            tracker,
+            budget,
+            pruned,
         }
     }
 
-    /// Requests that the VM fork from the conditional jump at
-    /// `current_instruction` to the jump target at `target_instruction`.
+    /// Merges the branches pruned by `other` into `self`.
+    ///
+    /// The fork budget is unaffected, as the forks taken by `other` are instead
+    /// granted individually using [`Self::grant_fork`].
+    pub fn merge(&mut self, other: &JumpTargets) {
+        self.pruned.extend(other.pruned.iter().copied());
+    }
+
+    /// Takes a fork to the jump target at `target_instruction` from the budget,
+    /// returning `false` if the limit for that target has already been
+    /// reached.
+    ///
+    /// Unlike [`Self::fork_to`], this does not check the instructions involved
+    /// in the fork, and is used to grant forks that were already requested
+    /// from a copy of this tracker.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if `target_instruction` is out of bounds in the
+    /// instruction stream.
+    pub fn grant_fork(&mut self, target_instruction: u32) -> execution::Result<bool> {
+        self.budget.take(target_instruction)
+    }
+
+    /// Requests that the VM fork from the jump at `current_instruction` to the
+    /// jump target at `target_instruction`.
+    ///
+    /// This is usually a conditional jump, but an unconditional jump whose
+    /// destination can take multiple values also forks to each of them.
     ///
     /// If the fork is successful, the count for that target is incremented and
     /// `true` is returned. Otherwise, `false` is returned.
     ///
     /// # Errors
     ///
-    /// Returns [`Err`] if `current_instruction` is not [`JumpI`] or if
-    /// `target_instruction` is not [`JumpDest`].
+    /// Returns [`Err`] if `current_instruction` is not [`Jump`] or [`JumpI`],
+    /// or if `target_instruction` is not [`JumpDest`].
     pub fn fork_to(
         &mut self,
         current_instruction: u32,
@@ -187,13 +296,9 @@ impl JumpTargets {
             .locate(current_instruction),
         )?;
 
-        // The source instruction needs to be a conditional jump.
-        if concrete_current_inst
-            .as_ref()
-            .as_any()
-            .downcast_ref::<JumpI>()
-            .is_none()
-        {
+        // The source instruction needs to be a jump.
+        let source = concrete_current_inst.as_ref().as_any();
+        if !source.is::<JumpI>() && !source.is::<Jump>() {
             Err(Error::NotJumpSource {
                 pointer: current_instruction,
             }
@@ -214,12 +319,7 @@ impl JumpTargets {
         }
 
         // With that out of the way we can jump
-        if self.tracker.at_visit_limit(target_instruction)? {
-            Ok(false)
-        } else {
-            self.tracker.mark_visited(target_instruction)?;
-            Ok(true)
-        }
+        self.budget.take(target_instruction)
     }
 
     /// Gets the number of times that the virtual machine has conditionally
@@ -251,10 +351,43 @@ impl JumpTargets {
             .locate(instruction_pointer))?;
         }
 
-        self.tracker.visit_count(instruction_pointer)
+        self.budget.fork_count(instruction_pointer)
+    }
+
+    /// Records that the branch of the conditional jump at
+    /// `current_instruction` that continues at `pruned_instruction` was not
+    /// explored, as the jump's condition was known to make it infeasible.
+    ///
+    /// Pruning a branch does not count towards the fork limit for its target.
+    pub fn prune(&mut self, current_instruction: u32, pruned_instruction: u32) {
+        self.pruned.insert(PrunedBranch {
+            jump:        current_instruction,
+            destination: pruned_instruction,
+        });
+    }
+
+    /// Gets the branches of conditional jumps that have been pruned, ordered
+    /// by the offset of the jump.
+    #[must_use]
+    pub fn pruned_branches(&self) -> Vec<PrunedBranch> {
+        self.pruned.iter().copied().collect()
     }
 }
 
+/// A branch of a conditional jump that was not explored by the virtual machine
+/// as the condition of the jump was concretely known.
+#[derive(Copy, Clone, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct PrunedBranch {
+    /// The offset of the conditional jump instruction.
+    pub jump: u32,
+
+    /// The offset at which the pruned branch would have continued execution.
+    ///
+    /// This is the jump target if the condition was known to be false, and
+    /// the instruction following the jump if it was known to be true.
+    pub destination: u32,
+}
+
 #[cfg(test)]
 mod test {
     mod visited_opcodes {
@@ -332,8 +465,8 @@ mod test {
             disassembly::InstructionStream,
             error::{container::Locatable, execution::Error},
             opcode::{
-                control::{JumpDest, JumpI},
-                memory::Push0,
+                control::{Jump, JumpDest, JumpI},
+                memory::{Push0, PushN},
             },
             vm::data::JumpTargets,
         };
@@ -354,6 +487,22 @@ mod test {
             Ok(())
         }
 
+        #[test]
+        fn can_track_visits_from_unconditional_jumps() -> anyhow::Result<()> {
+            // Create some opcodes, pushing the target so it is not treated as data
+            let opcodes = bytecode![PushN::new(1, vec![0x03])?, Jump, JumpDest];
+            let instructions = InstructionStream::try_from(opcodes.as_slice())?;
+            let thread = instructions.new_thread(0)?;
+
+            // Create the container
+            let mut targets = JumpTargets::new(thread, 1);
+
+            assert!(targets.fork_to(2, 3)?);
+            assert!(!targets.fork_to(2, 3)?);
+
+            Ok(())
+        }
+
         #[test]
         fn errors_if_source_is_not_conditional_jump() -> anyhow::Result<()> {
             // Create some opcodes
@@ -421,5 +570,31 @@ mod test {
 
             Ok(())
         }
+
+        #[test]
+        fn grants_forks_from_the_budget_of_each_tracker() -> anyhow::Result<()> {
+            // Create some opcodes
+            let opcodes = bytecode![JumpI, JumpDest];
+            let instructions = InstructionStream::try_from(opcodes.as_slice())?;
+            let thread = instructions.new_thread(0)?;
+
+            // Create the container, along with a copy of it
+            let mut targets = JumpTargets::new(thread, 2);
+            let mut cloned = targets.clone();
+
+            // Forks through the copy do not count towards the limit of the original
+            assert!(cloned.fork_to(0, 1)?);
+            assert!(cloned.fork_to(0, 1)?);
+            assert!(!cloned.fork_to(0, 1)?);
+            assert_eq!(targets.cond_jump_count(1)?, 0);
+
+            // Until they are granted by it
+            assert!(targets.grant_fork(1)?);
+            assert!(targets.fork_to(0, 1)?);
+            assert!(!targets.grant_fork(1)?);
+            assert_eq!(targets.cond_jump_count(1)?, 2);
+
+            Ok(())
+        }
     }
 }
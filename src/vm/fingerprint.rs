@@ -0,0 +1,295 @@
+//! This module contains the fingerprinting of the abstract state of threads in
+//! the virtual machine, used to avoid exploring the same state more than once.
+//!
+//! Many of the threads forked during execution reach the same `JUMPDEST` with
+//! structurally identical stacks, storage and memory. Executing each of them
+//! to completion produces no new information, as the values they compute are
+//! later deduplicated anyway. When [`crate::vm::Config::thread_deduplication`]
+//! is enabled, the virtual machine instead stops any thread whose abstract
+//! state has already been explored by another.
+
+use std::{
+    collections::{hash_map::DefaultHasher, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use crate::vm::{
+    solver::PathCondition,
+    state::{memory::Memory, storage::Storage},
+    thread::VMThread,
+    value::RuntimeBoxedVal,
+};
+
+/// A fingerprint of the abstract state of a thread of execution.
+///
+/// Threads with the same fingerprint are likely to be at the same position,
+/// within the same internal calls, under the same path condition, and with
+/// structurally equal values on their stacks and in the most-recent writes to
+/// their storage and memory. As fingerprints can collide, the states
+/// themselves are compared using [`AbstractState`].
+pub type Fingerprint = u64;
+
+/// Computes the fingerprint of the current state of `thread`.
+#[must_use]
+pub fn fingerprint(thread: &VMThread) -> Fingerprint {
+    let state = thread.state();
+    let mut hasher = DefaultHasher::new();
+    thread.instructions().instruction_pointer().hash(&mut hasher);
+    thread
+        .call_stack()
+        .return_addresses()
+        .for_each(|address| address.hash(&mut hasher));
+    thread.path_condition().fingerprint().hash(&mut hasher);
+    state.stack().values().hash(&mut hasher);
+    state.storage().fingerprint().hash(&mut hasher);
+    state.transient_storage().fingerprint().hash(&mut hasher);
+    state.memory().fingerprint().hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// Computes a fingerprint of the provided `items` that does not depend on the
+/// order in which they are provided.
+///
+/// This allows the contents of unordered containers to be fingerprinted.
+#[must_use]
+pub fn fingerprint_unordered<T: Hash>(items: impl Iterator<Item = T>) -> Fingerprint {
+    let mut item_hashes: Vec<u64> = items
+        .map(|item| {
+            let mut hasher = DefaultHasher::new();
+            item.hash(&mut hasher);
+            hasher.finish()
+        })
+        .collect();
+    item_hashes.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    item_hashes.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// The abstract state of a thread of execution, as used to determine whether
+/// that state has already been explored.
+///
+/// States are hashed by their [`Fingerprint`], but are compared in full, so
+/// two distinct states with colliding fingerprints are never treated as the
+/// same state.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct AbstractState {
+    /// The fingerprint of the state.
+    fingerprint: Fingerprint,
+
+    /// The position of the thread in the instruction stream.
+    instruction_pointer: u32,
+
+    /// The return addresses of the internal calls that the thread is inside.
+    return_addresses: Vec<u32>,
+
+    /// The conditions under which the thread reached its position.
+    path_condition: PathCondition,
+
+    /// The values on the stack of the thread.
+    stack: Vec<RuntimeBoxedVal>,
+
+    /// The most-recent writes to the storage of the thread.
+    storage: Storage,
+
+    /// The most-recent writes to the transient storage of the thread.
+    transient_storage: Storage,
+
+    /// The most-recent writes to the memory of the thread.
+    memory: Memory,
+}
+
+impl AbstractState {
+    /// Captures the current abstract state of `thread`.
+    #[must_use]
+    pub fn new(thread: &VMThread) -> Self {
+        let state = thread.state();
+        let fingerprint = fingerprint(thread);
+        let instruction_pointer = thread.instructions().instruction_pointer();
+        let return_addresses = thread.call_stack().return_addresses().collect();
+        let path_condition = thread.path_condition().clone();
+        let stack = state.stack().values().to_vec();
+        let storage = state.storage().latest();
+        let transient_storage = state.transient_storage().latest();
+        let memory = state.memory().latest();
+        Self {
+            fingerprint,
+            instruction_pointer,
+            return_addresses,
+            path_condition,
+            stack,
+            storage,
+            transient_storage,
+            memory,
+        }
+    }
+
+    /// Gets the fingerprint of the state.
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        self.fingerprint
+    }
+}
+
+impl Hash for AbstractState {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.fingerprint.hash(state);
+    }
+}
+
+/// The tracking of the abstract states that have been explored by the threads
+/// of the virtual machine.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct ExploredStates {
+    /// The states that have been explored.
+    states: HashSet<AbstractState>,
+
+    /// The number of threads that were stopped as they reached a state that
+    /// had already been explored.
+    dropped_threads: usize,
+}
+
+impl ExploredStates {
+    /// Constructs a new tracker in which no states have been explored.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that a thread is exploring the provided `state`, returning
+    /// `false` if that state has already been explored by another thread.
+    ///
+    /// Threads for which this returns `false` are counted as being dropped.
+    pub fn explore(&mut self, state: AbstractState) -> bool {
+        let is_new = self.states.insert(state);
+        if !is_new {
+            self.dropped_threads += 1;
+        }
+
+        is_new
+    }
+
+    /// Creates a new tracker in which the same states have been explored as in
+    /// `self`, but in which no threads have yet been dropped.
+    ///
+    /// This allows the states explored by threads running in parallel to be
+    /// tracked separately and then merged back together.
+    #[must_use]
+    pub fn fork(&self) -> Self {
+        let states = self.states.clone();
+        let dropped_threads = 0;
+        Self {
+            states,
+            dropped_threads,
+        }
+    }
+
+    /// Merges the states explored and the threads dropped in `other` into
+    /// `self`.
+    pub fn merge(&mut self, other: ExploredStates) {
+        self.states.extend(other.states);
+        self.dropped_threads += other.dropped_threads;
+    }
+
+    /// Gets the number of threads that were dropped as their state had already
+    /// been explored.
+    #[must_use]
+    pub fn dropped_threads(&self) -> usize {
+        self.dropped_threads
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        disassembly::InstructionStream,
+        vm::{
+            fingerprint::{fingerprint, fingerprint_unordered, AbstractState, ExploredStates},
+            state::VMState,
+            thread::VMThread,
+            value::{Provenance, RSV, RSVD},
+            Config,
+        },
+    };
+
+    fn new_thread(instructions: &InstructionStream) -> anyhow::Result<VMThread> {
+        let instructions_len = u32::try_from(instructions.len())?;
+        let state = VMState::new_at_start(instructions_len, Config::default());
+        Ok(VMThread::new(state, instructions.new_thread(0)?))
+    }
+
+    #[test]
+    fn unordered_fingerprints_ignore_order() {
+        assert_eq!(
+            fingerprint_unordered([1, 2, 3].iter()),
+            fingerprint_unordered([3, 1, 2].iter())
+        );
+        assert_ne!(
+            fingerprint_unordered([1, 2, 3].iter()),
+            fingerprint_unordered([1, 2].iter())
+        );
+    }
+
+    #[test]
+    fn drops_threads_with_explored_states() -> anyhow::Result<()> {
+        let instructions = InstructionStream::try_from("5b5b00")?;
+        let mut thread = new_thread(&instructions)?;
+        let value = RSV::new_value(0, Provenance::Synthetic);
+        thread.state_mut().stack_mut().push(value.clone())?;
+
+        // A fork with the same state has the same fingerprint, wherever it came from
+        let fork = thread.fork(0);
+        assert_eq!(fingerprint(&thread), fingerprint(&fork));
+
+        // But writing to storage or moving elsewhere changes it
+        let mut written = thread.fork(0);
+        written.state_mut().storage_mut().store(value.clone(), value);
+        assert_ne!(fingerprint(&thread), fingerprint(&written));
+        assert_ne!(fingerprint(&thread), fingerprint(&thread.fork(1)));
+
+        let mut explored = ExploredStates::new();
+        assert!(explored.explore(AbstractState::new(&thread)));
+        assert!(explored.explore(AbstractState::new(&written)));
+        assert!(!explored.explore(AbstractState::new(&fork)));
+        assert_eq!(explored.dropped_threads(), 1);
+
+        Ok(())
+    }
+
+    #[test]
+    fn distinguishes_states_by_path_condition() -> anyhow::Result<()> {
+        let instructions = InstructionStream::try_from("5b5b00")?;
+        let thread = new_thread(&instructions)?;
+        let condition = RSV::new_synthetic(0, RSVD::CallValue);
+        let mut constrained = thread.fork(0);
+        constrained.path_condition_mut().assume(&condition, true);
+        assert_ne!(fingerprint(&thread), fingerprint(&constrained));
+
+        let mut explored = ExploredStates::new();
+        assert!(explored.explore(AbstractState::new(&thread)));
+        assert!(explored.explore(AbstractState::new(&constrained)));
+        assert_eq!(explored.dropped_threads(), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn does_not_drop_distinct_states_with_colliding_fingerprints() -> anyhow::Result<()> {
+        let instructions = InstructionStream::try_from("5b5b00")?;
+        let mut thread = new_thread(&instructions)?;
+        let state = AbstractState::new(&thread);
+        let value = RSV::new_value(0, Provenance::Synthetic);
+        thread.state_mut().stack_mut().push(value)?;
+        let mut colliding = AbstractState::new(&thread);
+        colliding.fingerprint = state.fingerprint();
+
+        let mut explored = ExploredStates::new();
+        assert!(explored.explore(state));
+        assert!(explored.explore(colliding));
+        assert_eq!(explored.dropped_threads(), 0);
+
+        Ok(())
+    }
+}
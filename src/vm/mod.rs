@@ -1,20 +1,44 @@
 //! This module contains the symbolic virtual machine.
 
+pub mod call;
+pub mod coverage;
 pub mod data;
+pub mod fingerprint;
+pub mod scheduler;
+pub mod solver;
 pub mod state;
 pub mod thread;
+pub mod trace;
 pub mod value;
-
-use std::collections::VecDeque;
+pub mod value_set;
+pub mod widening;
+
+use std::{
+    collections::VecDeque,
+    mem,
+    ops::Range,
+    panic,
+    sync::{Arc, OnceLock},
+};
 
 use crate::{
+    cfg::{dispatcher::SelectorTable, loops::BackEdges, ControlFlowGraph},
     constant::{
         BLOCK_GAS_LIMIT,
         DEFAULT_CONDITIONAL_JUMP_PER_TARGET_FORK_LIMIT,
+        DEFAULT_INTERNAL_CALL_SUMMARIES_ENABLED,
         DEFAULT_ITERATIONS_PER_OPCODE,
+        DEFAULT_JUMP_DESTINATION_SET_LIMIT,
+        DEFAULT_LOOP_WIDENING_ENABLED,
+        DEFAULT_MAXIMUM_CALL_DEPTH,
         DEFAULT_MEMORY_SINGLE_OPERATION_MAX_BYTES,
+        DEFAULT_PATH_CONDITION_SOLVING_ENABLED,
         DEFAULT_PERMISSIVE_ERRORS_ENABLED,
+        DEFAULT_STATE_MERGING_ENABLED,
+        DEFAULT_THREAD_DEDUPLICATION_ENABLED,
+        DEFAULT_TRACE_RECORDING_ENABLED,
         DEFAULT_VALUE_SIZE_LIMIT,
+        DEFAULT_WORKER_THREADS,
     },
     disassembly::{ExecutionThread, InstructionStream},
     error::{
@@ -22,12 +46,22 @@ use crate::{
         container::Locatable,
         execution::{Error, Errors, LocatedError, Result},
     },
-    opcode::DynOpcode,
+    extractor::chain::Chain,
+    opcode::{
+        control::{Invalid, JumpDest, JumpI},
+        DynOpcode,
+        Opcode,
+    },
     vm::{
-        data::JumpTargets,
+        call::CallSummaries,
+        coverage::{BranchDirection, Coverage, CoverageReport},
+        data::{JumpTargets, PrunedBranch},
+        fingerprint::{AbstractState, ExploredStates},
+        scheduler::{DynScheduler, SchedulingStrategy},
         state::{stack::LocatedStackHandle, VMState},
         thread::VMThread,
-        value::{known::KnownWord, Provenance, RuntimeBoxedVal, RSV, RSVD},
+        trace::{KillReason, Replay, ThreadId, ThreadPath, Trace, TraceEvent},
+        value::{arena::ValueArena, known::KnownWord, Provenance, RuntimeBoxedVal, RSV, RSVD},
     },
     watchdog::DynWatchdog,
 };
@@ -42,14 +76,39 @@ pub struct VM {
     /// The instructions that are being executed by this virtual machine.
     instructions: InstructionStream,
 
+    /// The static control-flow graph of the instructions.
+    graph: Arc<ControlFlowGraph>,
+
+    /// The functions dispatched to by the contract, as recognised statically
+    /// from its dispatcher.
+    selectors: Arc<SelectorTable>,
+
+    /// The jumps that close loops, as recognised statically from the
+    /// control-flow graph of the contract.
+    back_edges: Arc<BackEdges>,
+
     /// Global tracking for jump target information, allowing global bounding of
     /// how many times each target is conditionally jumped to.
     jump_targets: JumpTargets,
 
+    /// The summaries of internal functions that have returned, shared between
+    /// all threads.
+    call_summaries: CallSummaries,
+
+    /// The instructions and branches that have been executed by any thread.
+    coverage: Coverage,
+
     /// The queue of execution threads that will be taken when executing the
     /// provided `instructions`.
     thread_queue: VecDeque<VMThread>,
 
+    /// The identifier to give to the next thread that is enqueued.
+    next_thread_id: ThreadId,
+
+    /// The strategy used to choose the thread that is executed next whenever
+    /// the current thread dies.
+    scheduler: DynScheduler,
+
     /// The stored states that are no longer associated with a thread of
     /// execution.
     stored_states: Vec<VMState>,
@@ -60,6 +119,13 @@ pub struct VM {
     /// Whether the currently executing thread needs to die.
     current_thread_killed: bool,
 
+    /// Whether the currently executing thread needs to die as it reached a
+    /// state that had already been explored.
+    current_thread_duplicate: bool,
+
+    /// The abstract states that have been explored by any thread.
+    explored_states: ExploredStates,
+
     /// Any errors that were encountered during the course of execution.
     errors: Errors,
 
@@ -69,6 +135,13 @@ pub struct VM {
     /// A watchdog that gets polled at intervals to check whether the analysis
     /// needs to exit.
     watchdog: DynWatchdog,
+
+    /// The trace of execution, if it is being recorded.
+    trace: Option<Trace>,
+
+    /// The path of the single thread that is being executed, if the virtual
+    /// machine is replaying one.
+    replay: Option<Replay>,
 }
 
 impl VM {
@@ -100,29 +173,51 @@ impl VM {
         let initial_state = VMState::new_at_start(instructions_len, config.clone());
         let initial_instruction_thread = instructions.new_thread(0)?;
         let initial_thread = VMThread::new(initial_state, initial_instruction_thread);
+        let graph = Arc::new(ControlFlowGraph::new(&instructions));
+        let selectors = Arc::new(SelectorTable::from_graph(&graph, &instructions));
+        let back_edges = Arc::new(BackEdges::new(&graph));
         let jump_targets = JumpTargets::new(
             instructions.new_thread(0)?,
             config.maximum_forks_per_fork_target,
         );
+        let call_summaries = CallSummaries::new();
+        let coverage = Coverage::new();
+        let scheduler = config.scheduling_strategy.new_scheduler();
 
         // Set up the data for the VM.
         let mut thread_queue = VecDeque::new();
         thread_queue.push_back(initial_thread);
+        let next_thread_id = 1;
         let stored_states = Vec::new();
         let current_thread_killed = false;
+        let current_thread_duplicate = false;
+        let explored_states = ExploredStates::new();
         let errors = Errors::default();
         let builder = ValueBuilder::new(&config);
+        let trace = config.trace_recording.then(Trace::new);
+        let replay = None;
 
         Ok(Self {
             instructions,
+            graph,
+            selectors,
+            back_edges,
             jump_targets,
+            call_summaries,
+            coverage,
             thread_queue,
+            next_thread_id,
+            scheduler,
             stored_states,
             config,
             current_thread_killed,
+            current_thread_duplicate,
+            explored_states,
             errors,
             builder,
             watchdog,
+            trace,
+            replay,
         })
     }
 
@@ -141,10 +236,35 @@ impl VM {
     ///
     /// Note that if this errors, it will still be possible to collect any
     /// stored state information for as far as execution proceeded.
-    #[allow(clippy::missing_panics_doc)] // All panics are guarded by conditions
     pub fn execute(&mut self) -> std::result::Result<(), Errors> {
+        if self.executes_in_parallel() {
+            self.execute_in_parallel()?;
+        } else {
+            self.run(false)?;
+        }
+
+        // If we reach here, we have run out of things to execute.
+        if self.errors.is_empty() {
+            // If no errors have resulted, we can return happily.
+            Ok(())
+        } else {
+            // Otherwise we return the descriptive errors.
+            Err(self.errors.clone())
+        }
+    }
+
+    /// Executes the queued threads one at a time until none remain, or until
+    /// the current thread dies if `until_current_dies` is set.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if a fatal error occurs during execution, while errors
+    /// that allow execution to continue are buffered.
+    #[allow(clippy::missing_panics_doc)] // All panics are guarded by conditions
+    fn run(&mut self, until_current_dies: bool) -> Result<()> {
         let poll_interval = self.watchdog.poll_every();
         let mut counter = 0;
+        let stored_states = self.stored_states.len();
 
         while let Ok(instruction) = self.current_instruction() {
             let instruction_pointer = self
@@ -159,6 +279,11 @@ impl VM {
                 Err(Error::StoppedByWatchdog).locate(instruction_pointer)?;
             }
 
+            // Threads that have converged on this position continue as one, unless
+            // another thread has already been here in the same state
+            self.merge_converging_threads()?;
+            self.deduplicate_current_thread()?;
+
             // We have to mark as being visited beforehand, so this is reflected in any
             // state bifurcations
             let current_thread = self.current_thread_mut()?;
@@ -166,18 +291,62 @@ impl VM {
                 .state_mut()
                 .visited_instructions_mut()
                 .mark_visited(instruction_pointer)?;
-
-            let result = instruction.execute(self);
+            let selector = current_thread.selector();
+            let step = current_thread.steps();
+            let call_depth = current_thread.call_stack().depth();
+            self.coverage.record_instruction(instruction_pointer, selector);
+            let queued_threads = self.thread_queue.len();
+
+            // The stack is only inspected if there is a trace to record it in
+            let stack_before = if self.trace.is_some() {
+                Some(self.stack_summary(instruction.arg_count())?)
+            } else {
+                None
+            };
+
+            // Opcodes that are not available on the configured chain behave as if they
+            // were invalid
+            let result = if self.config.chain().supports(instruction.as_ref()) {
+                instruction.execute(self)
+            } else {
+                Invalid::new(instruction.as_byte()).execute(self)
+            };
             match result {
                 Ok(_) => {
-                    self.current_thread_mut()
-                        .expect(
-                            "We already know a thread is present as we executed an instruction \
-                             from it",
-                        )
-                        .consume_gas(instruction.min_gas_cost());
+                    let gas_cost = self.config.chain().min_gas_cost(instruction.as_ref());
+                    let thread = self.current_thread_mut().expect(
+                        "We already know a thread is present as we executed an instruction from it",
+                    );
+                    thread.consume_gas(gas_cost);
+                    thread.call_stack_mut().record_instruction(instruction.as_ref());
+                    self.scheduler.observe(instruction_pointer);
+
+                    if instruction.as_ref().as_any().is::<JumpI>() {
+                        self.record_branches(instruction_pointer, queued_threads)?;
+                    }
+
+                    // A thread that jumps is stepped past its destination without executing
+                    // it, but the destination has still been reached
+                    if !self.current_thread_killed {
+                        self.reach_destination(instruction_pointer, call_depth)?;
+                    }
+
+                    if let Some((depth, popped)) = stack_before {
+                        let opcode = instruction.as_text_code();
+                        self.trace_execution(instruction_pointer, step, opcode, depth, popped)?;
+                    }
                 }
                 Err(payload) => {
+                    if self.trace.is_some() {
+                        let thread = self.current_thread()?.id();
+                        let message = payload.to_string();
+                        self.record_trace_event(TraceEvent::Errored {
+                            thread,
+                            offset: instruction_pointer,
+                            message,
+                        });
+                    }
+
                     // If execution errored and we are not in permissive error mode, add the error
                     // to the collection of them and then kill the current
                     // thread to continue. If we are in permissive error mode we
@@ -198,22 +367,211 @@ impl VM {
                     self.kill_current_thread();
                 }
             }
+            self.current_thread_mut()?.count_step();
+
+            // When replaying a path, only the thread that follows it is executed
+            if self.replay.is_some() && self.follow_replay(step, queued_threads) {
+                counter += 1;
+                continue;
+            }
 
             // This should never be called if there is nothing to advance to, so if it
             // errors we forward it immediately.
             self.advance()?;
 
             counter += 1;
+
+            // A thread has died exactly when its state has been stored
+            if until_current_dies && self.stored_states.len() > stored_states {
+                break;
+            }
         }
 
-        // If we reach here, we have run out of things to execute.
-        if self.errors.is_empty() {
-            // If no errors have resulted, we can return happily.
-            Ok(())
-        } else {
-            // Otherwise we return the descriptive errors.
-            Err(self.errors.clone())
+        Ok(())
+    }
+
+    /// Checks whether the virtual machine spreads the execution of its threads
+    /// over multiple workers.
+    ///
+    /// Execution is always sequential while a trace is being recorded or a
+    /// thread is being replayed, as both follow the order in which events
+    /// occur on a single worker.
+    fn executes_in_parallel(&self) -> bool {
+        self.config.worker_threads > 1 && self.trace.is_none() && self.replay.is_none()
+    }
+
+    /// Executes the queued threads over the number of workers given by
+    /// [`Config::worker_threads`].
+    ///
+    /// Execution proceeds in rounds. Each round runs every queued thread until
+    /// it dies, with the threads spread over the workers, and then absorbs the
+    /// results of each thread in the order in which the threads were queued.
+    /// The threads that they forked make up the queue for the next round.
+    ///
+    /// Each worker forks against its own copy of the fork budget, and the forks
+    /// that it requests are only granted from the budget of `self` as the
+    /// threads are absorbed. The results of execution thus depend only on the
+    /// order of the queue and the number of workers, and never on the timing
+    /// of the workers.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if a fatal error occurs on any worker, once the round in
+    /// which it occurred has been absorbed.
+    fn execute_in_parallel(&mut self) -> Result<()> {
+        let worker_count = self.config.worker_threads;
+
+        while !self.thread_queue.is_empty() {
+            let mut partitions: Vec<Vec<(usize, VMThread)>> =
+                (0..worker_count).map(|_| Vec::new()).collect();
+            let threads: Vec<VMThread> = self.thread_queue.drain(..).collect();
+            for (index, thread) in threads.into_iter().enumerate() {
+                partitions[index % worker_count].push((index, thread));
+            }
+            let workers: Vec<VM> = (0..worker_count).map(|_| self.new_worker()).collect();
+
+            let (workers, results): (Vec<VM>, Vec<Vec<(usize, ThreadResults)>>) =
+                std::thread::scope(|scope| {
+                    let handles: Vec<_> = workers
+                        .into_iter()
+                        .zip(partitions)
+                        .map(|(mut worker, partition)| {
+                            scope.spawn(move || {
+                                let results = partition
+                                    .into_iter()
+                                    .map(|(index, thread)| (index, worker.execute_thread(thread)))
+                                    .collect::<Vec<_>>();
+                                (worker, results)
+                            })
+                        })
+                        .collect();
+
+                    handles
+                        .into_iter()
+                        .map(|handle| {
+                            handle.join().unwrap_or_else(|payload| panic::resume_unwind(payload))
+                        })
+                        .unzip()
+                });
+            let mut finished: Vec<(usize, ThreadResults)> = results.into_iter().flatten().collect();
+            finished.sort_by_key(|(index, _)| *index);
+
+            let mut fatal_error = None;
+            for (_, results) in finished {
+                if let Err(error) = self.absorb_thread(results) {
+                    fatal_error.get_or_insert(error);
+                }
+            }
+            for worker in workers {
+                self.absorb_worker(worker);
+            }
+            if let Some(error) = fatal_error {
+                return Err(error);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Creates a virtual machine that has no threads, for use as a worker when
+    /// executing in parallel.
+    ///
+    /// The worker starts from copies of the fork budget, call summaries and
+    /// explored states of `self`, which it shares between all of the threads
+    /// that it executes in a round.
+    fn new_worker(&self) -> VM {
+        let instructions = self.instructions.clone();
+        let graph = self.graph.clone();
+        let selectors = self.selectors.clone();
+        let back_edges = self.back_edges.clone();
+        let jump_targets = self.jump_targets.clone();
+        let call_summaries = self.call_summaries.clone();
+        let coverage = Coverage::new();
+        let thread_queue = VecDeque::new();
+        let next_thread_id = self.next_thread_id;
+        let scheduler = self.config.scheduling_strategy.new_scheduler();
+        let stored_states = Vec::new();
+        let config = self.config.clone();
+        let current_thread_killed = false;
+        let current_thread_duplicate = false;
+        let explored_states = self.explored_states.fork();
+        let errors = Errors::default();
+        let builder = self.builder.clone();
+        let watchdog = self.watchdog.clone();
+        let trace = None;
+        let replay = None;
+
+        Self {
+            instructions,
+            graph,
+            selectors,
+            back_edges,
+            jump_targets,
+            call_summaries,
+            coverage,
+            thread_queue,
+            next_thread_id,
+            scheduler,
+            stored_states,
+            config,
+            current_thread_killed,
+            current_thread_duplicate,
+            explored_states,
+            errors,
+            builder,
+            watchdog,
+            trace,
+            replay,
+        }
+    }
+
+    /// Executes the provided `thread` on this worker until it dies, returning
+    /// the results that need to be absorbed in the order of the queue.
+    fn execute_thread(&mut self, thread: VMThread) -> ThreadResults {
+        self.thread_queue.push_back(thread);
+        let outcome = self.run(true);
+        let stored_states = mem::take(&mut self.stored_states);
+        let errors = mem::take(&mut self.errors);
+        let forks = mem::take(&mut self.thread_queue);
+
+        ThreadResults {
+            stored_states,
+            errors,
+            forks,
+            outcome,
+        }
+    }
+
+    /// Absorbs the `results` of executing a single thread on a worker into
+    /// `self`, enqueueing each of the threads that it forked as long as the
+    /// fork limit for its target has not been reached.
+    ///
+    /// Forked threads have not yet executed, so each of them is still at the
+    /// target of the fork that created it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if the thread was stopped by a fatal error.
+    fn absorb_thread(&mut self, results: ThreadResults) -> Result<()> {
+        self.stored_states.extend(results.stored_states);
+        self.errors.add_many_located(results.errors);
+        for thread in results.forks {
+            let target = thread.instructions().instruction_pointer();
+            if self.jump_targets.grant_fork(target)? {
+                self.enqueue_thread(thread);
+            }
         }
+
+        results.outcome
+    }
+
+    /// Absorbs the results that the provided `worker` shares between all of
+    /// the threads that it executed into `self`.
+    fn absorb_worker(&mut self, worker: VM) {
+        self.coverage.merge(worker.coverage);
+        self.jump_targets.merge(&worker.jump_targets);
+        self.call_summaries.merge(worker.call_summaries);
+        self.explored_states.merge(worker.explored_states);
     }
 
     /// Gets the instruction indicated by the current instruction pointer.
@@ -276,22 +634,50 @@ impl VM {
                 .thread_queue
                 .pop_front()
                 .expect("We already know a thread is present");
+            if self.trace.is_some() {
+                let reason = if self.current_thread_duplicate {
+                    KillReason::Duplicate
+                } else if should_die {
+                    KillReason::Halted
+                } else if is_out_of_gas {
+                    KillReason::OutOfGas
+                } else if oob_instruction {
+                    KillReason::EndOfCode
+                } else {
+                    KillReason::IterationLimit
+                };
+                self.record_trace_event(TraceEvent::Killed {
+                    thread: thread.id(),
+                    offset: instruction_pointer,
+                    reason,
+                });
+            }
             self.stored_states.push(thread.into());
 
             // The thread no longer is the current, so whether is was or wasn't killed the
             // next one certainly isn't.
             self.current_thread_killed = false;
+            self.current_thread_duplicate = false;
 
             // If we have run out of gas, mark it as an error.
             if is_out_of_gas {
                 self.errors.add_located(instruction_pointer, Error::GasLimitExceeded);
             }
+
+            // The scheduler then decides which of the remaining threads runs next.
+            self.schedule();
         } else {
-            // And then continue execution on the current thread.
-            self.current_thread_mut()
-                .expect("We already know a thread is present")
-                .instructions_mut()
-                .step();
+            // And then continue execution on the current thread, which may fall through
+            // into the header of a loop.
+            let loop_widening = self.config.loop_widening;
+            let thread = self
+                .current_thread_mut()
+                .expect("We already know a thread is present");
+            thread.instructions_mut().step();
+            if loop_widening {
+                let instruction_pointer = thread.instructions().instruction_pointer();
+                thread.enter_loop(instruction_pointer);
+            }
         }
 
         Ok(())
@@ -355,6 +741,65 @@ impl VM {
         &mut self.jump_targets
     }
 
+    /// Gets the static control-flow graph of the instructions being executed.
+    #[must_use]
+    pub fn control_flow_graph(&self) -> &ControlFlowGraph {
+        &self.graph
+    }
+
+    /// Gets the functions dispatched to by the contract, as recognised
+    /// statically from its dispatcher.
+    #[must_use]
+    pub fn selectors(&self) -> &SelectorTable {
+        &self.selectors
+    }
+
+    /// Gets the summaries of internal functions that have returned.
+    #[must_use]
+    pub fn call_summaries(&self) -> &CallSummaries {
+        &self.call_summaries
+    }
+
+    /// Gets the summaries of internal functions that have returned.
+    #[must_use]
+    pub fn call_summaries_mut(&mut self) -> &mut CallSummaries {
+        &mut self.call_summaries
+    }
+
+    /// Gets the instructions and branches that have been executed by any
+    /// thread.
+    #[must_use]
+    pub fn coverage(&self) -> &Coverage {
+        &self.coverage
+    }
+
+    /// Records the directions in which execution continued from the
+    /// conditional jump at `instruction_pointer` that was just executed, given
+    /// that there were `queued_threads` before it was.
+    ///
+    /// The jump was taken if it forked new threads or moved the current thread,
+    /// and it fell through if the current thread remains at the jump to be
+    /// stepped past it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if there is no current thread.
+    fn record_branches(&mut self, instruction_pointer: u32, queued_threads: usize) -> Result<()> {
+        let forked = self.thread_queue.len() > queued_threads;
+        let moved = self.instruction_pointer()? != instruction_pointer;
+        let continues = !self.current_thread_killed;
+
+        let coverage = &mut self.coverage;
+        if forked || (moved && continues) {
+            coverage.record_branch(instruction_pointer, BranchDirection::Jumped);
+        }
+        if !moved && continues {
+            coverage.record_branch(instruction_pointer, BranchDirection::FellThrough);
+        }
+
+        Ok(())
+    }
+
     /// Gets the current value of the instruction pointer for the thread that is
     /// being executed.
     ///
@@ -390,24 +835,320 @@ impl VM {
     }
 
     /// Adds a virtual machine thread to the queue of threads to be executed.
-    pub fn enqueue_thread(&mut self, thread: VMThread) {
+    ///
+    /// The thread is given a new identifier as it is enqueued.
+    pub fn enqueue_thread(&mut self, mut thread: VMThread) {
+        thread.set_id(self.next_thread_id);
+        self.next_thread_id += 1;
         self.thread_queue.push_back(thread);
     }
 
+    /// Moves the thread chosen by the scheduler to the front of the queue,
+    /// making it the currently executing thread.
+    fn schedule(&mut self) {
+        if self.thread_queue.is_empty() {
+            return;
+        }
+
+        let index = self.scheduler.select(&self.thread_queue);
+        if let Some(thread) = self.thread_queue.remove(index) {
+            self.thread_queue.push_front(thread);
+        }
+    }
+
+    /// Gets the scheduler that chooses the thread to execute whenever the
+    /// current thread dies.
+    #[must_use]
+    pub fn scheduler(&self) -> &DynScheduler {
+        &self.scheduler
+    }
+
+    /// Replaces the scheduler that chooses the thread to execute whenever the
+    /// current thread dies, allowing strategies beyond the built-in
+    /// [`SchedulingStrategy`]s to be used.
+    pub fn set_scheduler(&mut self, scheduler: DynScheduler) {
+        self.scheduler = scheduler;
+    }
+
     /// Forks the currently executing thread to `jump_target`, maintaining the
     /// state at the moment of forking in the new thread.
     ///
-    /// The new thread is then added to the thread queue to await execution.
+    /// The new thread is then added to the thread queue to await execution, and
+    /// a reference to it is returned.
     ///
     /// # Errors
     ///
     /// Returns [`Err`] if the thread cannot be forked.
-    pub fn fork_current_thread(&mut self, jump_target: u32) -> Result<()> {
+    #[allow(clippy::missing_panics_doc)] // The thread is always enqueued before it is returned
+    pub fn fork_current_thread(&mut self, jump_target: u32) -> Result<&mut VMThread> {
         // It is a programmer error to ask for a thread to be forked when none exists,
         // so we forward the error immediately.
-        let new_thread = self.current_thread_mut()?.fork(jump_target);
+        let instruction_pointer = self.instruction_pointer()?;
+        let mut new_thread = self.current_thread_mut()?.fork(jump_target);
+
+        // A thread forked along a back-edge has completed an iteration of a loop
+        if self.config.loop_widening {
+            if self.closes_loop(instruction_pointer, jump_target) {
+                new_thread.complete_loop_iteration(jump_target);
+            } else {
+                new_thread.enter_loop(jump_target);
+            }
+        }
         self.enqueue_thread(new_thread);
 
+        if self.trace.is_some() {
+            let parent = self.current_thread()?;
+            let event = TraceEvent::Forked {
+                thread: parent.id(),
+                step:   parent.steps(),
+                offset: instruction_pointer,
+                child:  self.next_thread_id - 1,
+                target: jump_target,
+            };
+            self.record_trace_event(event);
+        }
+
+        Ok(self.thread_queue.back_mut().expect("We have just enqueued a thread"))
+    }
+
+    /// Handles the current thread reaching its next instruction after executing
+    /// the instruction at `instruction_pointer` from within `call_depth`
+    /// internal calls.
+    ///
+    /// If the thread has moved anywhere other than to the next instruction in
+    /// sequence, the destination is recorded as covered, the loops that the
+    /// thread is inside are tracked, and the thread is merged or deduplicated
+    /// with any others there.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if there is no current thread.
+    fn reach_destination(&mut self, instruction_pointer: u32, call_depth: usize) -> Result<()> {
+        let next_instruction_pointer = self.instruction_pointer()?;
+        if next_instruction_pointer == instruction_pointer {
+            return Ok(());
+        }
+
+        let selector = self.current_thread()?.selector();
+        self.coverage.record_instruction(next_instruction_pointer, selector);
+        self.track_loops(instruction_pointer, next_instruction_pointer, call_depth)?;
+        self.merge_converging_threads()?;
+        self.deduplicate_current_thread()
+    }
+
+    /// Tracks the loops that the current thread is inside if
+    /// [`Config::loop_widening`] is enabled, given that it has just jumped from
+    /// `jump` to `destination` from within `call_depth` internal calls.
+    ///
+    /// The thread has completed an iteration of the loop at `destination` if
+    /// the jump closes that loop without entering or leaving an internal call.
+    /// Otherwise, it is entering any loop at `destination` afresh.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if there is no current thread.
+    fn track_loops(&mut self, jump: u32, destination: u32, call_depth: usize) -> Result<()> {
+        if !self.config.loop_widening {
+            return Ok(());
+        }
+        let closes_loop = self.closes_loop(jump, destination);
+        let thread = self.current_thread_mut()?;
+        if closes_loop && thread.call_stack().depth() == call_depth {
+            thread.complete_loop_iteration(destination);
+        } else {
+            thread.enter_loop(destination);
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether the jump at `jump` to `header` closes the loop with its
+    /// header at `header`.
+    ///
+    /// Jumps are classified using the back-edges of the control-flow graph
+    /// where possible, which tells loops apart from calls to internal functions
+    /// at lower offsets. Any other jump closes a loop if it jumps backwards.
+    fn closes_loop(&self, jump: u32, header: u32) -> bool {
+        header < jump && self.back_edges.closes_loop(jump, header).unwrap_or(true)
+    }
+
+    /// Merges any queued threads that have converged on the position of the
+    /// current thread into the current thread, if [`Config::state_merging`] is
+    /// enabled and that position is a `JUMPDEST`.
+    ///
+    /// Threads are not merged when a single thread is being replayed, as the
+    /// replay must follow the original path of the thread exactly.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if there is no current thread.
+    fn merge_converging_threads(&mut self) -> Result<()> {
+        if !self.config.state_merging || self.replay.is_some() || self.current_thread_killed {
+            return Ok(());
+        }
+        let instruction = self.current_instruction()?;
+        if !instruction.as_ref().as_any().is::<JumpDest>() {
+            return Ok(());
+        }
+
+        let mut index = 1;
+        while index < self.thread_queue.len() {
+            if !self.thread_queue[0].can_merge(&self.thread_queue[index]) {
+                index += 1;
+                continue;
+            }
+
+            let other = self.thread_queue.remove(index).expect("The index is in bounds");
+            if self.trace.is_some() {
+                let current = self.current_thread()?;
+                let event = TraceEvent::Merged {
+                    thread: current.id(),
+                    offset: current.instructions().instruction_pointer(),
+                    merged: other.id(),
+                };
+                self.record_trace_event(event);
+            }
+            self.current_thread_mut()?.merge(other);
+        }
+
+        Ok(())
+    }
+
+    /// Stops the current thread if [`Config::thread_deduplication`] is enabled,
+    /// it is at a `JUMPDEST`, and its state has already been explored by
+    /// another thread.
+    ///
+    /// The stopped thread is kept as any other thread that dies, so none of the
+    /// values that it computed before reaching the explored state are lost.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if there is no current thread.
+    fn deduplicate_current_thread(&mut self) -> Result<()> {
+        if !self.config.thread_deduplication || self.replay.is_some() || self.current_thread_killed
+        {
+            return Ok(());
+        }
+        let instruction = self.current_instruction()?;
+        if !instruction.as_ref().as_any().is::<JumpDest>() {
+            return Ok(());
+        }
+
+        let state = AbstractState::new(self.current_thread()?);
+        if !self.explored_states.explore(state) {
+            self.current_thread_duplicate = true;
+            self.kill_current_thread();
+        }
+
+        Ok(())
+    }
+
+    /// Gets the number of threads that were stopped as they reached a state
+    /// that had already been explored by another thread.
+    #[must_use]
+    pub fn dropped_thread_count(&self) -> usize {
+        self.explored_states.dropped_threads()
+    }
+
+    /// Restricts execution to the single thread that follows `path`, discarding
+    /// any other threads as they are forked.
+    ///
+    /// This is used to [`trace::replay`] a thread from a recorded [`Trace`].
+    pub fn follow_path(&mut self, path: ThreadPath) {
+        self.replay = Some(Replay::new(path));
+    }
+
+    /// Discards the threads that were forked by the instruction executed at
+    /// `step` by the current thread, given that there were `queued_threads`
+    /// before it was executed, unless one of them continues the path being
+    /// replayed.
+    ///
+    /// If one does, it replaces the current thread, and `true` is returned to
+    /// indicate that it should not be advanced before it is executed.
+    fn follow_replay(&mut self, step: usize, queued_threads: usize) -> bool {
+        let Some(replay) = &mut self.replay else {
+            return false;
+        };
+        let forked: Vec<_> = self.thread_queue.drain(queued_threads..).collect();
+        let Some(fork) = replay.next_fork().filter(|fork| fork.step == step) else {
+            return false;
+        };
+        let Some(child) = forked
+            .into_iter()
+            .find(|thread| thread.instructions().instruction_pointer() == fork.target)
+        else {
+            return false;
+        };
+
+        replay.follow_fork();
+        self.thread_queue.pop_front();
+        self.thread_queue.push_front(child);
+        self.current_thread_killed = false;
+
+        true
+    }
+
+    /// Gets the trace of execution, if it is being recorded.
+    #[must_use]
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// Records `event` in the trace of execution, if it is being recorded.
+    fn record_trace_event(&mut self, event: TraceEvent) {
+        if let Some(trace) = &mut self.trace {
+            trace.record(event);
+        }
+    }
+
+    /// Gets the depth of the stack of the current thread, along with
+    /// summaries of up to `count` values from the top of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if there is no current thread.
+    fn stack_summary(&self, count: usize) -> Result<(usize, Vec<String>)> {
+        let stack = self.current_thread()?.state().stack();
+        let depth = stack.depth();
+        let summaries = (0..count.min(depth))
+            .filter_map(|index| u32::try_from(index).ok())
+            .filter_map(|index| stack.read(index).ok())
+            .map(trace::summarize)
+            .collect();
+
+        Ok((depth, summaries))
+    }
+
+    /// Records that the current thread executed the instruction `opcode` at
+    /// `instruction_pointer` as its `step`th instruction, taking the `popped`
+    /// values from a stack of the provided `depth`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Err`] if there is no current thread.
+    fn trace_execution(
+        &mut self,
+        instruction_pointer: u32,
+        step: usize,
+        opcode: String,
+        depth: usize,
+        popped: Vec<String>,
+    ) -> Result<()> {
+        let remaining_depth = depth - popped.len();
+        let thread = self.current_thread()?;
+        let pushed_count = thread.state().stack().depth().saturating_sub(remaining_depth);
+        let (_, pushed) = self.stack_summary(pushed_count)?;
+        let event = TraceEvent::Executed {
+            thread: thread.id(),
+            step,
+            offset: instruction_pointer,
+            opcode,
+            popped,
+            pushed,
+            gas: thread.gas_usage(),
+        };
+        self.record_trace_event(event);
+
         Ok(())
     }
 
@@ -514,14 +1255,37 @@ impl VM {
     /// the analysis in the [`crate::tc::TypeChecker`].
     #[must_use]
     pub fn consume(self) -> ExecutionResult {
+        let pruned_branches = self.jump_targets.pruned_branches();
+        let coverage = self.coverage.report(&self.instructions);
         ExecutionResult {
             instructions: self.instructions,
-            states:       self.stored_states,
-            errors:       self.errors,
+            states: self.stored_states,
+            errors: self.errors,
+            pruned_branches,
+            coverage,
+            trace: self.trace,
+            dropped_threads: self.explored_states.dropped_threads(),
+            arena: self.builder.arena().clone(),
         }
     }
 }
 
+/// The results of executing a single thread on a worker, which are absorbed
+/// in the order in which the threads were queued.
+struct ThreadResults {
+    /// The states of the threads that died.
+    stored_states: Vec<VMState>,
+
+    /// The errors that were encountered during execution.
+    errors: Errors,
+
+    /// The threads that were forked, but not yet executed.
+    forks: VecDeque<VMThread>,
+
+    /// Whether execution stopped without a fatal error.
+    outcome: Result<()>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ExecutionResult {
     /// The instructions over which the execution result was gathered.
@@ -538,6 +1302,25 @@ pub struct ExecutionResult {
     /// themselves before continuing to determine if the data is useful to use
     /// as the basis for continued analysis.
     pub errors: Errors,
+
+    /// The branches of conditional jumps in `instructions` that were not
+    /// explored as their conditions were concretely known.
+    pub pruned_branches: Vec<PrunedBranch>,
+
+    /// The parts of `instructions` that were explored during execution.
+    pub coverage: CoverageReport,
+
+    /// The trace of execution, if [`Config::trace_recording`] was enabled.
+    pub trace: Option<Trace>,
+
+    /// The number of threads that were stopped as they reached a state that
+    /// had already been explored, if [`Config::thread_deduplication`] was
+    /// enabled.
+    pub dropped_threads: usize,
+
+    /// The arena in which the values in `states` were interned during
+    /// execution.
+    pub arena: ValueArena,
 }
 
 impl ExecutionResult {
@@ -546,12 +1329,38 @@ impl ExecutionResult {
     pub fn all_values(self) -> Vec<RuntimeBoxedVal> {
         self.states.into_iter().flat_map(VMState::all_values).collect()
     }
-}
 
-/// The configuration for the virtual machine instance.
-#[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Config {
-    /// The maximum amount of gas that the virtual machine can consume.
+    /// Merges the states, errors, and dropped thread counts from `other` into
+    /// `self`, retaining the instructions, pruned branches, coverage, and arena
+    /// of `self`.
+    ///
+    /// This is used to combine the evidence gathered from executing the
+    /// constructor of a contract with that from executing its runtime code.
+    pub fn merge(&mut self, other: ExecutionResult) {
+        self.states.extend(other.states);
+        self.errors.add_many_located(other.errors);
+        self.dropped_threads += other.dropped_threads;
+    }
+
+    /// Gets the range of the code that was returned as runtime code by any of
+    /// the states in this execution result.
+    ///
+    /// Where multiple states return different code, the largest range is
+    /// chosen.
+    #[must_use]
+    pub fn returned_code(&self) -> Option<Range<usize>> {
+        self.states
+            .iter()
+            .filter_map(VMState::returned_code)
+            .max_by_key(|range| (range.len(), std::cmp::Reverse(range.start)))
+    }
+}
+
+/// The configuration for the virtual machine instance.
+#[allow(clippy::struct_excessive_bools)] // Each of the flags is independent of the others
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Config {
+    /// The maximum amount of gas that the virtual machine can consume.
     ///
     /// Note that this limit is applied optimistically, assuming that every
     /// operation takes the minimal amount of gas it can. In reality, execution
@@ -592,6 +1401,137 @@ pub struct Config {
     /// Whether to continue execution when non critical errors happen during
     /// execution.
     pub permissive_errors: bool,
+
+    /// The maximum number of words that a jump destination which is not
+    /// concretely known can take for the virtual machine to follow the jump
+    /// to each of them.
+    ///
+    /// Defaults to [`DEFAULT_JUMP_DESTINATION_SET_LIMIT`].
+    pub jump_destination_set_limit: usize,
+
+    /// The maximum number of nested internal function calls that the virtual
+    /// machine tracks for each thread.
+    ///
+    /// Instructions visited inside a tracked call do not count towards the
+    /// iteration limit of the caller, so the body of an internal function is
+    /// executed afresh from each of its call sites. Calls nested more deeply
+    /// than this are treated as ordinary jumps, and setting it to zero disables
+    /// the tracking of calls entirely.
+    ///
+    /// Defaults to [`DEFAULT_MAXIMUM_CALL_DEPTH`].
+    pub maximum_call_depth: usize,
+
+    /// Whether to summarise internal functions that only manipulate the stack,
+    /// reusing the values returned by the first call with a given shape
+    /// instead of executing the function again.
+    ///
+    /// Functions that branch on a value that is not known are never
+    /// summarised, and summaries are only made when calls are tracked (see
+    /// [`Self::maximum_call_depth`]).
+    ///
+    /// Defaults to [`DEFAULT_INTERNAL_CALL_SUMMARIES_ENABLED`].
+    pub internal_call_summaries: bool,
+
+    /// Whether to track the conditions under which each thread reaches its
+    /// current position, and use them to skip the branches of conditional
+    /// jumps that the thread can never take.
+    ///
+    /// The conditions are solved in an interval domain (see
+    /// [`solver::PathCondition`]) that bounds each value compared against a
+    /// constant, rather than by a bit-vector solver. Conditions that relate
+    /// two non-constant values, or that use bitwise and arithmetic operations
+    /// on their operands, are ignored, and so never cause a branch to be
+    /// skipped.
+    ///
+    /// Defaults to [`DEFAULT_PATH_CONDITION_SOLVING_ENABLED`].
+    pub path_condition_solving: bool,
+
+    /// The strategy used to choose which of the queued threads of execution to
+    /// run whenever the current thread dies.
+    ///
+    /// Defaults to [`SchedulingStrategy::BreadthFirst`].
+    pub scheduling_strategy: SchedulingStrategy,
+
+    /// Whether to merge threads of execution that converge on the same
+    /// `JUMPDEST` with stacks of the same depth.
+    ///
+    /// The merged thread holds a choice between the values of each thread
+    /// wherever they differ, so that paths that re-converge are only executed
+    /// once from that point onwards. This prevents the number of threads from
+    /// growing exponentially on large contracts, at the cost of precision in
+    /// the path condition of the merged thread.
+    ///
+    /// As each thread runs until it stops, a thread is only merged with the
+    /// threads that are still waiting to execute at the `JUMPDEST` it reaches,
+    /// such as those forked to the end of a conditional block.
+    ///
+    /// Defaults to [`DEFAULT_STATE_MERGING_ENABLED`].
+    pub state_merging: bool,
+
+    /// Whether to stop threads that reach a `JUMPDEST` in an abstract state
+    /// that has already been explored by another thread.
+    ///
+    /// The abstract state of a thread is given by its position, its stack, and
+    /// the most-recent writes to its storage and memory. The number of threads
+    /// stopped in this way is reported in [`ExecutionResult::dropped_threads`].
+    ///
+    /// Defaults to [`DEFAULT_THREAD_DEDUPLICATION_ENABLED`].
+    pub thread_deduplication: bool,
+
+    /// Whether to widen the values that are carried between the iterations of
+    /// loops into the induction-variable form `base + i * stride`.
+    ///
+    /// A thread that jumps backwards to a `JUMPDEST` is treated as completing
+    /// an iteration of a loop, unless the jump enters or leaves an internal
+    /// call, or the control-flow graph shows that it is not a back-edge of
+    /// a loop. Values on its stack that grew by a known stride over the
+    /// iteration are then expressed in terms of a [`RSVD::LoopCounter`] for
+    /// the loop, rather than nesting further with each unrolled iteration.
+    /// See [`widening`] for more detail.
+    ///
+    /// Defaults to [`DEFAULT_LOOP_WIDENING_ENABLED`].
+    pub loop_widening: bool,
+
+    /// The number of workers over which the virtual machine spreads the
+    /// execution of its threads, each of which runs on its own OS thread.
+    ///
+    /// With more than one worker, the threads are executed breadth-first in
+    /// rounds, so [`Self::scheduling_strategy`] has no effect. Threads running
+    /// in the same round are only merged with the threads that they forked
+    /// themselves, and are only deduplicated against the threads that ran
+    /// before them on the same worker. Execution is always sequential while a
+    /// trace is being recorded.
+    ///
+    /// The workers do not share a single fork budget. Each one forks against
+    /// its own copy of the budget for the round, and the forks that they
+    /// request are then granted from the budget of the virtual machine in the
+    /// order of the queue. This keeps the results independent of how the
+    /// workers are scheduled, which a budget shared between them while they
+    /// run could not.
+    ///
+    /// Defaults to [`DEFAULT_WORKER_THREADS`].
+    pub worker_threads: usize,
+
+    /// Whether to record a [`Trace`] of the events that occur during
+    /// execution, such as the instructions executed by each thread and the
+    /// values that they consume and produce.
+    ///
+    /// Recording a trace slows execution considerably, so it is intended for
+    /// debugging only.
+    ///
+    /// Defaults to [`DEFAULT_TRACE_RECORDING_ENABLED`].
+    pub trace_recording: bool,
+
+    /// The chain whose execution semantics the virtual machine follows, if it
+    /// has been explicitly configured.
+    ///
+    /// This determines which opcodes are available and their gas costs. When
+    /// it is not set, the [`crate::extractor::Extractor`] uses the chain of the
+    /// contract being analysed, and the virtual machine otherwise follows the
+    /// latest version of Ethereum main-net (see [`Self::chain`]).
+    ///
+    /// Defaults to [`None`].
+    pub chain: Option<Chain>,
 }
 
 impl Config {
@@ -636,6 +1576,95 @@ impl Config {
         self.permissive_errors = value;
         self
     }
+
+    /// Sets the jump destination set limit configuration parameter to `value`.
+    #[must_use]
+    pub fn with_jump_destination_set_limit(mut self, value: usize) -> Self {
+        self.jump_destination_set_limit = value;
+        self
+    }
+
+    /// Sets the `maximum_call_depth` config parameter to `value`.
+    #[must_use]
+    pub fn with_max_call_depth(mut self, value: usize) -> Self {
+        self.maximum_call_depth = value;
+        self
+    }
+
+    /// Sets the internal call summaries configuration parameter to `value`.
+    #[must_use]
+    pub fn with_internal_call_summaries(mut self, value: bool) -> Self {
+        self.internal_call_summaries = value;
+        self
+    }
+
+    /// Sets the path condition solving configuration parameter to `value`.
+    #[must_use]
+    pub fn with_path_condition_solving(mut self, value: bool) -> Self {
+        self.path_condition_solving = value;
+        self
+    }
+
+    /// Sets the scheduling strategy configuration parameter to `value`.
+    #[must_use]
+    pub fn with_scheduling_strategy(mut self, value: SchedulingStrategy) -> Self {
+        self.scheduling_strategy = value;
+        self
+    }
+
+    /// Sets the state merging configuration parameter to `value`.
+    #[must_use]
+    pub fn with_state_merging(mut self, value: bool) -> Self {
+        self.state_merging = value;
+        self
+    }
+
+    /// Sets the thread deduplication configuration parameter to `value`.
+    #[must_use]
+    pub fn with_thread_deduplication(mut self, value: bool) -> Self {
+        self.thread_deduplication = value;
+        self
+    }
+
+    /// Sets the loop widening configuration parameter to `value`.
+    #[must_use]
+    pub fn with_loop_widening(mut self, value: bool) -> Self {
+        self.loop_widening = value;
+        self
+    }
+
+    /// Sets the `worker_threads` config parameter to `value`.
+    #[must_use]
+    pub fn with_worker_threads(mut self, value: usize) -> Self {
+        self.worker_threads = value;
+        self
+    }
+
+    /// Sets the trace recording configuration parameter to `value`.
+    #[must_use]
+    pub fn with_trace_recording(mut self, value: bool) -> Self {
+        self.trace_recording = value;
+        self
+    }
+
+    /// Sets the chain configuration parameter to `value`.
+    #[must_use]
+    pub fn with_chain(mut self, value: Chain) -> Self {
+        self.chain = Some(value);
+        self
+    }
+
+    /// Gets the chain whose execution semantics the virtual machine follows.
+    ///
+    /// This is the explicitly configured chain if there is one, and the latest
+    /// version of Ethereum main-net otherwise.
+    #[must_use]
+    pub fn chain(&self) -> &Chain {
+        static DEFAULT_CHAIN: OnceLock<Chain> = OnceLock::new();
+        self.chain
+            .as_ref()
+            .unwrap_or_else(|| DEFAULT_CHAIN.get_or_init(Chain::default))
+    }
 }
 
 impl Default for Config {
@@ -646,6 +1675,17 @@ impl Default for Config {
         let value_size_limit = DEFAULT_VALUE_SIZE_LIMIT;
         let single_memory_operation_size_limit = DEFAULT_MEMORY_SINGLE_OPERATION_MAX_BYTES;
         let permissive_errors = DEFAULT_PERMISSIVE_ERRORS_ENABLED;
+        let jump_destination_set_limit = DEFAULT_JUMP_DESTINATION_SET_LIMIT;
+        let maximum_call_depth = DEFAULT_MAXIMUM_CALL_DEPTH;
+        let internal_call_summaries = DEFAULT_INTERNAL_CALL_SUMMARIES_ENABLED;
+        let path_condition_solving = DEFAULT_PATH_CONDITION_SOLVING_ENABLED;
+        let scheduling_strategy = SchedulingStrategy::default();
+        let state_merging = DEFAULT_STATE_MERGING_ENABLED;
+        let thread_deduplication = DEFAULT_THREAD_DEDUPLICATION_ENABLED;
+        let loop_widening = DEFAULT_LOOP_WIDENING_ENABLED;
+        let worker_threads = DEFAULT_WORKER_THREADS;
+        let trace_recording = DEFAULT_TRACE_RECORDING_ENABLED;
+        let chain = None;
         Self {
             gas_limit,
             maximum_iterations_per_opcode,
@@ -653,6 +1693,17 @@ impl Default for Config {
             value_size_limit,
             single_memory_operation_size_limit,
             permissive_errors,
+            jump_destination_set_limit,
+            maximum_call_depth,
+            internal_call_summaries,
+            path_condition_solving,
+            scheduling_strategy,
+            state_merging,
+            thread_deduplication,
+            loop_widening,
+            worker_threads,
+            trace_recording,
+            chain,
         }
     }
 }
@@ -663,9 +1714,14 @@ impl Default for Config {
 /// It should be used for building all values that are constructed during the
 /// course of execution as it ensures that size and structure invariants are
 /// enforced for those values.
+///
+/// Every value it builds is interned in its [`ValueArena`], so identical values
+/// built by different threads of execution share the same structural node and
+/// compare in constant time.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct ValueBuilder {
     config: Config,
+    arena:  ValueArena,
 }
 
 impl ValueBuilder {
@@ -673,18 +1729,26 @@ impl ValueBuilder {
     #[must_use]
     pub fn new(config: &Config) -> Self {
         let config = config.clone();
-        Self { config }
+        let arena = ValueArena::new();
+        Self { config, arena }
+    }
+
+    /// Gets the arena in which the values built by this builder are interned.
+    #[must_use]
+    pub fn arena(&self) -> &ValueArena {
+        &self.arena
     }
 
     /// Constructs a new bare value at `instruction_pointer` with the specified
     /// `provenance`.
     ///
-    /// This function exists to make it easier to construct values with the
-    /// specified [`Config::value_size_limit`] without the need to pass it every
-    /// time to the raw constructor.
+    /// The bare values built at the same location with the same provenance
+    /// share their identity, so that threads executing the same instruction
+    /// produce equal values, unless that provenance reads state that can change
+    /// between executions of the instruction. See [`ValueArena::value`].
     #[must_use]
     pub fn value(&self, instruction_pointer: u32, provenance: Provenance) -> RuntimeBoxedVal {
-        RSV::new_value(instruction_pointer, provenance)
+        self.arena.value(instruction_pointer, provenance)
     }
 
     /// Constructs a new `SymbolicValue` representing the operation performed at
@@ -701,12 +1765,13 @@ impl ValueBuilder {
         data: RSVD,
         provenance: Provenance,
     ) -> RuntimeBoxedVal {
-        RSV::new(
+        let value = RSV::new(
             instruction_pointer,
             data,
             provenance,
             Some(self.config.value_size_limit),
-        )
+        );
+        self.arena.intern(value)
     }
 
     /// Constructs a new `SymbolicValue` representing the operation performed at
@@ -718,11 +1783,12 @@ impl ValueBuilder {
     /// time to the raw constructor.
     #[must_use]
     pub fn symbolic_exec(&self, instruction_pointer: u32, data: RSVD) -> RuntimeBoxedVal {
-        RSV::new_from_execution(
+        let value = RSV::new_from_execution(
             instruction_pointer,
             data,
             Some(self.config.value_size_limit),
-        )
+        );
+        self.arena.intern(value)
     }
 
     /// Constructs a new `SymbolicValue` representing a known value of
@@ -739,12 +1805,13 @@ impl ValueBuilder {
         value_data: KnownWord,
         provenance: Provenance,
     ) -> RuntimeBoxedVal {
-        RSV::new_known_value(
+        let value = RSV::new_known_value(
             instruction_pointer,
             value_data,
             provenance,
             Some(self.config.value_size_limit),
-        )
+        );
+        self.arena.intern(value)
     }
 
     /// Constructs a new `SymbolicValue` representing a known value of
@@ -756,27 +1823,69 @@ impl ValueBuilder {
     /// time to the raw constructor.
     #[must_use]
     pub fn known_exec(&self, instruction_pointer: u32, value_data: KnownWord) -> RuntimeBoxedVal {
-        RSV::new_known_value(
+        let value = RSV::new_known_value(
             instruction_pointer,
             value_data,
             Provenance::Execution,
             Some(self.config.value_size_limit),
-        )
+        );
+        self.arena.intern(value)
+    }
+}
+
+#[cfg(test)]
+mod test_util {
+    use crate::vm::value::{known::KnownWord, Provenance, RuntimeBoxedVal, RSV, RSVD};
+
+    /// Constructs a synthetic known value of `value`.
+    pub fn known(value: u32) -> RuntimeBoxedVal {
+        RSV::new_known_value(0, KnownWord::from_le(value), Provenance::Synthetic, None)
+    }
+
+    /// Constructs a synthetic value with the provided `data`.
+    pub fn synthetic(data: RSVD) -> RuntimeBoxedVal {
+        RSV::new_synthetic(0, data)
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
     use crate::{
         bytecode,
         disassembly::InstructionStream,
         error::execution::{Error, LocatedError},
+        extractor::chain::{version::EthereumVersion, Chain},
         opcode::{
-            control::{Invalid, JumpDest, JumpI, Return},
-            logic::IsZero,
-            memory::{CallDataSize, MStore, PushN, SStore},
+            arithmetic::Add,
+            control::{Call, Invalid, Jump, JumpDest, JumpI, Return, Stop},
+            environment::{CallValue, Caller, Gas},
+            logic::{IsZero, Shr, Xor},
+            memory::{
+                CallDataLoad,
+                CallDataSize,
+                DupN,
+                MStore,
+                Pop,
+                Push0,
+                PushN,
+                ReturnDataSize,
+                SStore,
+                SwapN,
+            },
+        },
+        vm::{
+            coverage::{BranchDirection, UntakenBranch},
+            data::PrunedBranch,
+            scheduler::SchedulingStrategy,
+            state::VMState,
+            trace::{self, KillReason, Trace, TraceEvent},
+            value::{known::KnownWord, Provenance, RSV, RSVD},
+            Config,
+            ValueBuilder,
+            VM,
         },
-        vm::{Config, VM},
         watchdog::LazyWatchdog,
     };
 
@@ -792,6 +1901,38 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn vm_treats_opcodes_unavailable_on_chain_as_invalid() -> anyhow::Result<()> {
+        // `PUSH0` is only available from Shanghai onwards
+        let bytes = bytecode![
+            Push0,  // Value to store
+            Push0,  // Key under which to store it
+            SStore  // Storage
+        ];
+        let paris = Chain::Ethereum {
+            version: EthereumVersion::Paris,
+        };
+
+        // On Paris the thread should stop at the first `PUSH0`
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_chain(paris);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let mut data = vm.consume();
+        assert_eq!(data.states.len(), 1);
+        assert_eq!(data.states[0].storage_mut().entry_count(), 0);
+
+        // But on the latest version execution should proceed as normal
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let mut data = vm.consume();
+        assert_eq!(data.states.len(), 1);
+        assert_eq!(data.states[0].storage_mut().entry_count(), 1);
+
+        Ok(())
+    }
+
     #[test]
     fn vm_executes_on_valid_bytecode() -> anyhow::Result<()> {
         // Create the instruction stream for this VM
@@ -845,6 +1986,764 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn vm_follows_only_feasible_branch_of_known_condition() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            PushN::new(1, vec![0x01])?, // The condition, which is always true
+            PushN::new(1, vec![0x06])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Jump as the condition is true
+            Stop,                       // Never executed
+            JumpDest,                   // The destination for the jump
+            Stop                        // Stop executing this thread
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+
+        // Prepare and execute the vm itself
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let data = vm.consume();
+
+        // We should have seen only one thread, with the fall-through pruned
+        assert_eq!(data.states.len(), 1);
+        assert_eq!(
+            data.pruned_branches,
+            vec![PrunedBranch {
+                jump:        4,
+                destination: 5,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_reports_coverage_of_execution() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            PushN::new(1, vec![0x01])?, // The condition, which is always true
+            PushN::new(1, vec![0x08])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Jump as the condition is true
+            Push0,                      // Never executed
+            Push0,                      // Never executed
+            SStore,                     // Never executed
+            JumpDest,                   // The destination for the jump
+            Stop                        // Stop executing this thread
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+
+        // Prepare and execute the vm itself
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let coverage = vm.consume().coverage;
+
+        // Only the instructions on the taken branch were executed
+        assert_eq!(coverage.instruction_count, 8);
+        assert_eq!(
+            coverage.visited_instructions.into_iter().collect::<Vec<_>>(),
+            vec![0, 2, 4, 8, 9]
+        );
+        assert_eq!(coverage.unvisited_storage_stores, vec![7]);
+        assert_eq!(
+            coverage.untaken_branches,
+            vec![UntakenBranch {
+                jump:      4,
+                direction: BranchDirection::FellThrough,
+            }]
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_dispatches_threads_that_fall_through_to_a_function() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            Push0,                                        // The offset of the selector
+            CallDataLoad,                                 // Load the first word of call data
+            PushN::new(1, vec![0xe0])?,                   // The shift that isolates the selector
+            Shr,                                          // Isolate the selector
+            PushN::new(4, vec![0xa9, 0x05, 0x9c, 0xbb])?, // The selector of a function
+            Xor,                                          // Compare it against the input
+            PushN::new(1, vec![0x10])?,                   // Push the jump destination offset
+            JumpI,                                        // Jump to the next check if not equal
+            Caller,                                       // The body of the function
+            Stop,                                         // Stop executing this thread
+            JumpDest,                                     // The next check
+            Stop                                          // Stop executing this thread
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+
+        // Prepare and execute the vm itself
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let coverage = vm.consume().coverage;
+
+        // Only the thread that fell through entered the function
+        assert_eq!(coverage.selectors, BTreeMap::from([(0xa905_9cbb, 2)]));
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_skips_branches_refuted_by_path_condition() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            CallValue,                  // Get a symbolic value
+            PushN::new(1, vec![0x0a])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Jump if the call value is nonzero
+            CallValue,                  // Get the same symbolic value again
+            IsZero,                     // Check if it is zero, which it must be
+            PushN::new(1, vec![0x0c])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Always jump
+            Stop,                       // Never executed
+            JumpDest,                   // The destination for the first jump
+            Stop,                       // Stop executing this thread
+            JumpDest,                   // The destination for the second jump
+            Stop                        // Stop executing this thread
+        ];
+
+        // With the solver we should only see the threads for the feasible paths
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_path_condition_solving(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let data = vm.consume();
+        assert_eq!(data.states.len(), 2);
+        assert_eq!(
+            data.pruned_branches,
+            vec![PrunedBranch {
+                jump:        8,
+                destination: 9,
+            }]
+        );
+
+        // But without it the infeasible one should also be explored
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_path_condition_solving(false);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let data = vm.consume();
+        assert_eq!(data.states.len(), 3);
+        assert!(data.pruned_branches.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_does_not_refute_branches_on_earlier_return_data_sizes() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            JumpDest,                   // The header of the loop
+            Push0,                      // Push the size of the return data buffer
+            Push0,                      // Push the offset of the return data buffer
+            Push0,                      // Push the size of the call data
+            Push0,                      // Push the offset of the call data
+            Push0,                      // Push the value to send
+            Caller,                     // Push the address to call
+            Gas,                        // Push the gas to send
+            Call,                       // Make a call that replaces the return data
+            Pop,                        // Discard whether the call succeeded
+            ReturnDataSize,             // Get the size of the data it returned
+            PushN::new(1, vec![0x10])?, // Push the offset of the exit
+            JumpI,                      // Exit the loop if the call returned any data
+            Push0,                      // Push the offset of the loop header
+            Jump,                       // Jump back to the loop header
+            JumpDest,                   // The exit, at 0x10
+            Stop                        // Stop executing
+        ];
+
+        // The return data of one call says nothing about that of the next, so the exit
+        // remains reachable from each iteration of the loop
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_path_condition_solving(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        assert!(vm.jump_targets().cond_jump_count(0x10)? > 1);
+        let data = vm.consume();
+        assert!(data.pruned_branches.is_empty());
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_runs_threads_in_the_order_chosen_by_the_scheduler() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            CallValue,                  // Get a symbolic value
+            PushN::new(1, vec![0x09])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork a thread if the call value is nonzero
+            CallValue,                  // Get the symbolic value again
+            PushN::new(1, vec![0x0b])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork another thread
+            Stop,                       // Stop executing the initial thread
+            JumpDest,                   // The destination for the first jump
+            Stop,                       // Stop executing this thread
+            JumpDest,                   // The destination for the second jump
+            Stop                        // Stop executing this thread
+        ];
+        let fork_points = |strategy| -> anyhow::Result<Vec<u32>> {
+            let instructions = InstructionStream::try_from(bytes.as_slice())?;
+            let config = Config::default().with_scheduling_strategy(strategy);
+            let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+            assert!(vm.execute().is_ok());
+            Ok(vm.stored_states().iter().map(VMState::fork_point).collect())
+        };
+
+        // Threads are stored in the order that they finish executing
+        assert_eq!(
+            fork_points(SchedulingStrategy::BreadthFirst)?,
+            vec![0, 3, 7]
+        );
+        assert_eq!(fork_points(SchedulingStrategy::DepthFirst)?, vec![0, 7, 3]);
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_replays_recorded_threads() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            CallValue,                  // Get a symbolic value
+            PushN::new(1, vec![0x09])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork a thread if the call value is nonzero
+            CallValue,                  // Get the symbolic value again
+            PushN::new(1, vec![0x0e])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork another thread
+            Stop,                       // Stop executing the initial thread
+            JumpDest,                   // The destination for the first jump
+            CallValue,                  // Get a value to store
+            Push0,                      // Push the storage key
+            SStore,                     // Store the call value
+            Stop,                       // Stop executing this thread
+            JumpDest,                   // The destination for the second jump
+            Caller,                     // Get a value to store
+            Push0,                      // Push the storage key
+            SStore,                     // Store the caller
+            Stop                        // Stop executing this thread
+        ];
+
+        // Execute with a trace being recorded
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_trace_recording(true);
+        let mut vm = VM::new(instructions, config.clone(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let result = vm.consume();
+        assert_eq!(result.states.len(), 3);
+        let trace = result.trace.expect("The trace was recorded");
+
+        // The trace should record the values stored by the second forked thread
+        let events: Vec<_> = trace.thread_events(2).collect();
+        let stored = vec!["0x0".to_string(), "msg.sender".to_string()];
+        assert!(events.iter().any(|event| matches!(
+            event,
+            TraceEvent::Executed { opcode, popped, .. } if opcode == "SSTORE" && popped == &stored
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(TraceEvent::Killed {
+                reason: KillReason::Halted,
+                ..
+            })
+        ));
+
+        // And replaying the thread should execute it alone
+        let path = trace.path_to(2).expect("The thread was recorded");
+        assert_eq!(path.forks.len(), 1);
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let replayed = trace::replay(instructions, config, LazyWatchdog.in_rc(), path)?;
+        assert_eq!(replayed.states.len(), 1);
+        assert_eq!(replayed.states[0].fork_point(), 7);
+        assert_eq!(
+            replayed.states[0].storage().all_values(),
+            result.states[2].storage().all_values()
+        );
+
+        // With a trace of its own that matches the original
+        let replayed_trace = replayed.trace.expect("The trace was recorded");
+        let steps = |trace: &Trace, thread| -> Vec<(usize, u32)> {
+            trace
+                .thread_events(thread)
+                .filter_map(|event| match event {
+                    TraceEvent::Executed { step, offset, .. } => Some((*step, *offset)),
+                    _ => None,
+                })
+                .collect()
+        };
+        let replayed_thread = replayed_trace
+            .events()
+            .iter()
+            .find_map(|event| match event {
+                TraceEvent::Killed { thread, .. } => Some(*thread),
+                _ => None,
+            })
+            .expect("The replayed thread was killed");
+        assert_eq!(steps(&replayed_trace, replayed_thread), steps(&trace, 2));
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_merges_threads_that_converge() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            Caller,                     // Get the value that is kept by the jumping thread
+            CallValue,                  // Get a symbolic condition
+            PushN::new(1, vec![0x07])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork a thread if the call value is nonzero
+            Pop,                        // Discard the value on the fall-through path
+            CallValue,                  // And replace it with another
+            JumpDest,                   // The point at which the paths converge
+            Push0,                      // Push the storage key
+            SStore,                     // Store the value from either path
+            Stop                        // Stop executing
+        ];
+
+        // Without merging, each path is executed to the end
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        assert_eq!(vm.consume().states.len(), 2);
+
+        // But with merging the paths continue as a single thread
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_state_merging(true).with_trace_recording(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let result = vm.consume();
+        assert_eq!(result.states.len(), 1);
+        let trace = result.trace.expect("The trace was recorded");
+        assert!(trace.events().contains(&TraceEvent::Merged {
+            thread: 0,
+            offset: 7,
+            merged: 1,
+        }));
+
+        // Which stores a choice between the values from each path
+        let stored = result.states[0]
+            .storage()
+            .generations(&RSV::new_known_value(
+                0,
+                KnownWord::zero(),
+                Provenance::Synthetic,
+                None,
+            ))
+            .expect("The slot was written");
+        let RSVD::Choice { alternatives } = stored[0].data() else {
+            panic!("Incorrect payload")
+        };
+        assert_eq!(alternatives.len(), 2);
+        assert!(alternatives.iter().any(|v| v.data() == &RSVD::CallValue));
+        assert!(alternatives.iter().any(|v| v.data() == &RSVD::Caller));
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_executes_threads_in_parallel() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            CallValue,                  // Get a symbolic condition
+            PushN::new(1, vec![0x07])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork a thread if the call value is nonzero
+            Caller,                     // Get a value to store on the fall-through path
+            Push0,                      // Push the storage key
+            SStore,                     // Store the value
+            JumpDest,                   // The point at which the paths converge
+            Caller,                     // Get another symbolic condition
+            PushN::new(1, vec![0x0f])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork a thread if the caller is nonzero
+            CallValue,                  // Get a value to store on the fall-through path
+            Push0,                      // Push the storage key
+            SStore,                     // Store the value
+            JumpDest,                   // The point at which the paths converge
+            Stop                        // Stop executing
+        ];
+
+        // Execute it on a single worker
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let sequential = vm.consume();
+        assert_eq!(sequential.states.len(), 4);
+
+        // And then spread over multiple workers
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_worker_threads(4);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        assert_eq!(vm.jump_targets().cond_jump_count(0x0f)?, 2);
+        let parallel = vm.consume();
+
+        // Both should find the same states in the same order
+        assert_eq!(parallel.states.len(), 4);
+        assert_eq!(parallel.coverage, sequential.coverage);
+        assert_eq!(parallel.all_values(), sequential.all_values());
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_grants_forks_at_the_limit_in_queue_order() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            CallValue,                  // Get a symbolic condition
+            PushN::new(1, vec![0x0b])?, // Push the offset of the first branch
+            JumpI,                      // Fork a thread if the call value is nonzero
+            Caller,                     // Get another symbolic condition
+            PushN::new(1, vec![0x0f])?, // Push the offset of the second branch
+            JumpI,                      // Fork a thread if the caller is nonzero
+            PushN::new(1, vec![0x10])?, // Push the offset of the shared code
+            Jump,                       // Jump to the shared code
+            JumpDest,                   // The first branch
+            Caller,                     // Get a value to store
+            Push0,                      // Push the storage key
+            SStore,                     // Store the value
+            JumpDest,                   // The second branch
+            JumpDest,                   // The shared code
+            Gas,                        // Get a condition that the path solver ignores
+            PushN::new(1, vec![0x16])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork a thread if the remaining gas is nonzero
+            Stop,                       // Stop executing
+            JumpDest,                   // The target that both branches fork to
+            Stop                        // Stop executing
+        ];
+        let config = Config::default().with_max_forks_per_fork_target(2);
+
+        // Execute it on a single worker
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let mut vm = VM::new(instructions, config.clone(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let sequential = vm.consume().all_values();
+
+        // Both branches reach the limit for the final target in the same round, and
+        // only the first branch in the queue is granted the fork, however the workers
+        // are scheduled
+        for _ in 0..8 {
+            let instructions = InstructionStream::try_from(bytes.as_slice())?;
+            let config = config.clone().with_worker_threads(4);
+            let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+            assert!(vm.execute().is_ok());
+            assert_eq!(vm.jump_targets().cond_jump_count(0x16)?, 2);
+            let parallel = vm.consume();
+
+            assert_eq!(parallel.states.len(), 5);
+            assert_eq!(parallel.states[4].storage().entry_count(), 1);
+            assert_eq!(parallel.all_values(), sequential);
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_widens_values_carried_around_loops() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            Push0,                      // The initial value of the loop counter
+            JumpDest,                   // The header of the loop
+            Caller,                     // Get a value to store
+            DupN::new(2)?,              // Use the loop counter as the storage key
+            SStore,                     // Store the value
+            PushN::new(1, vec![0x01])?, // Push the stride of the loop counter
+            Add,                        // Increment the loop counter
+            PushN::new(1, vec![0x01])?, // Push the offset of the loop header
+            Jump                        // Jump back to the loop header
+        ];
+        let induction = RSV::new_induction(
+            1,
+            1,
+            RSV::new_known_value(0, KnownWord::zero(), Provenance::Synthetic, None),
+            KnownWord::from(1),
+        );
+
+        // Without widening each iteration stores to a more deeply-nested key
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let result = vm.consume();
+        assert_eq!(result.states.len(), 1);
+        let storage = result.states[0].storage();
+        assert!(!storage.keys().contains(&&induction));
+        assert!(storage.keys().iter().any(|key| key.size() > 10));
+
+        // But with it the loop counter settles into induction form
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default().with_loop_widening(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let result = vm.consume();
+        assert_eq!(result.states.len(), 1);
+        let storage = result.states[0].storage();
+        assert!(storage.keys().contains(&&induction));
+        assert!(storage.keys().iter().all(|key| key.size() <= induction.size()));
+        assert_eq!(storage.entry_count(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_does_not_widen_calls_to_functions_at_lower_offsets() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            PushN::new(1, vec![0x08])?, // Push the offset of the caller
+            Jump,                       // Jump over the function
+            JumpDest,                   // The function, at 0x03
+            Caller,                     // Get a value to store
+            Push0,                      // Push the storage key
+            SStore,                     // Store the value
+            Jump,                       // Return to the caller
+            JumpDest,                   // The caller, at 0x08
+            PushN::new(1, vec![0x0e])?, // Push the first return address
+            PushN::new(1, vec![0x03])?, // Push the offset of the function
+            Jump,                       // Call the function backwards
+            JumpDest,                   // The first return address
+            PushN::new(1, vec![0x14])?, // Push the second return address
+            PushN::new(1, vec![0x03])?, // Push the offset of the function
+            Jump,                       // Call the function backwards again
+            JumpDest,                   // The second return address
+            Stop                        // Stop executing
+        ];
+
+        // Neither call is an iteration of a loop, whether or not calls are tracked
+        for maximum_call_depth in [0, 16] {
+            let instructions = InstructionStream::try_from(bytes.as_slice())?;
+            let config = Config::default()
+                .with_loop_widening(true)
+                .with_max_call_depth(maximum_call_depth);
+            let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+            assert!(vm.execute().is_ok());
+            let result = vm.consume();
+            assert!(result.errors.is_empty());
+            assert_eq!(result.states.len(), 1);
+            assert!(result.coverage.visited_instructions.contains(&0x15));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_does_not_merge_threads_from_different_loop_iterations() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            Caller,                     // Get the value that is kept by the jumping thread
+            CallValue,                  // Get a symbolic condition
+            PushN::new(1, vec![0x10])?, // Push the offset of the exit
+            JumpI,                      // Fork a thread to the exit before the loop
+            Pop,                        // Discard the value on the fall-through path
+            JumpDest,                   // The header of the loop, at 0x06
+            Caller,                     // Grow the stack on each iteration
+            Caller,                     // Get another symbolic condition
+            PushN::new(1, vec![0x06])?, // Push the offset of the loop header
+            JumpI,                      // Fork a thread for the next iteration
+            Pop,                        // Discard the value from this iteration
+            PushN::new(1, vec![0x10])?, // Push the offset of the exit
+            Jump,                       // Jump to the exit
+            JumpDest,                   // The exit, at 0x10
+            Stop                        // Stop executing
+        ];
+        let config = Config::default()
+            .with_state_merging(true)
+            .with_scheduling_strategy(SchedulingStrategy::DepthFirst)
+            .with_trace_recording(true);
+        let merged_across_iterations = TraceEvent::Merged {
+            thread: 2,
+            offset: 0x10,
+            merged: 1,
+        };
+
+        // Without widening the thread that has been around the loop once merges with
+        // the thread that skipped it
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let mut vm = VM::new(instructions, config.clone(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let trace = vm.consume().trace.expect("The trace was recorded");
+        assert!(trace.events().contains(&merged_across_iterations));
+
+        // But with it the threads have iterated differently and are kept apart
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = config.with_loop_widening(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let result = vm.consume();
+        assert!(result.errors.is_empty());
+        let trace = result.trace.expect("The trace was recorded");
+        assert!(
+            !trace
+                .events()
+                .iter()
+                .any(|event| matches!(event, TraceEvent::Merged { .. }))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_drops_threads_that_reach_explored_states() -> anyhow::Result<()> {
+        // Create the instruction stream for this VM
+        let bytes = bytecode![
+            Gas,                        // Get a condition that the path solver ignores
+            PushN::new(1, vec![0x04])?, // Push the jump destination offset onto the stack
+            JumpI,                      // Fork a thread if the remaining gas is nonzero
+            JumpDest,                   // Both paths continue here in the same state
+            CallValue,                  // Get a value to store
+            Push0,                      // Push the storage key
+            SStore,                     // Store the value
+            Stop                        // Stop executing
+        ];
+
+        // Without deduplication both threads execute the store
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let mut vm = VM::new(instructions, Config::default(), LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let result = vm.consume();
+        assert_eq!(result.dropped_threads, 0);
+        assert!(result.states.iter().all(|s| s.storage().entry_count() == 1));
+
+        // But with it the forked thread is stopped before doing so
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default()
+            .with_thread_deduplication(true)
+            .with_trace_recording(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        assert_eq!(vm.dropped_thread_count(), 1);
+        let result = vm.consume();
+        assert_eq!(result.dropped_threads, 1);
+        assert_eq!(result.states.len(), 2);
+        assert_eq!(result.states[1].storage().entry_count(), 0);
+        let trace = result.trace.expect("The trace was recorded");
+        assert!(trace.events().contains(&TraceEvent::Killed {
+            thread: 1,
+            offset: 4,
+            reason: KillReason::Duplicate,
+        }));
+
+        Ok(())
+    }
+
+    /// Bytecode that calls an internal function incrementing its argument from
+    /// two call sites.
+    fn internal_call_bytecode() -> anyhow::Result<Vec<u8>> {
+        Ok(bytecode![
+            PushN::new(1, vec![0x06])?, // Push the first return address
+            CallValue,                  // Push the argument
+            PushN::new(1, vec![0x10])?, // Push the function address
+            Jump,                       // Call the function
+            JumpDest,                   // The first return address
+            Pop,                        // Discard the result
+            PushN::new(1, vec![0x0e])?, // Push the second return address
+            CallValue,                  // Push the argument
+            PushN::new(1, vec![0x10])?, // Push the function address
+            Jump,                       // Call the function
+            JumpDest,                   // The second return address
+            Stop,                       // Stop executing this thread
+            JumpDest,                   // The function address
+            PushN::new(1, vec![0x01])?, // Push the increment
+            Add,                        // Increment the argument
+            SwapN::new(1)?,             // Bring the return address to the top
+            Jump                        // Return from the function
+        ])
+    }
+
+    #[test]
+    fn vm_executes_internal_functions_from_each_call_site() -> anyhow::Result<()> {
+        // With calls tracked, the second call can execute the function again
+        let instructions = InstructionStream::try_from(internal_call_bytecode()?.as_slice())?;
+        let config = Config::default()
+            .with_max_iterations_per_opcode(1)
+            .with_max_call_depth(16);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let data = vm.consume();
+        assert_eq!(data.states.len(), 1);
+        assert_eq!(data.states[0].stack().depth(), 1);
+
+        // But without it the function is only executed once
+        let instructions = InstructionStream::try_from(internal_call_bytecode()?.as_slice())?;
+        let config = Config::default()
+            .with_max_iterations_per_opcode(1)
+            .with_max_call_depth(0);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        let data = vm.consume();
+        assert_eq!(data.states.len(), 1);
+        assert_eq!(data.states[0].stack().depth(), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_reuses_summaries_of_internal_functions() -> anyhow::Result<()> {
+        let instructions = InstructionStream::try_from(internal_call_bytecode()?.as_slice())?;
+        let config = Config::default()
+            .with_max_call_depth(16)
+            .with_internal_call_summaries(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+        assert_eq!(vm.call_summaries().len(), 1);
+
+        // The second call returns the incremented argument without executing the body
+        let data = vm.consume();
+        assert_eq!(data.states.len(), 1);
+        let stack = data.states[0].stack();
+        assert_eq!(stack.depth(), 1);
+        assert_eq!(
+            stack.read(0)?,
+            &RSV::new_synthetic(
+                0,
+                RSVD::Add {
+                    left:  RSV::new_known_value(
+                        0,
+                        KnownWord::from_le(1u32),
+                        Provenance::Synthetic,
+                        None,
+                    ),
+                    right: RSV::new_synthetic(0, RSVD::CallValue),
+                },
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn vm_does_not_summarise_functions_that_branch_on_unknown_values() -> anyhow::Result<()> {
+        let bytes = bytecode![
+            PushN::new(1, vec![0x06])?, // Push the first return address
+            CallValue,                  // Push the argument
+            PushN::new(1, vec![0x10])?, // Push the function address
+            Jump,                       // Call the function
+            JumpDest,                   // The first return address
+            Pop,                        // Discard the result
+            PushN::new(1, vec![0x0e])?, // Push the second return address
+            CallValue,                  // Push the argument
+            PushN::new(1, vec![0x10])?, // Push the function address
+            Jump,                       // Call the function
+            JumpDest,                   // The second return address
+            Stop,                       // Stop executing this thread
+            JumpDest,                   // The function address
+            DupN::new(1)?,              // Duplicate the argument
+            PushN::new(1, vec![0x18])?, // Push the address of the shared return path
+            JumpI,                      // Skip the increment if the argument is nonzero
+            PushN::new(1, vec![0x01])?, // Push the increment
+            Add,                        // Increment the argument
+            JumpDest,                   // The shared return path
+            SwapN::new(1)?,             // Bring the return address to the top
+            Jump                        // Return from the function
+        ];
+        let instructions = InstructionStream::try_from(bytes.as_slice())?;
+        let config = Config::default()
+            .with_max_call_depth(16)
+            .with_internal_call_summaries(true);
+        let mut vm = VM::new(instructions, config, LazyWatchdog.in_rc())?;
+        assert!(vm.execute().is_ok());
+
+        // The value returned depends on the branch taken, so there is no summary
+        assert!(vm.call_summaries().is_empty());
+
+        Ok(())
+    }
+
     #[test]
     fn vm_executes_in_the_presence_of_errors() -> anyhow::Result<()> {
         // Create the instruction stream for this VM
@@ -909,6 +2808,37 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn builder_interns_identical_values() {
+        let builder = ValueBuilder::new(&Config::default());
+        let caller = builder.symbolic_exec(0, RSVD::Caller);
+        let not = || {
+            builder.symbolic_exec(
+                1,
+                RSVD::Not {
+                    value: caller.clone(),
+                },
+            )
+        };
+
+        // Identical values share one node, even when they are built elsewhere
+        assert_eq!(not(), not());
+        let elsewhere = builder.symbolic_exec(
+            2,
+            RSVD::Not {
+                value: caller.clone(),
+            },
+        );
+        assert_eq!(not(), elsewhere);
+        assert_eq!(elsewhere.instruction_pointer(), 2);
+        assert_eq!(builder.arena().len(), 2);
+
+        // And bare values are identified by where they were built
+        let size = builder.value(3, Provenance::CallDataSize);
+        assert_eq!(size, builder.value(3, Provenance::CallDataSize));
+        assert_ne!(size, builder.value(4, Provenance::CallDataSize));
+    }
+
     /// Utilities for aiding in the testing of the virtual machine.
     mod util {
         use crate::disassembly::InstructionStream;
@@ -0,0 +1,545 @@
+//! This module contains the strategies that the virtual machine uses to decide
+//! which of its queued threads of execution to run next.
+//!
+//! The virtual machine executes a single thread until it dies, at which point
+//! it asks its [`Scheduler`] to pick the next thread from the queue. As the
+//! exploration is bounded by the limits in the [`crate::vm::Config`], the order
+//! in which threads are run determines which parts of a large contract are
+//! reached before those limits are hit.
+
+use std::{
+    collections::{BTreeSet, HashSet, VecDeque},
+    fmt::Debug,
+    ops::Bound,
+};
+
+use crate::{
+    cfg::dispatcher::SelectorTable,
+    constant::{SCHEDULER_LOOKAHEAD_INSTRUCTIONS, SELECTOR_WIDTH_BYTES},
+    vm::{
+        thread::VMThread,
+        value::{RuntimeBoxedVal, RSVD},
+    },
+};
+
+/// A dynamically dispatched [`Scheduler`] instance.
+pub type DynScheduler = Box<dyn Scheduler>;
+
+/// The interface to a strategy for choosing the order in which the threads of
+/// execution in the virtual machine are run.
+pub trait Scheduler
+where
+    Self: Debug + Send + Sync,
+{
+    /// Chooses the thread in `threads` that the virtual machine should execute
+    /// next, returning its index in the queue.
+    ///
+    /// This is only called when `threads` is non-empty, and the returned index
+    /// must be in bounds for it.
+    #[must_use]
+    fn select(&mut self, threads: &VecDeque<VMThread>) -> usize;
+
+    /// Informs the scheduler that the instruction at `instruction_pointer` has
+    /// been executed by some thread.
+    ///
+    /// By default this does nothing.
+    fn observe(&mut self, instruction_pointer: u32) {
+        let _ = instruction_pointer;
+    }
+
+    /// Clones the scheduler, including any state it has accumulated, into a new
+    /// box.
+    #[must_use]
+    fn clone_box(&self) -> DynScheduler;
+}
+
+impl Clone for DynScheduler {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+/// The built-in scheduling strategies, which can be selected through
+/// [`crate::vm::Config::scheduling_strategy`].
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum SchedulingStrategy {
+    /// Runs threads in the order they were created, exploring the program
+    /// breadth-first.
+    #[default]
+    BreadthFirst,
+
+    /// Runs the most recently created thread first, exploring the program
+    /// depth-first.
+    DepthFirst,
+
+    /// Runs threads in a pseudo-random order that is determined by the `seed`.
+    Random { seed: u64 },
+
+    /// Runs the threads that are about to reach storage accesses that no thread
+    /// has executed yet first, falling back to breadth-first exploration.
+    CoverageGuided,
+
+    /// Runs threads for each function selector found in the dispatcher in turn,
+    /// so that no one external function uses up the exploration limits.
+    RoundRobinBySelector,
+}
+
+impl SchedulingStrategy {
+    /// Constructs a new scheduler implementing the strategy.
+    #[must_use]
+    pub fn new_scheduler(self) -> DynScheduler {
+        match self {
+            Self::BreadthFirst => Box::new(BreadthFirst),
+            Self::DepthFirst => Box::new(DepthFirst),
+            Self::Random { seed } => Box::new(Random::new(seed)),
+            Self::CoverageGuided => Box::new(CoverageGuided::new()),
+            Self::RoundRobinBySelector => Box::new(RoundRobinBySelector::new()),
+        }
+    }
+}
+
+/// A scheduler that runs threads in first-in first-out order.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct BreadthFirst;
+
+impl Scheduler for BreadthFirst {
+    fn select(&mut self, _: &VecDeque<VMThread>) -> usize {
+        0
+    }
+
+    fn clone_box(&self) -> DynScheduler {
+        Box::new(*self)
+    }
+}
+
+/// A scheduler that runs threads in last-in first-out order.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub struct DepthFirst;
+
+impl Scheduler for DepthFirst {
+    fn select(&mut self, threads: &VecDeque<VMThread>) -> usize {
+        threads.len().saturating_sub(1)
+    }
+
+    fn clone_box(&self) -> DynScheduler {
+        Box::new(*self)
+    }
+}
+
+/// A scheduler that runs threads in a pseudo-random order.
+///
+/// The order is entirely determined by the seed, so that runs of the virtual
+/// machine remain reproducible.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// Constructs a new random scheduler from the provided `seed`.
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        Self { state: seed }
+    }
+
+    /// Gets the next pseudo-random number in the sequence, using the
+    /// [SplitMix64](https://prng.di.unimi.it/splitmix64.c) generator.
+    fn next(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl Scheduler for Random {
+    #[allow(clippy::cast_possible_truncation)] // The result is less than `threads.len()`
+    fn select(&mut self, threads: &VecDeque<VMThread>) -> usize {
+        (self.next() % threads.len() as u64) as usize
+    }
+
+    fn clone_box(&self) -> DynScheduler {
+        Box::new(*self)
+    }
+}
+
+/// A scheduler that favours threads which are about to reach storage accesses
+/// that have not yet been executed by any thread.
+///
+/// Each queued thread is scored by the number of unexecuted `SLOAD` and
+/// `SSTORE` instructions in the straight-line code ahead of it, looking at most
+/// [`SCHEDULER_LOOKAHEAD_INSTRUCTIONS`] ahead. The thread with the highest
+/// score is run next, with ties going to the oldest thread.
+#[derive(Clone, Debug, Default)]
+pub struct CoverageGuided {
+    executed: HashSet<u32>,
+}
+
+impl CoverageGuided {
+    /// Constructs a new coverage-guided scheduler that has not observed any
+    /// executed instructions.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts the storage accesses ahead of `thread` that have not yet been
+    /// executed.
+    fn score(&self, thread: &VMThread) -> usize {
+        let instructions = thread.instructions();
+        let start = instructions.instruction_pointer();
+        let mut score = 0;
+
+        for offset in (start..).take(SCHEDULER_LOOKAHEAD_INSTRUCTIONS) {
+            let Some(instruction) = instructions.instruction(offset) else {
+                break;
+            };
+            // Instructions that take up no space in the bytecode do nothing at all
+            let Some(&byte) = instruction.encode().first() else {
+                continue;
+            };
+            if matches!(byte, 0x54 | 0x55) && !self.executed.contains(&offset) {
+                score += 1;
+            }
+
+            // The code beyond an unconditional change of control flow may not be reached
+            if matches!(byte, 0x00 | 0x56 | 0xf3 | 0xfd | 0xfe | 0xff) {
+                break;
+            }
+        }
+
+        score
+    }
+}
+
+impl Scheduler for CoverageGuided {
+    fn select(&mut self, threads: &VecDeque<VMThread>) -> usize {
+        threads
+            .iter()
+            .enumerate()
+            .rev()
+            .max_by_key(|(_, thread)| self.score(thread))
+            .map_or(0, |(index, _)| index)
+    }
+
+    fn observe(&mut self, instruction_pointer: u32) {
+        self.executed.insert(instruction_pointer);
+    }
+
+    fn clone_box(&self) -> DynScheduler {
+        Box::new(self.clone())
+    }
+}
+
+/// A scheduler that cycles between the function selectors of the queued
+/// threads, running the oldest thread for each selector in turn.
+///
+/// Threads that have not been dispatched to a function, as given by
+/// [`VMThread::selector`], are treated as a group of their own.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct RoundRobinBySelector {
+    /// The bound below which the selectors have already had their turn in the
+    /// current round.
+    served: Bound<Option<u32>>,
+}
+
+impl RoundRobinBySelector {
+    /// Constructs a new round-robin scheduler that has not yet run any thread.
+    #[must_use]
+    pub fn new() -> Self {
+        let served = Bound::Unbounded;
+        Self { served }
+    }
+}
+
+impl Default for RoundRobinBySelector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Scheduler for RoundRobinBySelector {
+    fn select(&mut self, threads: &VecDeque<VMThread>) -> usize {
+        let selectors: BTreeSet<_> = threads.iter().map(VMThread::selector).collect();
+        let next = selectors
+            .range((self.served, Bound::Unbounded))
+            .next()
+            .or_else(|| selectors.first())
+            .copied()
+            .unwrap_or_default();
+        self.served = Bound::Excluded(next);
+
+        threads
+            .iter()
+            .position(|thread| thread.selector() == next)
+            .unwrap_or_default()
+    }
+
+    fn clone_box(&self) -> DynScheduler {
+        Box::new(self.clone())
+    }
+}
+
+/// The dispatch of a thread to an external function by a conditional jump.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Dispatch {
+    /// The selector of the function that is dispatched to.
+    pub selector: u32,
+
+    /// Whether the function is entered when the jump is taken, rather than
+    /// when execution falls through.
+    pub on_jump: bool,
+}
+
+/// Gets the dispatch of a thread to an external function performed by a
+/// conditional jump on `condition`, if it performs one.
+///
+/// This recognises the comparisons that compilers emit in the dispatchers of
+/// contracts, where a known selector is compared to a value computed from the
+/// first word of the call data. These are comparisons using `EQ`, as emitted
+/// by Solidity, and using `XOR` or `SUB`, as emitted by Vyper, any of which may
+/// be negated using `ISZERO`.
+///
+/// If the dispatcher of the contract was recognised statically, only the
+/// selectors in its `table` are treated as being dispatched to.
+#[must_use]
+pub fn dispatch(condition: &RuntimeBoxedVal, table: &SelectorTable) -> Option<Dispatch> {
+    // Each negation of the condition swaps the branches
+    let mut condition = condition;
+    let mut negated = false;
+    while let RSVD::IsZero { number } = condition.data() {
+        condition = number;
+        negated = !negated;
+    }
+
+    let (left, right, on_equal) = match condition.data() {
+        RSVD::Equals { left, right } => (left, right, true),
+        RSVD::Xor { left, right } | RSVD::Subtract { left, right } => (left, right, false),
+        _ => return None,
+    };
+    let (selector, input) = match (
+        left.constant_fold().as_word(),
+        right.constant_fold().as_word(),
+    ) {
+        (Some(selector), None) => (selector, right),
+        (None, Some(selector)) => (selector, left),
+        _ => return None,
+    };
+    let selector = u32::try_from(selector.value_le()).ok()?;
+    if !table.is_empty() && table.entry(selector.to_be_bytes()).is_none() {
+        return None;
+    }
+
+    // The selector is found at the very start of the call data
+    let mut parts = vec![input.clone()];
+    while let Some(part) = parts.pop() {
+        if let RSVD::CallData { offset, size, .. } = part.data() {
+            let at_start = offset.constant_fold().as_word().is_some_and(|o| o.value_le() == 0);
+            let covers_selector = size
+                .constant_fold()
+                .as_word()
+                .is_some_and(|s| s.value_le() >= SELECTOR_WIDTH_BYTES as u128);
+            if at_start && covers_selector {
+                let on_jump = on_equal != negated;
+                return Some(Dispatch { selector, on_jump });
+            }
+        }
+        parts.extend(part.children());
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, VecDeque};
+
+    use crate::{
+        cfg::dispatcher::SelectorTable,
+        disassembly::InstructionStream,
+        extractor::chain::Chain,
+        vm::{
+            scheduler::{
+                dispatch,
+                BreadthFirst,
+                CoverageGuided,
+                DepthFirst,
+                Dispatch,
+                Random,
+                RoundRobinBySelector,
+                Scheduler,
+            },
+            state::VMState,
+            thread::VMThread,
+            test_util::{known, synthetic},
+            value::{RuntimeBoxedVal, RSVD},
+            Config,
+        },
+    };
+
+    /// Constructs a queue with a thread at each of the provided `offsets` into
+    /// the assembled `source`.
+    fn queue(source: &str, offsets: &[u32]) -> anyhow::Result<VecDeque<VMThread>> {
+        let instructions = InstructionStream::assemble(source, &Chain::default())?;
+        let len = u32::try_from(instructions.len())?;
+        offsets
+            .iter()
+            .map(|&offset| {
+                let state = VMState::new_at_start(len, Config::default());
+                Ok(VMThread::new(state, instructions.new_thread(offset)?))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn breadth_and_depth_first_select_the_ends_of_the_queue() -> anyhow::Result<()> {
+        let threads = queue("STOP\nSTOP\nSTOP", &[0, 1, 2])?;
+        assert_eq!(BreadthFirst.select(&threads), 0);
+        assert_eq!(DepthFirst.select(&threads), 2);
+
+        Ok(())
+    }
+
+    #[test]
+    fn random_selection_is_determined_by_the_seed() -> anyhow::Result<()> {
+        let threads = queue("STOP\nSTOP\nSTOP\nSTOP\nSTOP", &[0, 1, 2, 3, 4])?;
+        let select = |seed| {
+            let mut scheduler = Random::new(seed);
+            (0..16).map(|_| scheduler.select(&threads)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(select(7), select(7));
+        assert_ne!(select(7), select(8));
+        assert!(select(7).iter().all(|&index| index < threads.len()));
+
+        Ok(())
+    }
+
+    #[test]
+    fn coverage_guided_prefers_unexecuted_storage_accesses() -> anyhow::Result<()> {
+        let source = "
+                PUSH1 stores
+                POP
+                PUSH1 0x00 ; 0x03
+                SLOAD
+                STOP
+            stores:
+                JUMPDEST   ; 0x07
+                PUSH1 0x00
+                PUSH1 0x00
+                SSTORE
+                PUSH1 0x00
+                SLOAD
+                STOP
+        ";
+        let threads = queue(source, &[0x03, 0x07])?;
+        let mut scheduler = CoverageGuided::new();
+
+        // The second thread is about to reach more storage accesses
+        assert_eq!(scheduler.select(&threads), 1);
+
+        // But once they have been executed the first thread is more promising
+        (0x07..=0x10).for_each(|offset| scheduler.observe(offset));
+        assert_eq!(scheduler.select(&threads), 0);
+
+        // And with nothing left to discover the oldest thread goes first
+        scheduler.observe(0x05);
+        assert_eq!(scheduler.select(&threads), 0);
+
+        Ok(())
+    }
+
+    #[test]
+    fn round_robin_cycles_between_selectors() -> anyhow::Result<()> {
+        let mut threads = queue("STOP\nSTOP\nSTOP\nSTOP", &[0, 1, 2, 3])?;
+        threads[0].dispatch_to(2);
+        threads[2].dispatch_to(1);
+        threads[3].dispatch_to(1);
+
+        let mut scheduler = RoundRobinBySelector::new();
+        let order: Vec<_> = (0..4).map(|_| scheduler.select(&threads)).collect();
+        assert_eq!(order, vec![1, 2, 0, 1]);
+
+        Ok(())
+    }
+
+    fn input_selector() -> RuntimeBoxedVal {
+        synthetic(RSVD::RightShift {
+            shift: known(0xe0),
+            value: synthetic(RSVD::call_data(known(0), known(0x20))),
+        })
+    }
+
+    #[test]
+    fn recognises_dispatcher_comparisons() {
+        let table = SelectorTable::default();
+        let condition = synthetic(RSVD::Equals {
+            left:  known(0xa905_9cbb),
+            right: input_selector(),
+        });
+        assert_eq!(
+            dispatch(&condition, &table),
+            Some(Dispatch {
+                selector: 0xa905_9cbb,
+                on_jump:  true,
+            })
+        );
+
+        let elsewhere = synthetic(RSVD::Equals {
+            left:  synthetic(RSVD::call_data(known(4), known(0x20))),
+            right: known(0xa905_9cbb),
+        });
+        assert_eq!(dispatch(&elsewhere, &table), None);
+    }
+
+    #[test]
+    fn recognises_vyper_dispatcher_comparisons() {
+        let table = SelectorTable::default();
+
+        // The function is entered when the difference is zero
+        let condition = synthetic(RSVD::Xor {
+            left:  input_selector(),
+            right: known(0xa905_9cbb),
+        });
+        assert_eq!(
+            dispatch(&condition, &table),
+            Some(Dispatch {
+                selector: 0xa905_9cbb,
+                on_jump:  false,
+            })
+        );
+
+        // Unless the comparison is negated
+        let negated = synthetic(RSVD::IsZero {
+            number: synthetic(RSVD::Subtract {
+                left:  known(0xa905_9cbb),
+                right: input_selector(),
+            }),
+        });
+        assert_eq!(
+            dispatch(&negated, &table),
+            Some(Dispatch {
+                selector: 0xa905_9cbb,
+                on_jump:  true,
+            })
+        );
+    }
+
+    #[test]
+    fn only_recognises_selectors_in_a_recognised_dispatcher() {
+        let table = SelectorTable {
+            functions: BTreeMap::from([(0xa905_9cbb_u32.to_be_bytes(), 0x40)]),
+            ..SelectorTable::default()
+        };
+        let condition = |selector| {
+            synthetic(RSVD::Equals {
+                left:  known(selector),
+                right: input_selector(),
+            })
+        };
+
+        assert!(dispatch(&condition(0xa905_9cbb), &table).is_some());
+        assert_eq!(dispatch(&condition(0x1234), &table), None);
+    }
+}
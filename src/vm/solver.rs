@@ -0,0 +1,366 @@
+//! This module contains a lightweight solver for the path conditions gathered
+//! by the threads of the virtual machine.
+//!
+//! Each time a thread passes a conditional jump it learns a fact about the
+//! condition of that jump, namely whether it was zero or not. The solver
+//! tracks these facts as bounds on the 256-bit words that the conditions are
+//! built from, and uses them to prove that a branch can never be taken by the
+//! thread. Any fact that it cannot interpret is ignored, so it never considers
+//! a feasible branch to be infeasible.
+
+use std::collections::{BTreeSet, HashMap};
+
+use ethnum::U256;
+
+use crate::vm::{
+    fingerprint::{fingerprint_unordered, Fingerprint},
+    value::{RuntimeBoxedVal, RSVD},
+};
+
+/// The conditions under which a thread of execution in the virtual machine
+/// reached its current position.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct PathCondition {
+    /// The values that the path is known to constrain, along with the range
+    /// of values that each of them can take.
+    bounds: HashMap<RuntimeBoxedVal, Interval>,
+}
+
+impl PathCondition {
+    /// Constructs a new path condition that places no constraints on any
+    /// value.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the `condition` of a conditional jump was nonzero if
+    /// `holds` is true, or zero otherwise.
+    pub fn assume(&mut self, condition: &RuntimeBoxedVal, holds: bool) {
+        if let Some((term, relation)) = constraint(condition, holds) {
+            self.bounds.entry(term).or_default().constrain(relation);
+        }
+    }
+
+    /// Checks whether the path condition admits the `condition` being nonzero
+    /// if `holds` is true, or zero otherwise.
+    ///
+    /// This returns `false` only if the branch has been proven to be
+    /// infeasible.
+    #[must_use]
+    pub fn admits(&self, condition: &RuntimeBoxedVal, holds: bool) -> bool {
+        let Some((term, relation)) = constraint(condition, holds) else {
+            return true;
+        };
+        let mut interval = self.bounds.get(&term).cloned().unwrap_or_default();
+        interval.constrain(relation);
+
+        !interval.is_empty()
+    }
+
+    /// Weakens the path condition to the constraints that it shares with
+    /// `other`, such that it holds on the paths described by either.
+    pub fn join(&mut self, other: &PathCondition) {
+        self.bounds
+            .retain(|term, interval| other.bounds.get(term) == Some(interval));
+    }
+
+    /// Gets the number of values constrained by the path condition.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    /// Checks whether the path condition constrains no values.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    /// Computes a fingerprint of the bounds placed on each value by the path
+    /// condition.
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint_unordered(self.bounds.iter())
+    }
+}
+
+/// A relation between a symbolic value and a constant word.
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Relation {
+    Eq(U256),
+    Ne(U256),
+    Lt(U256),
+    Le(U256),
+    Gt(U256),
+    Ge(U256),
+}
+
+/// The set of unsigned words that a value can take, given as an inclusive
+/// range with a set of excluded values.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+struct Interval {
+    min:      U256,
+    max:      U256,
+    excluded: BTreeSet<U256>,
+}
+
+impl Interval {
+    /// Restricts the interval to the words that satisfy `relation`.
+    fn constrain(&mut self, relation: Relation) {
+        match relation {
+            Relation::Eq(k) => {
+                self.min = self.min.max(k);
+                self.max = self.max.min(k);
+            }
+            Relation::Ne(k) => {
+                self.excluded.insert(k);
+            }
+            Relation::Lt(k) => match k.checked_sub(U256::ONE) {
+                Some(k) => self.max = self.max.min(k),
+                None => self.clear(),
+            },
+            Relation::Le(k) => self.max = self.max.min(k),
+            Relation::Gt(k) => match k.checked_add(U256::ONE) {
+                Some(k) => self.min = self.min.max(k),
+                None => self.clear(),
+            },
+            Relation::Ge(k) => self.min = self.min.max(k),
+        }
+
+        // Move the bounds inwards past any excluded values
+        while !self.is_empty() && self.excluded.contains(&self.min) {
+            match self.min.checked_add(U256::ONE) {
+                Some(min) => self.min = min,
+                None => self.clear(),
+            }
+        }
+        while !self.is_empty() && self.excluded.contains(&self.max) {
+            match self.max.checked_sub(U256::ONE) {
+                Some(max) => self.max = max,
+                None => self.clear(),
+            }
+        }
+    }
+
+    /// Removes all words from the interval.
+    fn clear(&mut self) {
+        self.min = U256::MAX;
+        self.max = U256::MIN;
+    }
+
+    /// Checks whether the interval contains no words.
+    fn is_empty(&self) -> bool {
+        self.min > self.max
+    }
+}
+
+impl Default for Interval {
+    fn default() -> Self {
+        Self {
+            min:      U256::MIN,
+            max:      U256::MAX,
+            excluded: BTreeSet::new(),
+        }
+    }
+}
+
+/// Interprets the `condition` of a conditional jump being nonzero if `holds`
+/// is true, or zero otherwise, as a relation on a single symbolic value.
+///
+/// Returns [`None`] if the condition cannot be interpreted, or if it concerns
+/// values that can change over the course of a single execution.
+fn constraint(condition: &RuntimeBoxedVal, holds: bool) -> Option<(RuntimeBoxedVal, Relation)> {
+    let mut value = condition.clone();
+    let mut holds = holds;
+    while let RSVD::IsZero { number } = value.data() {
+        holds = !holds;
+        value = number.clone();
+    }
+
+    // Only the operands are folded, as the structure of the condition is what
+    // identifies the value being constrained
+    let word = |value: &RuntimeBoxedVal| value.constant_fold().as_word().map(|w| w.value_le());
+    let nonzero = (&value, Relation::Ne(U256::ZERO), Relation::Eq(U256::ZERO));
+    let (term, if_holds, otherwise) = match value.data() {
+        RSVD::Equals { left, right } => match (word(left), word(right)) {
+            (None, Some(k)) => (left, Relation::Eq(k), Relation::Ne(k)),
+            (Some(k), None) => (right, Relation::Eq(k), Relation::Ne(k)),
+            _ => nonzero,
+        },
+        RSVD::LessThan { left, right } => match (word(left), word(right)) {
+            (None, Some(k)) => (left, Relation::Lt(k), Relation::Ge(k)),
+            (Some(k), None) => (right, Relation::Gt(k), Relation::Le(k)),
+            _ => nonzero,
+        },
+        RSVD::GreaterThan { left, right } => match (word(left), word(right)) {
+            (None, Some(k)) => (left, Relation::Gt(k), Relation::Le(k)),
+            (Some(k), None) => (right, Relation::Lt(k), Relation::Ge(k)),
+            _ => nonzero,
+        },
+        _ => nonzero,
+    };
+    let relation = if holds { if_holds } else { otherwise };
+
+    (word(term).is_none() && is_stable(term)).then(|| (term.clone(), relation))
+}
+
+/// Checks whether `value` is guaranteed to evaluate to the same word whenever
+/// it occurs during a single execution.
+///
+/// Values are compared structurally, so this excludes any values that read
+/// state which can change between two reads, such as the remaining gas, the
+/// contents of storage, or the data returned by the most recent call. It also
+/// excludes the results of calls and contract creations, as two structurally
+/// identical calls may succeed or fail independently, and two creations
+/// produce different addresses.
+fn is_stable(value: &RuntimeBoxedVal) -> bool {
+    let mut parts = vec![value.clone()];
+    while let Some(part) = parts.pop() {
+        if matches!(
+            part.data(),
+            RSVD::Gas
+                | RSVD::SelfBalance
+                | RSVD::Balance { .. }
+                | RSVD::ExtCodeSize { .. }
+                | RSVD::ExtCodeHash { .. }
+                | RSVD::ReturnData { .. }
+                | RSVD::SLoad { .. }
+                | RSVD::TLoad { .. }
+                | RSVD::CallWithValue { .. }
+                | RSVD::CallWithoutValue { .. }
+                | RSVD::Create { .. }
+                | RSVD::Create2 { .. }
+        ) {
+            return false;
+        }
+        parts.extend(part.children());
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vm::{
+        solver::PathCondition,
+        test_util::{known, synthetic},
+        value::RSVD,
+    };
+
+    #[test]
+    fn refutes_contradicting_checks_on_the_same_value() {
+        let call_value = synthetic(RSVD::CallValue);
+        let mut path = PathCondition::new();
+        path.assume(&call_value, false);
+
+        // A second read of the call value yields the same value
+        let is_zero = synthetic(RSVD::IsZero {
+            number: synthetic(RSVD::CallValue),
+        });
+        assert!(path.admits(&is_zero, true));
+        assert!(!path.admits(&is_zero, false));
+    }
+
+    #[test]
+    fn refutes_disjoint_bounds() {
+        let too_short = synthetic(RSVD::LessThan {
+            left:  synthetic(RSVD::CallDataSize),
+            right: known(4),
+        });
+        let mut path = PathCondition::new();
+        path.assume(&too_short, true);
+
+        let long_enough = synthetic(RSVD::GreaterThan {
+            left:  synthetic(RSVD::CallDataSize),
+            right: known(0x23),
+        });
+        assert!(!path.admits(&long_enough, true));
+        assert!(path.admits(&long_enough, false));
+
+        let non_empty = synthetic(RSVD::LessThan {
+            left:  known(0),
+            right: synthetic(RSVD::CallDataSize),
+        });
+        assert!(path.admits(&non_empty, true));
+        assert!(path.admits(&non_empty, false));
+    }
+
+    #[test]
+    fn refutes_equalities_with_different_constants() {
+        let selector = synthetic(RSVD::new_value());
+        let mut path = PathCondition::new();
+        path.assume(
+            &synthetic(RSVD::Equals {
+                left:  selector.clone(),
+                right: known(0x1234),
+            }),
+            true,
+        );
+
+        let other = synthetic(RSVD::Equals {
+            left:  known(0x5678),
+            right: selector.clone(),
+        });
+        assert!(!path.admits(&other, true));
+        assert!(path.admits(&other, false));
+    }
+
+    #[test]
+    fn refutes_values_with_all_candidates_excluded() {
+        let value = synthetic(RSVD::new_value());
+        let mut path = PathCondition::new();
+        path.assume(
+            &synthetic(RSVD::LessThan {
+                left:  value.clone(),
+                right: known(2),
+            }),
+            true,
+        );
+        path.assume(&value, true);
+
+        let is_one = synthetic(RSVD::Equals {
+            left:  value.clone(),
+            right: known(1),
+        });
+        assert!(path.admits(&is_one, true));
+        assert!(!path.admits(&is_one, false));
+    }
+
+    #[test]
+    fn ignores_values_that_can_change() {
+        let gas = synthetic(RSVD::Gas);
+        let mut path = PathCondition::new();
+        path.assume(&gas, false);
+
+        assert!(path.is_empty());
+        assert!(path.admits(&gas, true));
+    }
+
+    #[test]
+    fn ignores_the_results_of_calls_and_creations() {
+        let call = || {
+            synthetic(RSVD::CallWithoutValue {
+                gas:           known(0x5000),
+                address:       known(0x1234),
+                argument_data: known(0),
+                ret_offset:    known(0),
+                ret_size:      known(0),
+            })
+        };
+        let create = || {
+            synthetic(RSVD::Create {
+                value: known(0),
+                data:  known(0),
+            })
+        };
+        let mut path = PathCondition::new();
+        path.assume(&call(), false);
+        path.assume(&create(), false);
+
+        // A second identical call may succeed where the first failed
+        assert!(path.is_empty());
+        assert!(path.admits(&call(), true));
+        assert!(path.admits(&create(), true));
+    }
+}
@@ -1,10 +1,17 @@
 //! This module contains the definition of the virtual machine's memory.
 
-use std::{collections::HashMap, hash::Hash};
+use std::{
+    collections::{hash_map::Entry, HashMap},
+    hash::Hash,
+};
 
 use crate::{
     constant::WORD_SIZE_BITS,
-    vm::value::{known::KnownWord, Provenance, RuntimeBoxedVal, RSV, RSVD},
+    vm::{
+        fingerprint::{fingerprint_unordered, Fingerprint},
+        state::merge_generations,
+        value::{known::KnownWord, Provenance, RuntimeBoxedVal, RSV, RSVD},
+    },
 };
 
 /// A representation of the transient memory of the symbolic virtual machine.
@@ -217,23 +224,28 @@ impl Memory {
     where
         K: Clone + Eq + Hash + PartialEq,
     {
-        let entry = map.entry(key.clone()).or_insert_with(|| {
-            // The instruction pointer is 0 here, as the uninitialized value was created
-            // when the program started.
-            let data =
-                RSV::new_known_value(0, KnownWord::zero(), Provenance::UninitializedMemory, None);
-
-            vec![MemStore {
-                data,
-                size: MemStoreSize::Word,
-            }]
-        });
+        let entry = map.entry(key.clone()).or_insert_with(|| vec![Self::uninitialized()]);
 
         // This is safe as we always guarantee that there at least one item in the
         // generational vector.
         &entry.last().unwrap().data
     }
 
+    /// Creates the all-zeroes store that is read from memory that has not
+    /// previously been written.
+    #[must_use]
+    fn uninitialized() -> MemStore {
+        // The instruction pointer is 0 here, as the uninitialized value was created
+        // when the program started.
+        let data =
+            RSV::new_known_value(0, KnownWord::zero(), Provenance::UninitializedMemory, None);
+
+        MemStore {
+            data,
+            size: MemStoreSize::Word,
+        }
+    }
+
     /// Gets all of the stores that were made at the provided `offset` during
     /// the course of execution.
     ///
@@ -258,6 +270,107 @@ impl Memory {
             .map(|result| result.size)
     }
 
+    /// Merges the `other` memory into this one at `instruction_pointer`, where
+    /// the threads of execution that hold them have converged.
+    ///
+    /// The generations written by both threads are retained at every offset.
+    /// Where the most-recent values at an offset differ, a choice between them
+    /// is written as the newest generation.
+    ///
+    /// An offset that was only written by one of the threads is still
+    /// uninitialized in the other, and so is merged as a choice between the
+    /// written value and zero.
+    pub fn merge(&mut self, other: Memory, instruction_pointer: u32) {
+        Self::merge_offsets(
+            &mut self.constant_offsets,
+            other.constant_offsets,
+            instruction_pointer,
+        );
+        Self::merge_offsets(
+            &mut self.symbolic_offsets,
+            other.symbolic_offsets,
+            instruction_pointer,
+        );
+    }
+
+    /// Merges the stores in `theirs` into the stores in `ours` at
+    /// `instruction_pointer`.
+    fn merge_offsets<K>(
+        ours: &mut HashMap<K, Vec<MemStore>>,
+        theirs: HashMap<K, Vec<MemStore>>,
+        instruction_pointer: u32,
+    ) where
+        K: Eq + Hash,
+    {
+        let choose = |ours: &MemStore, theirs: &MemStore| {
+            // Stores of differing sizes can only be treated as a full word
+            let size = if ours.size == theirs.size {
+                ours.size
+            } else {
+                MemStoreSize::Word
+            };
+            let data = RSV::new_choice(instruction_pointer, ours.data.clone(), theirs.data.clone());
+            MemStore { data, size }
+        };
+
+        for (offset, generations) in ours.iter_mut() {
+            if !theirs.contains_key(offset) {
+                merge_generations(generations, vec![Self::uninitialized()], choose);
+            }
+        }
+
+        for (offset, generations) in theirs {
+            match ours.entry(offset) {
+                Entry::Occupied(mut entry) => {
+                    merge_generations(entry.get_mut(), generations, choose);
+                }
+                Entry::Vacant(entry) => {
+                    let mut ours = vec![Self::uninitialized()];
+                    merge_generations(&mut ours, generations, choose);
+                    entry.insert(ours);
+                }
+            }
+        }
+    }
+
+    /// Creates a copy of the memory that retains only the most-recent value
+    /// written at each offset.
+    #[must_use]
+    pub fn latest(&self) -> Self {
+        fn latest<K: Clone + Eq + Hash>(
+            offsets: &HashMap<K, Vec<MemStore>>,
+        ) -> HashMap<K, Vec<MemStore>> {
+            offsets
+                .iter()
+                .map(|(k, vs)| (k.clone(), vs.last().into_iter().cloned().collect()))
+                .collect()
+        }
+        let constant_offsets = latest(&self.constant_offsets);
+        let symbolic_offsets = latest(&self.symbolic_offsets);
+        let max_single_operation_bytes = self.max_single_operation_bytes;
+        Self {
+            constant_offsets,
+            symbolic_offsets,
+            max_single_operation_bytes,
+        }
+    }
+
+    /// Computes a fingerprint of the most-recent value written at each offset
+    /// in the memory.
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        let constant = self
+            .constant_offsets
+            .iter()
+            .map(|(offset, generations)| (Some(offset), None, generations.last()));
+        let symbolic = self
+            .symbolic_offsets
+            .iter()
+            .map(|(offset, generations)| (None, Some(offset), generations.last()));
+
+        fingerprint_unordered(constant.chain(symbolic))
+    }
+
     /// Asks the memory for the number of entries it has in it.
     ///
     /// This has no equivalent operation on the EVM and is primarily useful for
@@ -296,7 +409,7 @@ impl Memory {
 }
 
 /// The data that actually gets stored into memory.
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 struct MemStore {
     data: RuntimeBoxedVal,
     size: MemStoreSize,
@@ -304,7 +417,7 @@ struct MemStore {
 
 /// The type of memory storage operation associated with the data at a given
 /// "location".
-#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 pub enum MemStoreSize {
     Byte,
     Word,
@@ -550,4 +663,28 @@ mod test {
         let generations = memory.generations(&offset).unwrap();
         assert_eq!(generations, vec![&value_1, &value_2]);
     }
+
+    #[test]
+    fn merges_offsets_written_on_one_side_with_zero() {
+        let offset = RSV::new_known_value(0, KnownWord::from(0x40), Provenance::Synthetic, None);
+        let value = new_synthetic_value(1);
+
+        let mut ours = Memory::new(DEFAULT_MEMORY_SINGLE_OPERATION_MAX_BYTES);
+        let mut theirs = Memory::new(DEFAULT_MEMORY_SINGLE_OPERATION_MAX_BYTES);
+        theirs.store(offset.clone(), value.clone());
+        ours.merge(theirs, 2);
+
+        let RSVD::Choice { alternatives } = ours.load(&offset).data().clone() else {
+            panic!("Invalid payload")
+        };
+        assert_eq!(alternatives.len(), 2);
+        assert!(
+            matches!(alternatives[0].data(), RSVD::KnownData { value } if value == &KnownWord::zero())
+        );
+        assert_eq!(
+            alternatives[0].provenance(),
+            Provenance::UninitializedMemory
+        );
+        assert_eq!(alternatives[1], value);
+    }
 }
@@ -5,6 +5,8 @@ pub mod memory;
 pub mod stack;
 pub mod storage;
 
+use std::{collections::HashSet, hash::Hash, ops::Range};
+
 use crate::vm::{
     data::VisitedOpcodes,
     state::{memory::Memory, stack::Stack, storage::Storage},
@@ -32,6 +34,9 @@ pub struct VMState {
     /// The symbolic EVM storage for this thread of execution.
     storage: Storage,
 
+    /// The symbolic EVM transient storage for this thread of execution.
+    transient_storage: Storage,
+
     /// A container for values that would otherwise be dropped but that might
     /// still be useful when it comes to later analysis.
     recorded_values: Vec<RuntimeBoxedVal>,
@@ -39,6 +44,14 @@ pub struct VMState {
     /// Values that were logged to the EVM's logging subsystem.
     logged_values: Vec<RuntimeBoxedVal>,
 
+    /// The copies from the contract's code into memory that were performed
+    /// with entirely concrete operands.
+    code_copies: Vec<CodeCopyRecord>,
+
+    /// The concrete range of memory that was returned from execution, if the
+    /// thread returned with concrete operands.
+    returned_memory: Option<Range<usize>>,
+
     /// The virtual machine's configuration.
     config: Config,
 
@@ -63,8 +76,11 @@ impl VMState {
         let stack = Stack::new();
         let memory = Memory::new(config.single_memory_operation_size_limit);
         let storage = Storage::new();
+        let transient_storage = Storage::new_transient();
         let recorded_values = Vec::default();
         let logged_values = Vec::default();
+        let code_copies = Vec::default();
+        let returned_memory = None;
         let visited_instructions =
             VisitedOpcodes::new(instructions_len, config.maximum_iterations_per_opcode);
 
@@ -73,8 +89,11 @@ impl VMState {
             stack,
             memory,
             storage,
+            transient_storage,
             recorded_values,
             logged_values,
+            code_copies,
+            returned_memory,
             config,
             visited_instructions,
         }
@@ -123,6 +142,18 @@ impl VMState {
         &mut self.storage
     }
 
+    /// Gets the transient storage associated with this virtual machine state.
+    #[must_use]
+    pub fn transient_storage(&self) -> &Storage {
+        &self.transient_storage
+    }
+
+    /// Gets the transient storage associated with this virtual machine state.
+    #[must_use]
+    pub fn transient_storage_mut(&mut self) -> &mut Storage {
+        &mut self.transient_storage
+    }
+
     /// Gets the structure that tracks whether a given opcode has been visited
     /// by the current thread of execution.
     #[must_use]
@@ -165,6 +196,51 @@ impl VMState {
         self.logged_values.as_slice()
     }
 
+    /// Records that the range of code described by `record` was copied into
+    /// memory.
+    pub fn record_code_copy(&mut self, record: CodeCopyRecord) {
+        self.code_copies.push(record);
+    }
+
+    /// Gets the concrete copies from the contract's code into memory that have
+    /// been performed on this thread.
+    #[must_use]
+    pub fn code_copies(&self) -> &[CodeCopyRecord] {
+        self.code_copies.as_slice()
+    }
+
+    /// Records that execution returned the provided concrete `range` of
+    /// memory.
+    pub fn record_returned_memory(&mut self, range: Range<usize>) {
+        self.returned_memory = Some(range);
+    }
+
+    /// Gets the concrete range of memory that was returned by this thread, if
+    /// any.
+    #[must_use]
+    pub fn returned_memory(&self) -> Option<&Range<usize>> {
+        self.returned_memory.as_ref()
+    }
+
+    /// Gets the range of the contract's code that was returned by this thread,
+    /// if the returned memory is entirely covered by a single concrete code
+    /// copy.
+    ///
+    /// This is the pattern used by constructors to return the runtime code of
+    /// the contract being deployed.
+    #[must_use]
+    pub fn returned_code(&self) -> Option<Range<usize>> {
+        let returned = self.returned_memory.as_ref()?;
+        self.code_copies.iter().rev().find_map(|copy| {
+            let covers_start = copy.memory_offset <= returned.start;
+            let covers_end = copy.memory_offset.saturating_add(copy.size) >= returned.end;
+            (covers_start && covers_end && !returned.is_empty()).then(|| {
+                let start = copy.code_offset.saturating_add(returned.start - copy.memory_offset);
+                start..start.saturating_add(returned.len())
+            })
+        })
+    }
+
     /// Gets the point in the instruction stream, specifically the value of the
     /// instruction pointer, at which this VM state was forked.
     #[must_use]
@@ -185,6 +261,26 @@ impl VMState {
         fork
     }
 
+    /// Merges the `other` state into this one at `instruction_pointer`, where
+    /// the threads of execution that hold them have converged.
+    ///
+    /// Locations in the stack, memory, and storage that hold differing values
+    /// are given a choice between those values, while the values recorded by
+    /// both threads are all retained. The visit counts of the merged state are
+    /// the greater of the counts in the two states.
+    pub fn merge(&mut self, other: VMState, instruction_pointer: u32) {
+        self.stack.merge(&other.stack, instruction_pointer);
+        self.memory.merge(other.memory, instruction_pointer);
+        self.storage.merge(other.storage, instruction_pointer);
+        self.transient_storage
+            .merge(other.transient_storage, instruction_pointer);
+        merge_values(&mut self.recorded_values, other.recorded_values);
+        merge_values(&mut self.logged_values, other.logged_values);
+        merge_values(&mut self.code_copies, other.code_copies);
+        self.returned_memory = self.returned_memory.take().or(other.returned_memory);
+        self.visited_instructions.merge(&other.visited_instructions);
+    }
+
     /// Consumes the state and produces all of the values that are registered in
     /// the virtual machine state.
     #[must_use]
@@ -193,6 +289,7 @@ impl VMState {
         values.extend(self.stack.all_values());
         values.extend(self.memory.all_values());
         values.extend(self.storage.stores_as_values());
+        values.extend(self.transient_storage.stores_as_values());
         values.extend(self.recorded_values);
         values.extend(self.logged_values);
 
@@ -200,10 +297,62 @@ impl VMState {
     }
 }
 
+/// Merges the generations of writes in `theirs` into the generations in `ours`,
+/// retaining every write from both.
+///
+/// If the most-recent writes in each differ, the result of calling `choose` on
+/// them is written as the newest generation.
+pub fn merge_generations<T>(ours: &mut Vec<T>, theirs: Vec<T>, choose: impl FnOnce(&T, &T) -> T)
+where
+    T: Clone + Eq + Hash,
+{
+    let latest = match (ours.last(), theirs.last()) {
+        (Some(our_latest), Some(their_latest)) if our_latest != their_latest => {
+            Some(choose(our_latest, their_latest))
+        }
+        (None, Some(their_latest)) => Some(their_latest.clone()),
+        _ => ours.last().cloned(),
+    };
+    merge_values(ours, theirs);
+
+    if let Some(latest) = latest {
+        if ours.last() != Some(&latest) {
+            ours.push(latest);
+        }
+    }
+}
+
+/// Appends the items in `theirs` that are not already in `ours` to `ours`.
+fn merge_values<T>(ours: &mut Vec<T>, theirs: Vec<T>)
+where
+    T: Clone + Eq + Hash,
+{
+    let mut seen: HashSet<T> = ours.iter().cloned().collect();
+    for item in theirs {
+        if seen.insert(item.clone()) {
+            ours.push(item);
+        }
+    }
+}
+
+/// A record of a copy from the contract's code into memory where all of the
+/// operands were concrete.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct CodeCopyRecord {
+    /// The offset in memory to which the code was copied.
+    pub memory_offset: usize,
+
+    /// The offset in the code from which the copy started.
+    pub code_offset: usize,
+
+    /// The number of bytes that were copied.
+    pub size: usize,
+}
+
 #[cfg(test)]
 mod test {
     use crate::vm::{
-        state::VMState,
+        state::{CodeCopyRecord, VMState},
         value::{RSV, RSVD},
         Config,
     };
@@ -250,4 +399,30 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn can_find_returned_code() {
+        let mut state = VMState::new_at_start(20, Config::default());
+        state.record_code_copy(CodeCopyRecord {
+            memory_offset: 0,
+            code_offset:   0x1f,
+            size:          0x40,
+        });
+        state.record_returned_memory(0..0x40);
+
+        assert_eq!(state.returned_code(), Some(0x1f..0x5f));
+    }
+
+    #[test]
+    fn returns_no_code_without_a_covering_copy() {
+        let mut state = VMState::new_at_start(20, Config::default());
+        state.record_code_copy(CodeCopyRecord {
+            memory_offset: 0x20,
+            code_offset:   0x1f,
+            size:          0x40,
+        });
+        state.record_returned_memory(0..0x40);
+
+        assert_eq!(state.returned_code(), None);
+    }
 }
@@ -7,7 +7,7 @@ use crate::{
         container::Locatable,
         execution::{Error, Result},
     },
-    vm::value::RuntimeBoxedVal,
+    vm::value::{RuntimeBoxedVal, RSV},
 };
 
 /// The representation of the symbolic virtual machine's stack.
@@ -122,6 +122,19 @@ impl Stack {
         self.data.len()
     }
 
+    /// Gets the values on the stack, ordered from the bottom of the stack.
+    #[must_use]
+    pub fn values(&self) -> &[RuntimeBoxedVal] {
+        self.data.as_slice()
+    }
+
+    /// Gets the values on the stack for modification in place, ordered from
+    /// the bottom of the stack.
+    #[must_use]
+    pub fn values_mut(&mut self) -> &mut [RuntimeBoxedVal] {
+        self.data.as_mut_slice()
+    }
+
     /// Checks if the stack is empty.
     #[must_use]
     pub fn is_empty(&self) -> bool {
@@ -168,6 +181,20 @@ impl Stack {
         }
     }
 
+    /// Merges the `other` stack into this one at `instruction_pointer`, where
+    /// the threads of execution that hold them have converged.
+    ///
+    /// Any frame that differs between the two stacks is replaced by a choice
+    /// between the two values. The stacks are expected to have the same depth,
+    /// and any frames beyond the depth of the shallower one are left alone.
+    pub fn merge(&mut self, other: &Stack, instruction_pointer: u32) {
+        for (ours, theirs) in self.data.iter_mut().zip(&other.data) {
+            if ours != theirs {
+                *ours = RSV::new_choice(instruction_pointer, ours.clone(), theirs.clone());
+            }
+        }
+    }
+
     /// Consumes the virtual machine stack and returns all of the values that it
     /// knows about.
     #[must_use]
@@ -1,9 +1,13 @@
 //! This module contains the definition of the virtual machine's storage
 //! container.
 
-use std::collections::HashMap;
+use std::collections::{hash_map::Entry, HashMap};
 
-use crate::vm::value::{Provenance, RuntimeBoxedVal, RSV, RSVD};
+use crate::vm::{
+    fingerprint::{fingerprint_unordered, Fingerprint},
+    state::merge_generations,
+    value::{Provenance, RuntimeBoxedVal, RSV, RSVD},
+};
 
 /// A representation of the persistent storage of the symbolic virtual machine.
 ///
@@ -22,8 +26,20 @@ use crate::vm::value::{Provenance, RuntimeBoxedVal, RSV, RSVD};
 /// Each storage location stores the total history of writes made to it during
 /// the course of a given thread of execution. You can call the `generations`
 /// method to get at these for a given key.
+///
+/// # Transient Storage
+///
+/// The same model is used for the transient storage introduced in
+/// [EIP-1153](https://eips.ethereum.org/EIPS/eip-1153), which is addressed in
+/// the same way but discarded at the end of each transaction. The
+/// [`StorageKind`] of the storage determines which symbolic values are used to
+/// represent its loads and stores, thereby ensuring that transient slots are
+/// never confused with persistent ones.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub struct Storage {
+    /// The kind of storage being modelled.
+    kind: StorageKind,
+
     /// Many storage writes are in the form of a known slot index.
     known_slots: HashMap<RuntimeBoxedVal, Vec<RuntimeBoxedVal>>,
 
@@ -32,17 +48,36 @@ pub struct Storage {
 }
 
 impl Storage {
-    /// Creates a new, empty storage.
+    /// Creates a new, empty, persistent storage.
     #[must_use]
     pub fn new() -> Self {
+        Self::new_of_kind(StorageKind::Persistent)
+    }
+
+    /// Creates a new, empty, transient storage.
+    #[must_use]
+    pub fn new_transient() -> Self {
+        Self::new_of_kind(StorageKind::Transient)
+    }
+
+    /// Creates a new, empty storage of the provided `kind`.
+    #[must_use]
+    pub fn new_of_kind(kind: StorageKind) -> Self {
         let fixed_slots = HashMap::new();
         let symbolic_slots = HashMap::new();
         Self {
+            kind,
             known_slots: fixed_slots,
             symbolic_slots,
         }
     }
 
+    /// Gets the kind of storage being modelled.
+    #[must_use]
+    pub fn kind(&self) -> StorageKind {
+        self.kind
+    }
+
     /// Stores the provided `value` in storage at the provided `key`,
     /// overwriting any existing value at that key for the purposes of
     /// execution.
@@ -81,17 +116,10 @@ impl Storage {
 
         // Once we have that we can pull the key out, or default in the map if it
         // doesn't exist
-        let entry = target_map.entry(key.clone()).or_insert_with(|| {
-            // The instruction pointer is 0 here, as the uninitialized value was created
-            // when the program started. It is _not_ synthetic.
-
-            vec![RSV::new(
-                0,
-                RSVD::UnwrittenStorageValue { key: key.clone() },
-                Provenance::NonWrittenStorage,
-                None,
-            )]
-        });
+        let kind = self.kind;
+        let entry = target_map
+            .entry(key.clone())
+            .or_insert_with(|| vec![Self::unwritten_value(kind, key)]);
 
         let most_recent = entry
             .last()
@@ -104,21 +132,42 @@ impl Storage {
         // Knowing this is very important in the context of deciding which portions of
         // storage slots are not truly used, as they will contain near-direct loads from
         // the same slot.
+        let data = match (kind, most_recent.data()) {
+            (StorageKind::Persistent, RSVD::SLoad { .. })
+            | (StorageKind::Transient, RSVD::TLoad { .. }) => most_recent.data().clone(),
+            (StorageKind::Persistent, _) => RSVD::SLoad {
+                key:   key.clone(),
+                value: most_recent.clone(),
+            },
+            (StorageKind::Transient, _) => RSVD::TLoad {
+                key:   key.clone(),
+                value: most_recent.clone(),
+            },
+        };
         RSV::new(
             most_recent.instruction_pointer(),
-            if let RSVD::SLoad { .. } = &most_recent.data() {
-                most_recent.data().clone()
-            } else {
-                RSVD::SLoad {
-                    key:   key.clone(),
-                    value: most_recent.clone(),
-                }
-            },
+            data,
             most_recent.provenance(),
             None,
         )
     }
 
+    /// Creates the value held at `key` in storage of the provided `kind` before
+    /// that key is written to during the current execution.
+    #[allow(clippy::unnecessary_box_returns)] // We use boxes everywhere during execution
+    #[must_use]
+    fn unwritten_value(kind: StorageKind, key: &RuntimeBoxedVal) -> RuntimeBoxedVal {
+        // The instruction pointer is 0 here, as the uninitialized value was created
+        // when the program started. It is _not_ synthetic.
+        let key = key.clone();
+        let data = match kind {
+            StorageKind::Persistent => RSVD::UnwrittenStorageValue { key },
+            StorageKind::Transient => RSVD::UnwrittenTransientStorageValue { key },
+        };
+
+        RSV::new(0, data, Provenance::NonWrittenStorage, None)
+    }
+
     /// Gets all of the stores that were made at the provided `key` during
     /// the course of execution.
     ///
@@ -134,6 +183,79 @@ impl Storage {
         target_map.get(key).map(|generations| generations.iter().collect())
     }
 
+    /// Merges the `other` storage into this one at `instruction_pointer`, where
+    /// the threads of execution that hold them have converged.
+    ///
+    /// The generations written by both threads are retained at every key. Where
+    /// the most-recent values at a key differ, a choice between them is written
+    /// as the newest generation.
+    ///
+    /// A key that was only written by one of the threads still holds its
+    /// unwritten value in the other, and so is merged as a choice between the
+    /// written value and the unwritten one.
+    pub fn merge(&mut self, other: Storage, instruction_pointer: u32) {
+        let kind = self.kind;
+        let choose = |ours: &RuntimeBoxedVal, theirs: &RuntimeBoxedVal| {
+            RSV::new_choice(instruction_pointer, ours.clone(), theirs.clone())
+        };
+
+        for (key, generations) in self.known_slots.iter_mut().chain(&mut self.symbolic_slots) {
+            if other.generations(key).is_none() {
+                let unwritten = vec![Self::unwritten_value(kind, key)];
+                merge_generations(generations, unwritten, choose);
+            }
+        }
+
+        for (key, generations) in other.known_slots.into_iter().chain(other.symbolic_slots) {
+            let target_map = match key.data() {
+                RSVD::KnownData { .. } => &mut self.known_slots,
+                _ => &mut self.symbolic_slots,
+            };
+            match target_map.entry(key) {
+                Entry::Occupied(mut entry) => {
+                    merge_generations(entry.get_mut(), generations, choose);
+                }
+                Entry::Vacant(entry) => {
+                    let mut ours = vec![Self::unwritten_value(kind, entry.key())];
+                    merge_generations(&mut ours, generations, choose);
+                    entry.insert(ours);
+                }
+            }
+        }
+    }
+
+    /// Creates a copy of the storage that retains only the most-recent value
+    /// written at each key.
+    #[must_use]
+    pub fn latest(&self) -> Self {
+        let latest = |slots: &HashMap<RuntimeBoxedVal, Vec<RuntimeBoxedVal>>| {
+            slots
+                .iter()
+                .map(|(k, vs)| (k.clone(), vs.last().into_iter().cloned().collect()))
+                .collect()
+        };
+        let kind = self.kind;
+        let known_slots = latest(&self.known_slots);
+        let symbolic_slots = latest(&self.symbolic_slots);
+        Self {
+            kind,
+            known_slots,
+            symbolic_slots,
+        }
+    }
+
+    /// Computes a fingerprint of the most-recent value written at each key in
+    /// the storage.
+    #[must_use]
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint_unordered(
+            self.known_slots
+                .iter()
+                .chain(&self.symbolic_slots)
+                .map(|(key, generations)| (key, generations.last())),
+        )
+    }
+
     /// Gets the number of entries in the storage.
     #[must_use]
     pub fn entry_count(&self) -> usize {
@@ -172,11 +294,13 @@ impl Storage {
     /// within the storage.
     ///
     /// Here, each `key -> value` pair, accounting for generations, is wrapped
-    /// into [`RSVD::StorageWrite`] of `(key, value)`, allowing for easier
-    /// analysis later.
+    /// into [`RSVD::StorageWrite`] (or [`RSVD::TransientStorageWrite`] for
+    /// transient storage) of `(key, value)`, allowing for easier analysis
+    /// later.
     #[must_use]
     pub fn stores_as_values(self) -> Vec<RuntimeBoxedVal> {
         let mut all_values: Vec<RuntimeBoxedVal> = Vec::new();
+        let kind = self.kind;
 
         self.known_slots
             .into_iter()
@@ -184,15 +308,13 @@ impl Storage {
             .for_each(|(k, vs)| {
                 all_values.extend(vs.into_iter().map(|v| {
                     let provenance = v.provenance();
-                    RSV::new(
-                        v.instruction_pointer(),
-                        RSVD::StorageWrite {
-                            key:   k.clone(),
-                            value: v,
-                        },
-                        provenance,
-                        None,
-                    )
+                    let instruction_pointer = v.instruction_pointer();
+                    let key = k.clone();
+                    let data = match kind {
+                        StorageKind::Persistent => RSVD::StorageWrite { key, value: v },
+                        StorageKind::Transient => RSVD::TransientStorageWrite { key, value: v },
+                    };
+                    RSV::new(instruction_pointer, data, provenance, None)
                 }));
             });
 
@@ -206,6 +328,18 @@ impl Default for Storage {
     }
 }
 
+/// The kinds of storage that can be modelled by [`Storage`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum StorageKind {
+    /// The persistent storage of the contract, accessed using `SLOAD` and
+    /// `SSTORE`.
+    Persistent,
+
+    /// The transient storage of the contract, accessed using `TLOAD` and
+    /// `TSTORE`.
+    Transient,
+}
+
 #[cfg(test)]
 mod test {
     use crate::vm::{
@@ -357,4 +491,69 @@ mod test {
         let generations = storage.generations(&key).unwrap();
         assert_eq!(generations, vec![&value_1, &value_2]);
     }
+
+    #[test]
+    fn merges_keys_written_on_one_side_with_the_unwritten_value() {
+        let ours_key = new_synthetic_value(0);
+        let theirs_key = new_synthetic_value(1);
+        let ours_value = new_synthetic_value(2);
+        let theirs_value = new_synthetic_value(3);
+
+        let mut ours = Storage::new();
+        ours.store(ours_key.clone(), ours_value.clone());
+        let mut theirs = Storage::new();
+        theirs.store(theirs_key.clone(), theirs_value.clone());
+        ours.merge(theirs, 4);
+
+        for (key, written, written_first) in [
+            (ours_key, ours_value, true),
+            (theirs_key, theirs_value, false),
+        ] {
+            let RSVD::SLoad { value, .. } = ours.load(&key).data().clone() else {
+                panic!("Invalid payload")
+            };
+            let RSVD::Choice { alternatives } = value.data() else {
+                panic!("Invalid payload")
+            };
+            let unwritten_index = usize::from(written_first);
+            assert_eq!(alternatives.len(), 2);
+            assert_eq!(alternatives[1 - unwritten_index], written);
+            assert!(matches!(
+                alternatives[unwritten_index].data(),
+                RSVD::UnwrittenStorageValue { key: unwritten } if unwritten == &key
+            ));
+        }
+    }
+
+    #[test]
+    fn transient_storage_uses_transient_values() {
+        let mut storage = Storage::new_transient();
+        let input_key = new_synthetic_value(0);
+        let unwritten_key = new_synthetic_value(1);
+        let input_value = new_synthetic_value(2);
+        storage.store(input_key.clone(), input_value.clone());
+
+        match storage.load(&input_key).data() {
+            RSVD::TLoad { key, value } => {
+                assert_eq!(key, &input_key);
+                assert_eq!(value, &input_value);
+            }
+            _ => panic!("Invalid payload"),
+        }
+        match storage.load(&unwritten_key).data() {
+            RSVD::TLoad { value, .. } => assert!(matches!(
+                value.data(),
+                RSVD::UnwrittenTransientStorageValue { key } if key == &unwritten_key
+            )),
+            _ => panic!("Invalid payload"),
+        }
+
+        let stores = storage.stores_as_values();
+        assert_eq!(stores.len(), 2);
+        assert!(
+            stores
+                .iter()
+                .all(|store| matches!(store.data(), RSVD::TransientStorageWrite { .. }))
+        );
+    }
 }
@@ -1,7 +1,16 @@
 //! This module contains the definition of the [`VMThread`] type, representing
 //! the divergent execution paths that can be taken during symbolic execution.
 
-use crate::{disassembly::ExecutionThread, vm::state::VMState};
+use crate::{
+    disassembly::ExecutionThread,
+    vm::{
+        call::CallStack,
+        solver::PathCondition,
+        state::VMState,
+        trace::ThreadId,
+        widening::LoopTracker,
+    },
+};
 
 /// A `VMThread` is a representation of a given execution path during the course
 /// of symbolic execution.
@@ -10,6 +19,9 @@ use crate::{disassembly::ExecutionThread, vm::state::VMState};
 /// but contains no logic for performing that execution itself.
 #[derive(Clone, Debug)]
 pub struct VMThread {
+    /// The identifier of this thread of execution.
+    id: ThreadId,
+
     /// The virtual machine's state for this thread of execution.
     state: VMState,
 
@@ -18,6 +30,24 @@ pub struct VMThread {
 
     /// The amount of gas used in executing this thread.
     gas_usage: usize,
+
+    /// The number of instructions executed by this thread and the threads that
+    /// it was forked from.
+    steps: usize,
+
+    /// The conditions under which this thread reached its current position.
+    path_condition: PathCondition,
+
+    /// The internal functions that this thread is currently executing.
+    call_stack: CallStack,
+
+    /// The function selector that the dispatcher sent this thread to, if it has
+    /// been dispatched to a function.
+    selector: Option<u32>,
+
+    /// The loops that this thread and the threads that it was forked from have
+    /// iterated over.
+    loops: LoopTracker,
 }
 
 impl VMThread {
@@ -25,11 +55,23 @@ impl VMThread {
     /// state at `thread.instruction_pointer()`.
     #[must_use]
     pub fn new(state: VMState, thread: ExecutionThread) -> Self {
+        let id = 0;
         let gas_usage = 0;
+        let steps = 0;
+        let path_condition = PathCondition::new();
+        let call_stack = CallStack::new();
+        let selector = None;
+        let loops = LoopTracker::new();
         Self {
+            id,
             state,
             thread,
             gas_usage,
+            steps,
+            path_condition,
+            call_stack,
+            selector,
+            loops,
         }
     }
 
@@ -65,21 +107,59 @@ impl VMThread {
     ///
     /// This method performs no validation as to whether the fork point is a
     /// valid fork point. This is up to the VM itself.
+    ///
+    /// The new thread shares the identifier of the current thread until it is
+    /// given its own by [`Self::set_id`].
     #[must_use]
     pub fn fork(&self, target: u32) -> Self {
+        let id = self.id;
         let instruction_pointer = self.thread.instruction_pointer();
         let state = self.state.fork(instruction_pointer);
         let gas_usage = self.gas_usage;
+        let steps = self.steps;
+        let path_condition = self.path_condition.clone();
+        let call_stack = self.call_stack.clone();
+        let selector = self.selector;
+        let loops = self.loops.clone();
         let mut thread = self.thread.clone();
         thread.at(target);
 
         Self {
+            id,
             state,
             thread,
             gas_usage,
+            steps,
+            path_condition,
+            call_stack,
+            selector,
+            loops,
         }
     }
 
+    /// Gets the identifier of this thread.
+    #[must_use]
+    pub fn id(&self) -> ThreadId {
+        self.id
+    }
+
+    /// Sets the identifier of this thread to `id`.
+    pub fn set_id(&mut self, id: ThreadId) {
+        self.id = id;
+    }
+
+    /// Gets the number of instructions executed by this thread and the threads
+    /// that it was forked from.
+    #[must_use]
+    pub fn steps(&self) -> usize {
+        self.steps
+    }
+
+    /// Records that this thread has executed an instruction.
+    pub fn count_step(&mut self) {
+        self.steps += 1;
+    }
+
     /// Adds the provided `gas` to the gas usage of this thread.
     pub fn consume_gas(&mut self, gas: usize) {
         self.gas_usage += gas;
@@ -90,6 +170,100 @@ impl VMThread {
     pub fn gas_usage(&self) -> usize {
         self.gas_usage
     }
+
+    /// Gets the conditions under which this thread reached its current
+    /// position.
+    #[must_use]
+    pub fn path_condition(&self) -> &PathCondition {
+        &self.path_condition
+    }
+
+    /// Gets the conditions under which this thread reached its current
+    /// position.
+    #[must_use]
+    pub fn path_condition_mut(&mut self) -> &mut PathCondition {
+        &mut self.path_condition
+    }
+
+    /// Gets the internal functions that this thread is currently executing.
+    #[must_use]
+    pub fn call_stack(&self) -> &CallStack {
+        &self.call_stack
+    }
+
+    /// Gets the internal functions that this thread is currently executing.
+    #[must_use]
+    pub fn call_stack_mut(&mut self) -> &mut CallStack {
+        &mut self.call_stack
+    }
+
+    /// Gets the function selector that the dispatcher sent this thread to, if
+    /// it has been dispatched to a function.
+    #[must_use]
+    pub fn selector(&self) -> Option<u32> {
+        self.selector
+    }
+
+    /// Records that the dispatcher sent this thread to the function with the
+    /// provided `selector`.
+    ///
+    /// A thread is only ever dispatched once, so this does nothing if the
+    /// thread already has a selector.
+    pub fn dispatch_to(&mut self, selector: u32) {
+        self.selector.get_or_insert(selector);
+    }
+
+    /// Checks whether `other` can be merged into this thread.
+    ///
+    /// This is the case when both threads are at the same position with stacks
+    /// of the same depth, are inside calls to the same internal functions, and
+    /// have iterated over the loops that they are inside in the same way.
+    ///
+    /// Threads that differ in their loop iterations are kept apart, as the
+    /// merged thread would otherwise widen its values against the iterations of
+    /// only one of them.
+    #[must_use]
+    pub fn can_merge(&self, other: &VMThread) -> bool {
+        self.thread.instruction_pointer() == other.thread.instruction_pointer()
+            && self.state.stack().depth() == other.state.stack().depth()
+            && self.call_stack.is_compatible_with(&other.call_stack)
+            && self.loops == other.loops
+    }
+
+    /// Records that this thread has completed an iteration of the loop with its
+    /// header at `header`, widening the values on its stack that are carried
+    /// between iterations.
+    ///
+    /// Returns the number of values on the stack that are in induction form as
+    /// a result.
+    pub fn complete_loop_iteration(&mut self, header: u32) -> usize {
+        self.loops.complete_iteration(header, self.state.stack_mut())
+    }
+
+    /// Records that this thread has reached `offset` other than by completing
+    /// an iteration of the loop with its header there, forgetting its previous
+    /// iterations of any loop that it is thereby entering afresh.
+    pub fn enter_loop(&mut self, offset: u32) {
+        self.loops.enter(offset);
+    }
+
+    /// Merges `other` into this thread, such that this thread represents the
+    /// execution of both from the current position onwards.
+    ///
+    /// The merged thread keeps the identifier of this thread, and is only
+    /// constrained by the conditions shared by the paths of both threads. It is
+    /// up to the caller to check that the threads [`Self::can_merge`].
+    pub fn merge(&mut self, other: VMThread) {
+        let instruction_pointer = self.thread.instruction_pointer();
+        self.gas_usage = self.gas_usage.max(other.gas_usage);
+        self.steps = self.steps.max(other.steps);
+        self.path_condition.join(&other.path_condition);
+        self.call_stack.merge(&other.call_stack);
+        if self.selector != other.selector {
+            self.selector = None;
+        }
+        self.state.merge(other.state, instruction_pointer);
+    }
 }
 
 impl From<VMThread> for VMState {
@@ -0,0 +1,400 @@
+//! This module contains the recording of execution traces from the virtual
+//! machine, and the replay of individual threads from those traces.
+//!
+//! When [`crate::vm::Config::trace_recording`] is enabled, the virtual machine
+//! logs an event for every instruction that each thread executes, along with
+//! the points at which threads fork and die. Any thread in the trace can then
+//! be re-executed on its own using [`replay`], which makes it possible to
+//! reproduce the circumstances in which a particular symbolic value was built
+//! without wading through the execution of every other thread.
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constant::TRACE_VALUE_SUMMARY_MAX_CHARS,
+    disassembly::InstructionStream,
+    error::execution::Errors,
+    vm::{value::RuntimeBoxedVal, Config, ExecutionResult, VM},
+    watchdog::DynWatchdog,
+};
+
+/// The identifier of a thread of execution in the virtual machine.
+///
+/// The initial thread is always identified by zero, and each subsequent thread
+/// is numbered in the order that it was created.
+pub type ThreadId = u32;
+
+/// An event that occurred during the execution of the virtual machine.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum TraceEvent {
+    /// A thread executed an instruction.
+    Executed {
+        /// The thread that executed the instruction.
+        thread: ThreadId,
+
+        /// The number of instructions executed by the thread and its ancestors
+        /// before this one.
+        step: usize,
+
+        /// The offset of the instruction.
+        offset: u32,
+
+        /// The textual representation of the instruction.
+        opcode: String,
+
+        /// Summaries of the values that the instruction took from the stack,
+        /// ordered from the top of the stack.
+        popped: Vec<String>,
+
+        /// Summaries of the values that the instruction left on the stack,
+        /// ordered from the top of the stack.
+        pushed: Vec<String>,
+
+        /// The gas used by the thread after executing the instruction.
+        gas: usize,
+    },
+
+    /// A thread forked a new thread while executing an instruction.
+    Forked {
+        /// The thread that forked.
+        thread: ThreadId,
+
+        /// The step at which the thread forked.
+        step: usize,
+
+        /// The offset of the instruction that caused the fork.
+        offset: u32,
+
+        /// The newly-created thread.
+        child: ThreadId,
+
+        /// The offset at which the new thread starts executing.
+        target: u32,
+    },
+
+    /// A thread encountered an error while executing an instruction.
+    Errored {
+        /// The thread that encountered the error.
+        thread: ThreadId,
+
+        /// The offset of the instruction that caused the error.
+        offset: u32,
+
+        /// The description of the error.
+        message: String,
+    },
+
+    /// A thread was merged into another thread that converged on the same
+    /// position, and stopped executing on its own.
+    Merged {
+        /// The thread that the merged thread was merged into.
+        thread: ThreadId,
+
+        /// The offset at which the threads converged.
+        offset: u32,
+
+        /// The thread that was merged.
+        merged: ThreadId,
+    },
+
+    /// A thread stopped executing.
+    Killed {
+        /// The thread that stopped.
+        thread: ThreadId,
+
+        /// The offset of the last instruction executed by the thread.
+        offset: u32,
+
+        /// The reason that the thread stopped.
+        reason: KillReason,
+    },
+}
+
+/// The reasons for which a thread of execution can stop.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KillReason {
+    /// The thread executed an instruction that halts execution, or that it
+    /// could not continue past.
+    Halted,
+
+    /// The thread ran out of gas.
+    OutOfGas,
+
+    /// The thread ran off the end of the bytecode.
+    EndOfCode,
+
+    /// The thread reached the maximum number of visits to an instruction.
+    IterationLimit,
+
+    /// The thread reached a state that had already been explored by another
+    /// thread.
+    Duplicate,
+}
+
+/// A trace of the events that occurred during the execution of the virtual
+/// machine, in the order that they occurred.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Trace {
+    events: Vec<TraceEvent>,
+}
+
+impl Trace {
+    /// Constructs a new trace containing no events.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that `event` occurred.
+    pub fn record(&mut self, event: TraceEvent) {
+        self.events.push(event);
+    }
+
+    /// Gets the events in the trace, in the order that they occurred.
+    #[must_use]
+    pub fn events(&self) -> &[TraceEvent] {
+        self.events.as_slice()
+    }
+
+    /// Gets the events in the trace that concern the provided `thread`.
+    pub fn thread_events(&self, thread: ThreadId) -> impl Iterator<Item = &TraceEvent> {
+        self.events.iter().filter(move |event| match event {
+            TraceEvent::Executed { thread: t, .. }
+            | TraceEvent::Forked { thread: t, .. }
+            | TraceEvent::Errored { thread: t, .. }
+            | TraceEvent::Merged { thread: t, .. }
+            | TraceEvent::Killed { thread: t, .. } => *t == thread,
+        })
+    }
+
+    /// Computes the path taken from the start of execution by the provided
+    /// `thread`, returning [`None`] if the thread does not occur in the trace.
+    #[must_use]
+    pub fn path_to(&self, thread: ThreadId) -> Option<ThreadPath> {
+        let parents: HashMap<_, _> = self
+            .events
+            .iter()
+            .filter_map(|event| match event {
+                TraceEvent::Forked {
+                    thread,
+                    step,
+                    child,
+                    target,
+                    ..
+                } => Some((*child, (*thread, PathFork::new(*step, *target)))),
+                _ => None,
+            })
+            .collect();
+        if thread != 0 && !parents.contains_key(&thread) {
+            return None;
+        }
+
+        let mut forks = Vec::new();
+        let mut current = thread;
+        while let Some((parent, fork)) = parents.get(&current) {
+            forks.push(*fork);
+            current = *parent;
+        }
+        forks.reverse();
+
+        Some(ThreadPath { forks })
+    }
+
+    /// Gets the number of events in the trace.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Checks whether the trace contains no events.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+/// The path taken by a thread from the start of execution, given by the forks
+/// that created it and each of its ancestors.
+///
+/// A fork is identified by the step at which it occurred rather than by the
+/// threads involved, so that the path remains valid when other threads are not
+/// executed.
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ThreadPath {
+    /// The forks along the path, in the order that they occurred.
+    pub forks: Vec<PathFork>,
+}
+
+/// A fork along the path taken by a thread.
+#[derive(Copy, Clone, Debug, Deserialize, Eq, Hash, PartialEq, Serialize)]
+pub struct PathFork {
+    /// The step at which the fork occurred.
+    pub step: usize,
+
+    /// The offset at which the forked thread starts executing.
+    pub target: u32,
+}
+
+impl PathFork {
+    /// Constructs a new fork that occurred at `step` and continued at
+    /// `target`.
+    #[must_use]
+    pub fn new(step: usize, target: u32) -> Self {
+        Self { step, target }
+    }
+}
+
+/// The progress of the virtual machine along a [`ThreadPath`] that it is
+/// replaying.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct Replay {
+    path:     ThreadPath,
+    position: usize,
+}
+
+impl Replay {
+    /// Constructs a new replay of `path` that has not yet followed any forks.
+    #[must_use]
+    pub fn new(path: ThreadPath) -> Self {
+        let position = 0;
+        Self { path, position }
+    }
+
+    /// Gets the next fork that the replay needs to follow, if there is one.
+    #[must_use]
+    pub fn next_fork(&self) -> Option<PathFork> {
+        self.path.forks.get(self.position).copied()
+    }
+
+    /// Records that the next fork in the path has been followed.
+    pub fn follow_fork(&mut self) {
+        self.position += 1;
+    }
+}
+
+/// Summarises `value` for inclusion in a trace, truncating its textual
+/// representation to at most [`TRACE_VALUE_SUMMARY_MAX_CHARS`] characters.
+#[must_use]
+pub fn summarize(value: &RuntimeBoxedVal) -> String {
+    let text = value.to_string();
+    match text.char_indices().nth(TRACE_VALUE_SUMMARY_MAX_CHARS) {
+        Some((end, _)) => format!("{}...", &text[..end]),
+        None => text,
+    }
+}
+
+/// Re-executes the thread that followed `path` through the provided
+/// `instructions`, without executing any of the other threads.
+///
+/// The resulting execution result contains the final state of that thread
+/// alone. Its values, or the trace of the replay if
+/// [`Config::trace_recording`] is enabled, can be inspected to find out how a
+/// particular symbolic value came about.
+///
+/// The replay reproduces the original execution exactly as long as the
+/// `config` is the same and [`Config::internal_call_summaries`] is disabled,
+/// as summaries depend on the execution of other threads.
+///
+/// # Errors
+///
+/// Returns [`Err`] if the virtual machine could not be constructed, or if
+/// execution stopped before the thread finished.
+pub fn replay(
+    instructions: InstructionStream,
+    config: Config,
+    watchdog: DynWatchdog,
+    path: ThreadPath,
+) -> Result<ExecutionResult, Errors> {
+    let mut vm = VM::new(instructions, config, watchdog)?;
+    vm.follow_path(path);
+
+    // Errors encountered by the thread itself are kept in the execution result
+    let outcome = vm.execute();
+    if vm.is_complete() {
+        Ok(vm.consume())
+    } else {
+        outcome.map(|()| vm.consume())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        constant::TRACE_VALUE_SUMMARY_MAX_CHARS,
+        vm::{
+            trace::{summarize, KillReason, PathFork, Trace, TraceEvent},
+            test_util::known,
+            value::{RSV, RSVD},
+        },
+    };
+
+    fn fork(thread: u32, step: usize, child: u32, target: u32) -> TraceEvent {
+        TraceEvent::Forked {
+            thread,
+            step,
+            offset: 0,
+            child,
+            target,
+        }
+    }
+
+    #[test]
+    fn computes_paths_through_forks() {
+        let mut trace = Trace::new();
+        trace.record(fork(0, 3, 1, 0x10));
+        trace.record(fork(0, 7, 2, 0x20));
+        trace.record(fork(1, 12, 3, 0x30));
+
+        assert_eq!(trace.path_to(0).map(|p| p.forks), Some(vec![]));
+        assert_eq!(
+            trace.path_to(2).map(|p| p.forks),
+            Some(vec![PathFork::new(7, 0x20)])
+        );
+        assert_eq!(
+            trace.path_to(3).map(|p| p.forks),
+            Some(vec![PathFork::new(3, 0x10), PathFork::new(12, 0x30)])
+        );
+        assert_eq!(trace.path_to(4), None);
+        assert_eq!(trace.thread_events(1).count(), 1);
+    }
+
+    #[test]
+    fn truncates_long_summaries() {
+        let mut value = known(1);
+        for _ in 0..TRACE_VALUE_SUMMARY_MAX_CHARS {
+            value = RSV::new_synthetic(
+                0,
+                RSVD::Add {
+                    left:  value,
+                    right: known(1),
+                },
+            );
+        }
+
+        let summary = summarize(&value);
+        assert!(summary.ends_with("..."));
+        assert_eq!(summary.chars().count(), TRACE_VALUE_SUMMARY_MAX_CHARS + 3);
+        assert_eq!(summarize(&known(1)), known(1).to_string());
+    }
+
+    #[test]
+    fn traces_can_be_serialized() -> anyhow::Result<()> {
+        let mut trace = Trace::new();
+        trace.record(fork(0, 3, 1, 0x10));
+        trace.record(TraceEvent::Killed {
+            thread: 1,
+            offset: 0x12,
+            reason: KillReason::OutOfGas,
+        });
+
+        let json = serde_json::to_string(&trace)?;
+        let decoded: Trace = serde_json::from_str(&json)?;
+        assert_eq!(decoded, trace);
+
+        Ok(())
+    }
+}
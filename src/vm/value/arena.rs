@@ -0,0 +1,442 @@
+//! This module contains the arena in which runtime symbolic values are
+//! hash-consed.
+//!
+//! The same value trees are often rebuilt identically by many threads of
+//! execution, and again by the lifting passes of the type checker. Interning a
+//! value in a [`ValueArena`] gives it the single [`InternedNode`] that the
+//! arena holds for its structure, so that values interned in the same arena
+//! are equal exactly when they share a node. Comparing them then takes
+//! constant time, however large their trees.
+//!
+//! The instruction pointer and provenance of a value are not part of its
+//! structure, and so are kept by the value rather than by its node. Values
+//! built at different locations can therefore share a node while still
+//! recording where each of them was built.
+
+use std::{
+    collections::HashMap,
+    fmt::{Debug, Formatter},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+        Mutex,
+        MutexGuard,
+        PoisonError,
+        Weak,
+    },
+};
+
+use uuid::Uuid;
+
+use crate::{
+    constant::{ARENA_SHARD_COUNT, MINIMUM_ARENA_SWEEP_INTERVAL},
+    vm::value::{Provenance, RuntimeBoxedVal, RSV, RSVD},
+};
+
+/// The source of the identifiers that tell arenas apart.
+static NEXT_ARENA_ID: AtomicU64 = AtomicU64::new(0);
+
+/// The node that represents a single structure in a [`ValueArena`].
+///
+/// Every value interned in an arena with that structure holds the same node,
+/// which keeps the node in the arena for as long as any of them is in use.
+pub struct InternedNode {
+    /// The identifier of the arena in which the node was interned.
+    arena: u64,
+
+    /// A value with the structure that the node represents, whose children
+    /// are interned in the same arena.
+    ///
+    /// The witness does not itself hold the node, so as not to form a cycle.
+    witness: RuntimeBoxedVal,
+}
+
+impl InternedNode {
+    /// Checks whether values holding the nodes `left` and `right` have the
+    /// same structure, without inspecting the values themselves.
+    ///
+    /// Returns [`None`] if this cannot be determined from the nodes alone, as
+    /// one of the values has not been interned or they were interned in
+    /// different arenas.
+    #[must_use]
+    pub fn same_structure(left: Option<&Arc<Self>>, right: Option<&Arc<Self>>) -> Option<bool> {
+        match (left, right) {
+            (Some(left), Some(right)) if left.arena == right.arena => {
+                Some(Arc::ptr_eq(left, right))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Debug for InternedNode {
+    /// The witness is omitted, as it repeats the structure of the value that
+    /// holds the node.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("InternedNode")
+            .field("arena", &self.arena)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A hash-consing arena for [`RuntimeBoxedVal`]s.
+///
+/// Interning a value returns an equivalent value that holds the arena's node
+/// for its structure, with every child in its tree interned in turn. Interned
+/// values therefore behave exactly like the values they replace, but compare
+/// in constant time with any other value interned in the same arena.
+///
+/// Cloning the arena produces a handle to the _same_ arena, allowing it to be
+/// shared by the virtual machine, its workers, and the type checker. The arena
+/// does not keep any node alive that is no longer held by a value in use
+/// elsewhere, and periodically sweeps such nodes out.
+///
+/// The nodes are split into [`ARENA_SHARD_COUNT`] shards by the digest of
+/// their structure, each with its own lock, so that the handles used by
+/// parallel workers rarely contend with one another.
+#[derive(Clone, Debug)]
+pub struct ValueArena {
+    /// The identifier of the arena, which is shared by all of its handles.
+    id: u64,
+
+    /// The shards holding the nodes interned in the arena.
+    shards: Arc<[Mutex<Nodes>]>,
+
+    /// The identities of the fresh values created at each location with each
+    /// provenance.
+    fresh_values: Arc<Mutex<HashMap<(u32, Provenance), Uuid>>>,
+}
+
+impl ValueArena {
+    /// Constructs a new arena in which no values have been interned.
+    #[must_use]
+    pub fn new() -> Self {
+        let id = NEXT_ARENA_ID.fetch_add(1, Ordering::Relaxed);
+        let shards = (0..ARENA_SHARD_COUNT).map(|_| Mutex::new(Nodes::default())).collect();
+        let fresh_values = Arc::new(Mutex::new(HashMap::new()));
+        Self {
+            id,
+            shards,
+            fresh_values,
+        }
+    }
+
+    /// Interns `value` and every node in its tree, returning the equivalent
+    /// value that holds the arena's node for its structure.
+    ///
+    /// Values that are already interned are returned immediately, so values
+    /// built from interned children only require the new nodes to be
+    /// interned. Only the shard holding each new node is locked while it is
+    /// interned.
+    #[must_use]
+    pub fn intern(&self, value: RuntimeBoxedVal) -> RuntimeBoxedVal {
+        if self.is_interned(&value) {
+            return value;
+        }
+
+        let data = value.data().map_children(&|child| self.intern(child.clone()));
+        let node = self.shard(value.digest()).node_for(self.id, &value, &data);
+
+        with_node(value, data, node)
+    }
+
+    /// Constructs a fresh value created at `instruction_pointer` with the
+    /// specified `provenance`, interned in the arena.
+    ///
+    /// All of the fresh values that the arena creates at the same location
+    /// with the same provenance share their identity, so the values produced
+    /// there by different threads of execution are equal. The exception is the
+    /// values whose provenance reads state that changes as execution proceeds,
+    /// such as the size of memory or the result of a message call, which are
+    /// always given a new identity as they may differ each time the location is
+    /// executed.
+    #[must_use]
+    pub fn value(&self, instruction_pointer: u32, provenance: Provenance) -> RuntimeBoxedVal {
+        let id = match provenance {
+            Provenance::MSize | Provenance::ReturnDataSize | Provenance::MessageCall => {
+                Uuid::new_v4()
+            }
+            _ => *self
+                .fresh_values
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry((instruction_pointer, provenance))
+                .or_insert_with(Uuid::new_v4),
+        };
+        let value = RSV::new(instruction_pointer, RSVD::Value { id }, provenance, None);
+        let node = self.shard(value.digest()).node_for(self.id, &value, value.data());
+        let data = value.data().clone();
+
+        with_node(value, data, node)
+    }
+
+    /// Gets the number of distinct structures that are interned in the arena
+    /// and are still in use.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| {
+                Self::lock(shard)
+                    .buckets
+                    .values()
+                    .flatten()
+                    .filter(|node| node.strong_count() > 0)
+                    .count()
+            })
+            .sum()
+    }
+
+    /// Checks whether there are no interned structures still in use in the
+    /// arena.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks whether `value` has been interned in this arena.
+    fn is_interned(&self, value: &RuntimeBoxedVal) -> bool {
+        value.node.as_ref().is_some_and(|node| node.arena == self.id)
+    }
+
+    /// Locks the shard of the arena that holds the nodes with the provided
+    /// `digest`.
+    #[allow(clippy::cast_possible_truncation)] // The result is less than `shards.len()`
+    fn shard(&self, digest: u64) -> MutexGuard<'_, Nodes> {
+        let index = (digest % self.shards.len() as u64) as usize;
+        Self::lock(&self.shards[index])
+    }
+
+    /// Locks the provided `shard` of the arena.
+    ///
+    /// The arena is never left in an inconsistent state by a panic, so a
+    /// poisoned lock is still safe to use.
+    fn lock(shard: &Mutex<Nodes>) -> MutexGuard<'_, Nodes> {
+        shard.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for ValueArena {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PartialEq for ValueArena {
+    /// Handles to an arena are equal if they refer to the same arena.
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for ValueArena {}
+
+/// The nodes interned in one shard of a [`ValueArena`].
+#[derive(Debug, Default)]
+struct Nodes {
+    /// The nodes in the shard, bucketed by the digest of their structure.
+    ///
+    /// Nodes that are no longer in use remain here until the shard is next
+    /// swept.
+    buckets: HashMap<u64, Vec<Weak<InternedNode>>>,
+
+    /// The number of nodes in `buckets`, including those no longer in use.
+    recorded: usize,
+
+    /// The number of recorded nodes at which the shard is next swept.
+    next_sweep: usize,
+}
+
+impl Nodes {
+    /// Gets the node for the structure of `value` once its children have been
+    /// replaced by those in `data`, which must already be interned in the
+    /// arena identified by `arena`.
+    ///
+    /// If there is no such node, a new one is recorded with `value` as its
+    /// witness.
+    fn node_for(&mut self, arena: u64, value: &RuntimeBoxedVal, data: &RSVD) -> Arc<InternedNode> {
+        let bucket = self.buckets.entry(value.digest()).or_default();
+        let existing = bucket
+            .iter()
+            .filter_map(Weak::upgrade)
+            .find(|node| node.witness.data() == data);
+        if let Some(node) = existing {
+            return node;
+        }
+
+        let witness = Arc::new(RSV {
+            data: data.clone(),
+            node: None,
+            ..value.as_ref().clone()
+        });
+        let node = Arc::new(InternedNode { arena, witness });
+        bucket.push(Arc::downgrade(&node));
+        self.recorded += 1;
+        if self.recorded >= self.next_sweep.max(MINIMUM_ARENA_SWEEP_INTERVAL) {
+            self.sweep();
+        }
+
+        node
+    }
+
+    /// Removes the nodes that are no longer in use from the shard.
+    fn sweep(&mut self) {
+        self.buckets.retain(|_, bucket| {
+            bucket.retain(|node| node.strong_count() > 0);
+            !bucket.is_empty()
+        });
+        self.recorded = self.buckets.values().map(Vec::len).sum();
+        self.next_sweep = self.recorded * 2;
+    }
+}
+
+/// Constructs the value that is `value` with its data replaced by `data` and
+/// holding the provided interned `node`.
+///
+/// The allocation of `value` is reused if it is not shared.
+fn with_node(value: RuntimeBoxedVal, data: RSVD, node: Arc<InternedNode>) -> RuntimeBoxedVal {
+    let value = Arc::try_unwrap(value).unwrap_or_else(|value| value.as_ref().clone());
+    Arc::new(RSV {
+        data,
+        node: Some(node),
+        ..value
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use crate::{
+        constant::{ARENA_SHARD_COUNT, MINIMUM_ARENA_SWEEP_INTERVAL},
+        vm::value::{
+            arena::{InternedNode, ValueArena},
+            known::KnownWord,
+            Provenance,
+            RuntimeBoxedVal,
+            RSV,
+            RSVD,
+        },
+    };
+
+    /// Builds `left + right` at `instruction_pointer`.
+    fn add(
+        instruction_pointer: u32,
+        left: RuntimeBoxedVal,
+        right: RuntimeBoxedVal,
+    ) -> RuntimeBoxedVal {
+        RSV::new_synthetic(instruction_pointer, RSVD::Add { left, right })
+    }
+
+    /// Checks whether `left` and `right` hold the same interned node.
+    fn same_node(left: &RuntimeBoxedVal, right: &RuntimeBoxedVal) -> bool {
+        InternedNode::same_structure(left.node.as_ref(), right.node.as_ref()) == Some(true)
+    }
+
+    /// Builds the known value `1` at `instruction_pointer`.
+    fn one_at(instruction_pointer: u32) -> RuntimeBoxedVal {
+        RSV::new_known_value(
+            instruction_pointer,
+            KnownWord::from(1),
+            Provenance::Synthetic,
+            None,
+        )
+    }
+
+    #[test]
+    fn interns_identical_trees_to_the_same_node() {
+        let arena = ValueArena::new();
+        let value = RSV::new_value(0, Provenance::Synthetic);
+
+        let first = arena.intern(add(2, value.clone(), one_at(1)));
+        let second = arena.intern(add(2, value.clone(), one_at(1)));
+        assert!(same_node(&first, &second));
+        assert_eq!(first, second);
+        assert_eq!(arena.len(), 3);
+
+        // Interning an interned value returns it unchanged
+        assert!(Arc::ptr_eq(&arena.intern(first.clone()), &first));
+        assert_eq!(arena.len(), 3);
+
+        // And structurally different values never share a node
+        let third = arena.intern(add(2, one_at(1), value));
+        assert_ne!(first, third);
+        assert_eq!(arena.len(), 4);
+    }
+
+    #[test]
+    fn shares_nodes_between_locations() {
+        let arena = ValueArena::new();
+        let value = RSV::new_value(0, Provenance::Synthetic);
+
+        // These are structurally equal, but a child was created elsewhere
+        let first = arena.intern(add(2, value.clone(), one_at(1)));
+        let second = arena.intern(add(2, value, one_at(3)));
+        assert!(same_node(&first, &second));
+        assert_eq!(first.children()[1].instruction_pointer(), 1);
+        assert_eq!(second.children()[1].instruction_pointer(), 3);
+        assert_eq!(arena.len(), 3);
+    }
+
+    #[test]
+    fn compares_values_from_different_arenas_structurally() {
+        let value = RSV::new_value(0, Provenance::Synthetic);
+        let first = ValueArena::new().intern(add(2, value.clone(), one_at(1)));
+        let second = ValueArena::new().intern(add(2, value.clone(), one_at(1)));
+        let third = ValueArena::new().intern(add(2, one_at(1), value));
+
+        assert_eq!(first, second);
+        assert_ne!(first, third);
+    }
+
+    #[test]
+    fn creates_fresh_values_with_identities_by_location() {
+        let arena = ValueArena::new();
+        let first = arena.value(1, Provenance::CallDataSize);
+
+        assert_eq!(first, arena.value(1, Provenance::CallDataSize));
+        assert_ne!(first, arena.value(2, Provenance::CallDataSize));
+        assert_ne!(first, arena.value(1, Provenance::ReturnDataSize));
+        assert_ne!(first, ValueArena::new().value(1, Provenance::CallDataSize));
+    }
+
+    #[test]
+    fn creates_fresh_values_that_read_changing_state_with_new_identities() {
+        let arena = ValueArena::new();
+        for provenance in [
+            Provenance::MSize,
+            Provenance::ReturnDataSize,
+            Provenance::MessageCall,
+        ] {
+            assert_ne!(arena.value(1, provenance), arena.value(1, provenance));
+        }
+    }
+
+    #[test]
+    fn does_not_keep_values_alive() {
+        let arena = ValueArena::new();
+        let value = arena.intern(RSV::new_value(0, Provenance::Synthetic));
+        assert_eq!(arena.len(), 1);
+
+        drop(value);
+        assert!(arena.is_empty());
+    }
+
+    #[test]
+    fn sweeps_nodes_that_are_no_longer_in_use() {
+        let arena = ValueArena::new();
+        let kept = arena.intern(RSV::new_value(0, Provenance::Synthetic));
+        for _ in 0..MINIMUM_ARENA_SWEEP_INTERVAL * ARENA_SHARD_COUNT * 4 {
+            drop(arena.intern(RSV::new_value(0, Provenance::Synthetic)));
+        }
+
+        let recorded: usize = arena
+            .shards
+            .iter()
+            .map(|shard| ValueArena::lock(shard).recorded)
+            .sum();
+        assert!(recorded <= MINIMUM_ARENA_SWEEP_INTERVAL * ARENA_SHARD_COUNT);
+        assert_eq!(arena.len(), 1);
+        assert_eq!(arena.intern(kept.clone()), kept);
+    }
+}
@@ -1,10 +1,14 @@
 //! This module contains the definition of the [`SymbolicValue`] and its
 //! supporting types.
 
+pub mod arena;
 pub mod known;
 
 use std::{
+    collections::hash_map::DefaultHasher,
     fmt::{Debug, Display, Formatter},
+    hash::{Hash, Hasher},
+    ptr,
     sync::Arc,
 };
 
@@ -14,7 +18,7 @@ use uuid::Uuid;
 use crate::{
     tc::state::type_variable::TypeVariable,
     utility::clip_uuid,
-    vm::value::known::KnownWord,
+    vm::value::{arena::InternedNode, known::KnownWord},
 };
 
 /// The type of auxiliary data used at runtime.
@@ -54,19 +58,26 @@ pub type TCSV = SV<TCAuxData>;
 ///
 /// Data is considered to be synthetic when it was generated by the analysis
 /// process to more concretely represent an operation.
-#[derive(Clone, Debug, Eq, Derivative)]
-#[derivative(
-    Hash(bound = "AuxData: std::hash::Hash"),
-    PartialEq(bound = "AuxData: std::cmp::PartialEq")
-)]
+///
+/// # Structural Digests
+///
+/// Each node caches a digest of its structure, computed from the digests of
+/// its children when it is constructed. Hashing a value only hashes this
+/// digest, and values with differing digests are known to be unequal without
+/// traversing them, so neither operation depends on the size of the tree.
+///
+/// # Interning
+///
+/// Values that have been interned in the same [`arena::ValueArena`] share a
+/// node for their structure if and only if they are equal, and so are
+/// compared by their nodes alone.
+#[derive(Clone, Debug)]
 pub struct SymbolicValue<AuxData> {
     /// The instruction pointer's value at the location where this part of the
     /// symbolic execution tree was recorded.
-    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     instruction_pointer: u32,
 
     /// Where the data at this level came from.
-    #[derivative(PartialEq = "ignore", Hash = "ignore")]
     provenance: Provenance,
 
     /// The actual execution tree that forms this value.
@@ -80,11 +91,59 @@ pub struct SymbolicValue<AuxData> {
     /// Note that this value _includes_ the node that it is being called on in
     /// the total count.
     size: usize,
+
+    /// The digest of the structure of the tree beginning at this node.
+    digest: u64,
+
+    /// The node for the structure of this value in the [`arena::ValueArena`] in
+    /// which it was interned, if it has been interned.
+    node: Option<Arc<InternedNode>>,
+}
+
+impl<AuxData> SymbolicValue<AuxData>
+where
+    AuxData: Hash,
+{
+    /// Computes the structural digest of a node with the provided `data` and
+    /// `aux_data`.
+    ///
+    /// As the children in `data` hash as their own digests, this does not
+    /// traverse the tree below the node.
+    fn digest_of(data: &SymbolicValueData<AuxData>, aux_data: &AuxData) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        data.hash(&mut hasher);
+        aux_data.hash(&mut hasher);
+        hasher.finish()
+    }
+}
+
+impl<AuxData> PartialEq for SymbolicValue<AuxData>
+where
+    AuxData: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        if ptr::eq(self, other) {
+            return true;
+        }
+        if let Some(same) = InternedNode::same_structure(self.node.as_ref(), other.node.as_ref()) {
+            return same;
+        }
+
+        self.digest == other.digest && self.aux_data == other.aux_data && self.data == other.data
+    }
+}
+
+impl<AuxData> Eq for SymbolicValue<AuxData> where AuxData: Eq {}
+
+impl<AuxData> Hash for SymbolicValue<AuxData> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.digest.hash(state);
+    }
 }
 
 impl<AuxData> SymbolicValue<AuxData>
 where
-    AuxData: Clone + PartialEq,
+    AuxData: Clone + Hash + PartialEq,
 {
     /// Gets the instruction pointer value for the location where this portion
     /// of the value was constructed.
@@ -128,6 +187,15 @@ where
         ValuePayload::new(self.data, self.aux_data)
     }
 
+    /// Gets the digest of the structure of the tree beginning at this node.
+    ///
+    /// Values that are equal always have the same digest, so values with
+    /// different digests can never be equal.
+    #[must_use]
+    pub fn digest(&self) -> u64 {
+        self.digest
+    }
+
     /// Compares two symbolic values for strict equality, _including_ the value
     /// of the `instruction_pointer`.
     #[must_use]
@@ -152,6 +220,7 @@ where
         let provenance = self.provenance;
         let aux_data = self.aux_data.clone();
         let size = data.child_size() + 1;
+        let digest = Self::digest_of(&data, &aux_data);
 
         Arc::new(Self {
             instruction_pointer,
@@ -159,6 +228,8 @@ where
             data,
             aux_data,
             size,
+            digest,
+            node: None,
         })
     }
 
@@ -186,12 +257,15 @@ where
     ) -> Arc<Self> {
         let data = self.data.transform(transform);
         let size = data.child_size() + 1;
+        let digest = Self::digest_of(&data, &self.aux_data);
         Arc::new(Self {
             instruction_pointer: self.instruction_pointer,
             data,
             provenance: self.provenance,
             aux_data: self.aux_data.clone(),
             size,
+            digest,
+            node: None,
         })
     }
 
@@ -241,6 +315,7 @@ impl RSV {
         } else {
             data
         };
+        let digest = Self::digest_of(&data, &());
 
         Arc::new(Self {
             instruction_pointer,
@@ -248,6 +323,8 @@ impl RSV {
             data,
             aux_data: (),
             size,
+            digest,
+            node: None,
         })
     }
 
@@ -299,6 +376,72 @@ impl RSV {
         Self::new(instruction_pointer, data, Provenance::Synthetic, None)
     }
 
+    /// Constructs a new, synthetic, `SymbolicValue` representing a choice
+    /// between `left` and `right`, made where the threads of execution that
+    /// held them were merged at `instruction_pointer`.
+    ///
+    /// If the two values are equal then `left` is returned as is. Alternatives
+    /// that are themselves choices are flattened into the new choice, and
+    /// duplicate alternatives are removed.
+    #[must_use]
+    pub fn new_choice(
+        instruction_pointer: u32,
+        left: RuntimeBoxedVal,
+        right: RuntimeBoxedVal,
+    ) -> RuntimeBoxedVal {
+        if left == right {
+            return left;
+        }
+
+        let mut alternatives: Vec<RuntimeBoxedVal> = Vec::new();
+        for value in [left, right] {
+            let values = match value.data() {
+                RSVD::Choice { alternatives } => alternatives.clone(),
+                _ => vec![value],
+            };
+            for value in values {
+                if !alternatives.contains(&value) {
+                    alternatives.push(value);
+                }
+            }
+        }
+
+        Self::new_synthetic(instruction_pointer, RSVD::Choice { alternatives })
+    }
+
+    /// Constructs a new, synthetic, `SymbolicValue` representing a value that
+    /// starts at `base` and is increased by `stride` on each iteration of the
+    /// loop whose header is at `header`.
+    ///
+    /// The value has the form `base + loop_counter(header) * stride`, and is
+    /// created at `instruction_pointer` as the loop is widened.
+    #[must_use]
+    pub fn new_induction(
+        instruction_pointer: u32,
+        header: u32,
+        base: RuntimeBoxedVal,
+        stride: KnownWord,
+    ) -> RuntimeBoxedVal {
+        let counter = Self::new_synthetic(instruction_pointer, RSVD::LoopCounter { header });
+        let stride =
+            Self::new_known_value(instruction_pointer, stride, Provenance::Synthetic, None);
+        let step = Self::new_synthetic(
+            instruction_pointer,
+            RSVD::Multiply {
+                left:  counter,
+                right: stride,
+            },
+        );
+
+        Self::new_synthetic(
+            instruction_pointer,
+            RSVD::Add {
+                left:  base,
+                right: step,
+            },
+        )
+    }
+
     /// Constructs a new `SymbolicValue` representing a symbolic value created
     /// at `instruction_pointer` with the provided `provenance`.
     ///
@@ -366,12 +509,15 @@ impl TCSV {
         aux_data: TCAuxData,
     ) -> TCBoxedVal {
         let size = data.child_size() + 1;
+        let digest = Self::digest_of(&data, &aux_data);
         Arc::new(Self {
             instruction_pointer,
             provenance,
             data,
             aux_data,
             size,
+            digest,
+            node: None,
         })
     }
 
@@ -517,6 +663,10 @@ pub enum SymbolicValueData<AuxData> {
     /// Gets the number of the current block.
     BlockNumber,
 
+    /// Gets the number of the most recent block on the parent (L1) chain, as
+    /// returned by `NUMBER` on chains such as Arbitrum.
+    L1BlockNumber,
+
     /// Gets the difficulty of the current block.
     Prevrandao,
 
@@ -533,6 +683,13 @@ pub enum SymbolicValueData<AuxData> {
     /// Gets the block base fee.
     BaseFee,
 
+    /// Gets the versioned hash of the blob at `index` in the current
+    /// transaction.
+    BlobHash { index: BoxedVal<AuxData> },
+
+    /// Gets the blob base fee of the current block.
+    BlobBaseFee,
+
     /// Gets the currently available gas.
     Gas,
 
@@ -549,8 +706,9 @@ pub enum SymbolicValueData<AuxData> {
         data:  BoxedVal<AuxData>,
     },
 
-    /// Registers the account for deletion.
-    SelfDestruct { target: BoxedVal<AuxData> },
+    /// Sends the balance of the account to `target`, and registers the
+    /// account for deletion if `deletes_account` is set.
+    SelfDestruct { target: BoxedVal<AuxData>, deletes_account: bool },
 
     /// Less than for symbolic values.
     LessThan { left: BoxedVal<AuxData>, right: BoxedVal<AuxData> },
@@ -618,6 +776,19 @@ pub enum SymbolicValueData<AuxData> {
     /// `size`.
     ReturnData { offset: BoxedVal<AuxData>, size: BoxedVal<AuxData> },
 
+    /// Data returned at `offset` for `size` from a call to the function with
+    /// `selector` on the system contract at `address`.
+    ///
+    /// This is used in place of [`Self::ReturnData`] when the call is to one of
+    /// the known system contracts of the chain, as the type of the returned
+    /// data can then be determined.
+    SystemCallData {
+        address:  BoxedVal<AuxData>,
+        selector: BoxedVal<AuxData>,
+        offset:   BoxedVal<AuxData>,
+        size:     BoxedVal<AuxData>,
+    },
+
     /// The return. Does not stay on the stack but is stored nevertheless.
     Return { data: BoxedVal<AuxData> },
 
@@ -640,6 +811,24 @@ pub enum SymbolicValueData<AuxData> {
     /// A representation of the storing of `value` at `key` in storage
     StorageWrite { key: BoxedVal<AuxData>, value: BoxedVal<AuxData> },
 
+    /// The value read from transient storage that has not been written to at
+    /// the time of loading during the course of execution in the virtual
+    /// machine.
+    UnwrittenTransientStorageValue { key: BoxedVal<AuxData> },
+
+    /// A value representing the return from a transient storage load at `key`.
+    ///
+    /// If there is no value in the transient storage slot for `key`, `value`
+    /// will be [`Self::UnwrittenTransientStorageValue`].
+    TLoad { key: BoxedVal<AuxData>, value: BoxedVal<AuxData> },
+
+    /// A transient storage slot at `key`.
+    TransientStorageSlot { key: BoxedVal<AuxData> },
+
+    /// A representation of the storing of `value` at `key` in transient
+    /// storage.
+    TransientStorageWrite { key: BoxedVal<AuxData>, value: BoxedVal<AuxData> },
+
     /// The concatenation of multiple values.
     Concat { values: Vec<BoxedVal<AuxData>> },
 
@@ -669,6 +858,14 @@ pub enum SymbolicValueData<AuxData> {
 
     /// A packed encoding of data as a value, containing `elements`.
     Packed { elements: Vec<PackedSpan<AuxData>> },
+
+    /// A value that may be any one of the `alternatives`, created where threads
+    /// of execution holding different values in the same location are merged.
+    Choice { alternatives: Vec<BoxedVal<AuxData>> },
+
+    /// The number of iterations that have been completed of the loop whose
+    /// header is the `JUMPDEST` at `header`.
+    LoopCounter { header: u32 },
 }
 
 impl<AuxData> SymbolicValueData<AuxData> {
@@ -696,7 +893,7 @@ impl<AuxData> SymbolicValueData<AuxData> {
 
 impl<AuxData> SymbolicValueData<AuxData>
 where
-    AuxData: Clone + PartialEq,
+    AuxData: Clone + Hash + PartialEq,
 {
     /// Gets the number of nodes in the tree that are children of `self`, not
     /// including `self` in the count.
@@ -707,7 +904,7 @@ where
     ///
     /// If you want to compute this value dynamically, instead recursively call
     /// [`Self::children`] and get the size of the result.
-    #[allow(clippy::match_same_arms)] // Merging doesn't make sense here
+    #[allow(clippy::match_same_arms, clippy::too_many_lines)] // Merging doesn't make sense here
     #[must_use]
     pub fn child_size(&self) -> usize {
         match self {
@@ -762,18 +959,21 @@ where
             SVD::CoinBase => 0,
             SVD::BlockTimestamp => 0,
             SVD::BlockNumber => 0,
+            SVD::L1BlockNumber => 0,
             SVD::Prevrandao => 0,
             SVD::GasLimit => 0,
             SVD::ChainId => 0,
             SVD::SelfBalance => 0,
             SVD::BaseFee => 0,
+            SVD::BlobHash { index } => index.size(),
+            SVD::BlobBaseFee => 0,
             SVD::Gas => 0,
             SVD::Log { data, topics } => {
                 data.size() + topics.iter().map(|t| t.size()).sum::<usize>()
             }
             SVD::Create { value, data } => value.size() + data.size(),
             SVD::Create2 { value, salt, data } => value.size() + salt.size() + data.size(),
-            SVD::SelfDestruct { target } => target.size(),
+            SVD::SelfDestruct { target, .. } => target.size(),
             SVD::LessThan { left, right } => left.size() + right.size(),
             SVD::GreaterThan { left, right } => left.size() + right.size(),
             SVD::SignedLessThan { left, right } => left.size() + right.size(),
@@ -797,18 +997,30 @@ where
                 size,
             } => address.size() + offset.size() + size.size(),
             SVD::ReturnData { offset, size } => offset.size() + size.size(),
+            SVD::SystemCallData {
+                address,
+                selector,
+                offset,
+                size,
+            } => address.size() + selector.size() + offset.size() + size.size(),
             SVD::Return { data } => data.size(),
             SVD::Revert { data } => data.size(),
             SVD::UnwrittenStorageValue { key } => key.size(),
             SVD::SLoad { key, value } => key.size() + value.size(),
             SVD::StorageSlot { key } => key.size(),
             SVD::StorageWrite { key, value } => key.size() + value.size(),
+            SVD::UnwrittenTransientStorageValue { key } => key.size(),
+            SVD::TLoad { key, value } => key.size() + value.size(),
+            SVD::TransientStorageSlot { key } => key.size(),
+            SVD::TransientStorageWrite { key, value } => key.size() + value.size(),
             SVD::Concat { values } => values.iter().map(|v| v.size()).sum(),
             SVD::MappingIndex { slot, key, .. } => slot.size() + key.size(),
             SVD::DynamicArrayIndex { slot, index } => slot.size() + index.size(),
             SVD::SubWord { value, .. } => value.size(),
             SVD::Shifted { value, .. } => value.size(),
             SVD::Packed { elements } => elements.iter().map(|s| s.value.size()).sum(),
+            SVD::Choice { alternatives } => alternatives.iter().map(|v| v.size()).sum(),
+            SVD::LoopCounter { .. } => 0,
         }
     }
 
@@ -826,250 +1038,281 @@ where
     ///
     /// This algorithm is recursive. If it turns out to be a problem in practice
     /// it can be re-written.
-    #[allow(clippy::match_same_arms, clippy::too_many_lines)]
     #[must_use]
     pub fn transform(&self, transform: impl Fn(&Self) -> Option<Self> + Copy) -> Self {
-        let inner_self = self.clone();
-        match transform(&inner_self) {
+        match transform(self) {
             Some(data) => data,
-            None => match self {
-                Self::Value { .. } => inner_self,
-                Self::KnownData { .. } => inner_self,
-                Self::Add { left, right } => Self::Add {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::Multiply { left, right } => Self::Multiply {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::Subtract { left, right } => Self::Subtract {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::Divide { divisor, dividend } => Self::Divide {
-                    dividend: dividend.transform_data(transform),
-                    divisor:  divisor.transform_data(transform),
-                },
-                Self::SignedDivide { divisor, dividend } => Self::SignedDivide {
-                    dividend: dividend.transform_data(transform),
-                    divisor:  divisor.transform_data(transform),
-                },
-                Self::Modulo { divisor, dividend } => Self::Modulo {
-                    dividend: dividend.transform_data(transform),
-                    divisor:  divisor.transform_data(transform),
-                },
-                Self::SignedModulo { divisor, dividend } => Self::SignedModulo {
-                    dividend: dividend.transform_data(transform),
-                    divisor:  divisor.transform_data(transform),
-                },
-                Self::Exp { value, exponent } => Self::Exp {
-                    value:    value.transform_data(transform),
-                    exponent: exponent.transform_data(transform),
-                },
-                Self::SignExtend { size, value } => Self::SignExtend {
-                    size:  size.transform_data(transform),
-                    value: value.transform_data(transform),
-                },
-                Self::CallWithValue {
-                    gas,
-                    address,
-                    value,
-                    argument_data,
-                    ret_offset,
-                    ret_size,
-                } => Self::CallWithValue {
-                    gas:           gas.transform_data(transform),
-                    address:       address.transform_data(transform),
-                    value:         value.transform_data(transform),
-                    argument_data: argument_data.transform_data(transform),
-                    ret_offset:    ret_offset.transform_data(transform),
-                    ret_size:      ret_size.transform_data(transform),
-                },
-                Self::CallWithoutValue {
-                    gas,
-                    address,
-                    argument_data,
-                    ret_offset,
-                    ret_size,
-                } => Self::CallWithoutValue {
-                    gas:           gas.transform_data(transform),
-                    address:       address.transform_data(transform),
-                    argument_data: argument_data.transform_data(transform),
-                    ret_offset:    ret_offset.transform_data(transform),
-                    ret_size:      ret_size.transform_data(transform),
-                },
-                Self::Sha3 { data } => Self::Sha3 {
-                    data: data.transform_data(transform),
-                },
-                Self::Address => inner_self,
-                Self::Balance { address } => Self::Balance {
-                    address: address.transform_data(transform),
-                },
-                Self::Origin => inner_self,
-                Self::Caller => inner_self,
-                Self::CallValue => inner_self,
-                Self::GasPrice => inner_self,
-                Self::ExtCodeHash { address } => Self::ExtCodeHash {
-                    address: address.transform_data(transform),
-                },
-                Self::BlockHash { block_number } => Self::BlockHash {
-                    block_number: block_number.transform_data(transform),
-                },
-                Self::CoinBase => inner_self,
-                Self::BlockTimestamp => inner_self,
-                Self::BlockNumber => inner_self,
-                Self::Prevrandao => inner_self,
-                Self::GasLimit => inner_self,
-                Self::ChainId => inner_self,
-                Self::SelfBalance => inner_self,
-                Self::BaseFee => inner_self,
-                Self::Gas => inner_self,
-                Self::Log { data, topics } => Self::Log {
-                    data:   data.transform_data(transform),
-                    topics: topics.iter().map(|t| t.transform_data(transform)).collect(),
-                },
-                Self::Create { value, data } => Self::Create {
-                    value: value.transform_data(transform),
-                    data:  data.transform_data(transform),
-                },
-                Self::Create2 { value, data, salt } => Self::Create2 {
-                    value: value.transform_data(transform),
-                    data:  data.transform_data(transform),
-                    salt:  salt.transform_data(transform),
-                },
-                Self::SelfDestruct { target } => Self::SelfDestruct {
-                    target: target.transform_data(transform),
-                },
-                Self::LessThan { left, right } => Self::LessThan {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::GreaterThan { left, right } => Self::GreaterThan {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::SignedLessThan { left, right } => Self::SignedLessThan {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::SignedGreaterThan { left, right } => Self::SignedGreaterThan {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::Equals { left, right } => Self::Equals {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::IsZero { number } => Self::IsZero {
-                    number: number.transform_data(transform),
-                },
-                Self::And { left, right } => Self::And {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::Or { left, right } => Self::Or {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::Xor { left, right } => Self::Xor {
-                    left:  left.transform_data(transform),
-                    right: right.transform_data(transform),
-                },
-                Self::Not { value } => Self::Not {
-                    value: value.transform_data(transform),
-                },
-                Self::LeftShift { shift, value } => Self::LeftShift {
-                    shift: shift.transform_data(transform),
-                    value: value.transform_data(transform),
-                },
-                Self::RightShift { shift, value } => Self::RightShift {
-                    shift: shift.transform_data(transform),
-                    value: value.transform_data(transform),
-                },
-                Self::ArithmeticRightShift { shift, value } => Self::ArithmeticRightShift {
-                    shift: shift.transform_data(transform),
-                    value: value.transform_data(transform),
-                },
-                Self::CallData { id, offset, size } => Self::CallData {
-                    id:     *id,
-                    offset: offset.transform_data(transform),
-                    size:   size.transform_data(transform),
-                },
-                Self::CallDataSize => inner_self,
-                Self::CodeCopy { offset, size } => Self::CodeCopy {
-                    offset: offset.transform_data(transform),
-                    size:   size.transform_data(transform),
-                },
-                Self::ExtCodeSize { address } => Self::ExtCodeSize {
-                    address: address.transform_data(transform),
-                },
-                Self::ExtCodeCopy {
-                    address,
-                    offset,
-                    size,
-                } => Self::ExtCodeCopy {
-                    address: address.transform_data(transform),
-                    offset:  offset.transform_data(transform),
-                    size:    size.transform_data(transform),
-                },
-                Self::ReturnData { offset, size } => Self::ReturnData {
-                    offset: offset.transform_data(transform),
-                    size:   size.transform_data(transform),
-                },
-                Self::Return { data } => Self::Return {
-                    data: data.transform_data(transform),
-                },
-                Self::Revert { data } => Self::Revert {
-                    data: data.transform_data(transform),
-                },
-                Self::UnwrittenStorageValue { key } => Self::UnwrittenStorageValue {
-                    key: key.transform_data(transform),
-                },
-                Self::SLoad { key, value } => Self::SLoad {
-                    key:   key.transform_data(transform),
-                    value: value.transform_data(transform),
-                },
-                Self::StorageSlot { key } => Self::StorageSlot {
-                    key: key.transform_data(transform),
-                },
-                Self::StorageWrite { key, value } => Self::StorageWrite {
-                    key:   key.transform_data(transform),
-                    value: value.transform_data(transform),
-                },
-                Self::Concat { values } => Self::Concat {
-                    values: values.iter().map(|v| v.transform_data(transform)).collect(),
-                },
-                Self::MappingIndex {
-                    slot,
-                    key,
-                    projection,
-                } => Self::MappingIndex {
-                    slot:       slot.transform_data(transform),
-                    key:        key.transform_data(transform),
-                    projection: *projection,
-                },
-                Self::DynamicArrayIndex { slot, index } => Self::DynamicArrayIndex {
-                    slot:  slot.transform_data(transform),
-                    index: index.transform_data(transform),
-                },
-                Self::SubWord {
-                    value,
-                    offset,
-                    size,
-                } => Self::SubWord {
-                    value:  value.transform_data(transform),
-                    offset: *offset,
-                    size:   *size,
-                },
-                Self::Shifted { offset, value } => Self::Shifted {
-                    offset: *offset,
-                    value:  value.transform_data(transform),
-                },
-                Self::Packed { elements } => Self::Packed {
-                    elements: elements.iter().map(|elem| elem.transform(transform)).collect(),
-                },
+            None => self.map_children(&|child| child.transform_data(transform)),
+        }
+    }
+
+    /// Rebuilds the data payload with each of its direct children replaced by
+    /// the result of applying `f` to that child.
+    ///
+    /// Nodes without children are returned unchanged.
+    #[allow(clippy::match_same_arms, clippy::too_many_lines)]
+    #[must_use]
+    pub fn map_children(&self, f: &impl Fn(&BoxedVal<AuxData>) -> BoxedVal<AuxData>) -> Self {
+        match self {
+            Self::Value { .. } => self.clone(),
+            Self::KnownData { .. } => self.clone(),
+            Self::Add { left, right } => Self::Add {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::Multiply { left, right } => Self::Multiply {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::Subtract { left, right } => Self::Subtract {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::Divide { divisor, dividend } => Self::Divide {
+                dividend: f(dividend),
+                divisor:  f(divisor),
             },
+            Self::SignedDivide { divisor, dividend } => Self::SignedDivide {
+                dividend: f(dividend),
+                divisor:  f(divisor),
+            },
+            Self::Modulo { divisor, dividend } => Self::Modulo {
+                dividend: f(dividend),
+                divisor:  f(divisor),
+            },
+            Self::SignedModulo { divisor, dividend } => Self::SignedModulo {
+                dividend: f(dividend),
+                divisor:  f(divisor),
+            },
+            Self::Exp { value, exponent } => Self::Exp {
+                value:    f(value),
+                exponent: f(exponent),
+            },
+            Self::SignExtend { size, value } => Self::SignExtend {
+                size:  f(size),
+                value: f(value),
+            },
+            Self::CallWithValue {
+                gas,
+                address,
+                value,
+                argument_data,
+                ret_offset,
+                ret_size,
+            } => Self::CallWithValue {
+                gas:           f(gas),
+                address:       f(address),
+                value:         f(value),
+                argument_data: f(argument_data),
+                ret_offset:    f(ret_offset),
+                ret_size:      f(ret_size),
+            },
+            Self::CallWithoutValue {
+                gas,
+                address,
+                argument_data,
+                ret_offset,
+                ret_size,
+            } => Self::CallWithoutValue {
+                gas:           f(gas),
+                address:       f(address),
+                argument_data: f(argument_data),
+                ret_offset:    f(ret_offset),
+                ret_size:      f(ret_size),
+            },
+            Self::Sha3 { data } => Self::Sha3 { data: f(data) },
+            Self::Address => self.clone(),
+            Self::Balance { address } => Self::Balance {
+                address: f(address),
+            },
+            Self::Origin => self.clone(),
+            Self::Caller => self.clone(),
+            Self::CallValue => self.clone(),
+            Self::GasPrice => self.clone(),
+            Self::ExtCodeHash { address } => Self::ExtCodeHash {
+                address: f(address),
+            },
+            Self::BlockHash { block_number } => Self::BlockHash {
+                block_number: f(block_number),
+            },
+            Self::CoinBase => self.clone(),
+            Self::BlockTimestamp => self.clone(),
+            Self::BlockNumber => self.clone(),
+            Self::L1BlockNumber => self.clone(),
+            Self::Prevrandao => self.clone(),
+            Self::GasLimit => self.clone(),
+            Self::ChainId => self.clone(),
+            Self::SelfBalance => self.clone(),
+            Self::BaseFee => self.clone(),
+            Self::BlobHash { index } => Self::BlobHash { index: f(index) },
+            Self::BlobBaseFee => self.clone(),
+            Self::Gas => self.clone(),
+            Self::Log { data, topics } => Self::Log {
+                data:   f(data),
+                topics: topics.iter().map(f).collect(),
+            },
+            Self::Create { value, data } => Self::Create {
+                value: f(value),
+                data:  f(data),
+            },
+            Self::Create2 { value, data, salt } => Self::Create2 {
+                value: f(value),
+                data:  f(data),
+                salt:  f(salt),
+            },
+            Self::SelfDestruct {
+                target,
+                deletes_account,
+            } => Self::SelfDestruct {
+                target:          f(target),
+                deletes_account: *deletes_account,
+            },
+            Self::LessThan { left, right } => Self::LessThan {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::GreaterThan { left, right } => Self::GreaterThan {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::SignedLessThan { left, right } => Self::SignedLessThan {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::SignedGreaterThan { left, right } => Self::SignedGreaterThan {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::Equals { left, right } => Self::Equals {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::IsZero { number } => Self::IsZero { number: f(number) },
+            Self::And { left, right } => Self::And {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::Or { left, right } => Self::Or {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::Xor { left, right } => Self::Xor {
+                left:  f(left),
+                right: f(right),
+            },
+            Self::Not { value } => Self::Not { value: f(value) },
+            Self::LeftShift { shift, value } => Self::LeftShift {
+                shift: f(shift),
+                value: f(value),
+            },
+            Self::RightShift { shift, value } => Self::RightShift {
+                shift: f(shift),
+                value: f(value),
+            },
+            Self::ArithmeticRightShift { shift, value } => Self::ArithmeticRightShift {
+                shift: f(shift),
+                value: f(value),
+            },
+            Self::CallData { id, offset, size } => Self::CallData {
+                id:     *id,
+                offset: f(offset),
+                size:   f(size),
+            },
+            Self::CallDataSize => self.clone(),
+            Self::CodeCopy { offset, size } => Self::CodeCopy {
+                offset: f(offset),
+                size:   f(size),
+            },
+            Self::ExtCodeSize { address } => Self::ExtCodeSize {
+                address: f(address),
+            },
+            Self::ExtCodeCopy {
+                address,
+                offset,
+                size,
+            } => Self::ExtCodeCopy {
+                address: f(address),
+                offset:  f(offset),
+                size:    f(size),
+            },
+            Self::ReturnData { offset, size } => Self::ReturnData {
+                offset: f(offset),
+                size:   f(size),
+            },
+            Self::SystemCallData {
+                address,
+                selector,
+                offset,
+                size,
+            } => Self::SystemCallData {
+                address:  f(address),
+                selector: f(selector),
+                offset:   f(offset),
+                size:     f(size),
+            },
+            Self::Return { data } => Self::Return { data: f(data) },
+            Self::Revert { data } => Self::Revert { data: f(data) },
+            Self::UnwrittenStorageValue { key } => Self::UnwrittenStorageValue { key: f(key) },
+            Self::SLoad { key, value } => Self::SLoad {
+                key:   f(key),
+                value: f(value),
+            },
+            Self::StorageSlot { key } => Self::StorageSlot { key: f(key) },
+            Self::StorageWrite { key, value } => Self::StorageWrite {
+                key:   f(key),
+                value: f(value),
+            },
+            Self::UnwrittenTransientStorageValue { key } => {
+                Self::UnwrittenTransientStorageValue { key: f(key) }
+            }
+            Self::TLoad { key, value } => Self::TLoad {
+                key:   f(key),
+                value: f(value),
+            },
+            Self::TransientStorageSlot { key } => Self::TransientStorageSlot { key: f(key) },
+            Self::TransientStorageWrite { key, value } => Self::TransientStorageWrite {
+                key:   f(key),
+                value: f(value),
+            },
+            Self::Concat { values } => Self::Concat {
+                values: values.iter().map(f).collect(),
+            },
+            Self::MappingIndex {
+                slot,
+                key,
+                projection,
+            } => Self::MappingIndex {
+                slot:       f(slot),
+                key:        f(key),
+                projection: *projection,
+            },
+            Self::DynamicArrayIndex { slot, index } => Self::DynamicArrayIndex {
+                slot:  f(slot),
+                index: f(index),
+            },
+            Self::SubWord {
+                value,
+                offset,
+                size,
+            } => Self::SubWord {
+                value:  f(value),
+                offset: *offset,
+                size:   *size,
+            },
+            Self::Shifted { offset, value } => Self::Shifted {
+                offset: *offset,
+                value:  f(value),
+            },
+            Self::Packed { elements } => Self::Packed {
+                elements: elements
+                    .iter()
+                    .map(|elem| PackedSpan::new(elem.offset, elem.size, f(&elem.value)))
+                    .collect(),
+            },
+            Self::Choice { alternatives } => Self::Choice {
+                alternatives: alternatives.iter().map(f).collect(),
+            },
+            Self::LoopCounter { .. } => self.clone(),
         }
     }
 
@@ -1090,7 +1333,7 @@ where
         #[allow(clippy::too_many_lines)]
         fn constant_folder<AuxData>(data: &SVD<AuxData>) -> Option<SVD<AuxData>>
         where
-            AuxData: Clone + PartialEq,
+            AuxData: Clone + Hash + PartialEq,
         {
             match data.clone() {
                 SVD::Add { left, right } => {
@@ -1267,7 +1510,7 @@ where
     }
 
     /// Gets the direct children of the provided node.
-    #[allow(clippy::match_same_arms)]
+    #[allow(clippy::match_same_arms, clippy::too_many_lines)]
     #[must_use]
     pub fn children(&self) -> Vec<BoxedVal<AuxData>> {
         match self {
@@ -1309,11 +1552,14 @@ where
             Self::CoinBase => vec![],
             Self::BlockTimestamp => vec![],
             Self::BlockNumber => vec![],
+            Self::L1BlockNumber => vec![],
             Self::Prevrandao => vec![],
             Self::GasLimit => vec![],
             Self::ChainId => vec![],
             Self::SelfBalance => vec![],
             Self::BaseFee => vec![],
+            Self::BlobHash { index } => vec![index],
+            Self::BlobBaseFee => vec![],
             Self::Gas => vec![],
             Self::Log { data, topics } => {
                 let mut vec = vec![data];
@@ -1322,7 +1568,7 @@ where
             }
             Self::Create { value, data } => vec![value, data],
             Self::Create2 { value, data, salt } => vec![value, data, salt],
-            Self::SelfDestruct { target } => vec![target],
+            Self::SelfDestruct { target, .. } => vec![target],
             Self::LessThan { left, right } => vec![left, right],
             Self::GreaterThan { left, right } => vec![left, right],
             Self::SignedLessThan { left, right } => vec![left, right],
@@ -1346,18 +1592,30 @@ where
                 size,
             } => vec![address, offset, size],
             Self::ReturnData { offset, size } => vec![offset, size],
+            Self::SystemCallData {
+                address,
+                selector,
+                offset,
+                size,
+            } => vec![address, selector, offset, size],
             Self::Return { data } => vec![data],
             Self::Revert { data } => vec![data],
             Self::UnwrittenStorageValue { key } => vec![key],
             Self::SLoad { key, value } => vec![key, value],
             Self::StorageSlot { key } => vec![key],
             Self::StorageWrite { key, value } => vec![key, value],
+            Self::UnwrittenTransientStorageValue { key } => vec![key],
+            Self::TLoad { key, value } => vec![key, value],
+            Self::TransientStorageSlot { key } => vec![key],
+            Self::TransientStorageWrite { key, value } => vec![key, value],
             Self::Concat { values } => values.iter().collect(),
             Self::MappingIndex { slot, key, .. } => vec![slot, key],
             Self::DynamicArrayIndex { slot, index } => vec![slot, index],
             Self::SubWord { value, .. } => vec![value],
             Self::Shifted { value, .. } => vec![value],
             Self::Packed { elements } => elements.iter().map(|e| &e.value).collect(),
+            Self::Choice { alternatives } => alternatives.iter().collect(),
+            Self::LoopCounter { .. } => vec![],
         }
         .into_iter()
         .cloned()
@@ -1430,16 +1688,28 @@ where
             Self::CoinBase => write!(f, "block.coinbase"),
             Self::BlockTimestamp => write!(f, "block.timestamp"),
             Self::BlockNumber => write!(f, "block.number"),
+            Self::L1BlockNumber => write!(f, "l1_block.number"),
             Self::Prevrandao => write!(f, "block.prevrandao"),
             Self::GasLimit => write!(f, "block.gaslimit"),
             Self::ChainId => write!(f, "block.chain_id"),
             Self::SelfBalance => write!(f, "address(this).balance"),
             Self::BaseFee => write!(f, "block.basefee"),
+            Self::BlobHash { index } => write!(f, "blob_hash({index})"),
+            Self::BlobBaseFee => write!(f, "block.blobbasefee"),
             Self::Gas => write!(f, "gasRemaining"),
             Self::Log { data, topics } => write!(f, "log({data}, {topics:?})"),
             Self::Create { value, data } => write!(f, "create({value}, {data})"),
             Self::Create2 { value, data, salt } => write!(f, "create({value}, {data}, {salt})"),
-            Self::SelfDestruct { target } => write!(f, "self_destruct({target})"),
+            Self::SelfDestruct {
+                target,
+                deletes_account,
+            } => {
+                if *deletes_account {
+                    write!(f, "self_destruct({target})")
+                } else {
+                    write!(f, "send_all({target})")
+                }
+            }
             Self::LessThan { left, right } => write!(f, "({left} < {right})"),
             Self::GreaterThan { left, right } => write!(f, "({left} > {right})"),
             Self::SignedLessThan { left, right } => write!(f, "({left} s< {right})"),
@@ -1467,12 +1737,26 @@ where
             Self::ReturnData { offset, size } => {
                 write!(f, "return_data_copy[{offset}, {size}]")
             }
+            Self::SystemCallData {
+                address,
+                selector,
+                offset,
+                size,
+            } => {
+                write!(f, "system_call({address}, {selector})[{offset}, {size}]")
+            }
             Self::Return { data } => write!(f, "return({data})"),
             Self::Revert { data } => write!(f, "revert({data})"),
             Self::UnwrittenStorageValue { key } => write!(f, "uninit_storage({key})"),
             Self::SLoad { key, value } => write!(f, "(s_load[{key}] => {value})"),
             Self::StorageSlot { key } => write!(f, "slot({key})"),
             Self::StorageWrite { key, value } => write!(f, "(s_store[{key}] = {value})"),
+            Self::UnwrittenTransientStorageValue { key } => {
+                write!(f, "uninit_transient_storage({key})")
+            }
+            Self::TLoad { key, value } => write!(f, "(t_load[{key}] => {value})"),
+            Self::TransientStorageSlot { key } => write!(f, "transient_slot({key})"),
+            Self::TransientStorageWrite { key, value } => write!(f, "(t_store[{key}] = {value})"),
             Self::Concat { values } => {
                 write!(f, "concat(")?;
                 for (i, value) in values.iter().enumerate() {
@@ -1507,6 +1791,17 @@ where
                 }
                 write!(f, ")")
             }
+            Self::Choice { alternatives } => {
+                write!(f, "choice(")?;
+                for (i, value) in alternatives.iter().enumerate() {
+                    write!(f, "{value}")?;
+                    if i + 1 < alternatives.len() {
+                        write!(f, ", ")?;
+                    }
+                }
+                write!(f, ")")
+            }
+            Self::LoopCounter { header } => write!(f, "loop_counter({header})"),
         }
     }
 }
@@ -1538,7 +1833,7 @@ impl<AuxData> PackedSpan<AuxData> {
 
 impl<AuxData> PackedSpan<AuxData>
 where
-    AuxData: Clone + PartialEq,
+    AuxData: Clone + Hash + PartialEq,
 {
     /// Transforms the [`Self::value`] within the span with the provided
     /// `transform`.
@@ -1633,7 +1928,7 @@ pub struct ValuePayload<AuxData> {
 
 impl<AuxData> ValuePayload<AuxData>
 where
-    AuxData: Clone + PartialEq,
+    AuxData: Clone + Hash + PartialEq,
 {
     /// Constructs a new value payload wrapping `data` and `aux`.
     #[must_use]
@@ -1649,6 +1944,29 @@ mod test {
 
     use crate::vm::value::{known::KnownWord, Provenance, RSV, RSVD};
 
+    #[test]
+    fn choices_are_flattened_and_deduplicated() {
+        let value_1 = RSV::new_value(0, Provenance::Synthetic);
+        let value_2 = RSV::new_value(1, Provenance::Synthetic);
+        let value_3 = RSV::new_value(2, Provenance::Synthetic);
+
+        assert_eq!(
+            RSV::new_choice(3, value_1.clone(), value_1.clone()),
+            value_1
+        );
+
+        let choice = RSV::new_choice(3, value_1.clone(), value_2.clone());
+        let choice = RSV::new_choice(4, choice, value_3.clone());
+        let choice = RSV::new_choice(5, choice, value_2.clone());
+        assert_eq!(
+            choice.data(),
+            &RSVD::Choice {
+                alternatives: vec![value_1, value_2, value_3],
+            }
+        );
+        assert_eq!(choice.size(), 4);
+    }
+
     #[test]
     fn equality_ignores_instruction_pointer() {
         let id = Uuid::new_v4();
@@ -1669,6 +1987,25 @@ mod test {
         assert_eq!(value_1, value_2);
     }
 
+    #[test]
+    fn digests_depend_only_on_structure() {
+        let id = Uuid::new_v4();
+        let leaf_1 = RSV::new_from_execution(0, RSVD::Value { id }, None);
+        let leaf_2 = RSV::new_synthetic(1, RSVD::Value { id });
+        let value_1 = RSV::new_synthetic(2, RSVD::Not { value: leaf_1 });
+        let value_2 = RSV::new_synthetic(3, RSVD::Not { value: leaf_2 });
+        assert_eq!(value_1.digest(), value_2.digest());
+
+        let other = RSV::new_synthetic(
+            2,
+            RSVD::IsZero {
+                number: value_1.clone(),
+            },
+        );
+        assert_ne!(value_1.digest(), other.digest());
+        assert_ne!(value_1, other);
+    }
+
     #[test]
     fn strict_equality_includes_instruction_pointer() {
         let id = Uuid::new_v4();
@@ -0,0 +1,202 @@
+//! This module contains a value-set analysis for the symbolic values produced
+//! by the virtual machine.
+//!
+//! Many values that are not concretely known can nevertheless only take a
+//! small number of words. The results of comparisons are always either zero
+//! or one, for example, and values read back from storage may have been
+//! written as known words. The analysis computes the finite set of words that
+//! a value can take where this is possible, which allows the virtual machine
+//! to follow jumps to computed destinations.
+
+use std::collections::BTreeSet;
+
+use ethnum::U256;
+
+use crate::{
+    constant::WORD_SIZE_BITS,
+    vm::value::{known::KnownWord, RuntimeBoxedVal, RSVD},
+};
+
+/// Computes the set of words that `value` can take, in ascending order.
+///
+/// Returns [`None`] if the value can take words that the analysis does not
+/// understand, or if it can take more than `limit` distinct words.
+#[must_use]
+pub fn possible_values(value: &RuntimeBoxedVal, limit: usize) -> Option<Vec<KnownWord>> {
+    values(value, limit).map(|set| set.into_iter().map(KnownWord::from_le).collect())
+}
+
+/// Computes the set of words that `value` can take, bounded by `limit`.
+fn values(value: &RuntimeBoxedVal, limit: usize) -> Option<BTreeSet<U256>> {
+    let result = match value.data() {
+        RSVD::KnownData { value } => BTreeSet::from([value.value_le()]),
+        RSVD::Add { left, right } => combine(left, right, limit, |a, b| a + b)?,
+        RSVD::Multiply { left, right } => combine(left, right, limit, |a, b| a * b)?,
+        RSVD::Subtract { left, right } => combine(left, right, limit, |a, b| a - b)?,
+        RSVD::Divide { dividend, divisor } => combine(dividend, divisor, limit, |a, b| a / b)?,
+        RSVD::Modulo { dividend, divisor } => combine(dividend, divisor, limit, |a, b| a % b)?,
+        RSVD::And { left, right } => combine(left, right, limit, |a, b| a & b)?,
+        RSVD::Or { left, right } => combine(left, right, limit, |a, b| a | b)?,
+        RSVD::Xor { left, right } => combine(left, right, limit, |a, b| a ^ b)?,
+        RSVD::LeftShift { shift, value } => combine(value, shift, limit, |v, s| {
+            if s.value_le() < WORD_SIZE_BITS as u128 {
+                v << s
+            } else {
+                KnownWord::zero()
+            }
+        })?,
+        RSVD::RightShift { shift, value } => combine(value, shift, limit, |v, s| {
+            if s.value_le() < WORD_SIZE_BITS as u128 {
+                v >> s
+            } else {
+                KnownWord::zero()
+            }
+        })?,
+        RSVD::Not { value } => map(value, limit, |v| !v)?,
+
+        // The results of comparisons are boolean even if their operands are unknown
+        RSVD::LessThan { left, right } => compare(left, right, limit, KnownWord::lt),
+        RSVD::GreaterThan { left, right } => compare(left, right, limit, KnownWord::gt),
+        RSVD::SignedLessThan { left, right } => compare(left, right, limit, KnownWord::signed_lt),
+        RSVD::SignedGreaterThan { left, right } => {
+            compare(left, right, limit, KnownWord::signed_gt)
+        }
+        RSVD::Equals { left, right } => compare(left, right, limit, KnownWord::eq),
+        RSVD::IsZero { number } => map(number, limit, KnownWord::is_zero).unwrap_or_else(booleans),
+
+        // Reads from storage take the value that was written there
+        RSVD::SLoad { value, .. } | RSVD::TLoad { value, .. } => values(value, limit)?,
+        _ => return None,
+    };
+
+    (result.len() <= limit).then_some(result)
+}
+
+/// Computes the set of words produced by applying `op` to the words that
+/// `value` can take.
+fn map(
+    value: &RuntimeBoxedVal,
+    limit: usize,
+    op: impl Fn(KnownWord) -> KnownWord,
+) -> Option<BTreeSet<U256>> {
+    let values = values(value, limit)?;
+    let result = values
+        .into_iter()
+        .map(|v| op(KnownWord::from_le(v)).value_le())
+        .collect();
+
+    Some(result)
+}
+
+/// Computes the set of words produced by applying `op` to every pair of words
+/// that `left` and `right` can take.
+fn combine(
+    left: &RuntimeBoxedVal,
+    right: &RuntimeBoxedVal,
+    limit: usize,
+    op: impl Fn(KnownWord, KnownWord) -> KnownWord,
+) -> Option<BTreeSet<U256>> {
+    let left = values(left, limit)?;
+    let right = values(right, limit)?;
+    let result = left
+        .iter()
+        .flat_map(|&l| right.iter().map(move |&r| (l, r)))
+        .map(|(l, r)| op(KnownWord::from_le(l), KnownWord::from_le(r)).value_le())
+        .collect();
+
+    Some(result)
+}
+
+/// Computes the set of words produced by the comparison `op` of `left` and
+/// `right`, which is at most both booleans.
+fn compare(
+    left: &RuntimeBoxedVal,
+    right: &RuntimeBoxedVal,
+    limit: usize,
+    op: impl Fn(KnownWord, KnownWord) -> KnownWord,
+) -> BTreeSet<U256> {
+    combine(left, right, limit, op).unwrap_or_else(booleans)
+}
+
+/// Gets the set containing both boolean words.
+fn booleans() -> BTreeSet<U256> {
+    BTreeSet::from([U256::ZERO, U256::ONE])
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vm::{
+        test_util::{known, synthetic},
+        value::{known::KnownWord, RSVD},
+        value_set::possible_values,
+    };
+
+    fn words(values: &[u32]) -> Vec<KnownWord> {
+        values.iter().map(|&v| KnownWord::from_le(v)).collect()
+    }
+
+    #[test]
+    fn computes_single_value_of_known_data() {
+        assert_eq!(possible_values(&known(0x42), 4), Some(words(&[0x42])));
+    }
+
+    #[test]
+    fn computes_values_selected_by_unknown_condition() {
+        // `0x10 + (x == 0) * 0x20`, which is either `0x10` or `0x30`
+        let condition = synthetic(RSVD::Equals {
+            left:  synthetic(RSVD::CallValue),
+            right: known(0),
+        });
+        let value = synthetic(RSVD::Add {
+            left:  known(0x10),
+            right: synthetic(RSVD::Multiply {
+                left:  condition,
+                right: known(0x20),
+            }),
+        });
+
+        assert_eq!(possible_values(&value, 4), Some(words(&[0x10, 0x30])));
+    }
+
+    #[test]
+    fn sees_through_storage_reads_of_known_values() {
+        let value = synthetic(RSVD::SLoad {
+            key:   known(0),
+            value: synthetic(RSVD::And {
+                left:  known(0xffff),
+                right: known(0x1234_5678),
+            }),
+        });
+
+        assert_eq!(possible_values(&value, 4), Some(words(&[0x5678])));
+    }
+
+    #[test]
+    fn gives_up_on_unknown_values() {
+        let value = synthetic(RSVD::Add {
+            left:  known(1),
+            right: synthetic(RSVD::CallValue),
+        });
+
+        assert_eq!(possible_values(&value, 4), None);
+    }
+
+    #[test]
+    fn gives_up_when_over_the_limit() {
+        let bit = |value| {
+            synthetic(RSVD::IsZero {
+                number: synthetic(value),
+            })
+        };
+        let value = synthetic(RSVD::Add {
+            left:  bit(RSVD::CallValue),
+            right: synthetic(RSVD::Multiply {
+                left:  bit(RSVD::CallDataSize),
+                right: known(2),
+            }),
+        });
+
+        assert_eq!(possible_values(&value, 4), Some(words(&[0, 1, 2, 3])));
+        assert_eq!(possible_values(&value, 3), None);
+    }
+}
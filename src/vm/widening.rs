@@ -0,0 +1,357 @@
+//! This module contains the detection of loops during execution, and the
+//! widening of the values that are carried between their iterations.
+//!
+//! Each unrolled iteration of a loop would otherwise compute its loop-carried
+//! values from those of the previous iteration, nesting them ever more deeply
+//! until they are culled by [`crate::vm::Config::value_size_limit`]. This loses
+//! the pattern that shows the purpose of the loop, such as the walk of a
+//! pointer over the elements of an array.
+//!
+//! When [`crate::vm::Config::loop_widening`] is enabled, a thread that jumps
+//! backwards to a `JUMPDEST` is treated as having completed an iteration of a
+//! loop with that `JUMPDEST` as its header. Any value on its stack that has
+//! grown by a known stride since the previous iteration is then widened to the
+//! induction-variable form `base + loop_counter(header) * stride`, which later
+//! iterations reproduce exactly.
+//!
+//! The iterations of a loop are only compared while the thread remains inside
+//! it. A thread that reaches the header of a loop in any other way is entering
+//! that loop afresh, and so forgets its previous iterations of it.
+
+use crate::vm::{
+    state::stack::Stack,
+    value::{known::KnownWord, Provenance, RuntimeBoxedVal, RSV, RSVD},
+};
+
+/// The tracking of the loops that a thread of execution has iterated over.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LoopTracker {
+    /// The stack of the thread at the end of its most-recent iteration of each
+    /// loop that it is inside, keyed by the offset of the loop header.
+    ///
+    /// The loops are ordered by when the thread first iterated over them, so
+    /// any loop nested within another comes after it.
+    iterations: Vec<(u32, Vec<RuntimeBoxedVal>)>,
+}
+
+impl LoopTracker {
+    /// Constructs a new tracker in which no loops have been iterated over.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records that the thread with the provided `stack` has completed an
+    /// iteration of the loop with its header at `header`, widening any of the
+    /// values on the stack that are carried between iterations.
+    ///
+    /// Any loops first iterated over since the previous iteration of this loop
+    /// are nested within it, and so the thread has left them.
+    ///
+    /// Returns the number of values on the stack that are in induction form as
+    /// a result.
+    pub fn complete_iteration(&mut self, header: u32, stack: &mut Stack) -> usize {
+        let values = stack.values_mut();
+        let mut widened = 0;
+
+        let Some(index) = self.position(header) else {
+            self.iterations.push((header, values.to_vec()));
+            return widened;
+        };
+        self.iterations.truncate(index + 1);
+        let (_, previous) = &mut self.iterations[index];
+        if previous.len() == values.len() {
+            for (value, previous) in values.iter_mut().zip(previous.iter()) {
+                if let Some(induction) = widen(header, previous, value) {
+                    *value = induction;
+                    widened += 1;
+                }
+            }
+        }
+        *previous = values.to_vec();
+
+        widened
+    }
+
+    /// Records that the thread has reached `offset` other than by completing
+    /// an iteration of the loop with its header there.
+    ///
+    /// If the thread has iterated over a loop with its header at `offset`, it
+    /// is entering that loop afresh, and so its iterations of that loop and of
+    /// the loops nested within it are forgotten.
+    pub fn enter(&mut self, offset: u32) {
+        if let Some(index) = self.position(offset) {
+            self.iterations.truncate(index);
+        }
+    }
+
+    /// Checks whether the thread has completed any iterations of the loop with
+    /// its header at `header` since it last entered that loop.
+    #[must_use]
+    pub fn has_iterated(&self, header: u32) -> bool {
+        self.position(header).is_some()
+    }
+
+    /// Gets the index of the iterations of the loop with its header at
+    /// `header`, if there are any.
+    fn position(&self, header: u32) -> Option<usize> {
+        self.iterations.iter().position(|(iterated, _)| *iterated == header)
+    }
+}
+
+/// Widens `current`, the value in a stack slot at the end of an iteration of
+/// the loop at `header`, given that it was `previous` at the end of the prior
+/// iteration.
+///
+/// Returns [`None`] if the value is not carried between iterations by adding a
+/// known stride to it.
+fn widen(
+    header: u32,
+    previous: &RuntimeBoxedVal,
+    current: &RuntimeBoxedVal,
+) -> Option<RuntimeBoxedVal> {
+    if current == previous {
+        return None;
+    }
+    let stride = stride(previous, current)?;
+
+    // Once the value has been widened, each iteration recomputes the same form
+    if induction_stride(previous, header) == Some(stride) {
+        return Some(previous.clone());
+    }
+
+    // The previous value was itself reached by a single stride from the base, so
+    // the base is always `previous - stride`, simplified where possible
+    let instruction_pointer = previous.instruction_pointer();
+    let base = match previous.data() {
+        RSVD::Add { left, right } if left.constant_fold().as_word() == Some(stride) => {
+            right.clone()
+        }
+        RSVD::Add { left, right } if right.constant_fold().as_word() == Some(stride) => {
+            left.clone()
+        }
+        RSVD::Subtract { left, right }
+            if right.constant_fold().as_word() == Some(KnownWord::zero() - stride) =>
+        {
+            left.clone()
+        }
+        _ => match previous.constant_fold().as_word() {
+            Some(word) => RSV::new_known_value(
+                instruction_pointer,
+                word - stride,
+                previous.provenance(),
+                None,
+            ),
+            None => RSV::new_synthetic(
+                instruction_pointer,
+                RSVD::Subtract {
+                    left:  previous.clone(),
+                    right: RSV::new_known_value(
+                        instruction_pointer,
+                        stride,
+                        Provenance::Synthetic,
+                        None,
+                    ),
+                },
+            ),
+        },
+    };
+
+    Some(RSV::new_induction(header, header, base, stride))
+}
+
+/// Gets the known, non-zero amount by which `current` differs from
+/// `previous`, if there is one.
+fn stride(previous: &RuntimeBoxedVal, current: &RuntimeBoxedVal) -> Option<KnownWord> {
+    let stride = if let (Some(previous), Some(current)) = (
+        previous.constant_fold().as_word(),
+        current.constant_fold().as_word(),
+    ) {
+        current - previous
+    } else {
+        match current.data() {
+            RSVD::Add { left, right } if left == previous => right.constant_fold().as_word()?,
+            RSVD::Add { left, right } if right == previous => left.constant_fold().as_word()?,
+            RSVD::Subtract { left, right } if left == previous => {
+                KnownWord::zero() - right.constant_fold().as_word()?
+            }
+            _ => return None,
+        }
+    };
+
+    (stride != KnownWord::zero()).then_some(stride)
+}
+
+/// Gets the stride of `value` if it is in induction form for the loop at
+/// `header`.
+fn induction_stride(value: &RuntimeBoxedVal, header: u32) -> Option<KnownWord> {
+    let RSVD::Add { right: step, .. } = value.data() else {
+        return None;
+    };
+    let RSVD::Multiply {
+        left: counter,
+        right: stride,
+    } = step.data()
+    else {
+        return None;
+    };
+
+    match counter.data() {
+        RSVD::LoopCounter {
+            header: counter_header,
+        } if *counter_header == header => stride.as_word(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::vm::{
+        state::stack::Stack,
+        value::{known::KnownWord, Provenance, RuntimeBoxedVal, RSV, RSVD},
+        widening::LoopTracker,
+    };
+
+    /// Creates the value of `value + 1`.
+    fn increment(value: RuntimeBoxedVal) -> RuntimeBoxedVal {
+        let one = RSV::new_known_value(0, KnownWord::from(1), Provenance::Synthetic, None);
+        RSV::new_synthetic(
+            0,
+            RSVD::Add {
+                left:  one,
+                right: value,
+            },
+        )
+    }
+
+    #[test]
+    fn widens_values_carried_between_iterations() -> anyhow::Result<()> {
+        let base = RSV::new_value(0, Provenance::Synthetic);
+        let invariant = RSV::new_value(0, Provenance::Synthetic);
+        let mut tracker = LoopTracker::new();
+        assert!(!tracker.has_iterated(4));
+
+        // At the end of the first iteration there is nothing to compare with
+        let mut stack = Stack::new();
+        stack.push(invariant.clone())?;
+        stack.push(increment(base.clone()))?;
+        assert_eq!(tracker.complete_iteration(4, &mut stack), 0);
+        assert!(tracker.has_iterated(4));
+
+        // But at the end of the second the counter is found to be carried
+        let counter = increment(stack.read(0)?.clone());
+        stack.pop()?;
+        stack.push(counter)?;
+        assert_eq!(tracker.complete_iteration(4, &mut stack), 1);
+        let widened = stack.read(0)?.clone();
+        assert_eq!(widened, RSV::new_induction(4, 4, base, KnownWord::from(1)));
+        assert_eq!(stack.read(1)?, &invariant);
+
+        // After which each iteration reproduces the same value
+        let counter = increment(stack.read(0)?.clone());
+        stack.pop()?;
+        stack.push(counter)?;
+        assert_eq!(tracker.complete_iteration(4, &mut stack), 1);
+        assert_eq!(stack.read(0)?, &widened);
+
+        Ok(())
+    }
+
+    #[test]
+    fn widens_known_values() -> anyhow::Result<()> {
+        let known = |value: usize| {
+            RSV::new_known_value(0, KnownWord::from(value), Provenance::Synthetic, None)
+        };
+        let mut tracker = LoopTracker::new();
+
+        let mut stack = Stack::new();
+        stack.push(known(0xa0))?;
+        tracker.complete_iteration(4, &mut stack);
+
+        stack.pop()?;
+        stack.push(known(0xc0))?;
+        assert_eq!(tracker.complete_iteration(4, &mut stack), 1);
+        assert_eq!(
+            stack.read(0)?,
+            &RSV::new_induction(4, 4, known(0x80), KnownWord::from(0x20))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn widens_symbolic_values_to_a_consistent_base() -> anyhow::Result<()> {
+        let one = RSV::new_known_value(0, KnownWord::from(1), Provenance::Synthetic, None);
+        let decrement = |value: RuntimeBoxedVal| {
+            RSV::new_synthetic(
+                0,
+                RSVD::Subtract {
+                    left:  value,
+                    right: one.clone(),
+                },
+            )
+        };
+        let minus_one = KnownWord::zero() - KnownWord::from(1);
+
+        // A value that was reached by a stride from the base is simplified
+        let base = RSV::new_value(0, Provenance::Synthetic);
+        let mut tracker = LoopTracker::new();
+        let mut stack = Stack::new();
+        stack.push(decrement(base.clone()))?;
+        tracker.complete_iteration(4, &mut stack);
+        let counter = decrement(stack.pop()?);
+        stack.push(counter)?;
+        assert_eq!(tracker.complete_iteration(4, &mut stack), 1);
+        assert_eq!(
+            stack.read(0)?,
+            &RSV::new_induction(4, 4, base.clone(), minus_one)
+        );
+
+        // But otherwise the base is still found a stride before the previous value
+        let mut tracker = LoopTracker::new();
+        let mut stack = Stack::new();
+        stack.push(base.clone())?;
+        tracker.complete_iteration(4, &mut stack);
+        stack.pop()?;
+        stack.push(decrement(base.clone()))?;
+        assert_eq!(tracker.complete_iteration(4, &mut stack), 1);
+        let RSVD::Add { left, .. } = stack.read(0)?.data() else {
+            panic!("Invalid payload")
+        };
+        let RSVD::Subtract { left, right } = left.data() else {
+            panic!("Invalid payload")
+        };
+        assert_eq!(left, &base);
+        assert_eq!(right.constant_fold().as_word(), Some(minus_one));
+
+        Ok(())
+    }
+
+    #[test]
+    fn forgets_iterations_of_loops_that_are_entered_afresh() -> anyhow::Result<()> {
+        let known = |value: usize| {
+            RSV::new_known_value(0, KnownWord::from(value), Provenance::Synthetic, None)
+        };
+        let mut tracker = LoopTracker::new();
+        let mut stack = Stack::new();
+        stack.push(known(0xa0))?;
+
+        // Completing an iteration of an outer loop leaves the loops nested within it
+        tracker.complete_iteration(4, &mut stack);
+        tracker.complete_iteration(8, &mut stack);
+        tracker.complete_iteration(4, &mut stack);
+        assert!(tracker.has_iterated(4));
+        assert!(!tracker.has_iterated(8));
+
+        // And entering a loop afresh forgets the previous iterations of it
+        tracker.complete_iteration(8, &mut stack);
+        tracker.enter(4);
+        assert!(!tracker.has_iterated(4));
+        assert!(!tracker.has_iterated(8));
+        stack.pop()?;
+        stack.push(known(0xc0))?;
+        assert_eq!(tracker.complete_iteration(4, &mut stack), 0);
+
+        Ok(())
+    }
+}
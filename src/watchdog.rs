@@ -14,7 +14,6 @@
 
 use std::{
     fmt::Debug,
-    rc::Rc,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -24,7 +23,7 @@ use std::{
 use crate::constant::DEFAULT_WATCHDOG_POLL_LOOP_ITERATIONS;
 
 /// A dynamically dispatched [`Watchdog`] instance.
-pub type DynWatchdog = Rc<dyn Watchdog>;
+pub type DynWatchdog = Arc<dyn Watchdog>;
 
 /// The interface to an object that can be polled to see if the extractor needs
 /// to abort processing.
@@ -34,7 +33,7 @@ pub type DynWatchdog = Rc<dyn Watchdog>;
 /// logic.
 pub trait Watchdog
 where
-    Self: Debug,
+    Self: Debug + Send + Sync,
 {
     /// Checks if the extractor should halt its analysis and return an error.
     #[must_use]
@@ -52,10 +51,13 @@ where
 pub struct LazyWatchdog;
 
 impl LazyWatchdog {
-    /// Wraps `self` into an [`Rc`].
+    /// Wraps `self` into an [`Arc`], giving a [`DynWatchdog`].
+    ///
+    /// The name is kept from when watchdogs were wrapped in an `Rc`, so that
+    /// existing callers continue to work.
     #[must_use]
-    pub fn in_rc(self) -> Rc<dyn Watchdog> {
-        Rc::new(self)
+    pub fn in_rc(self) -> DynWatchdog {
+        Arc::new(self)
     }
 }
 
@@ -106,10 +108,13 @@ impl FlagWatchdog {
         self
     }
 
-    /// Wraps the watchdog into an [`Rc`].
+    /// Wraps the watchdog into an [`Arc`], giving a [`DynWatchdog`].
+    ///
+    /// The name is kept from when watchdogs were wrapped in an `Rc`, so that
+    /// existing callers continue to work.
     #[must_use]
-    pub fn in_rc(self) -> Rc<dyn Watchdog> {
-        Rc::new(self)
+    pub fn in_rc(self) -> DynWatchdog {
+        Arc::new(self)
     }
 }
 
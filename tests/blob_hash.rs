@@ -0,0 +1,24 @@
+//! This module is an integration test that ensures that blob versioned hashes
+//! and blob base fees written to storage are given the correct types.
+#![cfg(test)]
+
+use storage_layout_extractor::{tc::abi::AbiType, watchdog::LazyWatchdog};
+
+mod common;
+
+#[test]
+fn types_stored_blob_data() -> anyhow::Result<()> {
+    // sstore(0, blobhash(0)); sstore(1, blobbasefee()); stop()
+    let bytecode = "0x5f495f554a60015500";
+    let extractor = common::new_extractor_from_bytecode(bytecode, LazyWatchdog.in_rc())?;
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // The blob hash is a 32-byte versioned hash, while the fee is a number
+    assert_eq!(layout.slot_count(), 2);
+    assert!(layout.has_slot(0, 0, AbiType::Bytes { length: Some(32) }));
+    assert!(layout.has_slot(1, 0, AbiType::UInt { size: None }));
+
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! This module is an integration test that ensures that a chain configured
+//! explicitly for the virtual machine takes precedence over the chain of the
+//! contract being analysed.
+#![cfg(test)]
+
+use storage_layout_extractor as sle;
+use storage_layout_extractor::{
+    extractor::{
+        chain::{version::EthereumVersion, Chain},
+        contract::Contract,
+    },
+    tc,
+    tc::abi::AbiType,
+    vm,
+    watchdog::LazyWatchdog,
+};
+
+mod common;
+
+// sstore(0, caller()); stop()
+const BYTECODE: &str = "0x335f5500";
+
+#[test]
+fn follows_the_chain_of_the_contract_by_default() -> anyhow::Result<()> {
+    let extractor = common::new_extractor_from_bytecode(BYTECODE, LazyWatchdog.in_rc())?;
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // `PUSH0` is available on the latest version of Ethereum
+    assert_eq!(layout.slot_count(), 1);
+    assert!(layout.has_slot(0, 0, AbiType::Address));
+
+    Ok(())
+}
+
+#[test]
+fn respects_an_explicitly_configured_chain() -> anyhow::Result<()> {
+    let bytecode = common::get_bytecode_from_string(BYTECODE)?;
+    let contract = Contract::new(bytecode, Chain::default());
+    let vm_config = vm::Config::default().with_chain(Chain::Ethereum {
+        version: EthereumVersion::Paris,
+    });
+    let extractor = sle::new(
+        contract,
+        vm_config,
+        tc::Config::default(),
+        LazyWatchdog.in_rc(),
+    );
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // `PUSH0` is not available on Paris, so the store is never executed
+    assert!(layout.is_empty());
+
+    Ok(())
+}
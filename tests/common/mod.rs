@@ -3,18 +3,18 @@
 
 #![cfg(test)]
 
-use std::{fs::File, io::Read};
+use std::fs;
 
 use anyhow::anyhow;
-use serde::{Deserialize, Serialize};
 use storage_layout_extractor as sle;
 use storage_layout_extractor::{
     extractor::{
+        artifact,
         chain::{
             version::{ChainVersion, EthereumVersion},
             Chain,
         },
-        contract::Contract,
+        contract::{BytecodeKind, Contract},
         InitialExtractor,
     },
     tc,
@@ -22,19 +22,6 @@ use storage_layout_extractor::{
     watchdog::{DynWatchdog, LazyWatchdog},
 };
 
-/// A wrapper for the parts of the JSON representation of the compiled contract
-/// on disk that we care about to enable easy deserialization.
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct CompiledContract {
-    deployed_bytecode: DeployedBytecode,
-}
-#[derive(Clone, Debug, Eq, PartialEq, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct DeployedBytecode {
-    object: String,
-}
-
 /// Constructs a new extractor to analyze the hex-encoded (with or without the
 /// `0x` prefix) contract bytecode provided in `code`.
 ///
@@ -60,6 +47,48 @@ pub fn new_extractor_from_bytecode(
     Ok(sle::new(contract, vm_config, unifier_config, watchdog))
 }
 
+/// Constructs a new extractor to analyze the hex-encoded (with or without the
+/// `0x` prefix) contract creation bytecode provided in `code`.
+///
+/// It uses the default configurations for the extractor.
+#[allow(unused)] // It is actually
+pub fn new_extractor_from_creation_bytecode(
+    code: impl Into<String>,
+    watchdog: DynWatchdog,
+) -> anyhow::Result<InitialExtractor> {
+    let bytecode = get_bytecode_from_string(code)?;
+
+    let contract = Contract::new_creation(
+        bytecode,
+        Chain::Ethereum {
+            version: EthereumVersion::latest(),
+        },
+    );
+
+    let vm_config = vm::Config::default();
+    let unifier_config = tc::Config::default();
+
+    Ok(sle::new(contract, vm_config, unifier_config, watchdog))
+}
+
+/// Constructs a new extractor to analyze the hex-encoded (with or without the
+/// `0x` prefix) contract bytecode provided in `code` as running on `chain`.
+///
+/// It uses the default configurations for the extractor on that chain.
+#[allow(unused)] // It is actually
+pub fn new_extractor_from_bytecode_on_chain(
+    code: impl Into<String>,
+    chain: Chain,
+    watchdog: DynWatchdog,
+) -> anyhow::Result<InitialExtractor> {
+    let bytecode = get_bytecode_from_string(code)?;
+    let unifier_config = tc::Config::for_chain(&chain);
+    let contract = Contract::new(bytecode, chain);
+    let vm_config = vm::Config::default();
+
+    Ok(sle::new(contract, vm_config, unifier_config, watchdog))
+}
+
 /// Constructs a new extractor to analyze the contract at the provided `path`.
 ///
 /// It uses the default configurations for the extractor
@@ -82,25 +111,66 @@ pub fn new_extractor_from_path(path: impl Into<String>) -> anyhow::Result<Initia
     ))
 }
 
+/// Constructs a new extractor to analyze the creation bytecode of the contract
+/// at the provided `path`.
+///
+/// It uses the default configurations for the extractor
+#[allow(unused)] // It is actually
+pub fn new_extractor_from_creation_path(
+    path: impl Into<String>,
+) -> anyhow::Result<InitialExtractor> {
+    let contract = new_creation_contract_from_file(
+        path,
+        Chain::Ethereum {
+            version: EthereumVersion::latest(),
+        },
+    )?;
+    let vm_config = vm::Config::default();
+    let unifier_config = tc::Config::default();
+
+    Ok(sle::new(
+        contract,
+        vm_config,
+        unifier_config,
+        LazyWatchdog.in_rc(),
+    ))
+}
+
+/// Creates a new contract from the creation bytecode in the file at the
+/// provided `path`.
+///
+/// The file at `path` must be a Foundry artifact for a Solidity contract that
+/// contains the creation bytecode.
+#[allow(unused)] // It is actually
+pub fn new_creation_contract_from_file(
+    path: impl Into<String>,
+    chain: Chain,
+) -> anyhow::Result<Contract> {
+    let config = artifact::Config::default().with_bytecode_kind(BytecodeKind::Creation);
+    load_contract_from_file(path, chain, &config)
+}
+
 /// Creates a new contract from the file at the provided `path`.
 ///
-/// The file at `path` must be a compiled representation of a Solidity
-/// contract, usually output as JSON.
+/// The file at `path` must be a Foundry artifact for a Solidity contract.
 #[allow(unused)] // It is actually
 pub fn new_contract_from_file(path: impl Into<String>, chain: Chain) -> anyhow::Result<Contract> {
-    let path = path.into();
-    let mut file = File::open(path).map_err(|_| anyhow!("File not available"))?;
-    let mut contents = vec![];
-    file.read_to_end(&mut contents)
-        .map_err(|_| anyhow!("File could not be read"))?;
-
-    let contract_rep: CompiledContract = serde_json::from_slice(contents.as_slice())
-        .map_err(|_| anyhow!("Could not parse compiled contract."))?;
+    let config = artifact::Config::default().with_metadata_stripping(false);
+    load_contract_from_file(path, chain, &config)
+}
 
-    // Generally unsafe but fine for ASCII.
-    let bytecode = get_bytecode_from_string(contract_rep.deployed_bytecode.object)?;
+/// Loads the contract from the Foundry artifact at the provided `path` as
+/// described by `config`.
+fn load_contract_from_file(
+    path: impl Into<String>,
+    chain: Chain,
+    config: &artifact::Config,
+) -> anyhow::Result<Contract> {
+    let path = path.into();
+    let contents = fs::read_to_string(path).map_err(|_| anyhow!("File could not be read"))?;
 
-    Ok(Contract::new(bytecode, chain))
+    artifact::from_foundry(&contents, chain, config)
+        .map_err(|_| anyhow!("Could not parse compiled contract."))
 }
 
 /// Gets the contract bytecode from the provided hex-encoded string `code`.
@@ -108,13 +178,6 @@ pub fn new_contract_from_file(path: impl Into<String>, chain: Chain) -> anyhow::
 /// This hex-encoded string may or may not start with the `0x` prefix. Both
 /// cases will be handled.
 pub fn get_bytecode_from_string(code: impl Into<String>) -> anyhow::Result<Vec<u8>> {
-    let bytecode_string = code.into();
-    // Remove the 0x if it is present
-    let no_0x_prefix = match bytecode_string.strip_prefix("0x") {
-        Some(no_0x_prefix) => no_0x_prefix,
-        None => &bytecode_string,
-    };
-
-    let bytecode = hex::decode(no_0x_prefix).map_err(|_| anyhow!("Could not decode hex"))?;
-    Ok(bytecode)
+    artifact::decode_hex(&code.into(), &artifact::Config::default())
+        .map_err(|_| anyhow!("Could not decode hex"))
 }
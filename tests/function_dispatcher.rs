@@ -0,0 +1,46 @@
+//! This module is an integration test that ensures that the function
+//! dispatchers of contracts compiled by Solidity are correctly recognised.
+#![cfg(test)]
+
+use std::collections::BTreeSet;
+
+mod common;
+
+/// Checks that the selectors recognised from the dispatcher of the contract at
+/// `path` are exactly those listed in its `methodIdentifiers`.
+fn assert_recognises_method_identifiers(path: &str) -> anyhow::Result<()> {
+    let artifact: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let expected: BTreeSet<[u8; 4]> = artifact["methodIdentifiers"]
+        .as_object()
+        .expect("Artifact has no method identifiers")
+        .values()
+        .map(|id| {
+            let bytes = hex::decode(id.as_str().expect("Identifier was not a string"))?;
+            Ok(bytes.as_slice().try_into()?)
+        })
+        .collect::<anyhow::Result<_>>()?;
+
+    // Disassemble the contract to recognise its dispatcher
+    let extractor = common::new_extractor_from_path(path)?.disassemble()?;
+    let table = extractor.selectors();
+
+    assert_eq!(table.selectors().collect::<BTreeSet<_>>(), expected);
+    assert!(table.fallback.is_some());
+
+    Ok(())
+}
+
+#[test]
+fn recognises_functions_of_simple_contract() -> anyhow::Result<()> {
+    assert_recognises_method_identifiers("./asset/SimpleContract.json")
+}
+
+#[test]
+fn recognises_functions_of_packed_encodings() -> anyhow::Result<()> {
+    assert_recognises_method_identifiers("./asset/PackedEncodings.json")
+}
+
+#[test]
+fn recognises_functions_of_uniswap_debug() -> anyhow::Result<()> {
+    assert_recognises_method_identifiers("./asset/UniswapDebug.json")
+}
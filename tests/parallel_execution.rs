@@ -0,0 +1,48 @@
+//! This module is an integration test that ensures that spreading execution
+//! over multiple workers produces the same layouts as executing on one.
+#![cfg(test)]
+
+use storage_layout_extractor as sle;
+use storage_layout_extractor::{
+    extractor::chain::{
+        version::{ChainVersion, EthereumVersion},
+        Chain,
+    },
+    layout::StorageLayout,
+    tc,
+    vm,
+    watchdog::LazyWatchdog,
+};
+
+mod common;
+
+/// Analyses the contract at the provided `path` with execution spread over
+/// the specified number of `workers`.
+fn analyze_with_workers(path: &str, workers: usize) -> anyhow::Result<StorageLayout> {
+    let contract = common::new_contract_from_file(
+        path,
+        Chain::Ethereum {
+            version: EthereumVersion::latest(),
+        },
+    )?;
+    let vm_config = vm::Config::default().with_worker_threads(workers);
+    let unifier_config = tc::Config::default();
+    let extractor = sle::new(contract, vm_config, unifier_config, LazyWatchdog.in_rc());
+
+    Ok(extractor.analyze()?)
+}
+
+#[test]
+fn parallel_execution_produces_identical_layouts() -> anyhow::Result<()> {
+    for path in [
+        "./asset/SimpleContract.json",
+        "./asset/PackedEncodings.json",
+    ] {
+        let sequential = analyze_with_workers(path, 1)?;
+        let parallel = analyze_with_workers(path, 4)?;
+
+        assert_eq!(parallel, sequential, "Layouts differ for {path}");
+    }
+
+    Ok(())
+}
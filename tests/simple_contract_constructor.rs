@@ -0,0 +1,59 @@
+//! This module is an integration test that tests the library's analysis
+//! capabilities on the creation bytecode of a very simple, hand-constructed,
+//! contract.
+#![cfg(test)]
+
+use storage_layout_extractor::{tc::abi::AbiType, watchdog::LazyWatchdog};
+
+mod common;
+
+#[test]
+fn analyses_simple_contract_from_creation_bytecode() -> anyhow::Result<()> {
+    // Create the extractor
+    let contract_path = "./asset/SimpleContract.json";
+    let extractor = common::new_extractor_from_creation_path(contract_path)?;
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // The constructor is trivial, so we should see the same layout as for the
+    // runtime code
+    assert_eq!(layout.slot_count(), 2);
+    assert!(layout.has_slot(
+        0,
+        0,
+        AbiType::Mapping {
+            key_type:   Box::new(AbiType::Bytes { length: Some(16) }),
+            value_type: Box::new(AbiType::Mapping {
+                key_type:   Box::new(AbiType::Bytes { length: Some(16) }),
+                value_type: Box::new(AbiType::Bytes { length: Some(32) }),
+            }),
+        }
+    ));
+    assert!(layout.has_slot(1, 0, AbiType::conflict()));
+
+    Ok(())
+}
+
+#[test]
+fn analyses_state_initialised_by_constructor() -> anyhow::Result<()> {
+    // A constructor that stores the deployer in slot 5 and then returns runtime
+    // code consisting of only `STOP`, which never touches storage:
+    //
+    //   CALLER, PUSH1 0x05, SSTORE,
+    //   PUSH1 0x01, DUP1, PUSH1 0x0f, PUSH1 0x00, CODECOPY,
+    //   PUSH1 0x00, RETURN,
+    //   STOP
+    let bytecode = "0x33600555600180600f6000396000f300";
+    let extractor = common::new_extractor_from_creation_bytecode(bytecode, LazyWatchdog.in_rc())?;
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // The runtime code alone would produce an empty layout, so the slot can only
+    // have been found by analysing the constructor
+    assert_eq!(layout.slot_count(), 1);
+    assert!(layout.has_slot(5, 0, AbiType::Address));
+
+    Ok(())
+}
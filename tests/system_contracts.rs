@@ -0,0 +1,68 @@
+//! This module is an integration test that ensures that the values returned
+//! from the system contracts of L2 chains are given the correct types.
+#![cfg(test)]
+
+use storage_layout_extractor as sle;
+use storage_layout_extractor::{
+    extractor::{
+        chain::{version::EthereumVersion, Chain},
+        contract::Contract,
+    },
+    tc,
+    tc::abi::AbiType,
+    vm,
+    watchdog::LazyWatchdog,
+};
+
+mod common;
+
+#[test]
+fn types_stored_l1_block_hash_on_optimism() -> anyhow::Result<()> {
+    // mstore(0, shl(224, 0x09bd5a60))
+    // pop(staticcall(gas(), L1_BLOCK_ADDRESS, 0, 4, 0x20, 0x20))
+    // sstore(0, mload(0x20)); stop()
+    let bytecode =
+        "0x6309bd5a6060e01b5f526020602060045f7342000000000000000000000000000000000000155afa506020515f5500";
+    let chain = Chain::Optimism {
+        version: EthereumVersion::Cancun,
+    };
+    let extractor =
+        common::new_extractor_from_bytecode_on_chain(bytecode, chain, LazyWatchdog.in_rc())?;
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // The value returned by `L1Block.hash()` is a 32-byte hash
+    assert_eq!(layout.slot_count(), 1);
+    assert!(layout.has_slot(0, 0, AbiType::Bytes { length: Some(32) }));
+
+    Ok(())
+}
+
+#[test]
+fn applies_chain_specific_rules_to_the_default_configuration() -> anyhow::Result<()> {
+    // The same contract as in `types_stored_l1_block_hash_on_optimism`
+    let bytecode = common::get_bytecode_from_string(
+        "0x6309bd5a6060e01b5f526020602060045f7342000000000000000000000000000000000000155afa506020515f5500",
+    )?;
+    let chain = Chain::Optimism {
+        version: EthereumVersion::Cancun,
+    };
+    let contract = Contract::new(bytecode, chain);
+    let extractor = sle::new(
+        contract,
+        vm::Config::default(),
+        tc::Config::default(),
+        LazyWatchdog.in_rc(),
+    );
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // The rules for the chain of the contract are used even though the type
+    // checker configuration was not created for it
+    assert_eq!(layout.slot_count(), 1);
+    assert!(layout.has_slot(0, 0, AbiType::Bytes { length: Some(32) }));
+
+    Ok(())
+}
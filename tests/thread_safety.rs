@@ -0,0 +1,56 @@
+//! This module is an integration test that ensures that the extractor can be
+//! moved to another thread to perform its analysis, with the resulting layout
+//! being moved back.
+#![cfg(test)]
+
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use storage_layout_extractor as sle;
+use storage_layout_extractor::{
+    extractor::chain::{
+        version::{ChainVersion, EthereumVersion},
+        Chain,
+    },
+    tc::{self, abi::AbiType},
+    vm,
+    watchdog::FlagWatchdog,
+};
+
+mod common;
+
+#[test]
+fn analyses_contract_on_another_thread() -> anyhow::Result<()> {
+    // Create the extractor on this thread, with a watchdog that it shares
+    let contract = common::new_contract_from_file(
+        "./asset/SimpleContract.json",
+        Chain::Ethereum {
+            version: EthereumVersion::latest(),
+        },
+    )?;
+    let flag = Arc::new(AtomicBool::new(false));
+    let watchdog = FlagWatchdog::new(flag.clone()).in_rc();
+    let extractor = sle::new(
+        contract,
+        vm::Config::default(),
+        tc::Config::default(),
+        watchdog,
+    );
+
+    // Analyze it on another thread and bring the layout back
+    let layout = thread::spawn(move || extractor.analyze())
+        .join()
+        .expect("The analysis thread panicked")?;
+    assert!(!flag.load(Ordering::Relaxed));
+
+    // Inspect it to check that things are correct
+    assert_eq!(layout.slot_count(), 2);
+    assert!(layout.has_slot(1, 0, AbiType::conflict()));
+
+    Ok(())
+}
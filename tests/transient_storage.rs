@@ -0,0 +1,45 @@
+//! This module is an integration test that ensures that accesses to transient
+//! storage are reported separately from accesses to persistent storage, even
+//! when they use the same slot indices.
+#![cfg(test)]
+
+use storage_layout_extractor::{tc::abi::AbiType, watchdog::LazyWatchdog};
+
+mod common;
+
+#[test]
+fn separates_transient_and_persistent_slots() -> anyhow::Result<()> {
+    // tstore(0, caller()); sstore(0, callvalue()); stop()
+    let bytecode = "0x3360005d3460005500";
+    let extractor = common::new_extractor_from_bytecode(bytecode, LazyWatchdog.in_rc())?;
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // The persistent slot should only see the `callvalue`
+    assert_eq!(layout.slot_count(), 1);
+    assert!(layout.has_slot(0, 0, AbiType::UInt { size: None }));
+
+    // While the transient slot should only see the `caller`
+    assert_eq!(layout.transient_slot_count(), 1);
+    assert!(layout.has_transient_slot(0, 0, AbiType::Address));
+
+    Ok(())
+}
+
+#[test]
+fn layout_with_only_transient_slots_is_not_empty() -> anyhow::Result<()> {
+    // tstore(0, caller()); stop()
+    let bytecode = "0x3360005d00";
+    let extractor = common::new_extractor_from_bytecode(bytecode, LazyWatchdog.in_rc())?;
+
+    // Get the final storage layout for the input contract
+    let layout = extractor.analyze()?;
+
+    // There are no persistent slots, but the layout still has content
+    assert_eq!(layout.slot_count(), 0);
+    assert_eq!(layout.transient_slot_count(), 1);
+    assert!(!layout.is_empty());
+
+    Ok(())
+}